@@ -6,7 +6,11 @@ use tauri::{AppHandle, Emitter, State};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use sayonara_wipe::config::ConfigLoader;
+use sayonara_wipe::crypto::CertificateVerifier;
+use tauri_plugin_dialog::DialogExt;
 
 // Structs for API responses with proper serialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +41,9 @@ struct CertificateResponse {
     #[serde(rename = "blockchainTx")]
     blockchain_tx: Option<String>,
     signature: Option<String>,
+    #[serde(rename = "failedCheck")]
+    failed_check: Option<String>,
+    detail: Option<String>,
 }
 
 // State management for jobs
@@ -313,33 +320,75 @@ async fn chat_query(q: String) -> Result<String, String> {
     ))
 }
 
+/// Ask the user for a saved certificate file; None when they cancel
 #[tauri::command]
-async fn verify_certificate(_job_id: String) -> Result<CertificateResponse, String> {
-    // Simulate verification delay
-    tokio::time::sleep(tokio::time::Duration::from_millis(1200)).await;
-
-    Ok(CertificateResponse {
-        valid: true,
-        blockchain_tx: Some(format!(
-            "0x{}", 
-            Uuid::new_v4().to_string().replace("-", "")
-        )),
-        signature: Some(format!(
-            "SHA256:{}",
-            Uuid::new_v4().to_string().replace("-", "")[..32].to_string()
-        )),
+async fn pick_certificate(app: AppHandle) -> Option<String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title("Select the certificate to verify")
+        .add_filter("Certificates", &["json", "p7m"])
+        .pick_file(move |path| {
+            let _ = tx.send(path);
+        });
+
+    rx.await
+        .ok()
+        .flatten()
+        .and_then(|path| path.into_path().ok())
+        .map(|path| path.display().to_string())
+}
+
+#[tauri::command]
+async fn verify_certificate(
+    job_id: String,
+    certificate_path: Option<String>,
+) -> Result<CertificateResponse, String> {
+    // Simulated erasures do not issue certificates, so there is nothing to
+    // check unless the user picks a saved certificate file
+    let Some(path) = certificate_path else {
+        return Ok(CertificateResponse {
+            valid: false,
+            blockchain_tx: None,
+            signature: None,
+            failed_check: Some("read".to_string()),
+            detail: Some(format!("No certificate recorded for job {}", job_id)),
+        });
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let config = ConfigLoader::new().load().map_err(|e| e.to_string())?.config;
+        let verifier =
+            CertificateVerifier::from_config(&config.signing).map_err(|e| e.to_string())?;
+        let report = verifier.verify_file(Path::new(&path), None, None);
+
+        Ok(CertificateResponse {
+            valid: report.valid,
+            blockchain_tx: None,
+            signature: report
+                .certificate
+                .as_ref()
+                .and_then(|cert| cert.signer_fingerprint.as_ref())
+                .map(|fingerprint| format!("SHA256:{}", fingerprint)),
+            failed_check: report.failed_check.map(str::to_string),
+            detail: report.failure().map(|check| check.detail.clone()),
+        })
     })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 fn main() {
     let job_state: JobState = Arc::new(Mutex::new(HashMap::new()));
 
     tauri::Builder::default()
+        .plugin(tauri_plugin_dialog::init())
         .manage(job_state)
         .invoke_handler(tauri::generate_handler![
             start_erase,
             prepare_iso,
             chat_query,
+            pick_certificate,
             verify_certificate
         ])
         .run(tauri::generate_context!())
@@ -76,12 +76,25 @@ function App() {
 
   const handleCertificateVerification = async (jobId) => {
     try {
-      const result = await invoke('verify_certificate', { jobId });
+      // Certificates are saved files; ask which one to check for this job
+      const certificatePath = await invoke('pick_certificate');
+      if (!certificatePath) {
+        setLogs(prev => [...prev, {
+          timestamp: new Date().toISOString(),
+          message: 'Certificate verification skipped: no certificate selected',
+          level: 'info'
+        }]);
+        return;
+      }
+
+      const result = await invoke('verify_certificate', { jobId, certificatePath });
       setCertificate(result);
 
       const logEntry = {
         timestamp: new Date().toISOString(),
-        message: `Certificate ${result.valid ? 'verified successfully' : 'verification failed'}`,
+        message: result.valid
+          ? 'Certificate verified successfully'
+          : `Certificate verification failed (${result.failedCheck}): ${result.detail}`,
         level: result.valid ? 'success' : 'error'
       };
       setLogs(prev => [...prev, logEntry]);
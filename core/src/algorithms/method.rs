@@ -0,0 +1,629 @@
+// Software overwrite methods
+//
+// A `WipeMethod` describes an overwrite as data: the passes to make, what
+// each pass writes, whether it is read back, and what goes into its
+// checkpoints. `execute` runs any method the same way, with error recovery,
+// checkpoint/resume and pass records, so a method only has to say what to
+// write. With `verify_passes` every pass is also read back in full (see
+// `readback`). Methods are found by name in the registry (see `registry`).
+
+use super::readback::{self, PassStream};
+use crate::crypto::evidence::PassVerification;
+use crate::crypto::secure_rng::{get_secure_rng, secure_random_bytes};
+use crate::error::{ErrorContext, PassTracker, Progress, RangeSet, RecoveryCoordinator};
+use crate::io::buffer_pool::PAGE_SIZE;
+use crate::io::{IOConfig, IOHandle, OptimizedIO};
+use crate::ui::progress::ProgressBar;
+use crate::DriveType;
+use crate::WipeConfig;
+use crate::{DriveError, DriveResult};
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Bytes read back per verification sample
+const SAMPLE_SIZE: usize = 4096;
+
+/// Random passes keep a sample of what they wrote every this many bytes
+const RANDOM_SAMPLE_INTERVAL: u64 = 100 * 1024 * 1024;
+
+/// Minimum entropy (bits/byte) of a random sample that reads back changed
+const MIN_RANDOM_ENTROPY: f64 = 7.5;
+
+/// What a pass writes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PassSource {
+    /// The same byte everywhere
+    Fixed(u8),
+    /// A byte sequence repeated from the start of the drive
+    Pattern(Vec<u8>),
+    /// Cryptographically secure random data
+    Random,
+}
+
+impl PassSource {
+    /// Fill `buffer`, which starts `offset` bytes into the drive
+    pub fn fill(&self, buffer: &mut [u8], offset: u64) -> Result<()> {
+        match self {
+            PassSource::Fixed(byte) => buffer.fill(*byte),
+            PassSource::Pattern(pattern) => fill_pattern(buffer, pattern, offset),
+            PassSource::Random => get_secure_rng().fill_bytes(buffer)?,
+        }
+        Ok(())
+    }
+
+    /// The data expected at `offset`, or `None` for random data
+    pub fn expected(&self, offset: u64, len: usize) -> Option<Vec<u8>> {
+        let mut expected = vec![0u8; len];
+        match self {
+            PassSource::Fixed(byte) => expected.fill(*byte),
+            PassSource::Pattern(pattern) => fill_pattern(&mut expected, pattern, offset),
+            PassSource::Random => return None,
+        }
+        Some(expected)
+    }
+
+    /// Name for pass records, e.g. `0x00`, `0x924924` or `random`
+    pub fn name(&self) -> String {
+        match self {
+            PassSource::Fixed(byte) => format!("0x{:02X}", byte),
+            PassSource::Pattern(bytes) => bytes
+                .iter()
+                .fold("0x".to_string(), |hex, byte| hex + &format!("{:02X}", byte)),
+            PassSource::Random => "random".to_string(),
+        }
+    }
+}
+
+fn fill_pattern(buffer: &mut [u8], pattern: &[u8], offset: u64) {
+    let len = pattern.len() as u64;
+    for (i, byte) in buffer.iter_mut().enumerate() {
+        *byte = pattern[((offset + i as u64) % len) as usize];
+    }
+}
+
+/// One overwrite of the whole drive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WipePass {
+    pub source: PassSource,
+    /// Shown while the pass runs, e.g. `Writing 0x00`
+    pub description: String,
+    /// Read the pass back once written
+    pub verify: bool,
+}
+
+impl WipePass {
+    pub fn new(source: PassSource, description: impl Into<String>) -> Self {
+        Self {
+            source,
+            description: description.into(),
+            verify: false,
+        }
+    }
+
+    /// Read this pass back once written
+    pub fn verified(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+}
+
+/// The file a method was defined in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodSource {
+    /// Name the file gives the method
+    pub name: String,
+    pub path: String,
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+}
+
+/// A software overwrite method
+///
+/// Implement this to add a site-specific method, register it with
+/// [`register_method`](super::registry::register_method), and it can be
+/// selected by name like the built-in ones (`--algorithm <name>`).
+pub trait WipeMethod: Send + Sync {
+    /// Registry name, as given to `--algorithm` (lowercase)
+    fn name(&self) -> &str;
+
+    /// Human-readable name, e.g. `DoD 5220.22-M 3-pass wipe`
+    fn description(&self) -> String;
+
+    /// Name checkpoints are saved under; changing it orphans saved ones
+    fn checkpoint_label(&self) -> &str;
+
+    /// The passes, in order
+    fn passes(&self) -> Vec<WipePass>;
+
+    /// State saved with every checkpoint, e.g. what the method detected
+    /// about the drive; `pass` is added to it
+    fn checkpoint_state(&self, _device_path: &str) -> Result<serde_json::Value> {
+        Ok(json!({}))
+    }
+
+    /// Formal name of the standard the method implements, if any, e.g.
+    /// `DoD 5220.22-M`
+    fn standard(&self) -> Option<&str> {
+        None
+    }
+
+    /// The file the method was loaded from, for methods defined outside
+    /// the code
+    fn source(&self) -> Option<MethodSource> {
+        None
+    }
+
+    /// What the drive reads back as once the method completes
+    fn expected_contents(&self) -> PassSource {
+        self.passes()
+            .pop()
+            .map(|pass| pass.source)
+            .unwrap_or(PassSource::Random)
+    }
+}
+
+/// Run `method` over the first `size` bytes of `device_path`
+///
+/// Resumes from a checkpoint saved under the method's label, checkpoints as
+/// it goes, and deletes the checkpoint once every pass has completed.
+pub fn execute(
+    method: &dyn WipeMethod,
+    device_path: &str,
+    size: u64,
+    drive_type: DriveType,
+    config: &WipeConfig,
+) -> Result<()> {
+    let passes = method.passes();
+    let total = passes.len();
+    let label = method.checkpoint_label();
+    if passes.is_empty() {
+        return Err(anyhow!("{} has no passes", method.description()));
+    }
+
+    println!(
+        "Starting {} with error recovery on {}",
+        method.description(),
+        device_path
+    );
+    println!(
+        "Drive size: {} bytes ({} GB)",
+        size,
+        size / (1024 * 1024 * 1024)
+    );
+
+    let state = method.checkpoint_state(device_path)?;
+
+    // Initialize recovery coordinator
+    let mut coordinator = RecoveryCoordinator::new(device_path, config)?;
+
+    // Check for existing checkpoint
+    let (start_pass, start_offset) = match coordinator.resume_from_checkpoint(label)? {
+        Some(resume) if resume.current_pass >= total => (total, 0),
+        Some(resume) => {
+            let offset = resume_offset(resume.pass_offset(size), config.verify_passes);
+            if offset > 0 {
+                println!(
+                    "Resuming pass {} at byte {} (checkpoint found)",
+                    resume.current_pass + 1,
+                    offset
+                );
+            } else {
+                println!(
+                    "Restarting pass {} from the start (checkpoint found)",
+                    resume.current_pass + 1
+                );
+            }
+            (resume.current_pass, offset)
+        }
+        None => (0, 0),
+    };
+
+    // Configure I/O based on drive type
+    let io_config = match drive_type {
+        DriveType::NVMe => IOConfig::nvme_optimized(),
+        DriveType::SSD => IOConfig::sata_ssd_optimized(),
+        DriveType::HDD => IOConfig::hdd_optimized(),
+        _ => IOConfig::default(),
+    };
+
+    // Open device with optimized I/O
+    let mut io_handle = OptimizedIO::open(device_path, io_config)?;
+
+    for (index, pass) in passes.iter().enumerate().skip(start_pass) {
+        println!("\n🔄 Pass {}/{}: {}", index + 1, total, pass.description);
+        let pass_started = Instant::now();
+        let pass_offset = if index == start_pass { start_offset } else { 0 };
+        let pass_state = with_pass(&state, index + 1);
+
+        let context = ErrorContext::new(
+            format!("{}_pass_{}", label.to_lowercase(), index + 1),
+            device_path,
+        );
+        let tracker = coordinator.pass_tracker(label, total, index, size, pass_state.clone());
+        let verification = coordinator.execute_with_recovery(
+            &format!("pass_{}", index + 1),
+            context,
+            || -> DriveResult<Option<PassVerification>> {
+                write_pass(
+                    &mut io_handle,
+                    size,
+                    pass,
+                    index,
+                    pass_offset,
+                    &tracker,
+                    config.verify_passes,
+                )
+                .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))
+            },
+        )?;
+        tracker.complete_verified(pass.source.name(), verification);
+
+        if pass.verify || config.verify_passes {
+            println!(
+                "  ✅ Pass {} completed and verified in {:.2}s",
+                index + 1,
+                pass_started.elapsed().as_secs_f64()
+            );
+        }
+
+        coordinator.maybe_checkpoint(
+            label,
+            total,
+            size * total as u64,
+            &Progress {
+                current_pass: index + 1,
+                bytes_written: size * (index as u64 + 1),
+                state: pass_state,
+                completed: RangeSet::new(),
+            },
+        )?;
+    }
+
+    // Final sync
+    io_handle.sync()?;
+
+    // Print performance report
+    OptimizedIO::print_performance_report(&io_handle, None);
+
+    // Clean up checkpoint on success
+    coordinator.delete_checkpoint()?;
+
+    println!("\n✅ {} completed successfully", method.description());
+    Ok(())
+}
+
+/// Where to resume a pass interrupted at `offset`
+///
+/// A pass read back in full is compared with what one run wrote under one
+/// keystream, so it is written again from the start: resuming part way
+/// would leave the bytes written before the interruption unverified while
+/// the pass still records a verification.
+fn resume_offset(offset: u64, read_back_all: bool) -> u64 {
+    if read_back_all {
+        0
+    } else {
+        offset
+    }
+}
+
+/// `state` with the number of the pass it belongs to
+fn with_pass(state: &serde_json::Value, pass: usize) -> serde_json::Value {
+    let mut state = state.clone();
+    match state.as_object_mut() {
+        Some(fields) => {
+            fields.insert("pass".to_string(), json!(pass));
+        }
+        None => state = json!({ "pass": pass, "state": state }),
+    }
+    state
+}
+
+/// Write one pass from `start_offset` to the end of the drive, then read it
+/// back if the pass asks for it
+///
+/// With `read_back_all` the whole pass is read back and compared with what
+/// was written, whatever the pass asks for; such a pass always starts at
+/// offset 0 (see `resume_offset`).
+fn write_pass(
+    io_handle: &mut IOHandle,
+    size: u64,
+    pass: &WipePass,
+    index: usize,
+    start_offset: u64,
+    tracker: &PassTracker,
+    read_back_all: bool,
+) -> Result<Option<PassVerification>> {
+    let mut bar = ProgressBar::new(48);
+    // Writing is the first half of a verified pass
+    let scale = if pass.verify || read_back_all {
+        50.0
+    } else {
+        100.0
+    };
+    let mut samples: HashMap<u64, Vec<u8>> = HashMap::new();
+    let keep_samples = pass.verify && !read_back_all && pass.source == PassSource::Random;
+    // Random data that is read back in full has to be derivable again
+    let stream = read_back_all
+        .then(|| PassStream::new(&pass.source))
+        .transpose()?;
+
+    OptimizedIO::sequential_write_from(
+        io_handle,
+        start_offset,
+        size,
+        |buffer, offset| {
+            let buf = buffer.as_mut_slice();
+            match &stream {
+                Some(stream) => stream.fill(buf, offset)?,
+                None => pass.source.fill(buf, offset)?,
+            }
+
+            // Remember the start of every 100MB of random data to read back
+            if keep_samples && offset.is_multiple_of(RANDOM_SAMPLE_INTERVAL) {
+                let len = SAMPLE_SIZE.min(buf.len()).min((size - offset) as usize);
+                samples.insert(offset, buf[..len].to_vec());
+            }
+            Ok(())
+        },
+        |handle, bytes_written| {
+            if bytes_written.is_multiple_of(50 * 1024 * 1024) || bytes_written >= size {
+                let progress = (bytes_written as f64 / size as f64) * scale;
+                bar.render(progress, Some(bytes_written), Some(size));
+            }
+
+            tracker.record(bytes_written, || Ok(handle.sync()?))?;
+            Ok(())
+        },
+    )?;
+
+    if let Some(stream) = &stream {
+        println!("\n  🔍 Reading pass {} back...", index + 1);
+        let device_path = io_handle.device_path.clone();
+        let mut reader = OptimizedIO::open(&device_path, IOConfig::verification_optimized())?;
+        let span = size - start_offset;
+        let verification = readback::read_back(
+            io_handle,
+            &mut reader,
+            stream,
+            start_offset..size,
+            tracker.bad_sectors(),
+            |compared| {
+                if compared.is_multiple_of(50 * 1024 * 1024) || compared >= span {
+                    let progress = 50.0 + (compared as f64 / span as f64) * 50.0;
+                    bar.render(progress, None, None);
+                }
+            },
+        )?;
+        bar.render(100.0, Some(size), Some(size));
+        report_read_back(index, &verification);
+        return Ok(Some(verification));
+    }
+
+    if pass.verify {
+        let device_path = io_handle.device_path.clone();
+        match &pass.source {
+            PassSource::Random => {
+                println!("\n  🔍 Verifying pass {} randomness...", index + 1);
+                verify_random(&device_path, size, &samples, &mut bar)?;
+            }
+            source => {
+                println!("\n  🔍 Verifying pass {} pattern...", index + 1);
+                verify_pattern(&device_path, start_offset, size, source, &mut bar)?;
+            }
+        }
+    }
+
+    bar.render(100.0, Some(size), Some(size));
+    Ok(None)
+}
+
+fn report_read_back(index: usize, verification: &PassVerification) {
+    let rewritten = &verification.rewritten;
+    if rewritten.is_empty() {
+        return;
+    }
+    println!(
+        "\n  🔁 Pass {}: rewrote {} bytes in {} range(s) that read back wrong",
+        index + 1,
+        rewritten.covered(),
+        rewritten.range_count()
+    );
+    let mismatched = &verification.mismatched;
+    if !mismatched.is_empty() {
+        println!(
+            "  ⚠️  {} bytes in {} range(s) still read back wrong; the certificate will not be compliant",
+            mismatched.covered(),
+            mismatched.range_count()
+        );
+    }
+}
+
+/// Read back up to 1000 samples spread over `[start, size)` and compare
+/// them with what `source` wrote there
+fn verify_pattern(
+    device_path: &str,
+    start: u64,
+    size: u64,
+    source: &PassSource,
+    bar: &mut ProgressBar,
+) -> Result<()> {
+    let mut handle = OptimizedIO::open(device_path, IOConfig::verification_optimized())?;
+
+    let span = size.saturating_sub(start);
+    let num_samples = (span / SAMPLE_SIZE as u64).clamp(1, 1000);
+    let interval = span / num_samples;
+
+    for i in 0..num_samples {
+        let offset = start + i * interval;
+        let len = SAMPLE_SIZE.min((size - offset) as usize);
+        if len == 0 {
+            break;
+        }
+        let data = read_sample(&mut handle, offset, len)?;
+        let expected = source
+            .expected(offset, len)
+            .ok_or_else(|| anyhow!("Random data has no pattern to compare with"))?;
+
+        if data.len() < len {
+            return Err(anyhow!(
+                "Verification failed at offset {}: short read",
+                offset + data.len() as u64
+            ));
+        }
+        if let Some(j) = (0..len).find(|&j| data[j] != expected[j]) {
+            return Err(anyhow!(
+                "Verification failed at offset {}: expected 0x{:02x}, got 0x{:02x}",
+                offset + j as u64,
+                expected[j],
+                data[j]
+            ));
+        }
+
+        // Verification is the second half of the pass
+        let progress = 50.0 + ((i as f64 / num_samples as f64) * 50.0);
+        bar.render(progress, None, None);
+    }
+    Ok(())
+}
+
+/// Check the kept samples of a random pass read back as written, or at
+/// least as random, then spot-check entropy elsewhere
+fn verify_random(
+    device_path: &str,
+    size: u64,
+    samples: &HashMap<u64, Vec<u8>>,
+    bar: &mut ProgressBar,
+) -> Result<()> {
+    let mut handle = OptimizedIO::open(device_path, IOConfig::verification_optimized())?;
+
+    for (verified, (offset, written)) in samples.iter().enumerate() {
+        let data = read_sample(&mut handle, *offset, written.len())?;
+
+        if data != *written {
+            // The drive may remap or transform data; it must still look random
+            let entropy = shannon_entropy(&data);
+            if entropy < MIN_RANDOM_ENTROPY {
+                return Err(anyhow!(
+                    "Low entropy detected at offset {}: {:.2} bits/byte",
+                    offset,
+                    entropy
+                ));
+            }
+        }
+
+        let progress = 50.0 + (((verified + 1) as f64 / samples.len() as f64) * 50.0);
+        bar.render(progress, None, None);
+    }
+
+    // Additionally check overall entropy at random positions
+    if size > SAMPLE_SIZE as u64 {
+        for _ in 0..100 {
+            let mut offset_bytes = [0u8; 8];
+            secure_random_bytes(&mut offset_bytes)?;
+            let offset = u64::from_le_bytes(offset_bytes) % (size - SAMPLE_SIZE as u64);
+
+            if let Ok(data) = read_sample(&mut handle, offset, SAMPLE_SIZE) {
+                let entropy = shannon_entropy(&data);
+                if entropy < 7.0 {
+                    println!(
+                        "  ⚠️  Warning: Lower entropy at offset {}: {:.2} bits/byte",
+                        offset, entropy
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read `len` bytes at `offset` through the handle's pooled buffer. The
+/// read is widened to whole pages because a direct I/O handle rejects
+/// unaligned buffers, offsets and lengths.
+fn read_sample(handle: &mut IOHandle, offset: u64, len: usize) -> Result<Vec<u8>> {
+    let mut buffer = handle.acquire_buffer()?;
+    let start = offset - offset % PAGE_SIZE as u64;
+    let skip = (offset - start) as usize;
+    let end = (skip + len)
+        .next_multiple_of(PAGE_SIZE)
+        .min(buffer.as_slice().len());
+    let read = handle.read_at(&mut buffer.as_mut_slice()[..end], start)?;
+    Ok(buffer.as_slice()[skip.min(read)..(skip + len).min(read)].to_vec())
+}
+
+/// Shannon entropy of `data` in bits per byte
+pub(crate) fn shannon_entropy(data: &[u8]) -> f64 {
+    let mut counts = [0u64; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+
+    let length = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f64 / length;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_follow_the_drive_offset() {
+        let source = PassSource::Pattern(vec![0x92, 0x49, 0x24]);
+        let mut first = vec![0u8; 4];
+        let mut second = vec![0u8; 4];
+        source.fill(&mut first, 0).unwrap();
+        source.fill(&mut second, 4).unwrap();
+        assert_eq!(first, [0x92, 0x49, 0x24, 0x92]);
+        assert_eq!(second, [0x49, 0x24, 0x92, 0x49]);
+        assert_eq!(source.expected(4, 4), Some(second));
+        assert_eq!(source.name(), "0x924924");
+        assert_eq!(PassSource::Random.expected(0, 4), None);
+    }
+
+    #[test]
+    fn test_read_back_pass_restarts_on_resume() {
+        assert_eq!(resume_offset(64 * 1024 * 1024, false), 64 * 1024 * 1024);
+        assert_eq!(resume_offset(64 * 1024 * 1024, true), 0);
+        assert_eq!(resume_offset(0, true), 0);
+    }
+
+    #[test]
+    fn test_sampled_verification_reads_through_direct_io() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        let size = 64 * 1024u64;
+        let mut bar = ProgressBar::new(48);
+
+        std::fs::write(path, vec![0x00; size as usize]).unwrap();
+        verify_pattern(path, 0, size, &PassSource::Fixed(0x00), &mut bar).unwrap();
+        // Samples need not start on a sector boundary
+        verify_pattern(path, 1000, size, &PassSource::Fixed(0x00), &mut bar).unwrap();
+        assert!(verify_pattern(path, 0, size, &PassSource::Fixed(0xFF), &mut bar).is_err());
+
+        // A random pass whose samples read back as zeros fails
+        let mut written = vec![0u8; SAMPLE_SIZE];
+        secure_random_bytes(&mut written).unwrap();
+        let samples = HashMap::from([(8192 + 100, written.clone())]);
+        assert!(verify_random(path, size, &samples, &mut bar).is_err());
+
+        let mut data = vec![0u8; size as usize];
+        data[8292..8292 + SAMPLE_SIZE].copy_from_slice(&written);
+        std::fs::write(path, data).unwrap();
+        verify_random(path, size, &samples, &mut bar).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_state_names_the_pass() {
+        let state = with_pass(&json!({"encoding": "PRML"}), 3);
+        assert_eq!(state, json!({"encoding": "PRML", "pass": 3}));
+        assert_eq!(with_pass(&json!({}), 1), json!({"pass": 1}));
+    }
+}
@@ -1,6 +1,11 @@
 pub mod dod;
 pub mod gutmann;
+pub mod method;
 pub mod random;
+pub mod readback;
+pub mod registry;
+pub mod scheme;
+pub mod standards;
 pub mod zero;
 
 #[cfg(test)]
@@ -15,5 +20,11 @@ mod random_test;
 // Re-export the main wiping implementations
 pub use dod::DoDWipe;
 pub use gutmann::GutmannWipe;
+pub use method::{MethodSource, PassSource, WipeMethod, WipePass};
 pub use random::RandomWipe;
+pub use registry::{lookup_method, register_method, MethodRegistry};
+pub use standards::{
+    DoDEceWipe, GostWipe, HmgIs5BaselineWipe, HmgIs5EnhancedWipe, NavsoWipe, RcmpOps2Wipe,
+    SchneierWipe, VsitrWipe,
+};
 pub use zero::ZeroWipe;
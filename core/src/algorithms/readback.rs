@@ -0,0 +1,301 @@
+// Read-back verification of overwrite passes
+//
+// With `verify_passes` every pass is read back in full once written and
+// compared byte for byte with what it should contain. Fixed bytes and
+// patterns are regenerated from the pass itself. Random passes are written
+// from a ChaCha20 keystream instead of the RNG, under a key drawn from the
+// secure RNG for that pass and held only in memory, so the data expected at
+// any offset can be derived again without keeping what was written.
+//
+// Blocks that read back wrong are logged with the bad sector handler and
+// written once more; whatever still reads back wrong after that is recorded
+// with the pass in the certificate evidence.
+
+use super::method::PassSource;
+use crate::crypto::evidence::PassVerification;
+use crate::crypto::secure_rng::secure_random_bytes;
+use crate::error::{BadSectorHandler, RangeSet};
+use crate::io::IOHandle;
+use anyhow::{anyhow, Result};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::ops::Range;
+
+/// Granularity mismatches are located, logged and rewritten at
+pub const VERIFY_BLOCK: u64 = 4096;
+
+/// Bytes read back per request
+const READ_CHUNK: u64 = 4 * 1024 * 1024;
+
+/// ChaCha20 keystream addressed by drive offset
+pub struct Keystream {
+    key: [u8; 32],
+}
+
+impl Keystream {
+    /// A keystream under a fresh key from the secure RNG
+    pub fn generate() -> Result<Self> {
+        let mut key = [0u8; 32];
+        secure_random_bytes(&mut key)?;
+        Ok(Self { key })
+    }
+
+    /// Fill `buffer` with the keystream from `offset` bytes in
+    pub fn fill(&self, buffer: &mut [u8], offset: u64) {
+        let mut rng = ChaCha20Rng::from_seed(self.key);
+        rng.set_word_pos((offset / 4) as u128);
+
+        // Offsets inside a 32-bit word start part way through it
+        let skip = (offset % 4) as usize;
+        let head = ((4 - skip) % 4).min(buffer.len());
+        let (first, rest) = buffer.split_at_mut(head);
+        if !first.is_empty() {
+            let mut word = [0u8; 4];
+            rng.fill_bytes(&mut word);
+            first.copy_from_slice(&word[skip..skip + head]);
+        }
+        rng.fill_bytes(rest);
+    }
+}
+
+impl Drop for Keystream {
+    fn drop(&mut self) {
+        for byte in self.key.iter_mut() {
+            // Volatile so the wipe of the key is not optimized away
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+/// What a pass writes, derivable again at any offset
+pub struct PassStream<'a> {
+    source: &'a PassSource,
+    keystream: Option<Keystream>,
+}
+
+impl<'a> PassStream<'a> {
+    /// Random passes get a keystream of their own
+    pub fn new(source: &'a PassSource) -> Result<Self> {
+        let keystream = match source {
+            PassSource::Random => Some(Keystream::generate()?),
+            _ => None,
+        };
+        Ok(Self { source, keystream })
+    }
+
+    /// Fill `buffer`, which starts `offset` bytes into the drive
+    pub fn fill(&self, buffer: &mut [u8], offset: u64) -> Result<()> {
+        match &self.keystream {
+            Some(keystream) => {
+                keystream.fill(buffer, offset);
+                Ok(())
+            }
+            None => self.source.fill(buffer, offset),
+        }
+    }
+}
+
+/// Read `range` back through `reader` and compare it with `stream`
+///
+/// Each mismatched range is logged with `bad_sectors` and rewritten once
+/// through `writer`, then read back again. `progress` receives the bytes
+/// compared so far.
+pub fn read_back(
+    writer: &mut IOHandle,
+    reader: &mut IOHandle,
+    stream: &PassStream,
+    range: Range<u64>,
+    bad_sectors: Option<&BadSectorHandler>,
+    mut progress: impl FnMut(u64),
+) -> Result<PassVerification> {
+    let rewritten = compare(reader, stream, range.clone(), &mut progress)?;
+
+    for mismatch in rewritten.iter() {
+        if let Some(handler) = bad_sectors {
+            handler.record_bad_sector(
+                mismatch.start,
+                format!(
+                    "Read back wrong after write ({} bytes at {}); rewriting",
+                    mismatch.end - mismatch.start,
+                    mismatch.start
+                ),
+            )?;
+        }
+        rewrite(writer, stream, mismatch)?;
+    }
+    if !rewritten.is_empty() {
+        writer.sync()?;
+    }
+
+    let mut mismatched = RangeSet::new();
+    for mismatch in rewritten.iter() {
+        let still = compare(reader, stream, mismatch, &mut |_| {})?;
+        mismatched = mismatched.union(&still);
+    }
+
+    Ok(PassVerification {
+        bytes: range.end - range.start,
+        rewritten,
+        mismatched,
+    })
+}
+
+/// Blocks of `range` that do not read back as `stream`
+///
+/// Unreadable blocks count as mismatched.
+fn compare(
+    reader: &mut IOHandle,
+    stream: &PassStream,
+    range: Range<u64>,
+    progress: &mut dyn FnMut(u64),
+) -> Result<RangeSet> {
+    let mut mismatched = RangeSet::new();
+    let mut expected = Vec::new();
+    let mut offset = range.start;
+    // Pooled buffers are aligned for direct I/O; a plain Vec is not
+    let mut buffer = reader.acquire_buffer()?;
+
+    while offset < range.end {
+        if crate::is_interrupted() {
+            return Err(anyhow!("Verification interrupted"));
+        }
+        let len = (range.end - offset)
+            .min(READ_CHUNK)
+            .min(buffer.as_slice().len() as u64);
+        let data = &mut buffer.as_mut_slice()[..len as usize];
+        let read = reader.read_at(data, offset).unwrap_or(0);
+        let data = &data[..read];
+        expected.resize(len as usize, 0);
+        stream.fill(&mut expected, offset)?;
+
+        let mut block = 0;
+        while block < len {
+            let end = (block + VERIFY_BLOCK).min(len);
+            let read = data.get(block as usize..end as usize);
+            if read != Some(&expected[block as usize..end as usize]) {
+                mismatched.insert(offset + block..offset + end);
+            }
+            block = end;
+        }
+
+        offset += len;
+        progress(offset - range.start);
+    }
+    Ok(mismatched)
+}
+
+/// Write `stream`'s data over `range` again
+fn rewrite(writer: &mut IOHandle, stream: &PassStream, range: Range<u64>) -> Result<()> {
+    let mut offset = range.start;
+    while offset < range.end {
+        let mut buffer = writer.acquire_buffer()?;
+        let len = (range.end - offset).min(buffer.as_slice().len() as u64) as usize;
+        let data = &mut buffer.as_mut_slice()[..len];
+        stream.fill(data, offset)?;
+        writer.write_at(data, offset)?;
+        offset += len as u64;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{IOConfig, OptimizedIO};
+    use std::io::{Seek, SeekFrom, Write};
+    use tempfile::NamedTempFile;
+
+    fn buffered() -> IOConfig {
+        IOConfig {
+            use_direct_io: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_keystream_is_the_same_from_any_offset() {
+        let keystream = Keystream::generate().unwrap();
+        let mut whole = vec![0u8; 1000];
+        keystream.fill(&mut whole, 0);
+
+        for offset in [0usize, 1, 3, 4, 63, 64, 65, 517] {
+            let mut part = vec![0u8; 200];
+            keystream.fill(&mut part, offset as u64);
+            assert_eq!(part, whole[offset..offset + 200], "offset {}", offset);
+        }
+        let mut tiny = [0u8; 2];
+        keystream.fill(&mut tiny, 5);
+        assert_eq!(tiny, whole[5..7]);
+
+        let mut other = vec![0u8; 1000];
+        Keystream::generate().unwrap().fill(&mut other, 0);
+        assert_ne!(other, whole);
+    }
+
+    #[test]
+    fn test_read_back_rewrites_blocks_that_read_back_wrong() {
+        let size = 64 * 1024u64;
+        let source = PassSource::Random;
+        let stream = PassStream::new(&source).unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        let mut data = vec![0u8; size as usize];
+        stream.fill(&mut data, 0).unwrap();
+        file.write_all(&data).unwrap();
+
+        // Corrupt one byte in the third block
+        file.seek(SeekFrom::Start(2 * VERIFY_BLOCK + 10)).unwrap();
+        file.write_all(&[!data[(2 * VERIFY_BLOCK + 10) as usize]])
+            .unwrap();
+        file.flush().unwrap();
+
+        let path = file.path().to_str().unwrap();
+        let mut writer = OptimizedIO::open(path, buffered()).unwrap();
+        let mut reader = OptimizedIO::open(path, buffered()).unwrap();
+        let handler = BadSectorHandler::new(path);
+        let mut compared = 0;
+
+        let verification = read_back(
+            &mut writer,
+            &mut reader,
+            &stream,
+            0..size,
+            Some(&handler),
+            |bytes| compared = bytes,
+        )
+        .unwrap();
+
+        assert_eq!(compared, size);
+        assert_eq!(verification.bytes, size);
+        assert_eq!(
+            verification.rewritten.iter().collect::<Vec<_>>(),
+            vec![2 * VERIFY_BLOCK..3 * VERIFY_BLOCK]
+        );
+        assert!(verification.mismatched.is_empty());
+        assert!(handler.is_bad_sector(2 * VERIFY_BLOCK));
+        assert_eq!(std::fs::read(path).unwrap(), data);
+    }
+
+    #[test]
+    fn test_ranges_still_wrong_after_rewriting_are_mismatched() {
+        // Reading a file the rewrite never reaches, so it stays wrong; its
+        // tail is past the end of the file and cannot be read at all
+        let mut stuck = NamedTempFile::new().unwrap();
+        stuck.write_all(&[0x00; 8192]).unwrap();
+        stuck.write_all(&[0xFF; 4096]).unwrap();
+        stuck.flush().unwrap();
+        let scratch = NamedTempFile::new().unwrap();
+
+        let source = PassSource::Fixed(0x00);
+        let stream = PassStream::new(&source).unwrap();
+        let mut writer = OptimizedIO::open(scratch.path().to_str().unwrap(), buffered()).unwrap();
+        let mut reader = OptimizedIO::open(stuck.path().to_str().unwrap(), buffered()).unwrap();
+
+        let verification =
+            read_back(&mut writer, &mut reader, &stream, 0..20480, None, |_| {}).unwrap();
+        assert_eq!(
+            verification.rewritten.iter().collect::<Vec<_>>(),
+            vec![8192..20480]
+        );
+        assert_eq!(verification.mismatched, verification.rewritten);
+    }
+}
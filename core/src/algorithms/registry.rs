@@ -0,0 +1,193 @@
+// Wipe method registry
+//
+// Maps `--algorithm` names to software overwrite methods. The built-in
+// methods (dod, gutmann, random, zero and the standards in `standards`) are
+// registered at startup; library
+// users add their own with `register_method` before parsing configuration,
+// after which the name parses to `Algorithm::Method` and runs everywhere the
+// built-in methods do.
+
+use super::method::WipeMethod;
+use super::standards::{
+    DoDEceWipe, GostWipe, HmgIs5BaselineWipe, HmgIs5EnhancedWipe, NavsoWipe, RcmpOps2Wipe,
+    SchneierWipe, VsitrWipe,
+};
+use super::{DoDWipe, GutmannWipe, RandomWipe, ZeroWipe};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// `--algorithm` names that select hardware commands rather than methods
+pub const HARDWARE_NAMES: [&str; 5] = ["secure", "crypto", "sanitize", "trim", "auto"];
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: RwLock<MethodRegistry> = RwLock::new(MethodRegistry::with_builtin());
+}
+
+/// Wipe methods by name
+#[derive(Clone, Default)]
+pub struct MethodRegistry {
+    methods: BTreeMap<String, Arc<dyn WipeMethod>>,
+}
+
+impl MethodRegistry {
+    /// A registry holding only the built-in methods
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::default();
+        let builtin: [Arc<dyn WipeMethod>; 12] = [
+            Arc::new(DoDWipe),
+            Arc::new(GutmannWipe),
+            Arc::new(RandomWipe),
+            Arc::new(ZeroWipe),
+            Arc::new(DoDEceWipe),
+            Arc::new(HmgIs5BaselineWipe),
+            Arc::new(HmgIs5EnhancedWipe),
+            Arc::new(VsitrWipe),
+            Arc::new(RcmpOps2Wipe),
+            Arc::new(GostWipe),
+            Arc::new(SchneierWipe),
+            Arc::new(NavsoWipe),
+        ];
+        for method in builtin {
+            registry
+                .register(method)
+                .expect("built-in method names are distinct");
+        }
+        registry
+    }
+
+    /// Add `method` under its name
+    ///
+    /// Names are case-insensitive and cannot replace a registered method or
+    /// shadow a hardware command.
+    pub fn register(&mut self, method: Arc<dyn WipeMethod>) -> Result<()> {
+        let name = method.name().to_lowercase();
+        if name.trim().is_empty() {
+            bail!("Invalid wipe method name '{}'", method.name());
+        }
+        if HARDWARE_NAMES.contains(&name.as_str()) {
+            bail!("'{}' is reserved for a hardware erase command", name);
+        }
+        if self.methods.contains_key(&name) {
+            bail!("A wipe method named '{}' is already registered", name);
+        }
+        self.methods.insert(name, method);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn WipeMethod>> {
+        self.methods.get(&name.to_lowercase()).cloned()
+    }
+
+    /// Registered names, sorted
+    pub fn names(&self) -> Vec<String> {
+        self.methods.keys().cloned().collect()
+    }
+
+    pub fn methods(&self) -> impl Iterator<Item = &Arc<dyn WipeMethod>> {
+        self.methods.values()
+    }
+}
+
+/// Make `method` selectable by name for the rest of the process
+pub fn register_method(method: impl WipeMethod + 'static) -> Result<()> {
+    register(Arc::new(method))
+}
+
+pub(crate) fn register(method: Arc<dyn WipeMethod>) -> Result<()> {
+    REGISTRY.write().unwrap().register(method)
+}
+
+/// The method registered as `name`
+pub fn lookup_method(name: &str) -> Option<Arc<dyn WipeMethod>> {
+    REGISTRY.read().unwrap().get(name)
+}
+
+/// A snapshot of the process-wide registry
+pub fn registry() -> MethodRegistry {
+    REGISTRY.read().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::method::{PassSource, WipePass};
+
+    struct SiteMethod;
+
+    impl WipeMethod for SiteMethod {
+        fn name(&self) -> &str {
+            "Site-2Pass"
+        }
+
+        fn description(&self) -> String {
+            "site 2-pass wipe".to_string()
+        }
+
+        fn checkpoint_label(&self) -> &str {
+            "Site2Pass"
+        }
+
+        fn passes(&self) -> Vec<WipePass> {
+            vec![
+                WipePass::new(PassSource::Fixed(0xA5), "Writing 0xA5"),
+                WipePass::new(PassSource::Fixed(0x00), "Writing 0x00").verified(),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_builtin_methods_are_registered() {
+        let registry = MethodRegistry::with_builtin();
+        assert_eq!(
+            registry.names(),
+            [
+                "dod",
+                "dod-ece",
+                "gost",
+                "gutmann",
+                "hmg-is5-baseline",
+                "hmg-is5-enhanced",
+                "navso",
+                "random",
+                "rcmp-ops2",
+                "schneier",
+                "vsitr",
+                "zero"
+            ]
+        );
+        assert_eq!(registry.get("DoD").unwrap().passes().len(), 3);
+        assert_eq!(registry.get("gutmann").unwrap().passes().len(), 35);
+        assert_eq!(
+            registry.get("zero").unwrap().expected_contents(),
+            PassSource::Fixed(0x00)
+        );
+        assert!(registry.get("secure").is_none());
+    }
+
+    #[test]
+    fn test_site_methods_register_once_under_free_names() {
+        let mut registry = MethodRegistry::with_builtin();
+        registry.register(Arc::new(SiteMethod)).unwrap();
+        assert_eq!(registry.get("site-2pass").unwrap().passes().len(), 2);
+        assert!(registry.register(Arc::new(SiteMethod)).is_err());
+        assert!(registry.register(Arc::new(ZeroWipe)).is_err());
+
+        struct Shadow;
+        impl WipeMethod for Shadow {
+            fn name(&self) -> &str {
+                "auto"
+            }
+            fn description(&self) -> String {
+                "shadow".to_string()
+            }
+            fn checkpoint_label(&self) -> &str {
+                "Shadow"
+            }
+            fn passes(&self) -> Vec<WipePass> {
+                Vec::new()
+            }
+        }
+        assert!(registry.register(Arc::new(Shadow)).is_err());
+    }
+}
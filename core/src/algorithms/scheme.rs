@@ -0,0 +1,353 @@
+// Overwrite schemes defined in a file
+//
+// A scheme file lists the passes of a site or contract procedure, e.g.
+// "0x00, 0xFF, random, verify, 0x00", in TOML (or JSON for `.json` files):
+//
+//   name = "Client X procedure"
+//
+//   [[pass]]
+//   type = "fixed"           # one byte everywhere
+//   byte = 0x00
+//
+//   [[pass]]
+//   type = "complement"      # every bit of the previous pass flipped;
+//                            # not after a random pass
+//
+//   [[pass]]
+//   type = "pattern"         # bytes repeated from the start of the drive
+//   bytes = [0x92, 0x49, 0x24]
+//
+//   [[pass]]
+//   type = "random"          # CSPRNG data
+//   verify = true            # read this pass back once written
+//
+// A `{ type = "verify" }` entry reads back the pass before it, for
+// procedures that list verification as a step of its own.
+//
+// A complement may only follow a fixed or pattern pass. Each random pass
+// draws a fresh key that is dropped once the pass is done, so there is
+// nothing left to invert and such a scheme is refused when it is loaded.
+//
+// `--algorithm scheme:<path>` loads the file and registers it as
+// `scheme:<canonical path>`, so it runs like any other `WipeMethod` and is
+// found again from any working directory. The SHA-256 of the file is
+// recorded in the certificate, and checkpoints are labelled by it: an
+// interrupted wipe only resumes under the scheme it started with.
+
+use super::method::{MethodSource, PassSource, WipeMethod, WipePass};
+use super::registry;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+
+/// `--algorithm` prefix naming a scheme file
+pub const SCHEME_PREFIX: &str = "scheme:";
+
+/// Largest repeating pattern a pass may write
+pub const MAX_PATTERN_LEN: usize = 4096;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemeFile {
+    name: String,
+    #[serde(rename = "pass", default)]
+    passes: Vec<SchemeStep>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum SchemeStep {
+    Fixed {
+        byte: u8,
+        #[serde(default)]
+        verify: bool,
+    },
+    Pattern {
+        bytes: Vec<u8>,
+        #[serde(default)]
+        verify: bool,
+    },
+    Complement {
+        #[serde(default)]
+        verify: bool,
+    },
+    Random {
+        #[serde(default)]
+        verify: bool,
+    },
+    Verify,
+}
+
+/// A wipe method loaded from a scheme file
+#[derive(Debug, Clone)]
+pub struct Scheme {
+    /// Registry name, `scheme:<canonical path>`
+    name: String,
+    /// The scheme's own `name`
+    title: String,
+    path: String,
+    sha256: String,
+    label: String,
+    passes: Vec<WipePass>,
+}
+
+impl Scheme {
+    /// Load the scheme at `path`; `.json` files are parsed as JSON,
+    /// everything else as TOML. The scheme is named after the canonical
+    /// path, so a resumed wipe finds it from any working directory.
+    pub fn load(path: &str) -> Result<Self> {
+        let canonical = std::fs::canonicalize(path)
+            .with_context(|| format!("Failed to read scheme {}", path))?;
+        let path = canonical
+            .to_str()
+            .with_context(|| format!("Scheme path {} is not UTF-8", canonical.display()))?;
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read scheme {}", path))?;
+        let json = Path::new(path).extension().and_then(|e| e.to_str()) == Some("json");
+        Self::parse(path, &bytes, json).with_context(|| format!("Invalid scheme {}", path))
+    }
+
+    /// Parse scheme file contents read from `path`
+    pub fn parse(path: &str, bytes: &[u8], json: bool) -> Result<Self> {
+        let file: SchemeFile = if json {
+            serde_json::from_slice(bytes)?
+        } else {
+            toml::from_str(std::str::from_utf8(bytes).context("Scheme is not UTF-8")?)?
+        };
+        if file.name.trim().is_empty() {
+            bail!("Scheme needs a name");
+        }
+
+        let mut passes: Vec<WipePass> = Vec::new();
+        for (index, step) in file.passes.into_iter().enumerate() {
+            let entry = index + 1;
+            let (source, verify) = match step {
+                SchemeStep::Fixed { byte, verify } => (PassSource::Fixed(byte), verify),
+                SchemeStep::Pattern { bytes, verify } => {
+                    if bytes.is_empty() || bytes.len() > MAX_PATTERN_LEN {
+                        bail!(
+                            "Pass {}: a pattern needs 1 to {} bytes",
+                            entry,
+                            MAX_PATTERN_LEN
+                        );
+                    }
+                    (PassSource::Pattern(bytes), verify)
+                }
+                SchemeStep::Complement { verify } => {
+                    let previous = passes.last().with_context(|| {
+                        format!("Pass {}: nothing before it to complement", entry)
+                    })?;
+                    (complement(&previous.source, entry)?, verify)
+                }
+                SchemeStep::Random { verify } => (PassSource::Random, verify),
+                SchemeStep::Verify => {
+                    let previous = passes
+                        .last_mut()
+                        .with_context(|| format!("Entry {}: nothing before it to verify", entry))?;
+                    previous.verify = true;
+                    continue;
+                }
+            };
+            let description = match &source {
+                PassSource::Random => "Writing random data".to_string(),
+                source => format!("Writing {}", source.name()),
+            };
+            let pass = WipePass::new(source, description);
+            passes.push(if verify { pass.verified() } else { pass });
+        }
+        if passes.is_empty() {
+            bail!("Scheme has no passes");
+        }
+
+        let sha256 = hex::encode(Sha256::digest(bytes));
+        Ok(Self {
+            name: format!("{}{}", SCHEME_PREFIX, path),
+            title: file.name,
+            path: path.to_string(),
+            label: format!("Scheme-{}", &sha256[..16]),
+            sha256,
+            passes,
+        })
+    }
+
+    /// Hex SHA-256 of the scheme file
+    pub fn sha256(&self) -> &str {
+        &self.sha256
+    }
+}
+
+/// Every bit of what `source` wrote, flipped
+fn complement(source: &PassSource, entry: usize) -> Result<PassSource> {
+    Ok(match source {
+        PassSource::Fixed(byte) => PassSource::Fixed(!byte),
+        PassSource::Pattern(bytes) => PassSource::Pattern(bytes.iter().map(|b| !b).collect()),
+        PassSource::Random => bail!(
+            "Pass {}: random data is not kept, so it cannot be complemented; \
+             follow the random pass with a fixed or pattern pass instead",
+            entry
+        ),
+    })
+}
+
+impl WipeMethod for Scheme {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} ({} passes, scheme {})",
+            self.title,
+            self.passes.len(),
+            self.path
+        )
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        &self.label
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        self.passes.clone()
+    }
+
+    fn source(&self) -> Option<MethodSource> {
+        Some(MethodSource {
+            name: self.title.clone(),
+            path: self.path.clone(),
+            sha256: self.sha256.clone(),
+        })
+    }
+}
+
+/// Load the scheme at `path` and register it, returning its registry name
+///
+/// Loading the same file again is a no-op; loading it after it changed is an
+/// error, since one process must not run two versions under one name.
+pub fn register_scheme(path: &str) -> Result<String> {
+    let scheme = Scheme::load(path)?;
+    let name = scheme.name.clone();
+    if let Some(existing) = registry::lookup_method(&name) {
+        let loaded = existing.source().map(|source| source.sha256);
+        if loaded.as_deref() != Some(scheme.sha256()) {
+            bail!("Scheme {} changed since it was loaded", path);
+        }
+        return Ok(name);
+    }
+    registry::register(Arc::new(scheme))?;
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT_SCHEME: &str = r#"
+name = "Client X procedure"
+
+[[pass]]
+type = "fixed"
+byte = 0x00
+
+[[pass]]
+type = "complement"
+
+[[pass]]
+type = "random"
+
+[[pass]]
+type = "verify"
+
+[[pass]]
+type = "pattern"
+bytes = [0x92, 0x49, 0x24]
+verify = true
+"#;
+
+    #[test]
+    fn test_scheme_passes_and_hash() {
+        let scheme = Scheme::parse("client.toml", CLIENT_SCHEME.as_bytes(), false).unwrap();
+        let passes = scheme.passes();
+        let sources: Vec<_> = passes.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(
+            sources,
+            [
+                PassSource::Fixed(0x00),
+                PassSource::Fixed(0xFF),
+                PassSource::Random,
+                PassSource::Pattern(vec![0x92, 0x49, 0x24]),
+            ]
+        );
+        let verified: Vec<_> = passes.iter().map(|p| p.verify).collect();
+        assert_eq!(verified, [false, false, true, true]);
+
+        assert_eq!(scheme.name(), "scheme:client.toml");
+        assert_eq!(
+            scheme.sha256(),
+            hex::encode(Sha256::digest(CLIENT_SCHEME.as_bytes()))
+        );
+        assert_eq!(
+            scheme.checkpoint_label(),
+            format!("Scheme-{}", &scheme.sha256()[..16])
+        );
+
+        // The same scheme in JSON
+        let json = r#"{"name": "Client X procedure", "pass": [
+            {"type": "fixed", "byte": 0},
+            {"type": "complement"},
+            {"type": "random", "verify": true},
+            {"type": "pattern", "bytes": [146, 73, 36], "verify": true}
+        ]}"#;
+        let from_json = Scheme::parse("client.json", json.as_bytes(), true).unwrap();
+        assert_eq!(from_json.passes(), passes);
+    }
+
+    #[test]
+    fn test_invalid_schemes_are_refused() {
+        let invalid = [
+            "name = \"x\"",
+            "name = \"x\"\n[[pass]]\ntype = \"complement\"",
+            "name = \"x\"\n[[pass]]\ntype = \"verify\"",
+            "name = \"x\"\n[[pass]]\ntype = \"pattern\"\nbytes = []",
+            "name = \"x\"\n[[pass]]\ntype = \"fixed\"\nbyte = 256",
+            "name = \"x\"\n[[pass]]\ntype = \"fixed\"\nbyte = 0\nverfy = true",
+            "name = \"x\"\n[[pass]]\ntype = \"shred\"",
+        ];
+        for text in invalid {
+            assert!(
+                Scheme::parse("bad.toml", text.as_bytes(), false).is_err(),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_complement_of_a_random_pass_is_refused() {
+        let text = r#"
+name = "x"
+
+[[pass]]
+type = "fixed"
+byte = 0x00
+
+[[pass]]
+type = "random"
+verify = true
+
+[[pass]]
+type = "complement"
+"#;
+        let error = Scheme::parse("bad.toml", text.as_bytes(), false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Pass 3"), "{}", error);
+        assert!(error.contains("cannot be complemented"), "{}", error);
+
+        // The same step after a `verify` entry still refers to the random pass
+        let after_verify = "name = \"x\"\n[[pass]]\ntype = \"random\"\n[[pass]]\ntype = \"verify\"\n[[pass]]\ntype = \"complement\"";
+        assert!(Scheme::parse("bad.toml", after_verify.as_bytes(), false).is_err());
+    }
+}
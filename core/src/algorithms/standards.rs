@@ -0,0 +1,256 @@
+// National and published overwrite standards
+//
+// Each standard is a `WipeMethod` with the pass sequence and read-back the
+// standard prescribes, registered under a short `--algorithm` name:
+//
+//   dod-ece            DoD 5220.22-M ECE, 7 passes
+//   hmg-is5-baseline   HMG IS5 Baseline, 1 pass
+//   hmg-is5-enhanced   HMG IS5 Enhanced, 3 passes
+//   vsitr              BSI VSITR, 7 passes
+//   rcmp-ops2          RCMP TSSIT OPS-II, 7 passes
+//   gost               GOST R 50739-95, 2 passes
+//   schneier           Bruce Schneier's algorithm, 7 passes
+//   navso              NAVSO P-5239-26 (RLL), 3 passes
+//
+// A pass marked verified is read back once written, as the standard
+// requires; standards that do not call for a read-back have none. The
+// certificate records each standard by its formal name (`standard()`).
+
+use super::method::{PassSource, WipeMethod, WipePass};
+
+fn fixed(byte: u8) -> WipePass {
+    WipePass::new(PassSource::Fixed(byte), format!("Writing 0x{:02X}", byte))
+}
+
+fn random() -> WipePass {
+    WipePass::new(PassSource::Random, "Writing random data")
+}
+
+/// DoD 5220.22-M ECE: the 3-pass E sequence, a C pass of a single
+/// character, then E again, each E sequence verified
+pub struct DoDEceWipe;
+
+impl WipeMethod for DoDEceWipe {
+    fn name(&self) -> &str {
+        "dod-ece"
+    }
+
+    fn description(&self) -> String {
+        "DoD 5220.22-M ECE 7-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "DoDECE"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        vec![
+            fixed(0x00),
+            fixed(0xFF),
+            random().verified(),
+            fixed(0x96),
+            fixed(0x00),
+            fixed(0xFF),
+            random().verified(),
+        ]
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("DoD 5220.22-M ECE")
+    }
+}
+
+/// HMG IS5 Baseline: zeros, verified
+pub struct HmgIs5BaselineWipe;
+
+impl WipeMethod for HmgIs5BaselineWipe {
+    fn name(&self) -> &str {
+        "hmg-is5-baseline"
+    }
+
+    fn description(&self) -> String {
+        "HMG IS5 Baseline 1-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "HMGIS5Baseline"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        vec![fixed(0x00).verified()]
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("HMG Infosec Standard No. 5, Baseline")
+    }
+}
+
+/// HMG IS5 Enhanced: zeros, ones, then random data, verified
+pub struct HmgIs5EnhancedWipe;
+
+impl WipeMethod for HmgIs5EnhancedWipe {
+    fn name(&self) -> &str {
+        "hmg-is5-enhanced"
+    }
+
+    fn description(&self) -> String {
+        "HMG IS5 Enhanced 3-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "HMGIS5Enhanced"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        vec![fixed(0x00), fixed(0xFF), random().verified()]
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("HMG Infosec Standard No. 5, Enhanced")
+    }
+}
+
+/// BSI VSITR: zeros and ones alternately for six passes, then 0xAA
+pub struct VsitrWipe;
+
+impl WipeMethod for VsitrWipe {
+    fn name(&self) -> &str {
+        "vsitr"
+    }
+
+    fn description(&self) -> String {
+        "BSI VSITR 7-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "VSITR"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        let mut passes: Vec<_> = [0x00, 0xFF].repeat(3).into_iter().map(fixed).collect();
+        passes.push(fixed(0xAA));
+        passes
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("BSI VSITR (Richtlinien zur Sicherheit von Verschlusssachen in der IT)")
+    }
+}
+
+/// RCMP TSSIT OPS-II: zeros and ones alternately for six passes, then
+/// random data, verified
+pub struct RcmpOps2Wipe;
+
+impl WipeMethod for RcmpOps2Wipe {
+    fn name(&self) -> &str {
+        "rcmp-ops2"
+    }
+
+    fn description(&self) -> String {
+        "RCMP TSSIT OPS-II 7-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "RCMPOPSII"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        let mut passes: Vec<_> = [0x00, 0xFF].repeat(3).into_iter().map(fixed).collect();
+        passes.push(random().verified());
+        passes
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("RCMP Technical Security Standard for Information Technology (TSSIT) OPS-II")
+    }
+}
+
+/// GOST R 50739-95: zeros, then random data
+pub struct GostWipe;
+
+impl WipeMethod for GostWipe {
+    fn name(&self) -> &str {
+        "gost"
+    }
+
+    fn description(&self) -> String {
+        "GOST R 50739-95 2-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "GOST"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        vec![fixed(0x00), random()]
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("GOST R 50739-95")
+    }
+}
+
+/// Bruce Schneier's algorithm: ones, zeros, then five passes of random data
+pub struct SchneierWipe;
+
+impl WipeMethod for SchneierWipe {
+    fn name(&self) -> &str {
+        "schneier"
+    }
+
+    fn description(&self) -> String {
+        "Bruce Schneier 7-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "Schneier"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        let mut passes = vec![fixed(0xFF), fixed(0x00)];
+        passes.extend(std::iter::repeat_with(random).take(5));
+        passes
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("Bruce Schneier's Algorithm (Applied Cryptography, 2nd ed.)")
+    }
+}
+
+/// NAVSO P-5239-26 for RLL-encoded drives: 0x01, the 0x27FFFFFF pattern,
+/// then random data, verified
+pub struct NavsoWipe;
+
+impl NavsoWipe {
+    /// Pass 2 pattern for RLL encoding
+    pub const RLL_PATTERN: [u8; 4] = [0x27, 0xFF, 0xFF, 0xFF];
+}
+
+impl WipeMethod for NavsoWipe {
+    fn name(&self) -> &str {
+        "navso"
+    }
+
+    fn description(&self) -> String {
+        "NAVSO P-5239-26 (RLL) 3-pass wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "NAVSO"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        vec![
+            fixed(0x01),
+            WipePass::new(
+                PassSource::Pattern(Self::RLL_PATTERN.to_vec()),
+                "Writing 0x27FFFFFF",
+            ),
+            random().verified(),
+        ]
+    }
+
+    fn standard(&self) -> Option<&str> {
+        Some("NAVSO P-5239-26 (RLL)")
+    }
+}
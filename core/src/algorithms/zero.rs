@@ -6,14 +6,10 @@
 // - Drive testing
 // - Preparing drives for reuse in same organization
 
-use crate::error::{ErrorContext, Progress, RecoveryCoordinator};
-use crate::io::{IOConfig, IOHandle, OptimizedIO};
-use crate::ui::progress::ProgressBar;
+use super::method::{self, PassSource, WipeMethod, WipePass};
 use crate::DriveType;
 use crate::WipeConfig;
-use crate::{DriveError, DriveResult};
 use anyhow::Result;
-use serde_json::json;
 
 pub struct ZeroWipe;
 
@@ -25,102 +21,13 @@ impl ZeroWipe {
         drive_type: DriveType,
         config: &WipeConfig,
     ) -> Result<()> {
-        println!(
-            "Starting single-pass zero wipe with error recovery on {}",
-            device_path
-        );
-        println!(
-            "Drive size: {} bytes ({} GB)",
-            size,
-            size / (1024 * 1024 * 1024)
-        );
-
-        // Initialize recovery coordinator
-        let mut coordinator = RecoveryCoordinator::new(device_path, config)?;
-
-        // Check for existing checkpoint
-        let should_resume = coordinator.resume_from_checkpoint("Zero")?.is_some();
-        if should_resume {
-            println!("Resuming zero wipe from checkpoint");
-        }
-
-        // Configure I/O based on drive type
-        let io_config = match drive_type {
-            DriveType::NVMe => IOConfig::nvme_optimized(),
-            DriveType::SSD => IOConfig::sata_ssd_optimized(),
-            DriveType::HDD => IOConfig::hdd_optimized(),
-            _ => IOConfig::default(),
-        };
-
-        // Open device with optimized I/O
-        let mut io_handle = OptimizedIO::open(device_path, io_config)?;
-
-        println!("\n🔄 Writing zeros to entire drive");
-
-        // Execute with recovery
-        let context = ErrorContext::new("zero_wipe", device_path);
-        coordinator.execute_with_recovery("zero_wipe", context, || -> DriveResult<()> {
-            Self::write_zeros(&mut io_handle, size)
-                .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))?;
-            Ok(())
-        })?;
-
-        // Save final checkpoint
-        coordinator.maybe_checkpoint(
-            "Zero",
-            1,
-            size,
-            &Progress {
-                current_pass: 1,
-                bytes_written: size,
-                state: json!({"complete": true}),
-            },
-        )?;
-
-        // Final sync
-        io_handle.sync()?;
-
-        // Print performance report
-        OptimizedIO::print_performance_report(&io_handle, None);
-
-        // Clean up checkpoint
-        coordinator.delete_checkpoint()?;
-
-        println!("\n✅ Zero wipe completed successfully");
-        println!("All sectors have been overwritten with zeros.");
-
-        Ok(())
-    }
-
-    /// Write zeros to the entire drive
-    fn write_zeros(io_handle: &mut IOHandle, size: u64) -> Result<()> {
-        let mut bytes_written = 0u64;
-        let mut bar = ProgressBar::new(48);
-
-        OptimizedIO::sequential_write(io_handle, size, |buffer| {
-            // Fill buffer with zeros
-            let buf = buffer.as_mut_slice();
-            buf.fill(0x00);
-
-            bytes_written += buf.len() as u64;
-
-            // Update progress every 50MB or at completion
-            if bytes_written.is_multiple_of(50 * 1024 * 1024) || bytes_written >= size {
-                let progress = (bytes_written as f64 / size as f64) * 100.0;
-                bar.render(progress, Some(bytes_written), Some(size));
-            }
-
-            Ok(())
-        })?;
-
-        bar.render(100.0, Some(size), Some(size));
-        Ok(())
+        method::execute(&ZeroWipe, device_path, size, drive_type, config)
     }
 
     /// Verify zeros were written (useful for testing)
     #[cfg(test)]
     pub fn verify_zeros(device_path: &str, size: u64, _sample_size: u64) -> Result<bool> {
-        use crate::io::OptimizedIO;
+        use crate::io::{IOConfig, OptimizedIO};
 
         let config = IOConfig::verification_optimized();
         let mut io_handle = OptimizedIO::open(device_path, config)?;
@@ -150,9 +57,28 @@ impl ZeroWipe {
     }
 }
 
+impl WipeMethod for ZeroWipe {
+    fn name(&self) -> &str {
+        "zero"
+    }
+
+    fn description(&self) -> String {
+        "single-pass zero wipe".to_string()
+    }
+
+    fn checkpoint_label(&self) -> &str {
+        "Zero"
+    }
+
+    fn passes(&self) -> Vec<WipePass> {
+        vec![WipePass::new(PassSource::Fixed(0x00), "Writing zeros")]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io::{IOConfig, IOHandle, OptimizedIO};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
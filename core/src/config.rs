@@ -0,0 +1,878 @@
+// Layered configuration loading
+//
+// Settings are merged from (lowest to highest precedence):
+//   1. Built-in defaults (`WipeConfig::default()`, `SafetyConfig::default()`, ...)
+//   2. System file: /etc/sayonara/config.toml
+//   3. Per-user file: $XDG_CONFIG_HOME/sayonara/config.toml (or platform equivalent)
+//   4. SAYONARA_* environment variables (`SAYONARA_WIPE__MAX_TEMPERATURE_CELSIUS=60`)
+//   5. Flags passed explicitly on the command line
+//
+// Every merged key remembers which layer set it so `config show --effective`
+// can explain where a value came from.
+
+use crate::crypto::anchor::Address;
+use crate::crypto::pkcs11::PinSource;
+use crate::crypto::{AnchorConfig, CertificateFormat, Pkcs11Config, SigningConfig};
+use crate::io::IOConfig;
+use crate::{Algorithm, HPADCOHandling, NistLevel, SafetyConfig, WipeConfig};
+use ::config::{Config, Environment, File, FileFormat};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// System-wide configuration file
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/sayonara/config.toml";
+
+/// Default directory for persistent state (checkpoint database, journals)
+pub const DEFAULT_STATE_DIR: &str = "/var/lib/sayonara-wipe";
+
+/// Prefix for environment variable overrides
+pub const ENV_PREFIX: &str = "SAYONARA";
+
+lazy_static::lazy_static! {
+    static ref INSTALLED: RwLock<Option<EffectiveConfig>> = RwLock::new(None);
+}
+
+/// Fully merged configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    /// Directory holding the checkpoint database and other persistent state
+    pub state_dir: PathBuf,
+    pub wipe: WipeConfig,
+    pub safety: SafetyConfig,
+    pub io: IOConfig,
+    /// Certificate signing key
+    pub signing: SigningConfig,
+    /// Blockchain anchoring of certificates
+    pub anchor: AnchorConfig,
+}
+
+impl Default for EffectiveConfig {
+    fn default() -> Self {
+        Self {
+            state_dir: PathBuf::from(DEFAULT_STATE_DIR),
+            wipe: WipeConfig {
+                // Matches `--algorithm auto`: the NIST 800-88 decision picks
+                algorithm: Algorithm::Auto,
+                ..WipeConfig::default()
+            },
+            safety: SafetyConfig::default(),
+            io: IOConfig::default(),
+            signing: SigningConfig::default(),
+            anchor: AnchorConfig::default(),
+        }
+    }
+}
+
+impl EffectiveConfig {
+    /// Check values that deserialize fine but make no sense for a wipe
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if !self.state_dir.is_absolute() {
+            problems.push(format!(
+                "state_dir must be an absolute path (got {})",
+                self.state_dir.display()
+            ));
+        }
+
+        if let Some(temp) = self.wipe.max_temperature_celsius {
+            if !(20..=100).contains(&temp) {
+                problems.push(format!(
+                    "wipe.max_temperature_celsius must be between 20 and 100 (got {})",
+                    temp
+                ));
+            }
+        }
+        if self.wipe.multiple_passes == Some(0) {
+            problems.push("wipe.multiple_passes must be at least 1".to_string());
+        }
+
+        if self.safety.temperature_check_interval_secs == 0 {
+            problems.push("safety.temperature_check_interval_secs must be non-zero".to_string());
+        }
+        if self.safety.operation_timeout_secs == 0 {
+            problems.push("safety.operation_timeout_secs must be non-zero".to_string());
+        }
+        // Only --unsafe-mode may skip these; a file or variable can't
+        for (key, enabled) in [
+            ("require_confirmation", self.safety.require_confirmation),
+            ("exclude_system_drives", self.safety.exclude_system_drives),
+            ("exclude_mounted_drives", self.safety.exclude_mounted_drives),
+        ] {
+            if !enabled {
+                problems.push(format!(
+                    "safety.{} cannot be turned off by configuration; pass --unsafe-mode instead",
+                    key
+                ));
+            }
+        }
+        if self.safety.max_retry_attempts > 100 {
+            problems.push(format!(
+                "safety.max_retry_attempts must be at most 100 (got {})",
+                self.safety.max_retry_attempts
+            ));
+        }
+
+        let io = &self.io;
+        if io.initial_buffer_size == 0 || io.max_buffer_size == 0 {
+            problems.push("io buffer sizes must be non-zero".to_string());
+        }
+        if io.initial_buffer_size > io.max_buffer_size {
+            problems.push(format!(
+                "io.initial_buffer_size ({}) exceeds io.max_buffer_size ({})",
+                io.initial_buffer_size, io.max_buffer_size
+            ));
+        }
+        if io.use_direct_io
+            && (!io.initial_buffer_size.is_multiple_of(4096)
+                || !io.max_buffer_size.is_multiple_of(4096))
+        {
+            problems.push("io buffer sizes must be multiples of 4096 with direct I/O".to_string());
+        }
+        if io.queue_depth == 0 {
+            problems.push("io.queue_depth must be at least 1".to_string());
+        }
+        if io.max_buffers == 0 {
+            problems.push("io.max_buffers must be at least 1".to_string());
+        }
+        if !(io.target_efficiency > 0.0 && io.target_efficiency <= 100.0) {
+            problems.push(format!(
+                "io.target_efficiency must be in (0, 100] (got {})",
+                io.target_efficiency
+            ));
+        }
+
+        if self.signing.format != CertificateFormat::Json && self.signing.certificate_path.is_none()
+        {
+            problems.push(format!(
+                "signing.format = \"{}\" requires signing.certificate_path",
+                self.signing.format
+            ));
+        }
+        if let Some(url) = &self.signing.tsa_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                problems.push(format!(
+                    "signing.tsa_url must be an http:// or https:// URL (got {})",
+                    url
+                ));
+            }
+        }
+        if let Some(policy) = &self.signing.tsa_policy {
+            if crate::crypto::timestamp::parse_policy(policy).is_err() {
+                problems.push(format!(
+                    "signing.tsa_policy must be a dotted OID (got {})",
+                    policy
+                ));
+            }
+        }
+        let pkcs11 = &self.signing.pkcs11;
+        if pkcs11.is_configured() {
+            if self.signing.key_path.is_some() {
+                problems.push(
+                    "signing.key_path and signing.pkcs11.module are mutually exclusive".into(),
+                );
+            }
+            if pkcs11.key_label.is_none() {
+                problems.push("signing.pkcs11.module requires signing.pkcs11.key_label".into());
+            }
+            if pkcs11.slot.is_some() && pkcs11.token_label.is_some() {
+                problems.push(
+                    "signing.pkcs11.slot and signing.pkcs11.token_label are mutually exclusive"
+                        .into(),
+                );
+            }
+        } else if *pkcs11 != Pkcs11Config::default() {
+            problems.push("signing.pkcs11 settings require signing.pkcs11.module".into());
+        }
+        if let Some(source) = &pkcs11.pin_source {
+            if let Err(e) = source.parse::<PinSource>() {
+                problems.push(format!("signing.pkcs11.pin_source: {}", e));
+            }
+        }
+        let policy = &self.signing.policy;
+        if policy.required > policy.signers.len() {
+            problems.push(format!(
+                "signing.policy.required ({}) exceeds the {} signers listed in signing.policy.signers",
+                policy.required,
+                policy.signers.len()
+            ));
+        }
+        if let Some(url) = &self.anchor.rpc_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                problems.push(format!(
+                    "anchor.rpc_url must be an http:// or https:// URL (got {})",
+                    url
+                ));
+            }
+            if self.anchor.contract.is_none() || self.anchor.key_path.is_none() {
+                problems.push("anchor.rpc_url requires anchor.contract and anchor.key_path".into());
+            }
+        }
+        if let Some(contract) = &self.anchor.contract {
+            if contract.parse::<Address>().is_err() {
+                problems.push(format!(
+                    "anchor.contract must be a 0x-prefixed 20-byte address (got {})",
+                    contract
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            bail!("Invalid configuration:\n  - {}", problems.join("\n  - "))
+        }
+    }
+}
+
+/// Layer that supplied a configuration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigLayer {
+    Default,
+    System,
+    User,
+    #[serde(rename = "env")]
+    Environment,
+    #[serde(rename = "cli")]
+    CommandLine,
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigLayer::Default => "default",
+            ConfigLayer::System => "system",
+            ConfigLayer::User => "user",
+            ConfigLayer::Environment => "env",
+            ConfigLayer::CommandLine => "cli",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Values given explicitly on the command line
+///
+/// Only `Some` fields override lower layers, so CLI defaults never mask a
+/// site-wide setting.
+#[derive(Debug, Clone, Default)]
+pub struct CliOverrides {
+    pub algorithm: Option<Algorithm>,
+    pub verify: Option<bool>,
+    pub handle_hpa_dco: Option<HPADCOHandling>,
+    pub use_trim_after: Option<bool>,
+    pub temperature_monitoring: Option<bool>,
+    pub max_temperature_celsius: Option<u32>,
+    pub freeze_mitigation: Option<bool>,
+    pub nist_level: Option<NistLevel>,
+    pub verify_passes: Option<bool>,
+    pub cert_format: Option<CertificateFormat>,
+    pub redact_identifiers: Option<bool>,
+    pub state_dir: Option<PathBuf>,
+}
+
+impl CliOverrides {
+    fn to_value(&self) -> Result<Value> {
+        let mut wipe = Map::new();
+        let mut signing = Map::new();
+        let mut root = Map::new();
+
+        if let Some(algorithm) = &self.algorithm {
+            wipe.insert("algorithm".into(), serde_json::to_value(algorithm)?);
+        }
+        if let Some(verify) = self.verify {
+            wipe.insert("verify".into(), Value::Bool(verify));
+        }
+        if let Some(handling) = &self.handle_hpa_dco {
+            wipe.insert("handle_hpa_dco".into(), serde_json::to_value(handling)?);
+        }
+        if let Some(trim) = self.use_trim_after {
+            wipe.insert("use_trim_after".into(), Value::Bool(trim));
+        }
+        if let Some(monitor) = self.temperature_monitoring {
+            wipe.insert("temperature_monitoring".into(), Value::Bool(monitor));
+        }
+        if let Some(temp) = self.max_temperature_celsius {
+            wipe.insert("max_temperature_celsius".into(), Value::from(temp));
+        }
+        if let Some(freeze) = self.freeze_mitigation {
+            wipe.insert("freeze_mitigation".into(), Value::Bool(freeze));
+        }
+        if let Some(level) = &self.nist_level {
+            wipe.insert("nist_level".into(), serde_json::to_value(level)?);
+        }
+        if let Some(verify_passes) = self.verify_passes {
+            wipe.insert("verify_passes".into(), Value::Bool(verify_passes));
+        }
+        if let Some(format) = &self.cert_format {
+            signing.insert("format".into(), serde_json::to_value(format)?);
+        }
+        if let Some(redact) = self.redact_identifiers {
+            signing.insert("redact_identifiers".into(), Value::Bool(redact));
+        }
+        if let Some(dir) = &self.state_dir {
+            root.insert("state_dir".into(), serde_json::to_value(dir)?);
+        }
+        if !wipe.is_empty() {
+            root.insert("wipe".into(), Value::Object(wipe));
+        }
+        if !signing.is_empty() {
+            root.insert("signing".into(), Value::Object(signing));
+        }
+
+        Ok(Value::Object(root))
+    }
+}
+
+/// Result of loading all layers
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: EffectiveConfig,
+    /// Dotted key (e.g. `wipe.max_temperature_celsius`) to the layer that set it
+    pub provenance: BTreeMap<String, ConfigLayer>,
+    /// Configuration files that were found and read
+    pub files: Vec<(ConfigLayer, PathBuf)>,
+}
+
+impl LoadedConfig {
+    /// Layer that set `key`, or `Default` for untouched keys
+    pub fn layer_of(&self, key: &str) -> ConfigLayer {
+        self.provenance
+            .get(key)
+            .copied()
+            .unwrap_or(ConfigLayer::Default)
+    }
+
+    /// Flattened `(key, value, layer)` triples in key order
+    pub fn entries(&self) -> Result<Vec<(String, Value, ConfigLayer)>> {
+        let mut flat = BTreeMap::new();
+        flatten("", &serde_json::to_value(&self.config)?, &mut flat);
+
+        Ok(flat
+            .into_iter()
+            .map(|(key, value)| {
+                let layer = self.layer_of(&key);
+                (key, value, layer)
+            })
+            .collect())
+    }
+}
+
+/// Builder for the layered configuration
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    system_file: Option<PathBuf>,
+    user_file: Option<PathBuf>,
+    environment: Option<HashMap<String, String>>,
+    overrides: CliOverrides,
+}
+
+impl Default for ConfigLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigLoader {
+    /// Loader using the standard system/user paths and the process environment
+    pub fn new() -> Self {
+        Self {
+            system_file: Some(PathBuf::from(SYSTEM_CONFIG_PATH)),
+            user_file: Self::default_user_file(),
+            environment: None,
+            overrides: CliOverrides::default(),
+        }
+    }
+
+    /// Per-user configuration file (e.g. ~/.config/sayonara/config.toml)
+    pub fn default_user_file() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "sayonara")
+            .map(|dirs| dirs.config_dir().join("config.toml"))
+    }
+
+    /// Override (or disable with `None`) the system configuration file
+    pub fn with_system_file(mut self, path: Option<PathBuf>) -> Self {
+        self.system_file = path;
+        self
+    }
+
+    /// Override (or disable with `None`) the per-user configuration file
+    pub fn with_user_file(mut self, path: Option<PathBuf>) -> Self {
+        self.user_file = path;
+        self
+    }
+
+    /// Use the given variables instead of the process environment
+    pub fn with_environment(mut self, vars: HashMap<String, String>) -> Self {
+        self.environment = Some(vars);
+        self
+    }
+
+    /// Apply explicitly passed command-line flags
+    pub fn with_overrides(mut self, overrides: CliOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Merge all layers, record provenance and validate the result
+    pub fn load(&self) -> Result<LoadedConfig> {
+        let defaults = serde_json::to_value(EffectiveConfig::default())?;
+        let mut known = BTreeMap::new();
+        flatten("", &defaults, &mut known);
+
+        let mut builder =
+            Config::builder().add_source(Config::try_from(&defaults).context("Invalid defaults")?);
+        let mut provenance = BTreeMap::new();
+        let mut files = Vec::new();
+
+        for (layer, path) in [
+            (ConfigLayer::System, &self.system_file),
+            (ConfigLayer::User, &self.user_file),
+        ] {
+            let Some(path) = path.as_ref().filter(|p| p.exists()) else {
+                continue;
+            };
+
+            let source = file_source(path);
+            let keys = layer_keys(source.clone())
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            if let Some(unknown) = keys.iter().find(|k| !known.contains_key(*k)) {
+                bail!(
+                    "Unknown configuration key '{}' in {}",
+                    unknown,
+                    path.display()
+                );
+            }
+
+            record(&mut provenance, keys, layer);
+            builder = builder.add_source(source);
+            files.push((layer, path.clone()));
+        }
+
+        let env = Environment::with_prefix(ENV_PREFIX)
+            .prefix_separator("_")
+            .separator("__")
+            .try_parsing(true)
+            .source(self.environment.clone());
+        // Other SAYONARA_* variables (e.g. SAYONARA_TEST_MODE) are not config keys
+        let env_keys: Vec<String> = layer_keys(env.clone())
+            .context("Failed to read SAYONARA_* environment variables")?
+            .into_iter()
+            .filter(|k| known.contains_key(k))
+            .collect();
+        record(&mut provenance, env_keys, ConfigLayer::Environment);
+        builder = builder.add_source(env);
+
+        let cli = self.overrides.to_value()?;
+        let mut cli_keys = BTreeMap::new();
+        flatten("", &cli, &mut cli_keys);
+        record(
+            &mut provenance,
+            cli_keys.into_keys(),
+            ConfigLayer::CommandLine,
+        );
+        builder = builder.add_source(Config::try_from(&cli)?);
+
+        let config: EffectiveConfig = builder
+            .build()
+            .context("Failed to merge configuration layers")?
+            .try_deserialize()
+            .context("Invalid configuration value")?;
+        config.validate()?;
+
+        Ok(LoadedConfig {
+            config,
+            provenance,
+            files,
+        })
+    }
+}
+
+/// Make `config` the process-wide configuration (state dir, retry limits)
+pub fn install(config: EffectiveConfig) {
+    if let Ok(mut installed) = INSTALLED.write() {
+        *installed = Some(config);
+    }
+}
+
+/// Process-wide configuration, if one has been installed
+pub fn installed() -> Option<EffectiveConfig> {
+    INSTALLED.read().ok().and_then(|c| c.clone())
+}
+
+/// Directory for persistent state, honouring the installed configuration
+pub fn state_dir() -> PathBuf {
+    installed()
+        .map(|c| c.state_dir)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_STATE_DIR))
+}
+
+fn file_source(path: &Path) -> File<::config::FileSourceFile, FileFormat> {
+    let format = match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => FileFormat::Json,
+        Some("yaml") | Some("yml") => FileFormat::Yaml,
+        _ => FileFormat::Toml,
+    };
+    File::from(path).format(format)
+}
+
+/// Dotted leaf keys supplied by a single source
+fn layer_keys<S>(source: S) -> Result<Vec<String>>
+where
+    S: ::config::Source + Send + Sync + 'static,
+{
+    let value: Value = Config::builder()
+        .add_source(source)
+        .build()?
+        .try_deserialize()
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let mut flat = BTreeMap::new();
+    flatten("", &value, &mut flat);
+    Ok(flat.into_keys().collect())
+}
+
+fn record(
+    provenance: &mut BTreeMap<String, ConfigLayer>,
+    keys: impl IntoIterator<Item = String>,
+    layer: ConfigLayer,
+) {
+    for key in keys {
+        provenance.insert(key, layer);
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&path, child, out);
+            }
+        }
+        _ if !prefix.is_empty() => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_file(dir: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    fn isolated_loader() -> ConfigLoader {
+        ConfigLoader::new()
+            .with_system_file(None)
+            .with_user_file(None)
+            .with_environment(HashMap::new())
+    }
+
+    #[test]
+    fn test_defaults_only() {
+        let loaded = isolated_loader().load().unwrap();
+
+        assert_eq!(loaded.config.state_dir, PathBuf::from(DEFAULT_STATE_DIR));
+        assert!(matches!(loaded.config.wipe.algorithm, Algorithm::Auto));
+        assert_eq!(loaded.config.wipe.max_temperature_celsius, Some(65));
+        assert_eq!(loaded.config.safety.max_retry_attempts, 3);
+        assert!(loaded.provenance.is_empty());
+        assert!(loaded.files.is_empty());
+        assert_eq!(
+            loaded.layer_of("wipe.max_temperature_celsius"),
+            ConfigLayer::Default
+        );
+    }
+
+    #[test]
+    fn test_layer_precedence() {
+        let dir = TempDir::new().unwrap();
+        let system = write_file(
+            &dir,
+            "system.toml",
+            r#"
+            [wipe]
+            max_temperature_celsius = 55
+            handle_hpa_dco = "TemporaryRemove"
+
+            [safety]
+            max_retry_attempts = 5
+            "#,
+        );
+        let user = write_file(
+            &dir,
+            "user.toml",
+            r#"
+            [wipe]
+            max_temperature_celsius = 50
+            "#,
+        );
+
+        let mut env = HashMap::new();
+        env.insert(
+            "SAYONARA_SAFETY__MAX_RETRY_ATTEMPTS".to_string(),
+            "7".to_string(),
+        );
+
+        let loaded = isolated_loader()
+            .with_system_file(Some(system))
+            .with_user_file(Some(user))
+            .with_environment(env)
+            .with_overrides(CliOverrides {
+                max_temperature_celsius: Some(45),
+                ..Default::default()
+            })
+            .load()
+            .unwrap();
+
+        assert_eq!(loaded.config.wipe.max_temperature_celsius, Some(45));
+        assert_eq!(loaded.config.safety.max_retry_attempts, 7);
+        assert!(matches!(
+            loaded.config.wipe.handle_hpa_dco,
+            HPADCOHandling::TemporaryRemove
+        ));
+
+        assert_eq!(
+            loaded.layer_of("wipe.max_temperature_celsius"),
+            ConfigLayer::CommandLine
+        );
+        assert_eq!(
+            loaded.layer_of("safety.max_retry_attempts"),
+            ConfigLayer::Environment
+        );
+        assert_eq!(loaded.layer_of("wipe.handle_hpa_dco"), ConfigLayer::System);
+        assert_eq!(loaded.layer_of("wipe.verify"), ConfigLayer::Default);
+        assert_eq!(loaded.files.len(), 2);
+    }
+
+    #[test]
+    fn test_missing_files_are_skipped() {
+        let loaded = isolated_loader()
+            .with_system_file(Some(PathBuf::from("/nonexistent/sayonara.toml")))
+            .load()
+            .unwrap();
+
+        assert!(loaded.files.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_file_key_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = write_file(&dir, "bad.toml", "[wipe]\nmax_temp = 50\n");
+
+        let err = isolated_loader()
+            .with_system_file(Some(path))
+            .load()
+            .unwrap_err();
+
+        assert!(err.to_string().contains("wipe.max_temp"));
+    }
+
+    #[test]
+    fn test_unrelated_env_vars_ignored() {
+        let mut env = HashMap::new();
+        env.insert("SAYONARA_TEST_MODE".to_string(), "1".to_string());
+        env.insert(
+            "SAYONARA_STATE_DIR".to_string(),
+            "/srv/sayonara".to_string(),
+        );
+
+        let loaded = isolated_loader().with_environment(env).load().unwrap();
+
+        assert_eq!(loaded.config.state_dir, PathBuf::from("/srv/sayonara"));
+        assert_eq!(loaded.layer_of("state_dir"), ConfigLayer::Environment);
+        assert!(!loaded.provenance.contains_key("test_mode"));
+    }
+
+    #[test]
+    fn test_algorithm_override() {
+        let loaded = isolated_loader()
+            .with_overrides(CliOverrides {
+                algorithm: Some(Algorithm::Gutmann),
+                verify: Some(false),
+                nist_level: Some(NistLevel::Purge),
+                verify_passes: Some(true),
+                ..Default::default()
+            })
+            .load()
+            .unwrap();
+
+        assert!(matches!(loaded.config.wipe.algorithm, Algorithm::Gutmann));
+        assert!(!loaded.config.wipe.verify);
+        assert_eq!(loaded.config.wipe.nist_level, Some(NistLevel::Purge));
+        assert!(loaded.config.wipe.verify_passes);
+        assert_eq!(loaded.layer_of("wipe.algorithm"), ConfigLayer::CommandLine);
+    }
+
+    #[test]
+    fn test_json_file_layer() {
+        let dir = TempDir::new().unwrap();
+        let path = write_file(&dir, "user.json", r#"{"io": {"queue_depth": 8}}"#);
+
+        let loaded = isolated_loader().with_user_file(Some(path)).load().unwrap();
+
+        assert_eq!(loaded.config.io.queue_depth, 8);
+        assert_eq!(loaded.layer_of("io.queue_depth"), ConfigLayer::User);
+    }
+
+    #[test]
+    fn test_signing_section() {
+        let dir = TempDir::new().unwrap();
+        let path = write_file(
+            &dir,
+            "user.toml",
+            "[signing]\n\
+             format = \"cms\"\n\
+             certificate_path = \"/etc/sayonara/signer.pem\"\n\
+             trust_anchors = [\"/etc/sayonara/root-ca.pem\"]\n\
+             [signing.pkcs11]\n\
+             module = \"/usr/lib/softhsm/libsofthsm2.so\"\n\
+             token_label = \"wipe-station\"\n\
+             key_label = \"sayonara\"\n\
+             pin_source = \"file:/etc/sayonara/hsm.pin\"\n",
+        );
+
+        let loaded = isolated_loader().with_user_file(Some(path)).load().unwrap();
+        let signing = &loaded.config.signing;
+        assert_eq!(signing.format, CertificateFormat::Cms);
+        assert_eq!(
+            signing.trust_anchors,
+            vec![PathBuf::from("/etc/sayonara/root-ca.pem")]
+        );
+        assert_eq!(loaded.layer_of("signing.format"), ConfigLayer::User);
+        assert_eq!(signing.pkcs11.key_label.as_deref(), Some("sayonara"));
+        assert_eq!(loaded.layer_of("signing.pkcs11.module"), ConfigLayer::User);
+
+        // CMS output without a certificate for the key cannot work
+        let err = isolated_loader()
+            .with_overrides(CliOverrides {
+                cert_format: Some(CertificateFormat::CmsDetached),
+                ..Default::default()
+            })
+            .load()
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("signing.certificate_path"));
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_values() {
+        let mut config = EffectiveConfig::default();
+        assert!(config.validate().is_ok());
+
+        config.wipe.max_temperature_celsius = Some(500);
+        config.io.queue_depth = 0;
+        config.state_dir = PathBuf::from("relative/dir");
+        config.signing.tsa_url = Some("tsa.example.com".to_string());
+        config.signing.tsa_policy = Some("not-an-oid".to_string());
+        config.anchor.rpc_url = Some("http://127.0.0.1:8545".to_string());
+        config.anchor.contract = Some("0x1234".to_string());
+        config.signing.key_path = Some(PathBuf::from("/etc/sayonara/signing.pem"));
+        config.signing.pkcs11.module = Some(PathBuf::from("/usr/lib/softhsm/libsofthsm2.so"));
+        config.signing.pkcs11.slot = Some(0);
+        config.signing.pkcs11.token_label = Some("wipe".to_string());
+        config.signing.pkcs11.pin_source = Some("123456".to_string());
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("max_temperature_celsius"));
+        assert!(err.contains("queue_depth"));
+        assert!(err.contains("state_dir"));
+        assert!(err.contains("signing.tsa_url"));
+        assert!(err.contains("signing.tsa_policy"));
+        assert!(err.contains("anchor.contract must be"));
+        assert!(err.contains("anchor.key_path"));
+        assert!(err.contains("signing.key_path and signing.pkcs11.module"));
+        assert!(err.contains("signing.pkcs11.key_label"));
+        assert!(err.contains("signing.pkcs11.slot and signing.pkcs11.token_label"));
+        assert!(err.contains("signing.pkcs11.pin_source"));
+
+        let mut config = EffectiveConfig::default();
+        config.signing.pkcs11.key_label = Some("sayonara".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("require signing.pkcs11.module"));
+    }
+
+    #[test]
+    fn test_invalid_layer_value_fails_load() {
+        let mut env = HashMap::new();
+        env.insert(
+            "SAYONARA_IO__INITIAL_BUFFER_SIZE".to_string(),
+            "1000".to_string(),
+        );
+
+        assert!(isolated_loader().with_environment(env).load().is_err());
+    }
+
+    #[test]
+    fn test_safety_interlocks_cannot_be_relaxed() {
+        let dir = TempDir::new().unwrap();
+        let user = write_file(
+            &dir,
+            "user.toml",
+            r#"
+            [safety]
+            exclude_mounted_drives = false
+            "#,
+        );
+        let err = isolated_loader()
+            .with_user_file(Some(user))
+            .load()
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("safety.exclude_mounted_drives"));
+
+        for key in [
+            "SAYONARA_SAFETY__EXCLUDE_SYSTEM_DRIVES",
+            "SAYONARA_SAFETY__REQUIRE_CONFIRMATION",
+        ] {
+            let mut env = HashMap::new();
+            env.insert(key.to_string(), "false".to_string());
+            assert!(isolated_loader().with_environment(env).load().is_err());
+        }
+
+        // Setting them on is harmless
+        let mut env = HashMap::new();
+        env.insert(
+            "SAYONARA_SAFETY__EXCLUDE_SYSTEM_DRIVES".to_string(),
+            "true".to_string(),
+        );
+        let loaded = isolated_loader().with_environment(env).load().unwrap();
+        assert!(loaded.config.safety.exclude_system_drives);
+    }
+
+    #[test]
+    fn test_entries_report_layers() {
+        let loaded = isolated_loader()
+            .with_overrides(CliOverrides {
+                use_trim_after: Some(false),
+                ..Default::default()
+            })
+            .load()
+            .unwrap();
+
+        let entries = loaded.entries().unwrap();
+        let trim = entries
+            .iter()
+            .find(|(key, _, _)| key == "wipe.use_trim_after")
+            .unwrap();
+        assert_eq!(trim.1, Value::Bool(false));
+        assert_eq!(trim.2, ConfigLayer::CommandLine);
+        assert!(entries.iter().any(|(key, _, _)| key == "io.queue_depth"));
+    }
+}
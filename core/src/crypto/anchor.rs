@@ -0,0 +1,1319 @@
+// Blockchain anchoring of certificates
+//
+// The WipeCertificate contract (blockchain/contracts/WipeCertificate.sol)
+// maps a uint256 ID to a hash string and lets only its deploying `issuer`
+// account write to it. Anchoring a certificate records the hex SHA-256 of the
+// signed certificate (`certificates::certificate_hash`) there:
+// - a single certificate is anchored under its UUID as a uint256
+// - a batch is anchored as the RFC 9162 Merkle root over the certificate
+//   hashes, under a fresh UUID; each certificate keeps its audit path
+//
+// `issueCertificate(id, hash)` is ABI-encoded into a legacy EIP-155
+// transaction, signed with the issuer's secp256k1 key on this host and sent
+// with `eth_sendRawTransaction`, so the node needs no unlocked accounts. The
+// transaction hash and block number are stored in the certificate's `anchor`,
+// which neither the signature nor a timestamp covers.
+//
+// Verification checks that the transaction succeeded in the recorded block
+// and emitted `CertificateIssued` for the anchored hash, and that the
+// contract still returns that hash. The contract, chain and issuer come from
+// the verifier's own configuration (`ExpectedAnchor`), never from the
+// certificate, whose `anchor` anyone can rewrite to point at a contract of
+// their own.
+
+use super::certificates::{certificate_hash, WipeCertificate};
+use super::transparency::{self, Hash};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use k256::ecdsa::SigningKey as SecpSigningKey;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Timeout for one JSON-RPC request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to wait for the anchoring transaction to be mined
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Headroom added to the node's gas estimate, in percent
+const GAS_MARGIN_PERCENT: u64 = 20;
+
+/// Blockchain anchoring configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnchorConfig {
+    /// Ethereum JSON-RPC endpoint; certificates are not anchored when unset
+    pub rpc_url: Option<String>,
+
+    /// Address of the deployed WipeCertificate contract
+    pub contract: Option<String>,
+
+    /// secp256k1 private key (hex) of the contract's issuer account
+    pub key_path: Option<PathBuf>,
+
+    /// Chain ID the contract lives on; the node's is used when unset
+    pub chain_id: Option<u64>,
+
+    /// Issuer account anchors must come from when verifying; the account of
+    /// `key_path` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+}
+
+/// Contract, chain and issuer a verifier accepts anchors from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedAnchor {
+    pub contract: Address,
+    /// Any chain the node is on when unset
+    pub chain_id: Option<u64>,
+    /// Whoever the contract lets write when unset
+    pub issuer: Option<Address>,
+}
+
+impl ExpectedAnchor {
+    pub fn new(contract: Address) -> Self {
+        Self {
+            contract,
+            chain_id: None,
+            issuer: None,
+        }
+    }
+
+    /// Expectations from `config`, or None when no contract is configured
+    pub fn from_config(config: &AnchorConfig) -> Result<Option<Self>> {
+        let Some(contract) = &config.contract else {
+            return Ok(None);
+        };
+        let issuer = match (&config.issuer, &config.key_path) {
+            (Some(issuer), _) => Some(issuer.parse()?),
+            (None, Some(path)) => Some(EthereumKey::load(path)?.address()),
+            (None, None) => None,
+        };
+        Ok(Some(Self {
+            contract: contract.parse()?,
+            chain_id: config.chain_id,
+            issuer,
+        }))
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    pub fn with_issuer(mut self, issuer: Address) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+}
+
+/// Where and how a certificate was anchored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockchainAnchor {
+    pub chain_id: u64,
+
+    /// Contract address (0x-prefixed hex)
+    pub contract: String,
+
+    /// `id` argument of `issueCertificate`, as a decimal uint256
+    pub anchor_id: String,
+
+    /// Hex hash stored on chain: the certificate hash or the batch root
+    pub anchored_hash: String,
+
+    /// 0x-prefixed hash of the anchoring transaction
+    pub transaction_hash: String,
+
+    pub block_number: u64,
+
+    /// Position in a batch, when the anchored hash is a Merkle root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<AnchorBatch>,
+}
+
+/// Audit path from a certificate hash to its batch's anchored root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorBatch {
+    pub leaf_index: u64,
+    pub batch_size: u64,
+
+    /// Sibling hashes from the leaf up to the root (hex)
+    pub audit_path: Vec<String>,
+}
+
+/// Anchor confirmed on chain
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorVerification {
+    pub chain_id: u64,
+    pub contract: Address,
+    pub block_number: u64,
+    pub block_time: DateTime<Utc>,
+    /// Blocks mined on top of the anchoring block, plus one
+    pub confirmations: u64,
+    /// Account that issued the anchor
+    pub issuer: Address,
+}
+
+/// 20-byte Ethereum account or contract address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address(pub [u8; 20]);
+
+impl FromStr for Address {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let hex = s
+            .strip_prefix("0x")
+            .ok_or_else(|| anyhow!("Address must start with 0x (got {})", s))?;
+        let bytes = hex::decode(hex)
+            .ok()
+            .filter(|b| b.len() == 20)
+            .ok_or_else(|| anyhow!("Invalid address {} (expected 20 hex bytes)", s))?;
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&bytes);
+        Ok(Self(address))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// secp256k1 key of an Ethereum account
+pub struct EthereumKey {
+    key: SecpSigningKey,
+    address: Address,
+}
+
+impl fmt::Debug for EthereumKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EthereumKey")
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl EthereumKey {
+    /// Parse a 32-byte private key in hex, with or without 0x
+    pub fn from_hex(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let bytes = hex::decode(text.strip_prefix("0x").unwrap_or(text))
+            .ok()
+            .filter(|b| b.len() == 32)
+            .ok_or_else(|| anyhow!("Expected a 32-byte hex secp256k1 private key"))?;
+        let key = SecpSigningKey::from_slice(&bytes)
+            .map_err(|_| anyhow!("Invalid secp256k1 private key"))?;
+
+        let point = key.verifying_key().to_encoded_point(false);
+        let digest = keccak256(&point.as_bytes()[1..]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&digest[12..]);
+        Ok(Self {
+            key,
+            address: Address(address),
+        })
+    }
+
+    /// Load a hex private key file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read anchoring key {}", path.display()))?;
+        Self::from_hex(&text)
+            .with_context(|| format!("Failed to load anchoring key {}", path.display()))
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Sign a legacy transaction for `chain_id` (EIP-155), returning the raw
+    /// encoding to submit
+    fn sign_transaction(&self, tx: &LegacyTransaction, chain_id: u64) -> Result<Vec<u8>> {
+        let mut fields = tx.rlp_fields();
+        fields.extend([rlp_uint(chain_id as u128), rlp_uint(0), rlp_uint(0)]);
+        let hash = keccak256(&rlp_list(&fields));
+
+        let (signature, recovery) = self
+            .key
+            .sign_prehash_recoverable(&hash)
+            .map_err(|_| anyhow!("secp256k1 signing failed"))?;
+        let bytes = signature.to_bytes();
+        let v = recovery.to_byte() as u128 + chain_id as u128 * 2 + 35;
+
+        let mut fields = tx.rlp_fields();
+        fields.extend([
+            rlp_uint(v),
+            rlp_bytes(strip_leading_zeros(&bytes[..32])),
+            rlp_bytes(strip_leading_zeros(&bytes[32..])),
+        ]);
+        Ok(rlp_list(&fields))
+    }
+}
+
+/// Pre-EIP-2718 transaction, accepted by every Ethereum node
+#[derive(Debug, Clone)]
+struct LegacyTransaction {
+    nonce: u64,
+    gas_price: u128,
+    gas_limit: u64,
+    to: Address,
+    value: u128,
+    data: Vec<u8>,
+}
+
+impl LegacyTransaction {
+    fn rlp_fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            rlp_uint(self.nonce as u128),
+            rlp_uint(self.gas_price),
+            rlp_uint(self.gas_limit as u128),
+            rlp_bytes(&self.to.0),
+            rlp_uint(self.value),
+            rlp_bytes(&self.data),
+        ]
+    }
+}
+
+/// Submits anchors for the configured contract and issuer key
+#[derive(Debug)]
+pub struct AnchorClient {
+    rpc: RpcClient,
+    contract: Address,
+    key: EthereumKey,
+    chain_id: Option<u64>,
+}
+
+impl AnchorClient {
+    pub fn new(rpc_url: &str, contract: Address, key: EthereumKey) -> Self {
+        Self {
+            rpc: RpcClient::new(rpc_url),
+            contract,
+            key,
+            chain_id: None,
+        }
+    }
+
+    /// Client for `config`, or None when no RPC endpoint is configured
+    pub fn from_config(config: &AnchorConfig) -> Result<Option<Self>> {
+        let Some(url) = &config.rpc_url else {
+            return Ok(None);
+        };
+        let contract = config
+            .contract
+            .as_deref()
+            .ok_or_else(|| anyhow!("anchor.rpc_url is set but anchor.contract is not"))?
+            .parse()?;
+        let key_path = config
+            .key_path
+            .as_deref()
+            .ok_or_else(|| anyhow!("anchor.rpc_url is set but anchor.key_path is not"))?;
+
+        let mut client = Self::new(url, contract, EthereumKey::load(key_path)?);
+        if let Some(chain_id) = config.chain_id {
+            client = client.with_chain_id(chain_id);
+        }
+        Ok(Some(client))
+    }
+
+    /// Refuse to anchor unless the node is on `chain_id`
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc.url
+    }
+
+    pub fn contract(&self) -> Address {
+        self.contract
+    }
+
+    /// Account the transactions are sent from
+    pub fn address(&self) -> Address {
+        self.key.address()
+    }
+
+    /// Anchor one certificate under its own ID
+    pub fn anchor(&self, certificate: &mut WipeCertificate) -> Result<()> {
+        let id = Uuid::parse_str(&certificate.certificate_id)
+            .with_context(|| {
+                format!(
+                    "Certificate ID {} is not a UUID and cannot be anchored on its own",
+                    certificate.certificate_id
+                )
+            })?
+            .as_u128();
+        let hash = hex::encode(certificate_hash(certificate)?);
+
+        certificate.anchor = Some(self.issue(id, &hash)?);
+        Ok(())
+    }
+
+    /// Anchor the Merkle root over `certificates` in one transaction
+    pub fn anchor_batch(&self, certificates: &mut [WipeCertificate]) -> Result<()> {
+        if let [certificate] = certificates {
+            return self.anchor(certificate);
+        }
+        if certificates.is_empty() {
+            bail!("No certificates to anchor");
+        }
+
+        let leaves = certificates
+            .iter()
+            .map(|c| Ok(transparency::leaf_hash(&certificate_hash(c)?)))
+            .collect::<Result<Vec<Hash>>>()?;
+        let root = hex::encode(transparency::root_hash(&leaves));
+
+        let anchor = self.issue(Uuid::new_v4().as_u128(), &root)?;
+        for (index, certificate) in certificates.iter_mut().enumerate() {
+            certificate.anchor = Some(BlockchainAnchor {
+                batch: Some(AnchorBatch {
+                    leaf_index: index as u64,
+                    batch_size: leaves.len() as u64,
+                    audit_path: transparency::inclusion_path(&leaves, index)
+                        .iter()
+                        .map(hex::encode)
+                        .collect(),
+                }),
+                ..anchor.clone()
+            });
+        }
+        Ok(())
+    }
+
+    /// Send `issueCertificate(id, hash)` and wait for it to be mined
+    fn issue(&self, id: u128, hash: &str) -> Result<BlockchainAnchor> {
+        let chain_id = self.rpc.chain_id()?;
+        if self.chain_id.is_some_and(|expected| expected != chain_id) {
+            bail!(
+                "{} is on chain {}, expected {}",
+                self.rpc.url,
+                chain_id,
+                self.chain_id.unwrap_or_default()
+            );
+        }
+
+        // The contract would revert; say why instead
+        let issuer = self.rpc.issuer(self.contract)?;
+        if issuer != self.address() {
+            bail!(
+                "Account {} is not the issuer of contract {} (issuer is {})",
+                self.address(),
+                self.contract,
+                issuer
+            );
+        }
+
+        let from = self.address().to_string();
+        let to = self.contract.to_string();
+        let data = encode_issue_certificate(id, hash);
+        let call = json!({ "from": from, "to": to, "data": format!("0x{}", hex::encode(&data)) });
+
+        let gas = self.rpc.quantity("eth_estimateGas", json!([call]))? as u64;
+        let tx = LegacyTransaction {
+            nonce: self
+                .rpc
+                .quantity("eth_getTransactionCount", json!([from, "pending"]))?
+                as u64,
+            gas_price: self.rpc.quantity("eth_gasPrice", json!([]))?,
+            gas_limit: gas + gas * GAS_MARGIN_PERCENT / 100,
+            to: self.contract,
+            value: 0,
+            data,
+        };
+        let raw = self.key.sign_transaction(&tx, chain_id)?;
+        let tx_hash = format!("0x{}", hex::encode(keccak256(&raw)));
+
+        let sent = self
+            .rpc
+            .call(
+                "eth_sendRawTransaction",
+                json!([format!("0x{}", hex::encode(&raw))]),
+            )
+            .context("Anchoring transaction was rejected")?;
+        if sent.as_str().map(str::to_ascii_lowercase) != Some(tx_hash.clone()) {
+            bail!("Node returned transaction hash {} for {}", sent, tx_hash);
+        }
+
+        let receipt = self.wait_for_receipt(&tx_hash)?;
+        if receipt["status"].as_str() != Some("0x1") {
+            bail!("Anchoring transaction {} reverted", tx_hash);
+        }
+        let block_number = parse_quantity(&receipt["blockNumber"])? as u64;
+
+        tracing::info!(
+            tx = %tx_hash,
+            block = block_number,
+            id = %id,
+            "Certificate hash anchored"
+        );
+        Ok(BlockchainAnchor {
+            chain_id,
+            contract: self.contract.to_string(),
+            anchor_id: id.to_string(),
+            anchored_hash: hash.to_string(),
+            transaction_hash: tx_hash,
+            block_number,
+            batch: None,
+        })
+    }
+
+    fn wait_for_receipt(&self, tx_hash: &str) -> Result<Value> {
+        let started = Instant::now();
+        loop {
+            let receipt = self
+                .rpc
+                .call("eth_getTransactionReceipt", json!([tx_hash]))?;
+            if !receipt.is_null() {
+                return Ok(receipt);
+            }
+            if started.elapsed() > RECEIPT_TIMEOUT {
+                bail!(
+                    "Anchoring transaction {} was not mined within {} seconds",
+                    tx_hash,
+                    RECEIPT_TIMEOUT.as_secs()
+                );
+            }
+            std::thread::sleep(RECEIPT_POLL_INTERVAL);
+        }
+    }
+}
+
+/// Check `certificate`'s anchor against the chain behind `rpc_url`
+///
+/// The anchor must be in `expected`'s contract, on its chain and from its
+/// issuer. Returns None for certificates that were never anchored.
+pub fn verify_anchor(
+    certificate: &WipeCertificate,
+    rpc_url: &str,
+    expected: &ExpectedAnchor,
+) -> Result<Option<AnchorVerification>> {
+    let Some(anchor) = &certificate.anchor else {
+        return Ok(None);
+    };
+    let contract: Address = anchor.contract.parse()?;
+    if contract != expected.contract {
+        bail!(
+            "Anchored in contract {}, not the configured {}",
+            contract,
+            expected.contract
+        );
+    }
+    if let Some(chain_id) = expected.chain_id.filter(|id| *id != anchor.chain_id) {
+        bail!(
+            "Anchored on chain {}, not the configured {}",
+            anchor.chain_id,
+            chain_id
+        );
+    }
+    let rpc = RpcClient::new(rpc_url);
+
+    // The certificate must hash to what was anchored
+    let hash = certificate_hash(certificate)?;
+    match &anchor.batch {
+        None => {
+            if hex::encode(hash) != anchor.anchored_hash {
+                bail!(
+                    "Certificate hashes to {} but {} was anchored",
+                    hex::encode(hash),
+                    anchor.anchored_hash
+                );
+            }
+        }
+        Some(batch) => {
+            let path = batch
+                .audit_path
+                .iter()
+                .map(|h| parse_hash(h).ok_or_else(|| anyhow!("Invalid audit path hash {}", h)))
+                .collect::<Result<Vec<_>>>()?;
+            let root = parse_hash(&anchor.anchored_hash)
+                .ok_or_else(|| anyhow!("Invalid anchored hash {}", anchor.anchored_hash))?;
+            if !transparency::verify_inclusion(
+                &transparency::leaf_hash(&hash),
+                batch.leaf_index,
+                batch.batch_size,
+                &path,
+                &root,
+            ) {
+                bail!(
+                    "Certificate is not entry {} of the batch anchored as {}",
+                    batch.leaf_index,
+                    anchor.anchored_hash
+                );
+            }
+        }
+    }
+
+    let chain_id = rpc.chain_id()?;
+    if chain_id != anchor.chain_id {
+        bail!(
+            "{} is on chain {}, the anchor is on chain {}",
+            rpc_url,
+            chain_id,
+            anchor.chain_id
+        );
+    }
+    let id = parse_decimal_u256(&anchor.anchor_id)?;
+
+    let receipt = rpc.call(
+        "eth_getTransactionReceipt",
+        json!([anchor.transaction_hash]),
+    )?;
+    if receipt.is_null() {
+        bail!("Transaction {} is not on chain", anchor.transaction_hash);
+    }
+    if receipt["status"].as_str() != Some("0x1") {
+        bail!("Transaction {} reverted", anchor.transaction_hash);
+    }
+    let block_number = parse_quantity(&receipt["blockNumber"])? as u64;
+    if block_number != anchor.block_number {
+        bail!(
+            "Transaction {} is in block {}, the certificate says {}",
+            anchor.transaction_hash,
+            block_number,
+            anchor.block_number
+        );
+    }
+
+    // The CertificateIssued event ties the transaction to the ID and hash
+    let topic = format!(
+        "0x{}",
+        hex::encode(keccak256(CERTIFICATE_ISSUED_EVENT.as_bytes()))
+    );
+    let id_topic = format!("0x{}", hex::encode(id));
+    let issuer = receipt["logs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|log| {
+            let address: Address = log["address"].as_str()?.parse().ok()?;
+            let topics = log["topics"].as_array()?;
+            let matches = address == contract
+                && topics.first()?.as_str()?.eq_ignore_ascii_case(&topic)
+                && topics.get(1)?.as_str()?.eq_ignore_ascii_case(&id_topic)
+                && decode_string(&hex::decode(log["data"].as_str()?.strip_prefix("0x")?).ok()?)
+                    .ok()?
+                    == anchor.anchored_hash;
+            matches.then(|| topic_address(topics.get(2)?.as_str()?))?
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Transaction {} did not issue {} under ID {} on {}",
+                anchor.transaction_hash,
+                anchor.anchored_hash,
+                anchor.anchor_id,
+                contract
+            )
+        })?;
+    if let Some(expected) = expected.issuer.filter(|e| *e != issuer) {
+        bail!(
+            "Anchored by {}, not the configured issuer {}",
+            issuer,
+            expected
+        );
+    }
+
+    // The issuer can overwrite an ID later; the anchor only counts if not
+    let current = rpc.get_certificate(contract, &id)?;
+    if current != anchor.anchored_hash {
+        bail!(
+            "Contract {} now holds \"{}\" under ID {}, not the anchored hash",
+            contract,
+            current,
+            anchor.anchor_id
+        );
+    }
+
+    let block = rpc.call(
+        "eth_getBlockByNumber",
+        json!([format!("0x{:x}", block_number), false]),
+    )?;
+    let block_time = DateTime::from_timestamp(parse_quantity(&block["timestamp"])? as i64, 0)
+        .ok_or_else(|| anyhow!("Invalid timestamp in block {}", block_number))?;
+    let latest = rpc.quantity("eth_blockNumber", json!([]))? as u64;
+
+    Ok(Some(AnchorVerification {
+        chain_id,
+        contract,
+        block_number,
+        block_time,
+        confirmations: latest.saturating_sub(block_number) + 1,
+        issuer,
+    }))
+}
+
+// ==================== JSON-RPC ====================
+
+#[derive(Debug)]
+struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let body = self
+            .agent
+            .post(&self.url)
+            .set("Content-Type", "application/json")
+            .send_string(&request.to_string())
+            .with_context(|| format!("{} request to {} failed", method, self.url))?
+            .into_string()?;
+        let response: Value = serde_json::from_str(&body)
+            .with_context(|| format!("{} returned an invalid {} response", self.url, method))?;
+
+        if let Some(error) = response.get("error") {
+            bail!(
+                "{} failed: {}",
+                method,
+                error["message"].as_str().unwrap_or("unknown error")
+            );
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    fn quantity(&self, method: &str, params: Value) -> Result<u128> {
+        parse_quantity(&self.call(method, params)?).with_context(|| format!("{} result", method))
+    }
+
+    fn chain_id(&self) -> Result<u64> {
+        Ok(self.quantity("eth_chainId", json!([]))? as u64)
+    }
+
+    fn eth_call(&self, contract: Address, data: &[u8]) -> Result<Vec<u8>> {
+        let result = self.call(
+            "eth_call",
+            json!([
+                { "to": contract.to_string(), "data": format!("0x{}", hex::encode(data)) },
+                "latest"
+            ]),
+        )?;
+        result
+            .as_str()
+            .and_then(|hex| hex::decode(hex.strip_prefix("0x")?).ok())
+            .ok_or_else(|| anyhow!("eth_call returned {}", result))
+    }
+
+    fn issuer(&self, contract: Address) -> Result<Address> {
+        let word = self.eth_call(contract, &selector(ISSUER))?;
+        if word.len() != 32 {
+            bail!("{} is not a WipeCertificate contract", contract);
+        }
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&word[12..]);
+        Ok(Address(address))
+    }
+
+    fn get_certificate(&self, contract: Address, id: &[u8; 32]) -> Result<String> {
+        let mut data = selector(GET_CERTIFICATE).to_vec();
+        data.extend_from_slice(id);
+        decode_string(&self.eth_call(contract, &data)?)
+            .with_context(|| format!("{} is not a WipeCertificate contract", contract))
+    }
+}
+
+fn parse_quantity(value: &Value) -> Result<u128> {
+    value
+        .as_str()
+        .and_then(|hex| hex.strip_prefix("0x"))
+        .and_then(|hex| u128::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| anyhow!("Expected a hex quantity, got {}", value))
+}
+
+// ==================== ABI ====================
+
+const ISSUE_CERTIFICATE: &str = "issueCertificate(uint256,string)";
+const GET_CERTIFICATE: &str = "getCertificate(uint256)";
+const ISSUER: &str = "issuer()";
+const CERTIFICATE_ISSUED_EVENT: &str = "CertificateIssued(uint256,string,address)";
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// First four bytes of the Keccak-256 of a function signature
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn abi_uint(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Calldata for `issueCertificate(id, hash)`
+pub fn encode_issue_certificate(id: u128, hash: &str) -> Vec<u8> {
+    let mut data = selector(ISSUE_CERTIFICATE).to_vec();
+    data.extend_from_slice(&abi_uint(id));
+    // Offset of the string's tail after the two head words
+    data.extend_from_slice(&abi_uint(64));
+    data.extend(abi_string_tail(hash));
+    data
+}
+
+/// Length word and zero-padded bytes of a dynamic `string`
+fn abi_string_tail(text: &str) -> Vec<u8> {
+    let mut tail = abi_uint(text.len() as u128).to_vec();
+    tail.extend_from_slice(text.as_bytes());
+    tail.resize(32 + text.len().div_ceil(32) * 32, 0);
+    tail
+}
+
+/// Decode an ABI-encoded `string` return value or event data
+fn decode_string(data: &[u8]) -> Result<String> {
+    let word = |at: usize| -> Result<usize> {
+        let word = data
+            .get(at..at + 32)
+            .ok_or_else(|| anyhow!("ABI data too short"))?;
+        if word[..24].iter().any(|b| *b != 0) {
+            bail!("ABI offset or length out of range");
+        }
+        Ok(u64::from_be_bytes(word[24..].try_into()?) as usize)
+    };
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let bytes = data
+        .get(offset + 32..offset + 32 + len)
+        .ok_or_else(|| anyhow!("ABI string runs past the data"))?;
+    Ok(String::from_utf8(bytes.to_vec())?)
+}
+
+/// Address in the low 20 bytes of an indexed event topic
+fn topic_address(topic: &str) -> Option<Address> {
+    let bytes = hex::decode(topic.strip_prefix("0x")?).ok()?;
+    let mut address = [0u8; 20];
+    address.copy_from_slice(bytes.get(12..32)?);
+    Some(Address(address))
+}
+
+fn parse_decimal_u256(text: &str) -> Result<[u8; 32]> {
+    let value: u128 = text
+        .parse()
+        .map_err(|_| anyhow!("Invalid anchor ID {}", text))?;
+    Ok(abi_uint(value))
+}
+
+// ==================== RLP ====================
+
+fn rlp_length_prefix(len: usize, short: u8, long: u8) -> Vec<u8> {
+    if len <= 55 {
+        vec![short + len as u8]
+    } else {
+        let len_bytes = strip_leading_zeros(&len.to_be_bytes()).to_vec();
+        let mut prefix = vec![long + len_bytes.len() as u8];
+        prefix.extend(len_bytes);
+        prefix
+    }
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if let [byte] = bytes {
+        if *byte < 0x80 {
+            return vec![*byte];
+        }
+    }
+    let mut out = rlp_length_prefix(bytes.len(), 0x80, 0xb7);
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn rlp_uint(value: u128) -> Vec<u8> {
+    rlp_bytes(strip_leading_zeros(&value.to_be_bytes()))
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut out = rlp_length_prefix(payload.len(), 0xc0, 0xf7);
+    out.extend(payload);
+    out
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn parse_hash(hex: &str) -> Option<Hash> {
+    hex::decode(hex).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::signing::{SignatureAlgorithm, SigningKey};
+    use crate::crypto::CertificateGenerator;
+    use crate::crypto::{VerificationResult, WipeDetails};
+    use crate::{DriveCapabilities, DriveInfo, DriveType, EncryptionStatus};
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// First account of a Hardhat (and Anvil) development node
+    const HARDHAT_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const HARDHAT_ADDRESS: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    const CONTRACT: &str = "0x5fbdb2315678afecb367f032d93f642f64180aa3";
+    const CHAIN_ID: u64 = 31337;
+
+    #[test]
+    fn test_keccak_and_selectors() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(selector("transfer(address,uint256)")),
+            "a9059cbb"
+        );
+    }
+
+    #[test]
+    fn test_eip155_signing_vector() {
+        // Example transaction from EIP-155
+        let key = EthereumKey::from_hex(&"46".repeat(32)).unwrap();
+        let tx = LegacyTransaction {
+            nonce: 9,
+            gas_price: 20_000_000_000,
+            gas_limit: 21000,
+            to: Address([0x35; 20]),
+            value: 1_000_000_000_000_000_000,
+            data: Vec::new(),
+        };
+        assert_eq!(
+            hex::encode(key.sign_transaction(&tx, 1).unwrap()),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+
+        let hardhat = EthereumKey::from_hex(HARDHAT_KEY).unwrap();
+        assert_eq!(hardhat.address().to_string(), HARDHAT_ADDRESS);
+        assert!(EthereumKey::from_hex("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_issue_certificate_calldata() {
+        let hash = "ab".repeat(32);
+        let data = encode_issue_certificate(7, &hash);
+        assert_eq!(data[..4], selector(ISSUE_CERTIFICATE));
+        assert_eq!(data.len(), 4 + 32 * 5);
+        assert_eq!(data[4..36], abi_uint(7));
+        assert_eq!(data[36..68], abi_uint(64));
+        assert_eq!(data[68..100], abi_uint(64));
+        assert_eq!(&data[100..164], hash.as_bytes());
+
+        // The string tail decodes back from a return value layout
+        let mut returned = abi_uint(32).to_vec();
+        returned.extend(abi_string_tail(&hash));
+        assert_eq!(decode_string(&returned).unwrap(), hash);
+        assert!(decode_string(&returned[..40]).is_err());
+    }
+
+    /// In-process stand-in for an Ethereum node running the contract
+    #[derive(Default)]
+    struct MockChain {
+        issuer: Option<Address>,
+        certificates: HashMap<[u8; 32], String>,
+        receipts: HashMap<String, Value>,
+        block: u64,
+        nonce: u64,
+    }
+
+    fn mock_node(issuer: Address) -> (String, Arc<Mutex<MockChain>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", server.server_addr().to_ip().unwrap());
+        let chain = Arc::new(Mutex::new(MockChain {
+            issuer: Some(issuer),
+            ..Default::default()
+        }));
+
+        let state = chain.clone();
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let call: Value = serde_json::from_str(&body).unwrap();
+                let result = mock_rpc(&mut state.lock().unwrap(), &call);
+                let response = json!({ "jsonrpc": "2.0", "id": call["id"], "result": result });
+                let _ = request.respond(tiny_http::Response::from_string(response.to_string()));
+            }
+        });
+        (url, chain)
+    }
+
+    fn mock_rpc(chain: &mut MockChain, call: &Value) -> Value {
+        let params = &call["params"];
+        let hex = |bytes: &[u8]| Value::from(format!("0x{}", hex::encode(bytes)));
+        let issuer = chain.issuer.unwrap();
+        match call["method"].as_str().unwrap() {
+            "eth_chainId" => json!(format!("0x{:x}", CHAIN_ID)),
+            "eth_gasPrice" => json!("0x3b9aca00"),
+            "eth_estimateGas" => json!("0xb71b"),
+            "eth_blockNumber" => json!(format!("0x{:x}", chain.block)),
+            "eth_getTransactionCount" => json!(format!("0x{:x}", chain.nonce)),
+            "eth_getBlockByNumber" => json!({ "timestamp": "0x6553f100" }),
+            "eth_getTransactionReceipt" => chain
+                .receipts
+                .get(params[0].as_str().unwrap())
+                .cloned()
+                .unwrap_or(Value::Null),
+            "eth_call" => {
+                let data = hex::decode(&params[0]["data"].as_str().unwrap()[2..])
+                    .ok()
+                    .unwrap();
+                if data[..4] == selector(ISSUER) {
+                    let mut word = [0u8; 32];
+                    word[12..].copy_from_slice(&issuer.0);
+                    hex(&word)
+                } else {
+                    let id: [u8; 32] = data[4..36].try_into().unwrap();
+                    let stored = chain.certificates.get(&id).cloned().unwrap_or_default();
+                    let mut returned = abi_uint(32).to_vec();
+                    returned.extend(abi_string_tail(&stored));
+                    hex(&returned)
+                }
+            }
+            "eth_sendRawTransaction" => {
+                let raw = hex::decode(&params[0].as_str().unwrap()[2..]).ok().unwrap();
+                let fields = rlp_decode_list(&raw);
+
+                // Recover the sender as the node would
+                let mut unsigned = fields[..6].to_vec();
+                unsigned.extend([
+                    strip_leading_zeros(&CHAIN_ID.to_be_bytes()).to_vec(),
+                    Vec::new(),
+                    Vec::new(),
+                ]);
+                let v = fields[6].iter().fold(0u64, |v, b| v << 8 | *b as u64);
+                let mut rs = [0u8; 64];
+                rs[32 - fields[7].len()..32].copy_from_slice(&fields[7]);
+                rs[64 - fields[8].len()..].copy_from_slice(&fields[8]);
+                let sender = VerifyingKey::recover_from_prehash(
+                    &keccak256(&rlp_list(
+                        &unsigned.iter().map(|f| rlp_bytes(f)).collect::<Vec<_>>(),
+                    )),
+                    &Signature::from_slice(&rs).unwrap(),
+                    RecoveryId::from_byte((v - CHAIN_ID * 2 - 35) as u8).unwrap(),
+                )
+                .unwrap();
+                let point = sender.to_encoded_point(false);
+                assert_eq!(keccak256(&point.as_bytes()[1..])[12..], issuer.0);
+
+                let data = &fields[5];
+                assert_eq!(data[..4], selector(ISSUE_CERTIFICATE));
+                let id: [u8; 32] = data[4..36].try_into().unwrap();
+                let len = data[4 + 64 + 31] as usize;
+                let hash = String::from_utf8(data[4 + 96..4 + 96 + len].to_vec()).unwrap();
+
+                chain.nonce += 1;
+                chain.block += 1;
+                chain.certificates.insert(id, hash.clone());
+                let mut event_data = abi_uint(32).to_vec();
+                event_data.extend(abi_string_tail(&hash));
+                let mut issuer_topic = [0u8; 32];
+                issuer_topic[12..].copy_from_slice(&issuer.0);
+                let tx_hash = format!("0x{}", hex::encode(keccak256(&raw)));
+                chain.receipts.insert(
+                    tx_hash.clone(),
+                    json!({
+                        "status": "0x1",
+                        "blockNumber": format!("0x{:x}", chain.block),
+                        "logs": [{
+                            "address": hex(&fields[3]),
+                            "topics": [
+                                hex(&keccak256(CERTIFICATE_ISSUED_EVENT.as_bytes())),
+                                hex(&id),
+                                hex(&issuer_topic),
+                            ],
+                            "data": hex(&event_data),
+                        }],
+                    }),
+                );
+                json!(tx_hash)
+            }
+            method => panic!("unexpected RPC method {}", method),
+        }
+    }
+
+    /// Items of an RLP list of byte strings
+    fn rlp_decode_list(data: &[u8]) -> Vec<Vec<u8>> {
+        let (payload, _) = rlp_item(data);
+        let mut items = Vec::new();
+        let mut rest = payload;
+        while !rest.is_empty() {
+            let (item, next) = rlp_item(rest);
+            items.push(item.to_vec());
+            rest = next;
+        }
+        items
+    }
+
+    /// Payload of the first RLP item and what follows it
+    fn rlp_item(data: &[u8]) -> (&[u8], &[u8]) {
+        let (offset, len) = match data[0] {
+            0x00..=0x7f => return (&data[..1], &data[1..]),
+            b @ 0x80..=0xb7 => (1, (b - 0x80) as usize),
+            b @ 0xb8..=0xbf => {
+                let n = (b - 0xb7) as usize;
+                (
+                    1 + n,
+                    data[1..1 + n].iter().fold(0, |l, b| l << 8 | *b as usize),
+                )
+            }
+            b @ 0xc0..=0xf7 => (1, (b - 0xc0) as usize),
+            b => {
+                let n = (b - 0xf7) as usize;
+                (
+                    1 + n,
+                    data[1..1 + n].iter().fold(0, |l, b| l << 8 | *b as usize),
+                )
+            }
+        };
+        (&data[offset..offset + len], &data[offset + len..])
+    }
+
+    fn issue(serial: &str) -> WipeCertificate {
+        let drive = DriveInfo {
+            device_path: "/dev/sdz".to_string(),
+            model: "Test Drive".to_string(),
+            serial: serial.to_string(),
+            size: 1 << 30,
+            drive_type: DriveType::HDD,
+            encryption_status: EncryptionStatus::None,
+            capabilities: DriveCapabilities::default(),
+            health_status: None,
+            temperature_celsius: None,
+        };
+        let (key, _) = SigningKey::generate(SignatureAlgorithm::Ed25519).unwrap();
+        CertificateGenerator::with_key(key)
+            .generate_certificate(
+                &drive,
+                WipeDetails {
+                    algorithm_used: "Zero".to_string(),
+                    passes_completed: 1,
+                    duration_seconds: 1,
+                    operator_id: None,
+                    checkpoint_rejections: Vec::new(),
+                },
+                VerificationResult {
+                    verified: true,
+                    entropy_score: 0.0,
+                    recovery_test_passed: true,
+                    verification_timestamp: Utc::now(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn hardhat_client(url: &str) -> AnchorClient {
+        AnchorClient::new(
+            url,
+            CONTRACT.parse().unwrap(),
+            EthereumKey::from_hex(HARDHAT_KEY).unwrap(),
+        )
+        .with_chain_id(CHAIN_ID)
+    }
+
+    fn expected() -> ExpectedAnchor {
+        ExpectedAnchor::new(CONTRACT.parse().unwrap())
+            .with_chain_id(CHAIN_ID)
+            .with_issuer(HARDHAT_ADDRESS.parse().unwrap())
+    }
+
+    #[test]
+    fn test_anchor_and_verify_against_mock_node() {
+        let issuer: Address = HARDHAT_ADDRESS.parse().unwrap();
+        let (url, chain) = mock_node(issuer);
+        let client = hardhat_client(&url);
+
+        let mut certificate = issue("SN1");
+        client.anchor(&mut certificate).unwrap();
+        let anchor = certificate.anchor.clone().unwrap();
+        assert_eq!(anchor.block_number, 1);
+        assert_eq!(
+            anchor.anchored_hash,
+            hex::encode(certificate_hash(&certificate).unwrap())
+        );
+
+        let verified = verify_anchor(&certificate, &url, &expected())
+            .unwrap()
+            .unwrap();
+        assert_eq!(verified.issuer, issuer);
+        assert_eq!(verified.chain_id, CHAIN_ID);
+
+        // The anchor is outside the signed bytes, the certificate is not
+        let mut modified = certificate.clone();
+        modified.wipe_details.passes_completed = 3;
+        assert!(verify_anchor(&modified, &url, &expected()).is_err());
+        assert!(verify_anchor(&issue("SN2"), &url, &expected())
+            .unwrap()
+            .is_none());
+
+        // A batch shares one transaction; each certificate proves its leaf
+        let mut batch: Vec<_> = (0..3).map(|i| issue(&format!("B{}", i))).collect();
+        client.anchor_batch(&mut batch).unwrap();
+        for certificate in &batch {
+            let anchor = certificate.anchor.as_ref().unwrap();
+            assert_eq!(anchor.block_number, 2);
+            assert!(anchor.batch.is_some());
+            verify_anchor(certificate, &url, &expected()).unwrap();
+        }
+        let mut swapped = batch[0].clone();
+        swapped.anchor = batch[1].anchor.clone();
+        assert!(verify_anchor(&swapped, &url, &expected()).is_err());
+
+        // Overwriting the ID on chain voids the anchor
+        let id = parse_decimal_u256(&anchor.anchor_id).unwrap();
+        chain
+            .lock()
+            .unwrap()
+            .certificates
+            .insert(id, "0".repeat(64));
+        let err = verify_anchor(&certificate, &url, &expected()).unwrap_err();
+        assert!(err.to_string().contains("now holds"), "{}", err);
+    }
+
+    #[test]
+    fn test_anchor_refuses_wrong_issuer_or_chain() {
+        let (url, _) = mock_node(Address([0x11; 20]));
+        let err = hardhat_client(&url).anchor(&mut issue("SN1")).unwrap_err();
+        assert!(err.to_string().contains("not the issuer"), "{}", err);
+
+        let err = hardhat_client(&url)
+            .with_chain_id(1)
+            .anchor(&mut issue("SN1"))
+            .unwrap_err();
+        assert!(err.to_string().contains("expected 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_verify_refuses_anchors_outside_the_configured_contract() {
+        // Anyone can deploy the contract and anchor a forged certificate in
+        // it, then point the certificate's anchor there
+        let rogue_key = EthereumKey::from_hex(&"42".repeat(32)).unwrap();
+        let (url, _) = mock_node(rogue_key.address());
+        let rogue_contract: Address = "0x000000000000000000000000000000000000dead"
+            .parse()
+            .unwrap();
+        let mut certificate = issue("SN1");
+        AnchorClient::new(&url, rogue_contract, rogue_key)
+            .anchor(&mut certificate)
+            .unwrap();
+
+        let trusting = ExpectedAnchor::new(rogue_contract);
+        assert!(verify_anchor(&certificate, &url, &trusting)
+            .unwrap()
+            .is_some());
+        let err = verify_anchor(&certificate, &url, &expected()).unwrap_err();
+        assert!(err.to_string().contains("not the configured"), "{}", err);
+
+        // Or in the right contract's address on another chain, where it
+        // deployed with a different issuer
+        let rogue_key = EthereumKey::from_hex(&"42".repeat(32)).unwrap();
+        let (url, _) = mock_node(rogue_key.address());
+        let mut certificate = issue("SN2");
+        AnchorClient::new(&url, CONTRACT.parse().unwrap(), rogue_key)
+            .anchor(&mut certificate)
+            .unwrap();
+        let err = verify_anchor(&certificate, &url, &expected()).unwrap_err();
+        assert!(
+            err.to_string().contains("not the configured issuer"),
+            "{}",
+            err
+        );
+
+        let mut wrong_chain = expected();
+        wrong_chain.chain_id = Some(1);
+        let err = verify_anchor(&certificate, &url, &wrong_chain).unwrap_err();
+        assert!(err.to_string().contains("chain"), "{}", err);
+    }
+
+    #[test]
+    fn test_expected_anchor_from_config() {
+        assert!(ExpectedAnchor::from_config(&AnchorConfig::default())
+            .unwrap()
+            .is_none());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let key_path = dir.path().join("anchor.key");
+        std::fs::write(&key_path, HARDHAT_KEY).unwrap();
+        let config = AnchorConfig {
+            contract: Some(CONTRACT.to_string()),
+            key_path: Some(key_path),
+            chain_id: Some(CHAIN_ID),
+            ..Default::default()
+        };
+        assert_eq!(
+            ExpectedAnchor::from_config(&config).unwrap(),
+            Some(expected())
+        );
+
+        // An explicit issuer wins over the key's account
+        let config = AnchorConfig {
+            issuer: Some("0x1111111111111111111111111111111111111111".to_string()),
+            ..config
+        };
+        let pinned = ExpectedAnchor::from_config(&config).unwrap().unwrap();
+        assert_eq!(pinned.issuer, Some(Address([0x11; 20])));
+    }
+
+    /// Runs against a real node:
+    ///
+    /// ```text
+    /// cd blockchain && npx hardhat node
+    /// PRIVATE_KEY=<account #0 key> npx hardhat run scripts/deploy.ts --network localhost
+    /// SAYONARA_ANCHOR_CONTRACT=<address> cargo test --lib anchor -- --ignored
+    /// ```
+    #[test]
+    #[ignore = "needs a Hardhat node with the WipeCertificate contract deployed"]
+    fn test_anchor_on_hardhat_node() {
+        let url = std::env::var("SAYONARA_ANCHOR_RPC")
+            .unwrap_or_else(|_| "http://127.0.0.1:8545".to_string());
+        let contract = std::env::var("SAYONARA_ANCHOR_CONTRACT")
+            .expect("SAYONARA_ANCHOR_CONTRACT must name the deployed contract");
+        let client = AnchorClient::new(
+            &url,
+            contract.parse().unwrap(),
+            EthereumKey::from_hex(HARDHAT_KEY).unwrap(),
+        );
+
+        let expected = ExpectedAnchor::new(client.contract());
+
+        let mut certificate = issue("HARDHAT-1");
+        client.anchor(&mut certificate).unwrap();
+        verify_anchor(&certificate, &url, &expected)
+            .unwrap()
+            .unwrap();
+
+        let mut batch: Vec<_> = (0..5).map(|i| issue(&format!("HARDHAT-B{}", i))).collect();
+        client.anchor_batch(&mut batch).unwrap();
+        for certificate in &batch {
+            verify_anchor(certificate, &url, &expected)
+                .unwrap()
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,766 @@
+// Per-drive evidence bundles
+//
+// The artifacts of a wipe outlive the process that produced them in
+// different places: the certificate file, the bad sector log, the SMART
+// snapshot and verification output taken during the run, and the checkpoint
+// history in SQLite (which is deleted once a wipe completes). When a
+// certificate is issued they are copied into an evidence directory named
+// after the certificate, so they can later be handed over as one archive.
+//
+// An exported bundle is a tar or zip file holding those artifacts and a
+// `manifest.json` listing the size and SHA-256 of every other file. The
+// manifest is signed over its canonical JSON (RFC 8785, without `signature`)
+// by the certificate signing key, so a bundle that is missing a file, has an
+// extra one, or has one modified no longer matches a signed manifest.
+
+use super::canonical_json;
+use super::signing::{PublicKey, SignatureAlgorithm, Signer};
+use super::verifier::{CertificateVerifier, CheckResult};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Manifest layout version
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Name of the manifest inside a bundle
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Evidence directory inside the configured state directory
+pub const EVIDENCE_DIR_NAME: &str = "evidence";
+
+/// Artifacts kept for each issued certificate, one directory per certificate
+#[derive(Debug, Clone)]
+pub struct EvidenceStore {
+    root: PathBuf,
+}
+
+impl EvidenceStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Store location in the configured state directory
+    pub fn default_path() -> PathBuf {
+        crate::config::state_dir().join(EVIDENCE_DIR_NAME)
+    }
+
+    /// Directory holding the artifacts of `certificate_id`
+    pub fn dir(&self, certificate_id: &str) -> Result<PathBuf> {
+        // Ids become directory names; refuse anything that could escape
+        if certificate_id.is_empty()
+            || !certificate_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            bail!("Invalid certificate id '{}'", certificate_id);
+        }
+        Ok(self.root.join(certificate_id))
+    }
+
+    /// Store `bytes` as `name` for `certificate_id`
+    pub fn add_bytes(&self, certificate_id: &str, name: &str, bytes: &[u8]) -> Result<()> {
+        check_entry_name(name)?;
+        let dir = self.dir(certificate_id)?;
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        std::fs::write(dir.join(name), bytes)
+            .with_context(|| format!("Failed to store evidence file {}", name))
+    }
+
+    /// Store pretty-printed JSON of `value` as `name`
+    pub fn add_json<T: Serialize>(
+        &self,
+        certificate_id: &str,
+        name: &str,
+        value: &T,
+    ) -> Result<()> {
+        self.add_bytes(certificate_id, name, &serde_json::to_vec_pretty(value)?)
+    }
+
+    /// Copy the file at `path` in as `name`
+    pub fn add_file(&self, certificate_id: &str, name: &str, path: &Path) -> Result<()> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        self.add_bytes(certificate_id, name, &bytes)
+    }
+
+    /// Every artifact kept for `certificate_id`, sorted by name
+    pub fn files(&self, certificate_id: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let dir = self.dir(certificate_id)?;
+        let entries = std::fs::read_dir(&dir).map_err(|_| {
+            anyhow!(
+                "No evidence stored for certificate {} (looked in {})",
+                certificate_id,
+                dir.display()
+            )
+        })?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            files.push((name, std::fs::read(entry.path())?));
+        }
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(files)
+    }
+}
+
+/// One file listed in a manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+}
+
+impl ManifestEntry {
+    fn for_file(path: &str, bytes: &[u8]) -> Self {
+        Self {
+            path: path.to_string(),
+            size: bytes.len() as u64,
+            sha256: hex::encode(Sha256::digest(bytes)),
+        }
+    }
+}
+
+/// Signed list of the files in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub certificate_id: String,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<ManifestEntry>,
+    pub signature_algorithm: SignatureAlgorithm,
+
+    /// Hex SHA-256 of the signer's SubjectPublicKeyInfo
+    pub signer_fingerprint: String,
+
+    /// Hex signature over the canonical JSON of the other fields
+    pub signature: String,
+}
+
+impl BundleManifest {
+    /// List and sign `files` for `certificate_id`
+    pub fn sign(
+        certificate_id: &str,
+        files: &[(String, Vec<u8>)],
+        key: &dyn Signer,
+    ) -> Result<Self> {
+        let mut manifest = Self {
+            version: BUNDLE_VERSION,
+            certificate_id: certificate_id.to_string(),
+            created_at: Utc::now(),
+            files: files
+                .iter()
+                .map(|(path, bytes)| ManifestEntry::for_file(path, bytes))
+                .collect(),
+            signature_algorithm: key.algorithm(),
+            signer_fingerprint: key.public_key().fingerprint(),
+            signature: String::new(),
+        };
+        manifest.signature = hex::encode(&key.sign(&manifest.signing_payload()?)?);
+        Ok(manifest)
+    }
+
+    /// Bytes covered by the signature
+    pub fn signing_payload(&self) -> Result<Vec<u8>> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("signature");
+        }
+        Ok(canonical_json::to_canonical_string(&value)?.into_bytes())
+    }
+
+    /// Check the manifest was signed by `key`
+    pub fn verify_signature(&self, key: &PublicKey) -> Result<()> {
+        if self.signer_fingerprint != key.fingerprint() {
+            bail!(
+                "Manifest signed by key {}, expected {}",
+                self.signer_fingerprint,
+                key.fingerprint()
+            );
+        }
+        let signature = hex::decode(&self.signature)
+            .ok()
+            .ok_or_else(|| anyhow!("Invalid signature"))?;
+        if self.signature_algorithm != key.algorithm()
+            || !key.verify(&self.signing_payload()?, &signature)
+        {
+            bail!("Manifest signature does not match its contents");
+        }
+        Ok(())
+    }
+}
+
+/// Archive container for a bundle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    #[default]
+    Tar,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+
+    /// Format implied by `path`'s extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "tar" => Some(ArchiveFormat::Tar),
+            "zip" => Some(ArchiveFormat::Zip),
+            _ => None,
+        }
+    }
+
+    /// Format of an archive, from its leading bytes
+    fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+            ArchiveFormat::Zip
+        } else {
+            ArchiveFormat::Tar
+        }
+    }
+}
+
+impl std::str::FromStr for ArchiveFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "tar" => Ok(ArchiveFormat::Tar),
+            "zip" => Ok(ArchiveFormat::Zip),
+            _ => bail!("Unknown bundle format '{}' (expected tar or zip)", s),
+        }
+    }
+}
+
+/// Write `files` and a manifest signed by `key` to `output`
+pub fn write_bundle(
+    output: &Path,
+    format: ArchiveFormat,
+    certificate_id: &str,
+    files: &[(String, Vec<u8>)],
+    key: &dyn Signer,
+) -> Result<BundleManifest> {
+    if files.iter().any(|(name, _)| name == MANIFEST_FILE) {
+        bail!("Evidence file name '{}' is reserved", MANIFEST_FILE);
+    }
+    for (name, _) in files {
+        check_entry_name(name)?;
+    }
+
+    let manifest = BundleManifest::sign(certificate_id, files, key)?;
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+    let entries = std::iter::once((MANIFEST_FILE, manifest_bytes.as_slice())).chain(
+        files
+            .iter()
+            .map(|(name, bytes)| (name.as_str(), bytes.as_slice())),
+    );
+
+    let file = std::fs::File::create(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    match format {
+        ArchiveFormat::Tar => {
+            let mut builder = tar::Builder::new(file);
+            let mtime = manifest.created_at.timestamp().max(0) as u64;
+            for (name, bytes) in entries {
+                let mut header = tar::Header::new_ustar();
+                header.set_size(bytes.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(mtime);
+                header.set_cksum();
+                builder.append_data(&mut header, name, bytes)?;
+            }
+            builder.into_inner()?.sync_all()?;
+        }
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipWriter::new(file);
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .unix_permissions(0o644);
+            for (name, bytes) in entries {
+                zip.start_file(name, options)?;
+                zip.write_all(bytes)?;
+            }
+            zip.finish()?.sync_all()?;
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Contents of a bundle, keyed by entry name
+pub fn read_bundle(path: &Path) -> Result<(ArchiveFormat, BTreeMap<String, Vec<u8>>)> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let format = ArchiveFormat::detect(&bytes);
+
+    let mut files = BTreeMap::new();
+    let mut insert = |name: String, data: Vec<u8>| -> Result<()> {
+        if files.insert(name.clone(), data).is_some() {
+            bail!("Bundle contains '{}' more than once", name);
+        }
+        Ok(())
+    };
+
+    match format {
+        ArchiveFormat::Tar => {
+            let mut archive = tar::Archive::new(Cursor::new(bytes));
+            for entry in archive.entries().context("Not a tar or zip archive")? {
+                let mut entry = entry.context("Corrupt tar entry")?;
+                if entry.header().entry_type().is_dir() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data)?;
+                insert(name, data)?;
+            }
+        }
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).context("Corrupt zip")?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data)?;
+                insert(name, data)?;
+            }
+        }
+    }
+
+    Ok((format, files))
+}
+
+/// Result of verifying a bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleReport {
+    /// Every check that ran passed
+    pub valid: bool,
+    pub format: Option<ArchiveFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_id: Option<String>,
+    /// Name of the first check that failed
+    pub failed_check: Option<&'static str>,
+    pub checks: Vec<CheckResult>,
+}
+
+impl BundleReport {
+    fn pass(&mut self, check: &'static str, detail: impl Into<String>) {
+        self.checks.push(CheckResult {
+            check,
+            passed: true,
+            detail: detail.into(),
+        });
+    }
+
+    fn fail(&mut self, check: &'static str, detail: impl Into<String>) {
+        self.failed_check.get_or_insert(check);
+        self.checks.push(CheckResult {
+            check,
+            passed: false,
+            detail: detail.into(),
+        });
+    }
+
+    fn finish(mut self) -> Self {
+        self.valid = self.failed_check.is_none() && !self.checks.is_empty();
+        self
+    }
+}
+
+/// Check the bundle at `path` is complete, unmodified and signed by `key`
+///
+/// Checks, in order:
+/// - read: the archive could be read
+/// - manifest: it has a parseable `manifest.json`
+/// - signature: the manifest is signed by `key`
+/// - files: every listed file is present with the listed hash, and nothing
+///   unlisted was added
+/// - certificate: the bundled certificate verifies and is the one the
+///   manifest names
+pub fn verify_bundle(path: &Path, key: &PublicKey, verifier: &CertificateVerifier) -> BundleReport {
+    let mut report = BundleReport {
+        valid: false,
+        format: None,
+        certificate_id: None,
+        failed_check: None,
+        checks: Vec::new(),
+    };
+
+    let (format, mut files) = match read_bundle(path) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            report.fail("read", format!("{:#}", e));
+            return report.finish();
+        }
+    };
+    report.format = Some(format);
+    report.pass(
+        "read",
+        format!("{} archive, {} files", format.file_extension(), files.len()),
+    );
+
+    let manifest = match files.remove(MANIFEST_FILE).map(|bytes| {
+        serde_json::from_slice::<BundleManifest>(&bytes).context("Manifest is not valid JSON")
+    }) {
+        Some(Ok(manifest)) if manifest.version <= BUNDLE_VERSION => manifest,
+        Some(Ok(manifest)) => {
+            report.fail(
+                "manifest",
+                format!("Unsupported manifest version {}", manifest.version),
+            );
+            return report.finish();
+        }
+        Some(Err(e)) => {
+            report.fail("manifest", format!("{:#}", e));
+            return report.finish();
+        }
+        None => {
+            report.fail("manifest", format!("Bundle has no {}", MANIFEST_FILE));
+            return report.finish();
+        }
+    };
+    report.certificate_id = Some(manifest.certificate_id.clone());
+    report.pass(
+        "manifest",
+        format!(
+            "{} files for certificate {}",
+            manifest.files.len(),
+            manifest.certificate_id
+        ),
+    );
+
+    match manifest.verify_signature(key) {
+        Ok(()) => report.pass(
+            "signature",
+            format!(
+                "Signed with {} key {}",
+                manifest.signature_algorithm, manifest.signer_fingerprint
+            ),
+        ),
+        Err(e) => report.fail("signature", e.to_string()),
+    }
+
+    let mut problems = Vec::new();
+    for listed in &manifest.files {
+        match files.get(&listed.path) {
+            None => problems.push(format!("{} is missing", listed.path)),
+            Some(bytes) if ManifestEntry::for_file(&listed.path, bytes) != *listed => {
+                problems.push(format!("{} was modified", listed.path))
+            }
+            Some(_) => {}
+        }
+    }
+    for name in files.keys() {
+        if !manifest.files.iter().any(|listed| &listed.path == name) {
+            problems.push(format!("{} is not in the manifest", name));
+        }
+    }
+    if problems.is_empty() {
+        report.pass(
+            "files",
+            format!("All {} files match the manifest", manifest.files.len()),
+        );
+    } else {
+        report.fail("files", problems.join("; "));
+    }
+
+    check_certificate(&mut report, &manifest, &files, verifier);
+
+    report.finish()
+}
+
+/// Verify the bundled certificate and match it to the manifest
+fn check_certificate(
+    report: &mut BundleReport,
+    manifest: &BundleManifest,
+    files: &BTreeMap<String, Vec<u8>>,
+    verifier: &CertificateVerifier,
+) {
+    let Some((name, bytes)) = files
+        .iter()
+        .find(|(name, _)| name.starts_with("certificate.") && !name.ends_with(".p7s"))
+    else {
+        report.fail("certificate", "Bundle has no certificate file");
+        return;
+    };
+    let detached = files.get(&format!("{}.p7s", name));
+
+    let certificate_report = verifier.verify_bytes(bytes, detached.map(Vec::as_slice), None);
+    match (
+        &certificate_report.certificate,
+        certificate_report.failure(),
+    ) {
+        (_, Some(failure)) => report.fail(
+            "certificate",
+            format!(
+                "{}: {} check failed: {}",
+                name, failure.check, failure.detail
+            ),
+        ),
+        (Some(certificate), None) if certificate.certificate_id != manifest.certificate_id => {
+            report.fail(
+                "certificate",
+                format!(
+                    "{} is certificate {}, manifest names {}",
+                    name, certificate.certificate_id, manifest.certificate_id
+                ),
+            )
+        }
+        (Some(_), None) => report.pass("certificate", format!("{} verifies", name)),
+        (None, None) => report.fail("certificate", format!("{} could not be parsed", name)),
+    }
+}
+
+/// Entry names are flat file names inside the bundle
+fn check_entry_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        bail!("Invalid evidence file name '{}'", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::certificates::{CertificateGenerator, VerificationResult, WipeDetails};
+    use crate::crypto::signing::SigningKey;
+    use crate::{DriveCapabilities, DriveInfo, DriveType, EncryptionStatus};
+    use tempfile::TempDir;
+
+    struct Fixture {
+        _dir: TempDir,
+        path: PathBuf,
+        key: SigningKey,
+        certificate_id: String,
+    }
+
+    fn key_pair() -> (SigningKey, SigningKey) {
+        let (key, pkcs8) = SigningKey::generate(SignatureAlgorithm::Ed25519).unwrap();
+        (key, SigningKey::from_pkcs8(&pkcs8).unwrap())
+    }
+
+    fn bundle(format: ArchiveFormat) -> Fixture {
+        let dir = TempDir::new().unwrap();
+        let (key, generator_key) = key_pair();
+        let certificate = CertificateGenerator::with_key(generator_key)
+            .generate_certificate(
+                &DriveInfo {
+                    device_path: "/dev/sdz".to_string(),
+                    model: "WDC WD10EZEX".to_string(),
+                    serial: "WD-123".to_string(),
+                    size: 1_000_204_886_016,
+                    drive_type: DriveType::HDD,
+                    encryption_status: EncryptionStatus::None,
+                    capabilities: DriveCapabilities::default(),
+                    health_status: None,
+                    temperature_celsius: None,
+                },
+                WipeDetails {
+                    algorithm_used: "Zero".to_string(),
+                    passes_completed: 1,
+                    duration_seconds: 60,
+                    operator_id: None,
+                    checkpoint_rejections: Vec::new(),
+                },
+                VerificationResult {
+                    verified: true,
+                    entropy_score: 0.0,
+                    recovery_test_passed: true,
+                    verification_timestamp: Utc::now(),
+                },
+            )
+            .unwrap();
+        let id = certificate.certificate_id.clone();
+
+        let store = EvidenceStore::new(dir.path().join("evidence"));
+        store
+            .add_json(&id, "certificate.json", &certificate)
+            .unwrap();
+        store
+            .add_bytes(&id, "bad_sectors.log", b"4096: read error\n")
+            .unwrap();
+        store
+            .add_json(
+                &id,
+                "smart.json",
+                &serde_json::json!({"overall_health": "Good"}),
+            )
+            .unwrap();
+
+        let path = dir
+            .path()
+            .join(format!("bundle.{}", format.file_extension()));
+        write_bundle(&path, format, &id, &store.files(&id).unwrap(), &key).unwrap();
+        Fixture {
+            _dir: dir,
+            path,
+            key,
+            certificate_id: id,
+        }
+    }
+
+    fn verify(fixture: &Fixture) -> BundleReport {
+        let verifier =
+            CertificateVerifier::default().with_public_key(fixture.key.public_key().clone());
+        verify_bundle(&fixture.path, fixture.key.public_key(), &verifier)
+    }
+
+    /// Rewrite the bundle with `edit` applied to its entries, keeping the
+    /// original manifest
+    fn rewrite(fixture: &Fixture, edit: impl FnOnce(&mut BTreeMap<String, Vec<u8>>)) {
+        let (format, mut files) = read_bundle(&fixture.path).unwrap();
+        edit(&mut files);
+        let file = std::fs::File::create(&fixture.path).unwrap();
+        match format {
+            ArchiveFormat::Tar => {
+                let mut builder = tar::Builder::new(file);
+                for (name, bytes) in &files {
+                    let mut header = tar::Header::new_ustar();
+                    header.set_size(bytes.len() as u64);
+                    header.set_mode(0o644);
+                    header.set_cksum();
+                    builder
+                        .append_data(&mut header, name, bytes.as_slice())
+                        .unwrap();
+                }
+                builder.finish().unwrap();
+            }
+            ArchiveFormat::Zip => {
+                let mut zip = zip::ZipWriter::new(file);
+                for (name, bytes) in &files {
+                    zip.start_file(name.as_str(), zip::write::SimpleFileOptions::default())
+                        .unwrap();
+                    zip.write_all(bytes).unwrap();
+                }
+                zip.finish().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_verifies() {
+        for format in [ArchiveFormat::Tar, ArchiveFormat::Zip] {
+            let fixture = bundle(format);
+            let report = verify(&fixture);
+            assert!(report.valid, "{:?}: {:?}", format, report.checks);
+            assert_eq!(report.format, Some(format));
+            assert_eq!(
+                report.certificate_id.as_deref(),
+                Some(fixture.certificate_id.as_str())
+            );
+
+            let (_, files) = read_bundle(&fixture.path).unwrap();
+            let names: Vec<_> = files.keys().map(String::as_str).collect();
+            assert_eq!(
+                names,
+                [
+                    "bad_sectors.log",
+                    "certificate.json",
+                    MANIFEST_FILE,
+                    "smart.json"
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_modified_missing_and_extra_files_fail() {
+        for format in [ArchiveFormat::Tar, ArchiveFormat::Zip] {
+            let fixture = bundle(format);
+            rewrite(&fixture, |files| {
+                files.insert("bad_sectors.log".to_string(), b"".to_vec());
+                files.remove("smart.json");
+                files.insert("notes.txt".to_string(), b"added later".to_vec());
+            });
+
+            let report = verify(&fixture);
+            assert!(!report.valid);
+            assert_eq!(report.failed_check, Some("files"));
+            let detail = &report
+                .checks
+                .iter()
+                .find(|c| c.check == "files")
+                .unwrap()
+                .detail;
+            assert!(
+                detail.contains("bad_sectors.log was modified"),
+                "{}",
+                detail
+            );
+            assert!(detail.contains("smart.json is missing"), "{}", detail);
+            assert!(
+                detail.contains("notes.txt is not in the manifest"),
+                "{}",
+                detail
+            );
+        }
+    }
+
+    #[test]
+    fn test_rehashed_manifest_fails_signature() {
+        let fixture = bundle(ArchiveFormat::Tar);
+        rewrite(&fixture, |files| {
+            let mut manifest: BundleManifest =
+                serde_json::from_slice(&files[MANIFEST_FILE]).unwrap();
+            manifest
+                .files
+                .retain(|entry| entry.path != "bad_sectors.log");
+            files.remove("bad_sectors.log");
+            files.insert(
+                MANIFEST_FILE.to_string(),
+                serde_json::to_vec(&manifest).unwrap(),
+            );
+        });
+
+        let report = verify(&fixture);
+        assert!(!report.valid);
+        assert_eq!(report.failed_check, Some("signature"));
+    }
+
+    #[test]
+    fn test_other_key_and_missing_manifest_fail() {
+        let fixture = bundle(ArchiveFormat::Zip);
+        let (other, _) = key_pair();
+        let verifier = CertificateVerifier::default().with_public_key(other.public_key().clone());
+        let report = verify_bundle(&fixture.path, other.public_key(), &verifier);
+        assert_eq!(report.failed_check, Some("signature"));
+
+        rewrite(&fixture, |files| {
+            files.remove(MANIFEST_FILE);
+        });
+        let report = verify(&fixture);
+        assert_eq!(report.failed_check, Some("manifest"));
+    }
+
+    #[test]
+    fn test_store_rejects_unsafe_names() {
+        let dir = TempDir::new().unwrap();
+        let store = EvidenceStore::new(dir.path());
+        assert!(store.dir("../etc").is_err());
+        assert!(store.add_bytes("abc-123", "../escape", b"x").is_err());
+        assert!(store.files("abc-123").is_err());
+        assert_eq!("zip".parse::<ArchiveFormat>().unwrap(), ArchiveFormat::Zip);
+        assert!("rar".parse::<ArchiveFormat>().is_err());
+    }
+}
@@ -0,0 +1,301 @@
+// RFC 8785 JSON Canonicalization Scheme (JCS)
+//
+// Signed documents are hashed over their canonical form rather than whatever
+// serde happens to emit, so a change in struct field order, float formatting
+// or whitespace cannot invalidate certificates issued by earlier releases.
+//
+// The rules, in short:
+// - object members are sorted by the UTF-16 code units of their names
+// - no insignificant whitespace
+// - strings escape only `"`, `\` and control characters, using the short
+//   forms `\b \t \n \f \r` where they exist and lowercase `\u00xx` otherwise
+// - numbers are written as ECMAScript `Number.prototype.toString` would
+//
+// Integers beyond ±2^53 cannot be represented exactly as IEEE 754 doubles
+// and are rejected rather than silently rounded.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Largest integer magnitude a double holds exactly (2^53)
+const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// Canonical encoding of any serializable value
+pub fn to_canonical_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    Ok(to_canonical_string(&serde_json::to_value(value)?)?.into_bytes())
+}
+
+/// Canonical encoding of a JSON value
+pub fn to_canonical_string(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    write_value(value, &mut out)?;
+    Ok(out)
+}
+
+fn write_value(value: &Value, out: &mut String) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                if u > MAX_SAFE_INTEGER {
+                    bail!(
+                        "Integer {} cannot be represented exactly in canonical JSON",
+                        u
+                    );
+                }
+                out.push_str(&u.to_string());
+            } else if let Some(i) = n.as_i64() {
+                if i.unsigned_abs() > MAX_SAFE_INTEGER {
+                    bail!(
+                        "Integer {} cannot be represented exactly in canonical JSON",
+                        i
+                    );
+                }
+                out.push_str(&i.to_string());
+            } else if let Some(f) = n.as_f64() {
+                out.push_str(&format_number(f)?);
+            } else {
+                bail!("Unsupported JSON number {}", n);
+            }
+        }
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut members: Vec<(&String, &Value)> = map.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (i, (name, member)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(name, out);
+                out.push(':');
+                write_value(member, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0C}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Format a double the way ECMAScript `Number.prototype.toString` does
+pub fn format_number(value: f64) -> Result<String> {
+    if !value.is_finite() {
+        bail!("{} cannot be represented in JSON", value);
+    }
+    if value == 0.0 {
+        // Also covers -0
+        return Ok("0".to_string());
+    }
+
+    // Rust's `{:e}` yields the shortest round-tripping digits, which is the
+    // digit string ECMAScript requires; only the layout differs
+    let (mut digits, exponent) = split_exponent(&format!("{:e}", value.abs()))?;
+
+    // When the value lies exactly halfway between two shortest candidates,
+    // ECMAScript takes the even one; Rust may round the other way
+    if digits.len() > 1 && digits.ends_with(['1', '3', '5', '7', '9']) {
+        // 767 significant digits represent any double exactly
+        let (exact, exact_exponent) = split_exponent(&format!("{:.766e}", value.abs()))?;
+        let k = digits.len();
+        if exact_exponent == exponent
+            && exact[k..].starts_with('5')
+            && exact[k + 1..].bytes().all(|b| b == b'0')
+        {
+            let truncated = &exact[..k];
+            let candidate = format!("{}.{}e{}", &truncated[..1], &truncated[1..], exponent);
+            if candidate.parse::<f64>().ok() == Some(value.abs()) {
+                digits = truncated.to_string();
+            }
+        }
+    }
+
+    // Position of the decimal point relative to the digit string
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let mut out = String::new();
+    if value < 0.0 {
+        out.push('-');
+    }
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n > 0 { '+' } else { '-' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+
+    Ok(out)
+}
+
+/// Split `d.ddde±x` into its digit string and exponent
+fn split_exponent(formatted: &str) -> Result<(String, i32)> {
+    let Some((mantissa, exponent)) = formatted.split_once('e') else {
+        bail!("Unexpected number format {}", formatted);
+    };
+    let digits = mantissa.chars().filter(|c| *c != '.').collect();
+    Ok((digits, exponent.parse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rfc8785_sample_object() {
+        // RFC 8785 section 3.2.2
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let value: Value = serde_json::from_str(input).unwrap();
+
+        assert_eq!(
+            to_canonical_string(&value).unwrap(),
+            "{\"literals\":[null,true,false],\
+             \"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\
+             \"string\":\"€$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}"
+        );
+    }
+
+    #[test]
+    fn test_rfc8785_member_sorting() {
+        // RFC 8785 section 3.2.3: sorted by UTF-16 code units, so the
+        // surrogate pair of U+1F600 sorts before U+FB33
+        let input = r#"{
+            "€": "Euro Sign",
+            "\r": "Carriage Return",
+            "דּ": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "😀": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "ö": "Latin Small Letter O With Diaeresis"
+        }"#;
+        let value: Value = serde_json::from_str(input).unwrap();
+        let canonical = to_canonical_string(&value).unwrap();
+
+        let order: Vec<usize> = [
+            "Carriage Return",
+            "One",
+            "Control",
+            "Latin Small",
+            "Euro Sign",
+            "Emoji",
+            "Hebrew",
+        ]
+        .iter()
+        .map(|name| canonical.find(name).unwrap())
+        .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]), "{}", canonical);
+    }
+
+    #[test]
+    fn test_rfc8785_number_vectors() {
+        // RFC 8785 appendix B
+        let vectors: &[(u64, &str)] = &[
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ];
+
+        for (bits, expected) in vectors {
+            assert_eq!(
+                format_number(f64::from_bits(*bits)).unwrap(),
+                *expected,
+                "bits {:016x}",
+                bits
+            );
+        }
+
+        assert!(format_number(f64::NAN).is_err());
+        assert!(format_number(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(
+            to_canonical_string(&json!([0, -1, 500_000_000_000u64, 9007199254740992u64])).unwrap(),
+            "[0,-1,500000000000,9007199254740992]"
+        );
+        assert!(to_canonical_string(&json!(9007199254740993u64)).is_err());
+        assert!(to_canonical_string(&json!(-9007199254740993i64)).is_err());
+    }
+
+    #[test]
+    fn test_nested_structures_and_whitespace() {
+        let value = json!({"b": [1, {"z": null, "a": "x"}], "a": {}});
+        assert_eq!(
+            to_canonical_string(&value).unwrap(),
+            r#"{"a":{},"b":[1,{"a":"x","z":null}]}"#
+        );
+    }
+}
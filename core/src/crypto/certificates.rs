@@ -1,17 +1,113 @@
-use anyhow::Result;
+use super::anchor::{AnchorClient, BlockchainAnchor};
+use super::canonical_json;
+use super::cms::{self, CmsVerification};
+use super::cosign::Cosignature;
+use super::evidence::WipeEvidence;
+use super::privacy::{self, DeviceCommitments, Disclosure};
+use super::signing::{
+    self, CertificateFormat, PublicKey, SignatureAlgorithm, Signer, SigningConfig, SigningKey,
+};
+use super::timestamp::{self, TimestampVerification, TsaClient};
+use super::transparency::TransparencyLog;
+use super::x509::{self, Certificate, TrustStore};
+use crate::error::CheckpointRejection;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::path::PathBuf;
 use uuid::Uuid;
 
+/// Secret compiled into releases before certificates were signed with real
+/// keys. Kept only to recognise those certificates as legacy on verification.
+const LEGACY_SIGNING_SECRET: &str = "your-private-signing-key";
+
+/// Certificate layout written by this release
+///
+/// 1. Checksum or signature over serde's output (no `schema_version` field)
+/// 2. Signature over the RFC 8785 canonical form
+/// 3. Signed `evidence` of how the wipe was carried out
+/// 4. Optional salted `commitments` in place of the serial, device path and WWN
+/// 5. Optional `cosignatures` added after issue
+/// 6. Optional `evidence.method.scheme` naming the scheme file a wipe followed
+/// 7. Optional `evidence.method.standard`, the formal name of the standard
+/// 8. Optional `evidence.method.nist`, the NIST 800-88 level and its reasoning
+/// 9. Optional `verification` on pass records, with ranges that read back wrong
+pub const CERTIFICATE_SCHEMA_VERSION: u32 = 9;
+
+/// Signed record of a completed wipe
+///
+/// The signature covers the RFC 8785 canonical JSON of every field except
+/// `signature`, `timestamp_token`, `anchor` and `cosignatures` (schema 1
+/// certificates were signed over serde's output instead). Fields added later
+/// must be optional and skipped when unset, so certificates issued before
+/// they existed keep the same signed bytes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WipeCertificate {
+    /// Layout version, see [`CERTIFICATE_SCHEMA_VERSION`]
+    #[serde(default = "unversioned_schema")]
+    pub schema_version: u32,
     pub certificate_id: String,
     pub device_info: DeviceCertInfo,
     pub wipe_details: WipeDetails,
     pub verification: VerificationResult,
+    /// How the wipe was carried out; absent before schema version 3 and for
+    /// certificates issued outside a wipe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<WipeEvidence>,
     pub timestamp: DateTime<Utc>,
+    /// Signature scheme; certificates without one predate signing keys
+    #[serde(default = "legacy_algorithm")]
+    pub signature_algorithm: SignatureAlgorithm,
+    /// SHA-256 fingerprint of the signer's public key (DER SubjectPublicKeyInfo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_fingerprint: Option<String>,
+    /// Hex-encoded signature over the certificate with this field empty
     pub signature: String,
+    /// RFC 3161 TimeStampToken (base64 DER) over [`timestamp_payload`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_token: Option<String>,
+    /// Blockchain transaction recording [`certificate_hash`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<BlockchainAnchor>,
+    /// Signatures added after issue, e.g. by a supervisor; see
+    /// [`cosign`](super::cosign)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosignatures: Vec<Cosignature>,
+}
+
+fn unversioned_schema() -> u32 {
+    1
+}
+
+fn legacy_algorithm() -> SignatureAlgorithm {
+    SignatureAlgorithm::LegacySha256
+}
+
+/// Outcome of checking a certificate signature against a public key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// Signed by the given key and unmodified since
+    Valid,
+
+    /// Pre-signing-key certificate whose checksum matches; this shows the
+    /// certificate is intact but not who issued it
+    Legacy,
+
+    /// Signed by a different key than the one supplied
+    KeyMismatch { expected: String, found: String },
+
+    /// Signature does not match the contents
+    Invalid,
+}
+
+impl SignatureStatus {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, SignatureStatus::Valid)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +117,10 @@ pub struct DeviceCertInfo {
     pub serial: String,
     pub size: u64,
     pub device_hash: String, // Hash of device identifying information
+    /// Commitments to the identifying values when they are redacted; see
+    /// [`privacy`](super::privacy)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitments: Option<DeviceCommitments>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +129,9 @@ pub struct WipeDetails {
     pub passes_completed: u32,
     pub duration_seconds: u64,
     pub operator_id: Option<String>,
+    /// Checkpoints refused on resume (failed integrity or drive identity checks)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoint_rejections: Vec<CheckpointRejection>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,21 +143,124 @@ pub struct VerificationResult {
 }
 
 pub struct CertificateGenerator {
-    private_key: String, // In practice, use proper key management
+    key: Box<dyn Signer>,
+    /// X.509 certificate for `key` and its intermediates, for CMS output
+    chain: Vec<Certificate>,
+    format: CertificateFormat,
+    tsa: Option<TsaClient>,
+    anchor: Option<AnchorClient>,
+    /// Transparency log database that issued certificates are appended to
+    transparency_log: Option<PathBuf>,
+    /// Commit to the serial, device path and WWN instead of recording them
+    redact_identifiers: bool,
 }
 
-impl Default for CertificateGenerator {
-    fn default() -> Self {
-        Self::new()
+impl CertificateGenerator {
+    /// Generator signing with `key`, saving JSON
+    pub fn with_key(key: SigningKey) -> Self {
+        Self::with_signer(Box::new(key))
     }
-}
 
-impl CertificateGenerator {
-    pub fn new() -> Self {
-        // In production, load this from secure key store
+    /// Generator signing through `signer`, e.g. a key on a PKCS#11 token
+    pub fn with_signer(signer: Box<dyn Signer>) -> Self {
         Self {
-            private_key: "your-private-signing-key".to_string(),
+            key: signer,
+            chain: Vec::new(),
+            format: CertificateFormat::Json,
+            tsa: None,
+            anchor: None,
+            transparency_log: None,
+            redact_identifiers: false,
+        }
+    }
+
+    /// Attach the X.509 certificate for the key, followed by intermediates
+    pub fn with_certificate_chain(mut self, chain: Vec<Certificate>) -> Result<Self> {
+        if let Some(leaf) = chain.first() {
+            cms::check_signer_certificate(self.key.as_ref(), leaf)?;
+        }
+        self.chain = chain;
+        Ok(self)
+    }
+
+    /// Save certificates as `format`; the CMS formats need a certificate chain
+    pub fn with_format(mut self, format: CertificateFormat) -> Result<Self> {
+        if format != CertificateFormat::Json && self.chain.is_empty() {
+            bail!(
+                "Certificate format {} needs an X.509 certificate for the signing key \
+                 (signing.certificate_path)",
+                format
+            );
+        }
+        self.format = format;
+        Ok(self)
+    }
+
+    /// Have new certificates timestamped by `tsa`
+    pub fn with_timestamp_authority(mut self, tsa: TsaClient) -> Self {
+        self.tsa = Some(tsa);
+        self
+    }
+
+    /// Have new certificates anchored on chain by `anchor`
+    pub fn with_anchor(mut self, anchor: AnchorClient) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    /// Append new certificates to the transparency log at `path`
+    pub fn with_transparency_log(mut self, path: PathBuf) -> Self {
+        self.transparency_log = Some(path);
+        self
+    }
+
+    /// Replace identifying drive values with salted commitments; the salts
+    /// come back from [`generate_certificate_with_disclosure`](Self::generate_certificate_with_disclosure)
+    pub fn with_redacted_identifiers(mut self, redact: bool) -> Self {
+        self.redact_identifiers = redact;
+        self
+    }
+
+    pub fn redacts_identifiers(&self) -> bool {
+        self.redact_identifiers
+    }
+
+    /// Generator signing with the key or token named in `config`, or the
+    /// host key
+    pub fn from_config(config: &SigningConfig) -> Result<Self> {
+        let chain = match &config.certificate_path {
+            Some(path) => x509::load_certificates(path)?,
+            None => Vec::new(),
+        };
+        let mut generator = Self::with_signer(signing::signer_from_config(config)?)
+            .with_certificate_chain(chain)?
+            .with_format(config.format)?
+            .with_redacted_identifiers(config.redact_identifiers);
+        if let Some(tsa) = TsaClient::from_config(config)? {
+            generator = generator.with_timestamp_authority(tsa);
         }
+        Ok(generator)
+    }
+
+    /// Generator for the installed configuration, logging every certificate
+    /// in the state directory's transparency log
+    pub fn configured() -> Result<Self> {
+        let config = crate::config::installed().unwrap_or_default();
+        let mut generator = Self::from_config(&config.signing)?
+            .with_transparency_log(TransparencyLog::default_path());
+        if let Some(anchor) = AnchorClient::from_config(&config.anchor)? {
+            generator = generator.with_anchor(anchor);
+        }
+        Ok(generator)
+    }
+
+    /// Public key that verifies this generator's certificates
+    pub fn public_key(&self) -> &PublicKey {
+        self.key.public_key()
+    }
+
+    pub fn format(&self) -> CertificateFormat {
+        self.format
     }
 
     pub fn generate_certificate(
@@ -63,61 +269,411 @@ impl CertificateGenerator {
         wipe_details: WipeDetails,
         verification: VerificationResult,
     ) -> Result<WipeCertificate> {
+        self.generate_certificate_with_evidence(device_info, wipe_details, verification, None)
+    }
+
+    /// [`generate_certificate`](Self::generate_certificate) with the
+    /// evidence gathered during the wipe
+    ///
+    /// When identifiers are redacted the salts are dropped, so nobody can
+    /// later open the commitments; use
+    /// [`generate_certificate_with_disclosure`](Self::generate_certificate_with_disclosure)
+    /// to keep them.
+    pub fn generate_certificate_with_evidence(
+        &self,
+        device_info: &crate::DriveInfo,
+        wipe_details: WipeDetails,
+        verification: VerificationResult,
+        evidence: Option<WipeEvidence>,
+    ) -> Result<WipeCertificate> {
+        self.generate_certificate_with_disclosure(device_info, wipe_details, verification, evidence)
+            .map(|(certificate, _)| certificate)
+    }
+
+    /// Issue a certificate, with the disclosure of its commitments when
+    /// identifiers are redacted
+    pub fn generate_certificate_with_disclosure(
+        &self,
+        device_info: &crate::DriveInfo,
+        wipe_details: WipeDetails,
+        verification: VerificationResult,
+        mut evidence: Option<WipeEvidence>,
+    ) -> Result<(WipeCertificate, Option<Disclosure>)> {
         let certificate_id = Uuid::new_v4().to_string();
 
-        let device_cert_info = DeviceCertInfo {
+        let mut device_cert_info = DeviceCertInfo {
             device_path: device_info.device_path.clone(),
             model: device_info.model.clone(),
             serial: device_info.serial.clone(),
             size: device_info.size,
             device_hash: self.calculate_device_hash(device_info)?,
+            commitments: None,
         };
 
+        let mut disclosure = None;
+        if self.redact_identifiers {
+            let wwn = evidence.as_ref().and_then(|e| e.drive.wwn.as_deref());
+            let (commitments, mut opened) =
+                Disclosure::commit(&device_info.serial, &device_info.device_path, wwn)?;
+            opened.certificate_id = certificate_id.clone();
+
+            // Copies of the values in the evidence (bad sector and
+            // verification reports, warnings) go too
+            if let Some(recorded) = evidence.take() {
+                let mut json = serde_json::to_value(recorded)?;
+                privacy::redact_json(&mut json, &opened.values());
+                evidence = Some(serde_json::from_value(json)?);
+            }
+
+            device_cert_info.serial = privacy::REDACTED.to_string();
+            device_cert_info.device_path = privacy::REDACTED.to_string();
+            device_cert_info.device_hash =
+                device_hash(&device_info.model, &commitments.serial, device_info.size);
+            device_cert_info.commitments = Some(commitments);
+            disclosure = Some(opened);
+        }
+
         let mut certificate = WipeCertificate {
+            schema_version: CERTIFICATE_SCHEMA_VERSION,
             certificate_id,
             device_info: device_cert_info,
             wipe_details,
             verification,
+            evidence,
             timestamp: Utc::now(),
+            signature_algorithm: self.key.algorithm(),
+            signer_fingerprint: Some(self.key.public_key().fingerprint()),
             signature: String::new(), // Will be filled by signing
+            timestamp_token: None,
+            anchor: None,
+            cosignatures: Vec::new(),
         };
 
         certificate.signature = self.sign_certificate(&certificate)?;
 
-        Ok(certificate)
+        // The wipe has happened either way; an unreachable TSA must not cost
+        // the operator the certificate
+        if let Some(tsa) = &self.tsa {
+            if let Err(e) = attach_timestamp(&mut certificate, tsa) {
+                tracing::warn!(
+                    "Certificate {} issued without a trusted timestamp: {:#}",
+                    certificate.certificate_id,
+                    e
+                );
+            }
+        }
+
+        // Like a timestamp, an anchor can be added later with `cert anchor`
+        if let Some(anchor) = &self.anchor {
+            if let Err(e) = anchor.anchor(&mut certificate) {
+                tracing::warn!(
+                    "Certificate {} issued without a blockchain anchor: {:#}",
+                    certificate.certificate_id,
+                    e
+                );
+            }
+        }
+
+        // Unlike the TSA the log is local, and a certificate missing from it
+        // is indistinguishable from a forged one
+        if let Some(path) = &self.transparency_log {
+            TransparencyLog::open(Some(&path.to_string_lossy()))?
+                .append(&certificate, self.key.as_ref())
+                .with_context(|| {
+                    format!(
+                        "Failed to record certificate {} in the transparency log",
+                        certificate.certificate_id
+                    )
+                })?;
+        }
+
+        Ok((certificate, disclosure))
     }
 
     fn calculate_device_hash(&self, device_info: &crate::DriveInfo) -> Result<String> {
-        let mut hasher = Sha256::new();
-        hasher.update(device_info.model.as_bytes());
-        hasher.update(device_info.serial.as_bytes());
-        hasher.update(device_info.size.to_le_bytes());
-
-        Ok(format!("{:x}", hasher.finalize()))
+        Ok(device_hash(
+            &device_info.model,
+            &device_info.serial,
+            device_info.size,
+        ))
     }
 
     fn sign_certificate(&self, certificate: &WipeCertificate) -> Result<String> {
-        // Create a signing payload (excluding the signature field)
-        let mut signing_data = certificate.clone();
-        signing_data.signature = String::new();
-
-        let json_data = serde_json::to_string(&signing_data)?;
-
-        let mut hasher = Sha256::new();
-        hasher.update(json_data.as_bytes());
-        hasher.update(self.private_key.as_bytes());
-
-        Ok(format!("{:x}", hasher.finalize()))
+        let signature = self.key.sign(&signing_payload(certificate)?)?;
+        Ok(hex::encode(&signature))
     }
 
+    /// True if `certificate` was signed by this generator's key and is intact
     pub fn verify_certificate(&self, certificate: &WipeCertificate) -> Result<bool> {
-        let expected_signature = self.sign_certificate(certificate)?;
-        Ok(expected_signature == certificate.signature)
+        Ok(verify_signature(certificate, self.public_key())?.is_valid())
     }
 
     pub fn save_certificate(&self, certificate: &WipeCertificate, path: &str) -> Result<()> {
-        let json_data = serde_json::to_string_pretty(certificate)?;
-        std::fs::write(path, json_data)?;
+        self.save_document(&serde_json::to_value(certificate)?, path)
+    }
+
+    /// Save a certificate, possibly extended with unsigned members, in the
+    /// configured format
+    ///
+    /// The CMS formats sign the canonical JSON, so extra members are covered
+    /// by the envelope even though the certificate's own signature skips them.
+    /// Detached signatures go to [`detached_signature_path`].
+    pub fn save_document(&self, document: &serde_json::Value, path: &str) -> Result<()> {
+        match self.format {
+            CertificateFormat::Json => {
+                std::fs::write(path, serde_json::to_string_pretty(document)?)?;
+            }
+            CertificateFormat::Cms => {
+                let content = canonical_json::to_canonical_string(document)?;
+                let envelope =
+                    cms::sign(self.key.as_ref(), &self.chain, content.as_bytes(), false)?;
+                std::fs::write(path, envelope)?;
+            }
+            CertificateFormat::CmsDetached => {
+                // The signature covers these exact bytes
+                let content = canonical_json::to_canonical_string(document)?;
+                let signature =
+                    cms::sign(self.key.as_ref(), &self.chain, content.as_bytes(), true)?;
+                std::fs::write(path, &content)?;
+                std::fs::write(detached_signature_path(path), signature)?;
+            }
+        }
         Ok(())
     }
 }
+
+/// Hash of the identifying drive details recorded as `device_hash`
+///
+/// With redacted identifiers `serial` is the serial commitment.
+pub fn device_hash(model: &str, serial: &str, size: u64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update(serial.as_bytes());
+    hasher.update(size.to_le_bytes());
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// Where the detached CMS signature for a certificate at `path` is written
+pub fn detached_signature_path(path: &str) -> String {
+    format!("{}.p7s", path)
+}
+
+/// Check a CMS-wrapped certificate
+///
+/// The envelope must verify with a signer chaining to `trust`, and the
+/// certificate inside must carry a valid signature by that same signer's key.
+/// `detached_content` is the certificate file for a detached signature.
+pub fn verify_cms_certificate(
+    envelope: &[u8],
+    detached_content: Option<&[u8]>,
+    trust: &TrustStore,
+) -> Result<(WipeCertificate, CmsVerification)> {
+    let verified = cms::verify(envelope, detached_content, trust)?;
+    let certificate: WipeCertificate = serde_json::from_slice(&verified.content)
+        .context("CMS content is not a wipe certificate")?;
+
+    let public_key = PublicKey::from_spki_der(&x509::spki_der(&verified.signer_certificate)?)?;
+    match verify_signature(&certificate, &public_key)? {
+        SignatureStatus::Valid => Ok((certificate, verified)),
+        status => bail!(
+            "Certificate signature does not match the CMS signer: {:?}",
+            status
+        ),
+    }
+}
+
+/// Check the signature of `certificate` against `public_key`
+///
+/// Certificates from before signing keys cannot be attributed to any key;
+/// they are reported as `Legacy` when their checksum still matches and they
+/// carry no field newer than version 1, which the checksum would not cover. A
+/// certificate from a newer release is an error rather than `Invalid`, since
+/// fields this release does not know about are lost on parsing.
+pub fn verify_signature(
+    certificate: &WipeCertificate,
+    public_key: &PublicKey,
+) -> Result<SignatureStatus> {
+    if certificate.schema_version > CERTIFICATE_SCHEMA_VERSION {
+        bail!(
+            "Certificate schema version {} is newer than this release supports ({})",
+            certificate.schema_version,
+            CERTIFICATE_SCHEMA_VERSION
+        );
+    }
+
+    if certificate.signature_algorithm == SignatureAlgorithm::LegacySha256 {
+        // The checksum covers only the fields of a version 1 certificate
+        return Ok(
+            if has_v1_fields_only(certificate)
+                && legacy_checksum(certificate)? == certificate.signature
+            {
+                SignatureStatus::Legacy
+            } else {
+                SignatureStatus::Invalid
+            },
+        );
+    }
+
+    let expected = public_key.fingerprint();
+    match &certificate.signer_fingerprint {
+        Some(found) if *found != expected => {
+            return Ok(SignatureStatus::KeyMismatch {
+                expected,
+                found: found.clone(),
+            })
+        }
+        _ => {}
+    }
+    if certificate.signature_algorithm != public_key.algorithm() {
+        return Ok(SignatureStatus::Invalid);
+    }
+
+    let Some(signature) = hex::decode(&certificate.signature).ok() else {
+        return Ok(SignatureStatus::Invalid);
+    };
+    let payload = if certificate.schema_version == 1 {
+        match v1_signing_payload(certificate)? {
+            Some(payload) => payload,
+            None => return Ok(SignatureStatus::Invalid),
+        }
+    } else {
+        signing_payload(certificate)?
+    };
+
+    Ok(if public_key.verify(&payload, &signature) {
+        SignatureStatus::Valid
+    } else {
+        SignatureStatus::Invalid
+    })
+}
+
+/// Bytes covered by the signature: the canonical JSON of the certificate
+/// without its `signature`, `timestamp_token` and `anchor` members
+pub fn signing_payload(certificate: &WipeCertificate) -> Result<Vec<u8>> {
+    let mut value = serde_json::to_value(certificate)?;
+    if let Some(members) = value.as_object_mut() {
+        members.remove("signature");
+        members.remove("timestamp_token");
+        members.remove("anchor");
+        members.remove("cosignatures");
+    }
+    Ok(canonical_json::to_canonical_string(&value)?.into_bytes())
+}
+
+/// Bytes schema 1 certificates were signed over: serde's output of the
+/// certificate as it was then, with `signature` empty
+///
+/// None when the certificate carries signed fields that schema did not have,
+/// since nothing would cover them.
+fn v1_signing_payload(certificate: &WipeCertificate) -> Result<Option<Vec<u8>>> {
+    // The fields and order of the certificate as it was then
+    #[derive(Serialize)]
+    struct V1Payload<'a> {
+        certificate_id: &'a str,
+        device_info: &'a DeviceCertInfo,
+        wipe_details: &'a WipeDetails,
+        verification: &'a VerificationResult,
+        timestamp: &'a DateTime<Utc>,
+        signature_algorithm: SignatureAlgorithm,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signer_fingerprint: &'a Option<String>,
+        signature: &'a str,
+    }
+
+    if certificate.evidence.is_some() || certificate.device_info.commitments.is_some() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_vec(&V1Payload {
+        certificate_id: &certificate.certificate_id,
+        device_info: &certificate.device_info,
+        wipe_details: &certificate.wipe_details,
+        verification: &certificate.verification,
+        timestamp: &certificate.timestamp,
+        signature_algorithm: certificate.signature_algorithm,
+        signer_fingerprint: &certificate.signer_fingerprint,
+        signature: "",
+    })?))
+}
+
+/// Bytes a time-stamp token attests: the canonical JSON of the signed
+/// certificate without its `timestamp_token`, `anchor` and `cosignatures`
+/// members
+pub fn timestamp_payload(certificate: &WipeCertificate) -> Result<Vec<u8>> {
+    let mut value = serde_json::to_value(certificate)?;
+    if let Some(members) = value.as_object_mut() {
+        members.remove("timestamp_token");
+        members.remove("anchor");
+        members.remove("cosignatures");
+    }
+    Ok(canonical_json::to_canonical_string(&value)?.into_bytes())
+}
+
+/// SHA-256 of [`timestamp_payload`], as logged and anchored
+pub fn certificate_hash(certificate: &WipeCertificate) -> Result<[u8; 32]> {
+    Ok(Sha256::digest(timestamp_payload(certificate)?).into())
+}
+
+/// Timestamp a signed certificate with `tsa`, replacing any earlier token
+pub fn attach_timestamp(certificate: &mut WipeCertificate, tsa: &TsaClient) -> Result<()> {
+    let token = tsa.timestamp(&timestamp_payload(certificate)?)?;
+    certificate.timestamp_token = Some(BASE64.encode(token));
+    Ok(())
+}
+
+/// Check the certificate's time-stamp token against TSA anchors in `trust`
+///
+/// `None` when the certificate has no token.
+pub fn verify_timestamp(
+    certificate: &WipeCertificate,
+    trust: &TrustStore,
+) -> Result<Option<TimestampVerification>> {
+    let Some(token) = &certificate.timestamp_token else {
+        return Ok(None);
+    };
+    let token = BASE64
+        .decode(token)
+        .map_err(|e| anyhow!("timestamp_token is not base64: {}", e))?;
+    timestamp::verify_token(&token, &timestamp_payload(certificate)?, trust).map(Some)
+}
+
+/// Checksum older releases stored in `signature`
+/// Whether `certificate` is a version 1 certificate with nothing added
+/// since, which is all a legacy checksum can vouch for
+fn has_v1_fields_only(certificate: &WipeCertificate) -> bool {
+    certificate.schema_version == 1
+        && certificate.evidence.is_none()
+        && certificate.device_info.commitments.is_none()
+        && certificate.signer_fingerprint.is_none()
+        && certificate.timestamp_token.is_none()
+        && certificate.anchor.is_none()
+        && certificate.cosignatures.is_empty()
+}
+
+fn legacy_checksum(certificate: &WipeCertificate) -> Result<String> {
+    // The fields and order of the certificate as it was then
+    #[derive(Serialize)]
+    struct LegacyPayload<'a> {
+        certificate_id: &'a str,
+        device_info: &'a DeviceCertInfo,
+        wipe_details: &'a WipeDetails,
+        verification: &'a VerificationResult,
+        timestamp: &'a DateTime<Utc>,
+        signature: &'a str,
+    }
+
+    let json_data = serde_json::to_string(&LegacyPayload {
+        certificate_id: &certificate.certificate_id,
+        device_info: &certificate.device_info,
+        wipe_details: &certificate.wipe_details,
+        verification: &certificate.verification,
+        timestamp: &certificate.timestamp,
+        signature: "",
+    })?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(json_data.as_bytes());
+    hasher.update(LEGACY_SIGNING_SECRET.as_bytes());
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,532 @@
+// CMS (RFC 5652) SignedData envelopes
+//
+// The canonical certificate JSON is wrapped in a DER ContentInfo/SignedData,
+// either enveloping (the JSON is inside) or detached (the JSON is stored
+// separately and the envelope carries only the signature). The envelope holds
+// the signer's X.509 certificate and intermediates, identifies the signer by
+// issuer and serial number, and signs the content-type, message-digest and
+// signing-time attributes:
+// - Ed25519 with SHA-512 message digests (RFC 8419)
+// - ECDSA P-256 with SHA-256 (RFC 5753)
+//
+// The result checks with stock tooling, e.g.
+//   openssl cms -verify -binary -inform DER -in cert.p7m -CAfile ca.pem
+//
+// The signing-time attribute is the signer's own claim, so validity periods
+// are never checked against it: they are checked at the genTime of a verified
+// RFC 3161 time-stamp token when the caller has one, and otherwise now.
+//
+// RFC 3161 time-stamp tokens are the same structure over a TSTInfo instead of
+// id-data, so `sign_typed`/`open_typed` serve the timestamp module as well.
+
+use super::signing::{SignatureAlgorithm, Signer};
+use super::x509::{self, Certificate, TrustStore, ValidatedChain};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use cms::cert::{CertificateChoices, IssuerAndSerialNumber};
+use cms::content_info::{CmsVersion, ContentInfo};
+use cms::signed_data::{
+    CertificateSet, EncapsulatedContentInfo, SignedData, SignerIdentifier, SignerInfo, SignerInfos,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use x509_cert::attr::Attribute;
+use x509_cert::der::asn1::{ObjectIdentifier, OctetString, SetOfVec, UtcTime};
+use x509_cert::der::{Any, Decode, Encode, EncodeValue, Tagged};
+use x509_cert::ext::pkix::SubjectKeyIdentifier;
+use x509_cert::spki::AlgorithmIdentifierOwned;
+use x509_cert::time::Time;
+
+const ID_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const ID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_CONTENT_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+const ID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const ID_SIGNING_TIME: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.5");
+pub(crate) const ID_SHA256: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+pub(crate) const ID_SHA384: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+pub(crate) const ID_SHA512: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+
+/// Signed content and the chain that vouched for it
+#[derive(Debug, Clone, Serialize)]
+pub struct CmsVerification {
+    /// The signed bytes (from the envelope, or the detached content given)
+    #[serde(skip)]
+    pub content: Vec<u8>,
+    /// Signer's X.509 certificate
+    #[serde(skip)]
+    pub signer_certificate: Certificate,
+    pub signer: String,
+    /// Signing time claimed by the signer
+    pub signing_time: Option<DateTime<Utc>>,
+    pub chain: ValidatedChain,
+    /// What `chain` was validated at
+    pub chain_time: ChainTime,
+}
+
+/// Source of the time a signer's chain is validated at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainTime {
+    /// genTime of a verified RFC 3161 time-stamp token
+    Timestamp,
+    /// The time of verification
+    Now,
+}
+
+/// Envelope whose signature checks out, before its signer is trusted
+#[derive(Debug, Clone)]
+pub struct SignedEnvelope {
+    /// The signed bytes (from the envelope, or the detached content given)
+    pub content: Vec<u8>,
+    pub signer_certificate: Certificate,
+    /// Other certificates carried in the envelope
+    pub certificates: Vec<Certificate>,
+    /// Signing time claimed by the signer
+    pub signing_time: Option<DateTime<Utc>>,
+    pub(crate) signed_attrs: SetOfVec<Attribute>,
+}
+
+impl SignedEnvelope {
+    /// Value of a single-valued signed attribute, if present
+    pub(crate) fn signed_attribute(&self, oid: ObjectIdentifier) -> Result<Option<&Any>> {
+        single_attribute(&self.signed_attrs, oid)
+    }
+
+    /// Validate the signer's chain against `trust`
+    ///
+    /// `timestamp` is the genTime of a verified time-stamp token over the
+    /// signed content; without one the chain must be valid now.
+    pub fn validate_chain(
+        &self,
+        trust: &TrustStore,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Result<(ValidatedChain, ChainTime)> {
+        let (at, source) = match timestamp {
+            Some(gen_time) => (gen_time, ChainTime::Timestamp),
+            None => (Utc::now(), ChainTime::Now),
+        };
+        let chain = x509::validate_chain(&self.signer_certificate, &self.certificates, trust, at)?;
+        Ok((chain, source))
+    }
+}
+
+/// Check that `certificate` holds the public half of `key`
+pub fn check_signer_certificate(key: &dyn Signer, certificate: &Certificate) -> Result<()> {
+    if x509::spki_der(certificate)? != key.public_key().to_spki_der() {
+        bail!(
+            "Certificate \"{}\" is not for the configured signing key ({})",
+            x509::subject_name(certificate),
+            key.public_key().fingerprint()
+        );
+    }
+    Ok(())
+}
+
+/// Sign `content` into a DER ContentInfo
+///
+/// `chain` starts with the certificate for `key`, followed by any
+/// intermediates to include. With `detached` the content is left out of the
+/// envelope and must be supplied again to verify it.
+pub fn sign(
+    key: &dyn Signer,
+    chain: &[Certificate],
+    content: &[u8],
+    detached: bool,
+) -> Result<Vec<u8>> {
+    sign_typed(key, chain, ID_DATA, content, detached, true, Vec::new())
+}
+
+/// [`sign`] for content of type `content_type`, with `extra_attrs` signed
+/// alongside the standard attributes
+///
+/// Without `include_chain` the certificates are left out of the envelope and
+/// the verifier must obtain them elsewhere.
+pub(crate) fn sign_typed(
+    key: &dyn Signer,
+    chain: &[Certificate],
+    content_type: ObjectIdentifier,
+    content: &[u8],
+    detached: bool,
+    include_chain: bool,
+    extra_attrs: Vec<Attribute>,
+) -> Result<Vec<u8>> {
+    let signer = chain
+        .first()
+        .ok_or_else(|| anyhow!("CMS signing needs the signer's X.509 certificate"))?;
+    check_signer_certificate(key, signer)?;
+
+    let (digest_oid, signature_oid) = match key.algorithm() {
+        SignatureAlgorithm::Ed25519 => (ID_SHA512, x509::ID_ED25519),
+        SignatureAlgorithm::EcdsaP256Sha256 => (ID_SHA256, x509::ECDSA_WITH_SHA256),
+        SignatureAlgorithm::LegacySha256 => bail!("Legacy keys cannot sign CMS envelopes"),
+    };
+    let digest_alg = algorithm_id(digest_oid);
+
+    let now = std::time::SystemTime::now();
+    let mut attrs = vec![
+        attribute(ID_CONTENT_TYPE, &content_type)?,
+        attribute(
+            ID_MESSAGE_DIGEST,
+            &OctetString::new(digest(digest_oid, content)?)?,
+        )?,
+        attribute(ID_SIGNING_TIME, &UtcTime::from_system_time(now)?)?,
+    ];
+    attrs.extend(extra_attrs);
+    let signed_attrs = SetOfVec::try_from(attrs)?;
+    let signature = key.sign(&signed_attrs.to_der()?)?;
+
+    let signer_info = SignerInfo {
+        version: CmsVersion::V1,
+        sid: SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+            issuer: signer.tbs_certificate.issuer.clone(),
+            serial_number: signer.tbs_certificate.serial_number.clone(),
+        }),
+        digest_alg: digest_alg.clone(),
+        signed_attrs: Some(signed_attrs),
+        signature_algorithm: algorithm_id(signature_oid),
+        signature: OctetString::new(signature)?,
+        unsigned_attrs: None,
+    };
+
+    let econtent = if detached {
+        None
+    } else {
+        Some(Any::encode_from(&OctetString::new(content)?)?)
+    };
+
+    let signed_data = SignedData {
+        version: CmsVersion::V1,
+        digest_algorithms: SetOfVec::try_from(vec![digest_alg])?,
+        encap_content_info: EncapsulatedContentInfo {
+            econtent_type: content_type,
+            econtent,
+        },
+        certificates: if include_chain {
+            Some(CertificateSet::try_from(
+                chain
+                    .iter()
+                    .cloned()
+                    .map(CertificateChoices::Certificate)
+                    .collect::<Vec<_>>(),
+            )?)
+        } else {
+            None
+        },
+        crls: None,
+        signer_infos: SignerInfos::try_from(vec![signer_info])?,
+    };
+
+    Ok(ContentInfo {
+        content_type: ID_SIGNED_DATA,
+        content: Any::encode_from(&signed_data)?,
+    }
+    .to_der()?)
+}
+
+/// Verify a DER ContentInfo produced by [`sign`] (or any single-signer
+/// SignedData over id-data with signed attributes)
+///
+/// `detached_content` is required when the envelope does not carry the
+/// content itself. The signer's chain must lead to an anchor in `trust` and be
+/// valid now.
+pub fn verify(
+    der: &[u8],
+    detached_content: Option<&[u8]>,
+    trust: &TrustStore,
+) -> Result<CmsVerification> {
+    let envelope = open(der, detached_content)?;
+    let (chain, chain_time) = envelope.validate_chain(trust, None)?;
+
+    Ok(CmsVerification {
+        signer: x509::subject_name(&envelope.signer_certificate),
+        content: envelope.content,
+        signer_certificate: envelope.signer_certificate,
+        signing_time: envelope.signing_time,
+        chain,
+        chain_time,
+    })
+}
+
+/// Check the signature and message digest of a DER ContentInfo without
+/// deciding whether its signer is trusted
+pub fn open(der: &[u8], detached_content: Option<&[u8]>) -> Result<SignedEnvelope> {
+    open_typed(der, detached_content, ID_DATA)
+}
+
+/// [`open`] for an envelope that must carry content of type `content_type`
+pub(crate) fn open_typed(
+    der: &[u8],
+    detached_content: Option<&[u8]>,
+    content_type: ObjectIdentifier,
+) -> Result<SignedEnvelope> {
+    let content_info =
+        ContentInfo::from_der(der).map_err(|e| anyhow!("Not a DER CMS message: {}", e))?;
+    if content_info.content_type != ID_SIGNED_DATA {
+        bail!(
+            "CMS content type {} is not signed data",
+            content_info.content_type
+        );
+    }
+    let signed_data: SignedData = content_info
+        .content
+        .decode_as()
+        .map_err(|e| anyhow!("Invalid CMS SignedData: {}", e))?;
+
+    let encap = &signed_data.encap_content_info;
+    if encap.econtent_type != content_type {
+        bail!(
+            "Unexpected encapsulated content type {}",
+            encap.econtent_type
+        );
+    }
+    let content = match (&encap.econtent, detached_content) {
+        (Some(econtent), _) => econtent
+            .decode_as::<OctetString>()
+            .map_err(|e| anyhow!("Invalid encapsulated content: {}", e))?
+            .into_bytes(),
+        (None, Some(content)) => content.to_vec(),
+        (None, None) => bail!("Detached CMS signature given without its content"),
+    };
+
+    let certificates: Vec<Certificate> = signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(c) => Some(c.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let signer_infos = signed_data.signer_infos.0.as_slice();
+    let [signer_info] = signer_infos else {
+        bail!("Expected one CMS signer, found {}", signer_infos.len());
+    };
+
+    let signer_certificate = certificates
+        .iter()
+        .find(|c| identifies(&signer_info.sid, c))
+        .ok_or_else(|| anyhow!("Signer certificate is not included in the CMS message"))?
+        .clone();
+
+    let signed_attrs = signer_info
+        .signed_attrs
+        .as_ref()
+        .ok_or_else(|| anyhow!("CMS signer has no signed attributes"))?;
+
+    let signed_type: ObjectIdentifier = single_attribute(signed_attrs, ID_CONTENT_TYPE)?
+        .ok_or_else(|| anyhow!("CMS signed attributes lack a content type"))?
+        .decode_as()
+        .map_err(|e| anyhow!("Invalid CMS content type attribute: {}", e))?;
+    if signed_type != encap.econtent_type {
+        bail!("Signed content type does not match the encapsulated content");
+    }
+
+    let message_digest: OctetString = single_attribute(signed_attrs, ID_MESSAGE_DIGEST)?
+        .ok_or_else(|| anyhow!("CMS signed attributes lack a message digest"))?
+        .decode_as()
+        .map_err(|e| anyhow!("Invalid CMS message digest attribute: {}", e))?;
+    if message_digest.as_bytes() != digest(signer_info.digest_alg.oid, &content)?.as_slice() {
+        bail!("Content does not match the signed message digest");
+    }
+
+    // UTCTime or GeneralizedTime
+    let signing_time = match single_attribute(signed_attrs, ID_SIGNING_TIME)? {
+        Some(value) => {
+            let time = Time::from_der(&value.to_der()?)
+                .map_err(|e| anyhow!("Invalid CMS signing time attribute: {}", e))?;
+            DateTime::from_timestamp(time.to_unix_duration().as_secs() as i64, 0)
+        }
+        None => None,
+    };
+
+    x509::verify_with_spki(
+        &signer_certificate.tbs_certificate.subject_public_key_info,
+        &signer_info.signature_algorithm,
+        &signed_attrs.to_der()?,
+        signer_info.signature.as_bytes(),
+    )
+    .context("CMS signature does not verify with the signer certificate")?;
+
+    let certificates = certificates
+        .into_iter()
+        .filter(|c| *c != signer_certificate)
+        .collect();
+
+    Ok(SignedEnvelope {
+        content,
+        signer_certificate,
+        certificates,
+        signing_time,
+        signed_attrs: signed_attrs.clone(),
+    })
+}
+
+fn algorithm_id(oid: ObjectIdentifier) -> AlgorithmIdentifierOwned {
+    AlgorithmIdentifierOwned {
+        oid,
+        parameters: None,
+    }
+}
+
+pub(crate) fn attribute<T: Tagged + EncodeValue>(
+    oid: ObjectIdentifier,
+    value: &T,
+) -> Result<Attribute> {
+    Ok(Attribute {
+        oid,
+        values: SetOfVec::try_from(vec![Any::encode_from(value)?])?,
+    })
+}
+
+/// Value of a single-valued attribute, if present
+fn single_attribute(
+    attributes: &SetOfVec<Attribute>,
+    oid: ObjectIdentifier,
+) -> Result<Option<&Any>> {
+    let Some(attribute) = attributes.iter().find(|a| a.oid == oid) else {
+        return Ok(None);
+    };
+    match attribute.values.as_slice() {
+        [value] => Ok(Some(value)),
+        _ => bail!("CMS attribute {} must have exactly one value", oid),
+    }
+}
+
+pub(crate) fn digest(algorithm: ObjectIdentifier, content: &[u8]) -> Result<Vec<u8>> {
+    Ok(match algorithm {
+        ID_SHA256 => Sha256::digest(content).to_vec(),
+        ID_SHA384 => Sha384::digest(content).to_vec(),
+        ID_SHA512 => Sha512::digest(content).to_vec(),
+        other => bail!("Unsupported CMS digest algorithm {}", other),
+    })
+}
+
+fn identifies(sid: &SignerIdentifier, certificate: &Certificate) -> bool {
+    let tbs = &certificate.tbs_certificate;
+    match sid {
+        SignerIdentifier::IssuerAndSerialNumber(id) => {
+            id.issuer == tbs.issuer && id.serial_number == tbs.serial_number
+        }
+        SignerIdentifier::SubjectKeyIdentifier(ski) => {
+            matches!(tbs.get::<SubjectKeyIdentifier>(), Ok(Some((_, own))) if own == *ski)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::signing::SigningKey;
+
+    const ROOT: &str = include_str!("../../tests/fixtures/pki/root.pem");
+    const OTHER_ROOT: &str = include_str!("../../tests/fixtures/pki/other-root.pem");
+    const INTERMEDIATE: &str = include_str!("../../tests/fixtures/pki/intermediate.pem");
+    const ED25519_CERT: &str = include_str!("../../tests/fixtures/pki/signer-ed25519.pem");
+    const ED25519_KEY: &str = include_str!("../../tests/fixtures/pki/signer-ed25519.key");
+    const P256_CERT: &str = include_str!("../../tests/fixtures/pki/signer-p256.pem");
+    const P256_KEY: &str = include_str!("../../tests/fixtures/pki/signer-p256.key");
+
+    fn chain(leaf: &str) -> Vec<Certificate> {
+        x509::parse_certificates(format!("{}{}", leaf, INTERMEDIATE).as_bytes()).unwrap()
+    }
+
+    fn trust(pem: &str) -> TrustStore {
+        TrustStore::new(x509::parse_certificates(pem.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn test_enveloping_roundtrip_both_algorithms() {
+        for (cert, key) in [(ED25519_CERT, ED25519_KEY), (P256_CERT, P256_KEY)] {
+            let key = SigningKey::from_pem_or_der(key.as_bytes()).unwrap();
+            let der = sign(&key, &chain(cert), b"{\"a\":1}", false).unwrap();
+
+            let verified = verify(&der, None, &trust(ROOT)).unwrap();
+            assert_eq!(verified.content, b"{\"a\":1}");
+            assert!(verified.signing_time.is_some());
+            assert_eq!(verified.chain.subjects.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_detached_signature() {
+        let key = SigningKey::from_pem_or_der(ED25519_KEY.as_bytes()).unwrap();
+        let der = sign(&key, &chain(ED25519_CERT), b"payload", true).unwrap();
+
+        assert!(verify(&der, None, &trust(ROOT)).is_err());
+        assert_eq!(
+            verify(&der, Some(b"payload"), &trust(ROOT))
+                .unwrap()
+                .content,
+            b"payload"
+        );
+
+        let err = verify(&der, Some(b"tampered"), &trust(ROOT))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("message digest"), "{}", err);
+    }
+
+    #[test]
+    fn test_chain_checked_at_trusted_time_not_signing_time() {
+        use chrono::TimeZone;
+
+        let key = SigningKey::from_pem_or_der(ED25519_KEY.as_bytes()).unwrap();
+        let der = sign(&key, &chain(ED25519_CERT), b"payload", false).unwrap();
+        let mut envelope = open(&der, None).unwrap();
+
+        // A signingTime outside the certificate's validity changes nothing
+        envelope.signing_time = Some(Utc.with_ymd_and_hms(2040, 1, 1, 0, 0, 0).unwrap());
+        let (_, source) = envelope.validate_chain(&trust(ROOT), None).unwrap();
+        assert_eq!(source, ChainTime::Now);
+
+        let (chain, source) = envelope
+            .validate_chain(&trust(ROOT), Some(Utc::now()))
+            .unwrap();
+        assert_eq!(source, ChainTime::Timestamp);
+        assert_eq!(chain.subjects.len(), 3);
+
+        // The time-stamp time is what the validity periods are checked at
+        let expired = Utc.with_ymd_and_hms(2040, 1, 1, 0, 0, 0).unwrap();
+        assert!(envelope
+            .validate_chain(&trust(ROOT), Some(expired))
+            .is_err());
+    }
+
+    #[test]
+    fn test_untrusted_chain_rejected() {
+        let key = SigningKey::from_pem_or_der(ED25519_KEY.as_bytes()).unwrap();
+        let der = sign(&key, &chain(ED25519_CERT), b"payload", false).unwrap();
+        assert!(verify(&der, None, &trust(OTHER_ROOT)).is_err());
+    }
+
+    #[test]
+    fn test_tampered_envelope_rejected() {
+        let key = SigningKey::from_pem_or_der(P256_KEY.as_bytes()).unwrap();
+        let mut der = sign(&key, &chain(P256_CERT), b"payload-payload", false).unwrap();
+
+        let at = der
+            .windows(15)
+            .position(|w| w == b"payload-payload")
+            .unwrap();
+        der[at] = b'P';
+        assert!(verify(&der, None, &trust(ROOT)).is_err());
+    }
+
+    #[test]
+    fn test_key_must_match_certificate() {
+        let (other, _) = SigningKey::generate(SignatureAlgorithm::Ed25519).unwrap();
+        let err = sign(&other, &chain(ED25519_CERT), b"payload", false)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("not for the configured signing key"),
+            "{}",
+            err
+        );
+        assert!(sign(&other, &[], b"payload", false).is_err());
+    }
+}
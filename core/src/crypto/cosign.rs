@@ -0,0 +1,424 @@
+// Dual-control co-signing
+//
+// The issuing signature is the operator's, made when the wipe completes.
+// Further people (a supervisor who witnessed the wipe and reviewed its
+// evidence, say) add cosignatures afterwards with `cert cosign`. Each
+// cosignature names the signer's role and signs, with the signer's own key,
+// the canonical JSON of its other fields, which include
+// `certificate_hash` (SHA-256 of the issued, signed certificate). A
+// cosignature therefore covers the certificate and its issuing signature
+// but not other cosignatures, timestamps or anchors, so signers can be added
+// in any order.
+//
+// `[signing.policy]` lists the signers whose signatures count, by role and
+// public key, and how many of them must have signed for a certificate to be
+// final (N of M). The issuing signature counts as the `operator` role.
+// Signatures by keys the policy does not list are reported but not counted.
+
+use super::canonical_json;
+use super::certificates::{certificate_hash, WipeCertificate};
+use super::signing::{PublicKey, SignatureAlgorithm, Signer};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Role of the issuing signature
+pub const ISSUER_ROLE: &str = "operator";
+
+/// Role `cert cosign` signs as by default
+pub const DEFAULT_COSIGNER_ROLE: &str = "supervisor";
+
+/// A further signature over an issued certificate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cosignature {
+    /// What the signer signs as, e.g. `supervisor`
+    pub role: String,
+
+    /// Name of the person signing, when given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+
+    pub signed_at: DateTime<Utc>,
+
+    /// Hex [`certificate_hash`] of the certificate as cosigned
+    pub certificate_hash: String,
+
+    pub signature_algorithm: SignatureAlgorithm,
+
+    /// Signer's public key (base64 DER SubjectPublicKeyInfo)
+    pub public_key: String,
+
+    /// Hex signature over the canonical JSON of the other fields
+    pub signature: String,
+}
+
+impl Cosignature {
+    /// Bytes covered by the signature
+    pub fn signing_payload(&self) -> Result<Vec<u8>> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("signature");
+        }
+        Ok(canonical_json::to_canonical_string(&value)?.into_bytes())
+    }
+
+    /// The signer's key
+    pub fn key(&self) -> Result<PublicKey> {
+        let der = BASE64
+            .decode(&self.public_key)
+            .context("Cosigner public key is not base64")?;
+        PublicKey::from_spki_der(&der)
+    }
+
+    /// Check the cosignature is intact and covers `certificate`, returning
+    /// the signer's key
+    pub fn verify(&self, certificate: &WipeCertificate) -> Result<PublicKey> {
+        let key = self.key()?;
+        if self.certificate_hash != hex::encode(certificate_hash(certificate)?) {
+            bail!(
+                "{} cosignature by key {} is for a different certificate",
+                self.role,
+                key.fingerprint()
+            );
+        }
+        let signature = hex::decode(&self.signature)
+            .ok()
+            .ok_or_else(|| anyhow!("Invalid signature"))?;
+        if self.signature_algorithm != key.algorithm()
+            || !key.verify(&self.signing_payload()?, &signature)
+        {
+            bail!(
+                "{} cosignature by key {} does not match its contents",
+                self.role,
+                key.fingerprint()
+            );
+        }
+        Ok(key)
+    }
+}
+
+/// Add a cosignature by `key` to `certificate`
+///
+/// A key that already signed the certificate cannot sign it again, so two
+/// roles are always two keys.
+pub fn cosign(
+    certificate: &mut WipeCertificate,
+    role: &str,
+    signer: Option<&str>,
+    key: &dyn Signer,
+) -> Result<()> {
+    if role.trim().is_empty() {
+        bail!("A cosignature needs a role");
+    }
+    let fingerprint = key.public_key().fingerprint();
+    if certificate.signer_fingerprint.as_deref() == Some(fingerprint.as_str()) {
+        bail!(
+            "Key {} issued this certificate; cosign with another key",
+            fingerprint
+        );
+    }
+    for existing in &certificate.cosignatures {
+        if existing.key()?.fingerprint() == fingerprint {
+            bail!(
+                "Key {} already cosigned this certificate as {}",
+                fingerprint,
+                existing.role
+            );
+        }
+    }
+
+    let mut cosignature = Cosignature {
+        role: role.to_string(),
+        signer: signer.map(str::to_string),
+        signed_at: Utc::now(),
+        certificate_hash: hex::encode(certificate_hash(certificate)?),
+        signature_algorithm: key.algorithm(),
+        public_key: BASE64.encode(key.public_key().to_spki_der()),
+        signature: String::new(),
+    };
+    cosignature.signature = hex::encode(&key.sign(&cosignature.signing_payload()?)?);
+    certificate.cosignatures.push(cosignature);
+    Ok(())
+}
+
+/// `[signing.policy]`: whose signatures count, and how many are required
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignaturePolicyConfig {
+    /// Listed signers that must have signed for a certificate to be final;
+    /// 0 leaves certificates final with the issuing signature alone
+    #[serde(default)]
+    pub required: usize,
+
+    #[serde(default)]
+    pub signers: Vec<PolicySignerConfig>,
+}
+
+/// One signer listed in the policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicySignerConfig {
+    pub role: String,
+    /// Signer's public key (PEM)
+    pub public_key: PathBuf,
+}
+
+/// Loaded signature policy
+#[derive(Debug, Clone, Default)]
+pub struct SignaturePolicy {
+    required: usize,
+    signers: Vec<(String, PublicKey)>,
+}
+
+/// A valid signature on a certificate, and whether the policy counts it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignerRecord {
+    pub role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    pub fingerprint: String,
+    /// The policy lists this key for this role
+    pub trusted: bool,
+}
+
+/// How a certificate's signatures measure up to the policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyOutcome {
+    pub required: usize,
+    /// Roles of the listed signers that have signed
+    pub satisfied: Vec<String>,
+    /// Roles of the listed signers that have not
+    pub missing: Vec<String>,
+}
+
+impl PolicyOutcome {
+    pub fn is_final(&self) -> bool {
+        self.satisfied.len() >= self.required
+    }
+}
+
+impl SignaturePolicy {
+    pub fn new(required: usize, signers: Vec<(String, PublicKey)>) -> Self {
+        Self { required, signers }
+    }
+
+    pub fn from_config(config: &SignaturePolicyConfig) -> Result<Self> {
+        let signers: Vec<(String, PublicKey)> = config
+            .signers
+            .iter()
+            .map(|signer| {
+                let key = PublicKey::load(&signer.public_key).with_context(|| {
+                    format!("Cannot load the {} key of signing.policy", signer.role)
+                })?;
+                Ok((signer.role.clone(), key))
+            })
+            .collect::<Result<_>>()?;
+
+        let mut keys: Vec<_> = signers.iter().map(|(_, key)| key.fingerprint()).collect();
+        keys.sort();
+        keys.dedup();
+        if config.required > keys.len() {
+            bail!(
+                "signing.policy.required ({}) exceeds the {} distinct keys listed in signing.policy.signers",
+                config.required,
+                keys.len()
+            );
+        }
+        Ok(Self::new(config.required, signers))
+    }
+
+    /// True if certificates need more than their issuing signature checked
+    pub fn is_enforced(&self) -> bool {
+        self.required > 0
+    }
+
+    /// Whether the policy lists `fingerprint` for `role`
+    pub fn lists(&self, role: &str, fingerprint: &str) -> bool {
+        self.signers
+            .iter()
+            .any(|(listed, key)| listed == role && key.fingerprint() == fingerprint)
+    }
+
+    /// Match valid signatures against the listed signers
+    ///
+    /// Each key counts once: a key listed under several roles satisfies only
+    /// the first of them it signed as.
+    pub fn evaluate(&self, signatures: &[SignerRecord]) -> PolicyOutcome {
+        let mut counted: Vec<String> = Vec::new();
+        let mut satisfied = Vec::new();
+        let mut missing = Vec::new();
+        for (role, key) in &self.signers {
+            let fingerprint = key.fingerprint();
+            let signed = signatures
+                .iter()
+                .any(|s| &s.role == role && s.fingerprint == fingerprint);
+            if !signed {
+                missing.push(role.clone());
+            } else if !counted.contains(&fingerprint) {
+                counted.push(fingerprint);
+                satisfied.push(role.clone());
+            }
+        }
+        PolicyOutcome {
+            required: self.required,
+            satisfied,
+            missing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::certificates::{CertificateGenerator, VerificationResult, WipeDetails};
+    use crate::crypto::signing::SigningKey;
+    use crate::{DriveCapabilities, DriveInfo, DriveType, EncryptionStatus};
+
+    fn key() -> SigningKey {
+        SigningKey::generate(SignatureAlgorithm::Ed25519).unwrap().0
+    }
+
+    fn certificate(issuer: SigningKey) -> WipeCertificate {
+        CertificateGenerator::with_key(issuer)
+            .generate_certificate(
+                &DriveInfo {
+                    device_path: "/dev/sdz".to_string(),
+                    model: "WDC WD10EZEX".to_string(),
+                    serial: "WD-123".to_string(),
+                    size: 1_000_204_886_016,
+                    drive_type: DriveType::HDD,
+                    encryption_status: EncryptionStatus::None,
+                    capabilities: DriveCapabilities::default(),
+                    health_status: None,
+                    temperature_celsius: None,
+                },
+                WipeDetails {
+                    algorithm_used: "Zero".to_string(),
+                    passes_completed: 1,
+                    duration_seconds: 60,
+                    operator_id: Some("alice".to_string()),
+                    checkpoint_rejections: Vec::new(),
+                },
+                VerificationResult {
+                    verified: true,
+                    entropy_score: 0.0,
+                    recovery_test_passed: true,
+                    verification_timestamp: Utc::now(),
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cosignature_is_bound_to_its_contents() {
+        let supervisor = key();
+        let mut cert = certificate(key());
+        cosign(&mut cert, "supervisor", Some("bob"), &supervisor).unwrap();
+        let cosignature = cert.cosignatures[0].clone();
+        assert_eq!(
+            cosignature.verify(&cert).unwrap().fingerprint(),
+            supervisor.public_key().fingerprint()
+        );
+
+        // Claiming another role breaks the signature
+        let mut relabelled = cosignature.clone();
+        relabelled.role = "auditor".to_string();
+        assert!(relabelled.verify(&cert).is_err());
+
+        // So does changing the certificate, and one key signs only once
+        let mut changed = cert.clone();
+        changed.wipe_details.passes_completed = 3;
+        assert!(cosignature.verify(&changed).is_err());
+        assert!(cosign(&mut cert, "auditor", None, &supervisor).is_err());
+    }
+
+    #[test]
+    fn test_policy_counts_only_listed_keys_in_their_role() {
+        let (operator, supervisor, stranger) = (key(), key(), key());
+        let policy = SignaturePolicy::new(
+            2,
+            vec![
+                (ISSUER_ROLE.to_string(), operator.public_key().clone()),
+                ("supervisor".to_string(), supervisor.public_key().clone()),
+            ],
+        );
+        let record = |role: &str, key: &SigningKey| SignerRecord {
+            role: role.to_string(),
+            signer: None,
+            fingerprint: key.public_key().fingerprint(),
+            trusted: policy.lists(role, &key.public_key().fingerprint()),
+        };
+
+        let signed = [
+            record(ISSUER_ROLE, &operator),
+            record("supervisor", &stranger),
+        ];
+        let outcome = policy.evaluate(&signed);
+        assert!(!outcome.is_final());
+        assert_eq!(outcome.missing, ["supervisor"]);
+        assert!(!signed[1].trusted);
+
+        // The supervisor's key signing as the operator does not count either
+        let outcome = policy.evaluate(&[record(ISSUER_ROLE, &supervisor)]);
+        assert!(outcome.satisfied.is_empty());
+
+        let outcome = policy.evaluate(&[
+            record(ISSUER_ROLE, &operator),
+            record("supervisor", &supervisor),
+        ]);
+        assert!(outcome.is_final());
+        assert_eq!(outcome.satisfied, [ISSUER_ROLE, "supervisor"]);
+    }
+
+    #[test]
+    fn test_policy_counts_each_key_once() {
+        let operator = key();
+        let policy = SignaturePolicy::new(
+            2,
+            vec![
+                (ISSUER_ROLE.to_string(), operator.public_key().clone()),
+                ("supervisor".to_string(), operator.public_key().clone()),
+            ],
+        );
+        let record = |role: &str| SignerRecord {
+            role: role.to_string(),
+            signer: None,
+            fingerprint: operator.public_key().fingerprint(),
+            trusted: true,
+        };
+
+        let outcome = policy.evaluate(&[record(ISSUER_ROLE), record("supervisor")]);
+        assert!(!outcome.is_final());
+        assert_eq!(outcome.satisfied, [ISSUER_ROLE]);
+    }
+
+    #[test]
+    fn test_policy_config_needs_enough_distinct_keys() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("operator.pub.pem");
+        std::fs::write(&path, key().public_key().to_pem()).unwrap();
+        let signer = |role: &str| PolicySignerConfig {
+            role: role.to_string(),
+            public_key: path.clone(),
+        };
+
+        let config = SignaturePolicyConfig {
+            required: 2,
+            signers: vec![signer(ISSUER_ROLE), signer("supervisor")],
+        };
+        assert!(SignaturePolicy::from_config(&config).is_err());
+
+        let config = SignaturePolicyConfig {
+            required: 3,
+            signers: vec![signer(ISSUER_ROLE)],
+        };
+        assert!(SignaturePolicy::from_config(&config).is_err());
+
+        let config = SignaturePolicyConfig {
+            required: 1,
+            signers: vec![signer(ISSUER_ROLE), signer("supervisor")],
+        };
+        assert!(SignaturePolicy::from_config(&config).is_ok());
+    }
+}
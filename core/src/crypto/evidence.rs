@@ -0,0 +1,681 @@
+// Wipe evidence carried in certificates
+//
+// A certificate's `evidence` records how the wipe was actually carried out,
+// beyond the device and algorithm name: the drive's firmware, WWN and
+// transport, the method that ran and every fallback taken to reach it, one
+// record per completed overwrite pass (with what reading it back found,
+// when passes are verified), what was found in and done about the
+// HPA/DCO, degraded modes, bad sectors, and which build of the tool on which
+// host issued it. It is covered by the signature like every other field.
+//
+// Pass records, degraded modes and bad-sector reports come from the recovery
+// coordinator inside each algorithm, which never sees the certificate. They
+// are collected per device in a process-wide recorder and taken when the
+// certificate is built, much as checkpoint rejections are.
+//
+// A certificate whose wipe ran in any degraded mode that is not compliance
+// safe (see `DegradedMode::is_compliance_safe`), with bytes left unwritten
+// over bad sectors, or with a pass that still read back wrong after being
+// rewritten, is marked `compliant: false`.
+
+use crate::drives::{probe_transport, DriveIdentity, SanitizationDecision};
+use crate::error::mechanisms::{BadSectorReport, DegradedMode};
+use crate::error::RangeSet;
+use crate::verification::VerificationReport;
+use crate::{DriveInfo, DriveType, EncryptionStatus, HPADCOHandling, HealthStatus, WipeConfig};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Name recorded as the issuing tool
+pub const TOOL_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Everything known about how a wipe was carried out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WipeEvidence {
+    pub drive: DriveEvidence,
+    pub method: MethodEvidence,
+
+    /// Overwrite passes completed, in order
+    #[serde(default)]
+    pub passes: Vec<PassRecord>,
+
+    pub hidden_areas: HiddenAreaEvidence,
+
+    /// Degraded modes the wipe ran in
+    #[serde(default)]
+    pub degraded_modes: Vec<DegradedModeRecord>,
+
+    /// False if any degraded mode or pass read-back compromised compliance
+    pub compliant: bool,
+
+    /// Bad sectors met during the wipe, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bad_sectors: Option<BadSectorReport>,
+
+    /// Full report of the forensic verification, when one was run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_report: Option<VerificationReport>,
+
+    /// Problems reported during the wipe that did not stop it
+    #[serde(default)]
+    pub warnings: Vec<String>,
+
+    pub tool: ToolIdentity,
+    pub host: HostIdentity,
+}
+
+/// Drive details beyond the certificate's `device_info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriveEvidence {
+    pub drive_type: DriveType,
+    pub firmware: Option<String>,
+    /// World Wide Name
+    pub wwn: Option<String>,
+    /// Bus the drive was attached through (`sata`, `nvme`, `usb`, ...)
+    pub transport: Option<String>,
+    pub encryption_status: EncryptionStatus,
+    /// SMART health when the wipe started
+    pub health: Option<HealthStatus>,
+    pub temperature_celsius: Option<u32>,
+    /// Freeze state when the wipe started, and what was done about it
+    pub freeze_mitigation: Option<String>,
+}
+
+/// The method requested and the one that actually ran
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodEvidence {
+    /// Algorithm as configured
+    pub requested: String,
+    /// Method that erased the drive
+    pub executed: String,
+    /// Methods tried in between that failed or were unavailable
+    #[serde(default)]
+    pub fallbacks: Vec<MethodFallback>,
+    /// Formal name of the standard the executed method implements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standard: Option<String>,
+    /// NIST SP 800-88 technique chosen for the drive, and why
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nist: Option<SanitizationDecision>,
+    /// The scheme file the overwrite followed, for `scheme:` methods
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<SchemeEvidence>,
+}
+
+impl MethodEvidence {
+    /// `requested` ran as asked
+    pub fn new(requested: impl Into<String>) -> Self {
+        let requested = requested.into();
+        Self {
+            executed: requested.clone(),
+            requested,
+            fallbacks: Vec::new(),
+            standard: None,
+            nist: None,
+            scheme: None,
+        }
+    }
+
+    /// `method` was chosen to carry out the request
+    pub fn executing(mut self, method: impl Into<String>) -> Self {
+        self.executed = method.into();
+        self
+    }
+
+    /// The method that was about to run could not, so `next` ran instead
+    ///
+    /// The standard recorded for the replaced method is cleared.
+    pub fn fall_back(&mut self, reason: impl Into<String>, next: impl Into<String>) {
+        let next = next.into();
+        self.standard = None;
+        self.fallbacks.push(MethodFallback {
+            method: std::mem::replace(&mut self.executed, next.clone()),
+            reason: reason.into(),
+            replaced_by: next,
+        });
+    }
+}
+
+/// One step from a method that could not be used to the next
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodFallback {
+    pub method: String,
+    pub reason: String,
+    pub replaced_by: String,
+}
+
+/// A scheme file, identified by its hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemeEvidence {
+    /// The scheme's own name
+    pub name: String,
+    pub path: String,
+    /// Hex SHA-256 of the file as run
+    pub sha256: String,
+    /// What each pass wrote, in order
+    pub passes: Vec<String>,
+}
+
+/// A completed overwrite pass
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassRecord {
+    /// Algorithm or checkpoint label the pass belongs to
+    pub algorithm: String,
+    /// 1-based pass number
+    pub pass: usize,
+    pub total_passes: usize,
+    /// What was written (`0x00`, `random`, ...)
+    pub pattern: String,
+    /// Bytes covered by the pass
+    pub bytes: u64,
+    /// When this run of the pass started; later than the pass's first write
+    /// if it was resumed from a checkpoint
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    /// What reading the pass back found, if it was read back in full
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<PassVerification>,
+}
+
+/// A pass read back in full and compared with what was written
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassVerification {
+    /// Bytes read back
+    pub bytes: u64,
+    /// Byte ranges that read back wrong and were written again
+    pub rewritten: RangeSet,
+    /// Byte ranges still wrong after being rewritten
+    pub mismatched: RangeSet,
+}
+
+/// HPA/DCO findings and handling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HiddenAreaEvidence {
+    pub handling: HPADCOHandling,
+    /// Whether the drive was checked for hidden areas at all
+    pub checked: bool,
+    /// Sectors hidden by a Host Protected Area
+    pub hpa_hidden_sectors: Option<u64>,
+    /// Sectors hidden by a Device Configuration Overlay
+    pub dco_hidden_sectors: Option<u64>,
+    /// What was done about them
+    pub action: String,
+}
+
+impl HiddenAreaEvidence {
+    /// Hidden areas were not looked for
+    pub fn unchecked(handling: HPADCOHandling) -> Self {
+        Self {
+            handling,
+            checked: false,
+            hpa_hidden_sectors: None,
+            dco_hidden_sectors: None,
+            action: "not checked".to_string(),
+        }
+    }
+
+    /// True if hidden sectors were found and left unwiped
+    pub fn left_unwiped(&self) -> bool {
+        let found = self.hpa_hidden_sectors.unwrap_or(0) + self.dco_hidden_sectors.unwrap_or(0);
+        found > 0
+            && matches!(
+                self.handling,
+                HPADCOHandling::Ignore | HPADCOHandling::Detect
+            )
+    }
+}
+
+/// A degraded mode the wipe ran in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradedModeRecord {
+    pub mode: DegradedMode,
+    pub description: String,
+    pub severity: u8,
+    pub compliance_safe: bool,
+}
+
+impl From<DegradedMode> for DegradedModeRecord {
+    fn from(mode: DegradedMode) -> Self {
+        Self {
+            mode,
+            description: mode.description().to_string(),
+            severity: mode.severity(),
+            compliance_safe: mode.is_compliance_safe(),
+        }
+    }
+}
+
+/// Build of the tool that issued the certificate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolIdentity {
+    pub name: String,
+    pub version: String,
+}
+
+impl ToolIdentity {
+    pub fn current() -> Self {
+        Self {
+            name: TOOL_NAME.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Host the wipe ran on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostIdentity {
+    pub hostname: Option<String>,
+    /// systemd machine ID (/etc/machine-id)
+    pub machine_id: Option<String>,
+    /// PRETTY_NAME from /etc/os-release
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+}
+
+impl HostIdentity {
+    pub fn probe() -> Self {
+        let os = std::fs::read_to_string("/etc/os-release")
+            .ok()
+            .and_then(|text| {
+                text.lines().find_map(|line| {
+                    let value = line.strip_prefix("PRETTY_NAME=")?;
+                    Some(value.trim_matches('"').to_string())
+                })
+            });
+        Self {
+            hostname: read_trimmed("/proc/sys/kernel/hostname"),
+            machine_id: read_trimmed("/etc/machine-id"),
+            os,
+            kernel: read_trimmed("/proc/sys/kernel/osrelease"),
+        }
+    }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Evidence gathered while wiping, before the certificate exists
+#[derive(Debug, Clone, Default)]
+pub struct RecordedEvidence {
+    pub passes: Vec<PassRecord>,
+    pub degraded_modes: Vec<DegradedMode>,
+    pub bad_sectors: Option<BadSectorReport>,
+    pub fallbacks: Vec<MethodFallback>,
+}
+
+fn recorder() -> &'static Mutex<HashMap<String, RecordedEvidence>> {
+    static RECORDER: OnceLock<Mutex<HashMap<String, RecordedEvidence>>> = OnceLock::new();
+    RECORDER.get_or_init(Default::default)
+}
+
+fn with_recorded(device: &str, update: impl FnOnce(&mut RecordedEvidence)) {
+    if let Ok(mut recorded) = recorder().lock() {
+        update(recorded.entry(device.to_string()).or_default());
+    }
+}
+
+/// Note a completed pass on `device`
+pub fn record_pass(device: &str, pass: PassRecord) {
+    with_recorded(device, |recorded| recorded.passes.push(pass));
+}
+
+/// Note that `method` could not be used on `device` and `replaced_by` ran
+/// in its place
+pub fn record_fallback(
+    device: &str,
+    method: impl Into<String>,
+    reason: impl Into<String>,
+    replaced_by: impl Into<String>,
+) {
+    let fallback = MethodFallback {
+        method: method.into(),
+        reason: reason.into(),
+        replaced_by: replaced_by.into(),
+    };
+    with_recorded(device, |recorded| recorded.fallbacks.push(fallback));
+}
+
+/// Note degraded modes and bad sectors from a recovery coordinator
+/// finishing with `device`
+pub fn record_recovery(device: &str, modes: &[DegradedMode], bad_sectors: Option<BadSectorReport>) {
+    with_recorded(device, |recorded| {
+        for mode in modes {
+            if !recorded.degraded_modes.contains(mode) {
+                recorded.degraded_modes.push(*mode);
+            }
+        }
+        if let Some(report) = bad_sectors {
+            recorded.bad_sectors = Some(report);
+        }
+    });
+}
+
+/// Forget anything recorded for `device`, before a new wipe of it starts
+pub fn clear_recorded(device: &str) {
+    if let Ok(mut recorded) = recorder().lock() {
+        recorded.remove(device);
+    }
+}
+
+/// Everything recorded for `device` since it was last cleared or taken
+pub fn take_recorded(device: &str) -> RecordedEvidence {
+    recorder()
+        .lock()
+        .ok()
+        .and_then(|mut recorded| recorded.remove(device))
+        .unwrap_or_default()
+}
+
+impl WipeEvidence {
+    /// Assemble the evidence for a finished wipe of `drive`
+    ///
+    /// Takes what was recorded for the device during the wipe and probes the
+    /// drive and host for the rest. Hidden areas left in place and disabled
+    /// verification count as the matching degraded modes.
+    pub fn collect(
+        drive: &DriveInfo,
+        config: &WipeConfig,
+        mut method: MethodEvidence,
+        hidden_areas: HiddenAreaEvidence,
+        freeze_mitigation: Option<String>,
+        warnings: Vec<String>,
+    ) -> Self {
+        let identity = DriveIdentity::probe(&drive.device_path);
+        let recorded = take_recorded(&drive.device_path);
+
+        for fallback in recorded.fallbacks {
+            method.executed = fallback.replaced_by.clone();
+            method.fallbacks.push(fallback);
+        }
+
+        let mut modes = recorded.degraded_modes;
+        if hidden_areas.left_unwiped() && !modes.contains(&DegradedMode::SkipHiddenAreas) {
+            modes.push(DegradedMode::SkipHiddenAreas);
+        }
+        if !config.verify && !modes.contains(&DegradedMode::SkipVerification) {
+            modes.push(DegradedMode::SkipVerification);
+        }
+
+        let mut evidence = Self {
+            drive: DriveEvidence {
+                drive_type: drive.drive_type.clone(),
+                firmware: identity.firmware,
+                wwn: identity.wwn,
+                transport: probe_transport(&drive.device_path),
+                encryption_status: drive.encryption_status.clone(),
+                health: drive.health_status.clone(),
+                temperature_celsius: drive.temperature_celsius,
+                freeze_mitigation,
+            },
+            method,
+            passes: recorded.passes,
+            hidden_areas,
+            compliant: false,
+            degraded_modes: modes.into_iter().map(DegradedModeRecord::from).collect(),
+            bad_sectors: recorded.bad_sectors,
+            verification_report: None,
+            warnings,
+            tool: ToolIdentity::current(),
+            host: HostIdentity::probe(),
+        };
+        evidence.compliant = evidence.compliance_problems().is_empty();
+        evidence
+    }
+
+    /// Attach the forensic verification report
+    pub fn with_verification_report(mut self, report: VerificationReport) -> Self {
+        self.verification_report = Some(report);
+        self
+    }
+
+    /// Degraded modes that make the wipe non-compliant
+    pub fn compliance_risks(&self) -> impl Iterator<Item = &DegradedModeRecord> {
+        self.degraded_modes
+            .iter()
+            .filter(|record| !record.compliance_safe)
+    }
+
+    /// Everything that makes the wipe non-compliant: unsafe degraded modes,
+    /// bytes skipped over bad sectors, and passes with bytes still wrong
+    /// after being rewritten
+    pub fn compliance_problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self
+            .compliance_risks()
+            .map(|record| record.description.clone())
+            .collect();
+        if let Some(skipped) = self
+            .bad_sectors
+            .as_ref()
+            .map(|report| &report.skipped_ranges)
+            .filter(|skipped| !skipped.is_empty())
+        {
+            problems.push(format!(
+                "{} bytes in {} range(s) left unwritten over bad sectors",
+                skipped.covered(),
+                skipped.range_count()
+            ));
+        }
+        for pass in &self.passes {
+            let Some(verification) = &pass.verification else {
+                continue;
+            };
+            if !verification.mismatched.is_empty() {
+                problems.push(format!(
+                    "Pass {} ({}): {} bytes still read back wrong after rewriting",
+                    pass.pass,
+                    pass.pattern,
+                    verification.mismatched.covered()
+                ));
+            }
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::BadSectorHandler;
+    use crate::DriveCapabilities;
+
+    fn drive(device_path: &str) -> DriveInfo {
+        DriveInfo {
+            device_path: device_path.to_string(),
+            model: "WDC WD10EZEX".to_string(),
+            serial: "WD-123".to_string(),
+            size: 1_000_204_886_016,
+            drive_type: DriveType::HDD,
+            encryption_status: EncryptionStatus::None,
+            capabilities: DriveCapabilities::default(),
+            health_status: Some(HealthStatus::Good),
+            temperature_celsius: Some(34),
+        }
+    }
+
+    fn pass(pass: usize) -> PassRecord {
+        PassRecord {
+            algorithm: "DoD".to_string(),
+            pass,
+            total_passes: 3,
+            pattern: "0x00".to_string(),
+            bytes: 1 << 20,
+            started_at: Utc::now(),
+            completed_at: Utc::now(),
+            verification: None,
+        }
+    }
+
+    #[test]
+    fn test_fallbacks_chain_to_the_executed_method() {
+        let mut method = MethodEvidence::new("SecureErase").executing("NVMe sanitize");
+        method.fall_back("sanitize not supported", "DoD 5220.22-M");
+        assert_eq!(method.requested, "SecureErase");
+        assert_eq!(method.executed, "DoD 5220.22-M");
+        assert_eq!(
+            method.fallbacks,
+            vec![MethodFallback {
+                method: "NVMe sanitize".to_string(),
+                reason: "sanitize not supported".to_string(),
+                replaced_by: "DoD 5220.22-M".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_recorded_evidence_is_collected_per_device() {
+        let device = "/dev/sayonara_evidence_test_a";
+        let other = "/dev/sayonara_evidence_test_b";
+        clear_recorded(device);
+        record_pass(device, pass(1));
+        record_pass(other, pass(1));
+        record_pass(device, pass(2));
+        record_recovery(
+            device,
+            &[DegradedMode::SlowerIO, DegradedMode::SlowerIO],
+            None,
+        );
+        record_fallback(
+            device,
+            "NVMe sanitize",
+            "not supported",
+            "software overwrite",
+        );
+
+        let config = WipeConfig::default();
+        let evidence = WipeEvidence::collect(
+            &drive(device),
+            &config,
+            MethodEvidence::new("DoD5220").executing("wipe orchestrator"),
+            HiddenAreaEvidence::unchecked(HPADCOHandling::Detect),
+            None,
+            Vec::new(),
+        );
+        assert_eq!(
+            evidence.passes.iter().map(|p| p.pass).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(evidence.degraded_modes.len(), 1);
+        assert_eq!(evidence.method.executed, "software overwrite");
+        assert_eq!(evidence.method.fallbacks[0].method, "NVMe sanitize");
+        assert_eq!(evidence.compliant, config.verify);
+        assert_eq!(evidence.tool.name, TOOL_NAME);
+        assert_eq!(evidence.drive.health, Some(HealthStatus::Good));
+
+        // Taken once; the other device's passes are untouched
+        assert!(take_recorded(device).passes.is_empty());
+        assert_eq!(take_recorded(other).passes.len(), 1);
+    }
+
+    #[test]
+    fn test_unsafe_degraded_modes_make_the_wipe_non_compliant() {
+        let device = "/dev/sayonara_evidence_test_c";
+        clear_recorded(device);
+        let config = WipeConfig {
+            verify: true,
+            ..WipeConfig::default()
+        };
+
+        let hidden = HiddenAreaEvidence {
+            handling: HPADCOHandling::Detect,
+            checked: true,
+            hpa_hidden_sectors: Some(2048),
+            dco_hidden_sectors: None,
+            action: "detected, left in place".to_string(),
+        };
+        let evidence = WipeEvidence::collect(
+            &drive(device),
+            &config,
+            MethodEvidence::new("Zero"),
+            hidden,
+            None,
+            Vec::new(),
+        );
+        assert!(!evidence.compliant);
+        let risks: Vec<_> = evidence.compliance_risks().map(|r| r.mode).collect();
+        assert_eq!(risks, vec![DegradedMode::SkipHiddenAreas]);
+
+        record_recovery(device, &[DegradedMode::TolerateBadSectors], None);
+        let evidence = WipeEvidence::collect(
+            &drive(device),
+            &config,
+            MethodEvidence::new("Zero"),
+            HiddenAreaEvidence::unchecked(HPADCOHandling::TemporaryRemove),
+            None,
+            Vec::new(),
+        );
+        assert!(evidence.compliant);
+        assert_eq!(evidence.degraded_modes.len(), 1);
+
+        // Tolerating bad sectors is fine; leaving bytes unwritten is not
+        let handler = BadSectorHandler::new(device);
+        handler.record_bad_range(4096, 8192, "EIO").unwrap();
+        record_recovery(
+            device,
+            &[DegradedMode::TolerateBadSectors],
+            Some(handler.generate_report()),
+        );
+        let evidence = WipeEvidence::collect(
+            &drive(device),
+            &config,
+            MethodEvidence::new("Zero"),
+            HiddenAreaEvidence::unchecked(HPADCOHandling::TemporaryRemove),
+            None,
+            Vec::new(),
+        );
+        assert!(!evidence.compliant);
+        assert_eq!(
+            evidence.compliance_problems(),
+            vec!["8192 bytes in 1 range(s) left unwritten over bad sectors"]
+        );
+    }
+
+    #[test]
+    fn test_pass_still_wrong_after_rewrite_is_non_compliant() {
+        let device = "/dev/sayonara_evidence_test_d";
+        clear_recorded(device);
+        let config = WipeConfig {
+            verify: true,
+            ..WipeConfig::default()
+        };
+
+        let mut verified = pass(1);
+        verified.verification = Some(PassVerification {
+            bytes: 1 << 20,
+            rewritten: RangeSet::from_range(0..4096),
+            mismatched: RangeSet::new(),
+        });
+        record_pass(device, verified.clone());
+        let evidence = WipeEvidence::collect(
+            &drive(device),
+            &config,
+            MethodEvidence::new("Zero"),
+            HiddenAreaEvidence::unchecked(HPADCOHandling::TemporaryRemove),
+            None,
+            Vec::new(),
+        );
+        assert!(evidence.compliant);
+
+        // Rewriting did not fix it
+        verified.verification.as_mut().unwrap().mismatched = RangeSet::from_range(0..4096);
+        record_pass(device, verified);
+        let evidence = WipeEvidence::collect(
+            &drive(device),
+            &config,
+            MethodEvidence::new("Zero"),
+            HiddenAreaEvidence::unchecked(HPADCOHandling::TemporaryRemove),
+            None,
+            Vec::new(),
+        );
+        assert!(!evidence.compliant);
+        assert_eq!(
+            evidence.compliance_problems(),
+            vec!["Pass 1 (0x00): 4096 bytes still read back wrong after rewriting"]
+        );
+    }
+}
@@ -1,9 +1,34 @@
+pub mod anchor;
+pub mod bundle;
+pub mod canonical_json;
 pub mod certificates;
+pub mod cms;
+pub mod cosign;
+pub mod evidence;
+pub mod nist_form;
+pub mod pdf;
+pub mod pkcs11;
+pub mod privacy;
 pub mod secure_rng; // Made public for testing
+pub mod signing;
+pub mod timestamp;
+pub mod transparency;
+pub mod verifier;
+pub mod x509;
 
 #[cfg(test)]
 mod secure_rng_tests;
 
 // Re-export
-pub use certificates::{CertificateGenerator, VerificationResult, WipeCertificate, WipeDetails};
-pub use secure_rng::secure_random_bytes; // Export for compliance tests
+pub use anchor::{AnchorConfig, ExpectedAnchor};
+pub use certificates::{
+    verify_cms_certificate, verify_signature, CertificateGenerator, SignatureStatus,
+    VerificationResult, WipeCertificate, WipeDetails,
+};
+pub use evidence::{MethodEvidence, WipeEvidence};
+pub use pkcs11::{Pkcs11Config, Pkcs11Signer};
+pub use secure_rng::secure_random_bytes;
+pub use signing::{
+    CertificateFormat, PublicKey, SignatureAlgorithm, Signer, SigningConfig, SigningKey,
+};
+pub use verifier::{CertificateReport, CertificateVerifier}; // Export for compliance tests
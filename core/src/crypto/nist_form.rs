@@ -0,0 +1,720 @@
+// NIST SP 800-88 Rev. 1 Appendix G "Certificate of Sanitization"
+//
+// Maps a wipe certificate, and the forensic verification report when there
+// is one, onto the sample certificate form of Appendix G: the person who
+// performed the sanitization, the media, the sanitization method and its
+// verification, the media's destination, and signature and validation
+// blocks. The form renders as printable HTML or as a PDF.
+//
+// Much of the form is about the organization rather than the drive
+// (classification, property numbers, backups, who validated the work).
+// Those fields are kept, with no value, and rendered as boxed
+// "MANUAL ENTRY REQUIRED" entries so nothing is silently dropped from the
+// form. The signed certificate stays authoritative; the form only presents
+// it.
+
+use super::certificates::WipeCertificate;
+use super::evidence::WipeEvidence;
+use super::pdf::{Font, PdfWriter};
+use super::privacy;
+use crate::verification::{VerificationLevel, VerificationReport};
+use crate::DriveType;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// Shown in place of every field sayonara cannot fill
+pub const MANUAL_ENTRY: &str = "MANUAL ENTRY REQUIRED";
+
+/// Statement above the signature block, as worded in Appendix G
+pub const ATTESTATION: &str =
+    "I attest that the information provided on this statement is accurate to the best of my knowledge.";
+
+const TITLE: &str = "Certificate of Sanitization";
+const SUBTITLE: &str = "NIST SP 800-88 Rev. 1, Appendix G";
+
+/// One entry of the form; `None` must be completed by hand
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormField {
+    pub label: &'static str,
+    pub value: Option<String>,
+}
+
+/// A block of the form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormSection {
+    pub title: &'static str,
+    /// Guidance printed under the title
+    pub note: Option<&'static str>,
+    pub fields: Vec<FormField>,
+}
+
+/// A filled-in Appendix G form
+#[derive(Debug, Clone, Serialize)]
+pub struct SanitizationForm {
+    pub certificate_id: String,
+    pub sections: Vec<FormSection>,
+}
+
+fn field(label: &'static str, value: Option<String>) -> FormField {
+    FormField { label, value }
+}
+
+fn manual(label: &'static str) -> FormField {
+    field(label, None)
+}
+
+impl SanitizationForm {
+    /// Fill the form from `certificate`
+    ///
+    /// `report` defaults to the verification report in the certificate's
+    /// evidence.
+    pub fn new(certificate: &WipeCertificate, report: Option<&VerificationReport>) -> Self {
+        let evidence = certificate.evidence.as_ref();
+        let report = report.or_else(|| evidence.and_then(|e| e.verification_report.as_ref()));
+        let operator = certificate.wipe_details.operator_id.clone();
+        // The recorded 800-88 decision wins over classifying the method name
+        let (method_type, method_used) = match evidence {
+            Some(e) => match &e.method.nist {
+                Some(nist) => (
+                    nist.level.map(|level| level.to_string()),
+                    Some(nist.technique.clone()),
+                ),
+                None => {
+                    let (method_type, method_used) = classify_method(&e.method.executed);
+                    (
+                        method_type.map(str::to_string),
+                        method_used.map(str::to_string),
+                    )
+                }
+            },
+            None => (None, None),
+        };
+
+        let person = FormSection {
+            title: "Person Performing Sanitization",
+            note: None,
+            fields: vec![
+                field("Name", operator.clone()),
+                manual("Title"),
+                manual("Organization"),
+                manual("Location"),
+                manual("Phone"),
+            ],
+        };
+
+        let info = &certificate.device_info;
+        let media = FormSection {
+            title: "Media Information",
+            note: None,
+            fields: vec![
+                manual("Make / Vendor"),
+                field("Model Number", non_empty(&info.model)),
+                field("Serial Number", non_empty(&info.serial)),
+                manual("Media Property Number"),
+                field(
+                    "Media Type",
+                    evidence.map(|e| media_type(&e.drive.drive_type).to_string()),
+                ),
+                manual("Source (user name or PC property number)"),
+                manual("Classification"),
+                manual("Data Backed Up"),
+                manual("Backup Location"),
+            ],
+        };
+
+        let tool = evidence.map(|e| format!("{} {}", e.tool.name, e.tool.version));
+        let sanitization = FormSection {
+            title: "Sanitization Details",
+            note: None,
+            fields: vec![
+                field("Method Type", method_type),
+                field("Method Used", method_used),
+                field("Method Details", Some(method_details(certificate))),
+                field("Tool Used (include version)", tool),
+                field(
+                    "Verification Method",
+                    verification_method(certificate, report),
+                ),
+                manual("Post Sanitization Classification"),
+                field("Notes", Some(notes(certificate, report))),
+            ],
+        };
+
+        let destination = FormSection {
+            title: "Media Destination",
+            note: Some(
+                "Internal Reuse / External Reuse / Recycling Facility / Manufacturer / Other",
+            ),
+            fields: vec![manual("Destination")],
+        };
+
+        let signature = FormSection {
+            title: "Signature",
+            note: Some(ATTESTATION),
+            fields: vec![
+                field("Name", operator),
+                manual("Title"),
+                manual("Date"),
+                manual("Location"),
+                manual("Phone"),
+                manual("Signature"),
+            ],
+        };
+
+        let validation = FormSection {
+            title: "Validation",
+            note: None,
+            fields: vec![
+                manual("Name"),
+                manual("Title"),
+                manual("Date"),
+                manual("Location"),
+                manual("Phone"),
+                manual("Signature"),
+            ],
+        };
+
+        Self {
+            certificate_id: certificate.certificate_id.clone(),
+            sections: vec![
+                person,
+                media,
+                sanitization,
+                destination,
+                signature,
+                validation,
+            ],
+        }
+    }
+
+    /// Fields left for completion by hand, as (section, label)
+    pub fn manual_fields(&self) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {
+        self.sections.iter().flat_map(|section| {
+            section
+                .fields
+                .iter()
+                .filter(|field| field.value.is_none())
+                .map(move |field| (section.title, field.label))
+        })
+    }
+
+    /// Printable, self-contained HTML document
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title} {id}</title>\n<style>\n{style}</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n<p class=\"subtitle\">{subtitle} &middot; Certificate {id}</p>\n",
+            title = TITLE,
+            subtitle = SUBTITLE,
+            id = escape_html(&self.certificate_id),
+            style = HTML_STYLE,
+        );
+        for section in &self.sections {
+            let _ = writeln!(html, "<h2>{}</h2>", section.title);
+            if let Some(note) = section.note {
+                let _ = writeln!(html, "<p class=\"note\">{}</p>", escape_html(note));
+            }
+            html.push_str("<table>\n");
+            for field in &section.fields {
+                match &field.value {
+                    Some(value) => {
+                        let _ = writeln!(
+                            html,
+                            "<tr><th>{}</th><td>{}</td></tr>",
+                            field.label,
+                            escape_html(value).replace('\n', "<br>")
+                        );
+                    }
+                    None => {
+                        let _ = writeln!(
+                            html,
+                            "<tr><th>{}</th><td class=\"manual\">{}</td></tr>",
+                            field.label, MANUAL_ENTRY
+                        );
+                    }
+                }
+            }
+            html.push_str("</table>\n");
+        }
+        let _ = write!(
+            html,
+            "<p class=\"footer\">Generated by {} {} from signed wipe certificate {}. \
+             The signed certificate is authoritative.</p>\n</body>\n</html>\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            escape_html(&self.certificate_id)
+        );
+        html
+    }
+
+    /// The form as a PDF
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let mut pdf = PdfWriter::new(format!("{} {}", TITLE, self.certificate_id));
+        pdf.paragraph(TITLE, Font::Bold, 18.0);
+        pdf.paragraph(
+            &format!("{} - Certificate {}", SUBTITLE, self.certificate_id),
+            Font::Regular,
+            9.0,
+        );
+        for section in &self.sections {
+            pdf.heading(section.title, 12.0);
+            if let Some(note) = section.note {
+                pdf.paragraph(note, Font::Regular, 9.0);
+            }
+            for field in &section.fields {
+                match &field.value {
+                    Some(value) => pdf.field(field.label, value, false),
+                    None => pdf.field(field.label, MANUAL_ENTRY, true),
+                }
+            }
+            pdf.gap(4.0);
+        }
+        pdf.paragraph(
+            &format!(
+                "Generated by {} {} from signed wipe certificate {}. The signed certificate is authoritative.",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION"),
+                self.certificate_id
+            ),
+            Font::Regular,
+            8.0,
+        );
+        pdf.finish()
+    }
+}
+
+/// Appendix G method type (Clear/Purge) and method used for a method
+/// recorded in the evidence
+fn classify_method(executed: &str) -> (Option<&'static str>, Option<&'static str>) {
+    let method = executed.to_ascii_lowercase();
+    if method.contains("crypto") {
+        (Some("Purge"), Some("Crypto Erase"))
+    } else if method.contains("sanitize") {
+        (Some("Purge"), Some("Block Erase (NVMe Sanitize)"))
+    } else if method.contains("secureerase") || method.contains("secure erase") {
+        (Some("Purge"), Some("Other: ATA/NVMe Secure Erase"))
+    } else if method.contains("trim") {
+        (None, Some("Other: TRIM"))
+    } else if [
+        "dod",
+        "gutmann",
+        "random",
+        "zero",
+        "overwrite",
+        "hmg",
+        "vsitr",
+        "rcmp",
+        "gost",
+        "schneier",
+        "navso",
+        "scheme",
+    ]
+    .iter()
+    .any(|name| method.contains(name))
+    {
+        (Some("Clear"), Some("Overwrite"))
+    } else {
+        (None, None)
+    }
+}
+
+fn media_type(drive_type: &DriveType) -> &'static str {
+    match drive_type {
+        DriveType::HDD | DriveType::SMR => "Magnetic (hard disk drive)",
+        DriveType::SSD | DriveType::NVMe => "Flash memory (solid state drive)",
+        DriveType::EMMC | DriveType::UFS => "Flash memory (embedded)",
+        DriveType::USB => "Flash memory (USB removable)",
+        DriveType::Optane => "3D XPoint (Intel Optane)",
+        DriveType::HybridSSHD => "Hybrid (magnetic with flash cache)",
+        DriveType::RAID => "RAID array member",
+        DriveType::Unknown => "Unknown",
+    }
+}
+
+fn method_details(certificate: &WipeCertificate) -> String {
+    let details = &certificate.wipe_details;
+    let mut lines = Vec::new();
+    match &certificate.evidence {
+        Some(evidence) => {
+            let method = &evidence.method;
+            if method.executed == method.requested {
+                lines.push(format!("Executed: {}", method.executed));
+            } else {
+                lines.push(format!(
+                    "Executed: {} (requested {})",
+                    method.executed, method.requested
+                ));
+            }
+            if let Some(standard) = &method.standard {
+                lines.push(format!("Standard: {}", standard));
+            }
+            for fallback in &method.fallbacks {
+                lines.push(format!(
+                    "Fell back from {} to {}: {}",
+                    fallback.method, fallback.replaced_by, fallback.reason
+                ));
+            }
+            if let Some(nist) = &method.nist {
+                for reason in &nist.reasoning {
+                    lines.push(format!("NIST 800-88: {}", reason));
+                }
+            }
+            lines.push(pass_summary(evidence));
+            lines.push(format!(
+                "Hidden areas (HPA/DCO): {}",
+                evidence.hidden_areas.action
+            ));
+        }
+        None => {
+            lines.push(format!("Algorithm: {}", details.algorithm_used));
+            lines.push(format!("Passes completed: {}", details.passes_completed));
+        }
+    }
+    lines.push(format!(
+        "Capacity: {} bytes; duration {} s",
+        certificate.device_info.size, details.duration_seconds
+    ));
+    lines.join("\n")
+}
+
+fn pass_summary(evidence: &WipeEvidence) -> String {
+    if evidence.passes.is_empty() {
+        return "No software overwrite passes".to_string();
+    }
+    let patterns: Vec<_> = evidence
+        .passes
+        .iter()
+        .map(|pass| pass.pattern.as_str())
+        .collect();
+    let mut summary = format!(
+        "{} overwrite pass(es): {}",
+        evidence.passes.len(),
+        patterns.join(", ")
+    );
+    let read_back: Vec<_> = evidence
+        .passes
+        .iter()
+        .filter_map(|pass| pass.verification.as_ref())
+        .collect();
+    if !read_back.is_empty() {
+        let mismatched: u64 = read_back.iter().map(|v| v.mismatched.covered()).sum();
+        let _ = write!(
+            summary,
+            "; {} read back in full, {} byte(s) still mismatched",
+            read_back.len(),
+            mismatched
+        );
+    }
+    summary
+}
+
+fn verification_method(
+    certificate: &WipeCertificate,
+    report: Option<&VerificationReport>,
+) -> Option<String> {
+    if let Some(report) = report {
+        let kind = match report.verification_level {
+            VerificationLevel::Level1RandomSampling => "Quick Sampling (random)",
+            VerificationLevel::Level2SystematicSampling => "Quick Sampling (systematic)",
+            VerificationLevel::Level3FullScan => "Full",
+            VerificationLevel::Level4ForensicScan => "Full (forensic, including hidden areas)",
+        };
+        return Some(format!(
+            "{}: {}; confidence {:.1}%, entropy {:.3}",
+            kind,
+            report.verification_method,
+            report.confidence_level,
+            report.post_wipe_analysis.entropy_score
+        ));
+    }
+
+    let verification = &certificate.verification;
+    if verification.verified {
+        Some(format!(
+            "Quick Sampling: sector recovery test passed, entropy {:.3}",
+            verification.entropy_score
+        ))
+    } else if verification.entropy_score == 0.0 && !verification.recovery_test_passed {
+        Some("None performed".to_string())
+    } else {
+        Some("Quick Sampling: sector recovery test FAILED".to_string())
+    }
+}
+
+fn notes(certificate: &WipeCertificate, report: Option<&VerificationReport>) -> String {
+    let mut lines = vec![format!(
+        "Signed wipe certificate {} issued {} ({}{})",
+        certificate.certificate_id,
+        certificate.timestamp.to_rfc3339(),
+        certificate.signature_algorithm,
+        certificate
+            .signer_fingerprint
+            .as_deref()
+            .map(|fp| format!(", key {}", fp))
+            .unwrap_or_default()
+    )];
+    lines.push(format!(
+        "Device {}, identity hash {}",
+        certificate.device_info.device_path, certificate.device_info.device_hash
+    ));
+
+    if let Some(evidence) = &certificate.evidence {
+        if let Some(firmware) = &evidence.drive.firmware {
+            lines.push(format!("Firmware {}", firmware));
+        }
+        if let Some(host) = &evidence.host.hostname {
+            lines.push(format!("Sanitized on host {}", host));
+        }
+        if evidence.compliant {
+            lines.push("No compliance-affecting degraded modes".to_string());
+        } else {
+            lines.push(format!(
+                "NOT COMPLIANT: {}",
+                evidence.compliance_problems().join("; ")
+            ));
+        }
+        for warning in &evidence.warnings {
+            lines.push(format!("Warning: {}", warning));
+        }
+    }
+    if let Some(report) = report {
+        if !report.compliance_standards.is_empty() {
+            lines.push(format!(
+                "Verification meets: {}",
+                report.compliance_standards.join(", ")
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty() && value != "Unknown" && value != privacy::REDACTED)
+        .then(|| value.to_string())
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: Helvetica, Arial, sans-serif; font-size: 10pt; max-width: 7.5in; margin: 0.5in auto; color: #000; }
+h1 { font-size: 18pt; margin-bottom: 0; }
+h2 { font-size: 12pt; border-bottom: 1px solid #000; margin: 14pt 0 4pt; }
+.subtitle, .note, .footer { font-size: 9pt; color: #333; }
+table { width: 100%; border-collapse: collapse; }
+th { width: 2.4in; text-align: left; vertical-align: top; padding: 3pt 6pt 3pt 0; }
+td { padding: 3pt; vertical-align: top; }
+td.manual { color: #b00; font-weight: bold; border: 1px dashed #b00; height: 2em; }
+@media print { body { margin: 0; } h2 { break-after: avoid; } tr { break-inside: avoid; } }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::evidence::{
+        HiddenAreaEvidence, MethodEvidence, PassRecord, PassVerification,
+    };
+    use crate::crypto::signing::{SignatureAlgorithm, SigningKey};
+    use crate::crypto::{CertificateGenerator, VerificationResult, WipeDetails};
+    use crate::drives::SanitizationDecision;
+    use crate::error::RangeSet;
+    use crate::{
+        DriveCapabilities, DriveInfo, EncryptionStatus, HPADCOHandling, NistLevel, WipeConfig,
+    };
+
+    fn drive() -> DriveInfo {
+        DriveInfo {
+            device_path: "/dev/sayonara_nist_form_test".to_string(),
+            model: "Samsung SSD 870 EVO 1TB".to_string(),
+            serial: "S5H2NS0W123456".to_string(),
+            size: 1_000_204_886_016,
+            drive_type: DriveType::SSD,
+            encryption_status: EncryptionStatus::None,
+            capabilities: DriveCapabilities::default(),
+            health_status: None,
+            temperature_celsius: None,
+        }
+    }
+
+    fn certificate(operator: Option<&str>, with_evidence: bool) -> WipeCertificate {
+        let generator = CertificateGenerator::with_key(
+            SigningKey::generate(SignatureAlgorithm::Ed25519).unwrap().0,
+        );
+        let mut method = MethodEvidence::new("SecureErase");
+        method.fall_back("drive is frozen", "DoD5220");
+        let evidence = with_evidence.then(|| {
+            WipeEvidence::collect(
+                &drive(),
+                &WipeConfig::default(),
+                method,
+                HiddenAreaEvidence::unchecked(HPADCOHandling::Detect),
+                None,
+                vec!["TRIM failed: <unsupported>".to_string()],
+            )
+        });
+        generator
+            .generate_certificate_with_evidence(
+                &drive(),
+                WipeDetails {
+                    algorithm_used: "SecureErase".to_string(),
+                    passes_completed: 3,
+                    duration_seconds: 5400,
+                    operator_id: operator.map(str::to_string),
+                    checkpoint_rejections: Vec::new(),
+                },
+                VerificationResult {
+                    verified: true,
+                    entropy_score: 7.9,
+                    recovery_test_passed: true,
+                    verification_timestamp: chrono::Utc::now(),
+                },
+                evidence,
+            )
+            .unwrap()
+    }
+
+    fn value<'a>(form: &'a SanitizationForm, section: &str, label: &str) -> Option<&'a str> {
+        form.sections
+            .iter()
+            .find(|s| s.title == section)
+            .and_then(|s| s.fields.iter().find(|f| f.label == label))
+            .and_then(|f| f.value.as_deref())
+    }
+
+    #[test]
+    fn test_certificate_fields_fill_the_form() {
+        let form = SanitizationForm::new(&certificate(Some("jdoe"), true), None);
+
+        assert_eq!(
+            value(&form, "Person Performing Sanitization", "Name"),
+            Some("jdoe")
+        );
+        assert_eq!(
+            value(&form, "Media Information", "Serial Number"),
+            Some("S5H2NS0W123456")
+        );
+        assert_eq!(
+            value(&form, "Media Information", "Media Type"),
+            Some("Flash memory (solid state drive)")
+        );
+        // The method that ran, not the one requested, decides Clear/Purge
+        assert_eq!(
+            value(&form, "Sanitization Details", "Method Type"),
+            Some("Clear")
+        );
+        assert_eq!(
+            value(&form, "Sanitization Details", "Method Used"),
+            Some("Overwrite")
+        );
+        let details = value(&form, "Sanitization Details", "Method Details").unwrap();
+        assert!(details.contains("Fell back from SecureErase to DoD5220: drive is frozen"));
+        let tool = value(&form, "Sanitization Details", "Tool Used (include version)").unwrap();
+        assert!(tool.contains(env!("CARGO_PKG_VERSION")));
+        let notes = value(&form, "Sanitization Details", "Notes").unwrap();
+        assert!(notes.contains(&form.certificate_id));
+    }
+
+    #[test]
+    fn test_recorded_nist_decision_fills_method() {
+        let mut cert = certificate(None, true);
+        cert.evidence.as_mut().unwrap().method.nist = Some(SanitizationDecision {
+            target: Some(NistLevel::Purge),
+            level: Some(NistLevel::Purge),
+            media: "ATA flash SSD".to_string(),
+            technique: "ATA Enhanced Secure Erase".to_string(),
+            reasoning: vec!["Drive supports ATA enhanced secure erase".to_string()],
+        });
+        let form = SanitizationForm::new(&cert, None);
+
+        assert_eq!(
+            value(&form, "Sanitization Details", "Method Type"),
+            Some("Purge")
+        );
+        assert_eq!(
+            value(&form, "Sanitization Details", "Method Used"),
+            Some("ATA Enhanced Secure Erase")
+        );
+        let details = value(&form, "Sanitization Details", "Method Details").unwrap();
+        assert!(details.contains("NIST 800-88: Drive supports ATA enhanced secure erase"));
+    }
+
+    #[test]
+    fn test_read_back_passes_are_summarized() {
+        let mut cert = certificate(None, true);
+        let evidence = cert.evidence.as_mut().unwrap();
+        evidence.passes = vec![PassRecord {
+            algorithm: "DoD".to_string(),
+            pass: 1,
+            total_passes: 1,
+            pattern: "0x00".to_string(),
+            bytes: 1 << 20,
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            verification: Some(PassVerification {
+                bytes: 1 << 20,
+                rewritten: RangeSet::from_range(4096..12288),
+                mismatched: RangeSet::from_range(8192..12288),
+            }),
+        }];
+
+        assert_eq!(
+            pass_summary(evidence),
+            "1 overwrite pass(es): 0x00; 1 read back in full, 4096 byte(s) still mismatched"
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields_are_marked_for_manual_entry() {
+        let form = SanitizationForm::new(&certificate(None, false), None);
+        let manual: Vec<_> = form.manual_fields().collect();
+
+        for expected in [
+            ("Person Performing Sanitization", "Name"),
+            ("Media Information", "Classification"),
+            ("Sanitization Details", "Method Type"),
+            ("Media Destination", "Destination"),
+            ("Signature", "Signature"),
+            ("Validation", "Date"),
+        ] {
+            assert!(manual.contains(&expected), "{:?} not manual", expected);
+        }
+
+        let html = form.to_html();
+        assert_eq!(
+            html.matches(&format!("class=\"manual\">{}", MANUAL_ENTRY))
+                .count(),
+            manual.len()
+        );
+        let pdf = String::from_utf8_lossy(&form.to_pdf()).to_string();
+        assert_eq!(
+            pdf.matches(&format!("({})", MANUAL_ENTRY)).count(),
+            manual.len()
+        );
+    }
+
+    #[test]
+    fn test_html_escapes_certificate_text() {
+        let form = SanitizationForm::new(&certificate(Some("<script>"), true), None);
+        let html = form.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("TRIM failed: &lt;unsupported&gt;"));
+        assert!(html.contains(ATTESTATION));
+    }
+}
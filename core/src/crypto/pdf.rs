@@ -0,0 +1,379 @@
+// Minimal PDF writer for printable certificate forms
+//
+// Produces PDF 1.4 documents of US Letter pages laid out top to bottom:
+// headings, wrapped paragraphs and label/value rows. Text is set in the
+// standard Helvetica fonts every PDF reader provides, so nothing is embedded,
+// and encoded as WinAnsi; characters outside Latin-1 print as `?`. Content
+// streams are left uncompressed so the output is easy to inspect and diff.
+
+use std::io::Write as _;
+
+const PAGE_WIDTH: f32 = 612.0;
+const PAGE_HEIGHT: f32 = 792.0;
+const MARGIN: f32 = 54.0;
+const BODY_SIZE: f32 = 10.0;
+const LINE_GAP: f32 = 1.35;
+
+/// Width of the label column in `field` rows
+const LABEL_WIDTH: f32 = 170.0;
+
+/// Helvetica advance widths (1/1000 em) for ASCII 32..=126, from the AFM
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Typeface of a run of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Lays text out onto pages and serializes them as a PDF
+#[derive(Debug)]
+pub struct PdfWriter {
+    title: String,
+    pages: Vec<Vec<u8>>,
+    current: Vec<u8>,
+    y: f32,
+}
+
+impl PdfWriter {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            pages: Vec::new(),
+            current: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    /// Bold section heading with a rule beneath it
+    pub fn heading(&mut self, text: &str, size: f32) {
+        let height = size * LINE_GAP + 8.0;
+        self.reserve(height + BODY_SIZE * LINE_GAP * 2.0);
+        self.y -= 6.0;
+        self.show(MARGIN, self.y - size, Font::Bold, size, text);
+        self.y -= size * LINE_GAP;
+        let _ = writeln!(
+            self.current,
+            "0.5 w {:.1} {:.1} m {:.1} {:.1} l S",
+            MARGIN,
+            self.y,
+            PAGE_WIDTH - MARGIN,
+            self.y
+        );
+        self.y -= 4.0;
+    }
+
+    /// Paragraph wrapped to the page width
+    pub fn paragraph(&mut self, text: &str, font: Font, size: f32) {
+        for line in wrap(text, size, PAGE_WIDTH - 2.0 * MARGIN) {
+            self.reserve(size * LINE_GAP);
+            self.show(MARGIN, self.y - size, font, size, &line);
+            self.y -= size * LINE_GAP;
+        }
+        self.y -= size * 0.5;
+    }
+
+    /// Label and value side by side; `missing` values are boxed so they
+    /// stand out for completion by hand
+    pub fn field(&mut self, label: &str, value: &str, missing: bool) {
+        let value_x = MARGIN + LABEL_WIDTH;
+        let value_width = PAGE_WIDTH - MARGIN - value_x;
+        let lines = wrap(value, BODY_SIZE, value_width - 8.0);
+        let line_height = BODY_SIZE * LINE_GAP;
+        let height = line_height * lines.len().max(1) as f32;
+        let box_height = if missing {
+            height + line_height
+        } else {
+            height
+        };
+        self.reserve(box_height + 4.0);
+
+        self.show(MARGIN, self.y - BODY_SIZE, Font::Bold, BODY_SIZE, label);
+        if missing {
+            let _ = writeln!(
+                self.current,
+                "0.6 w [3 2] 0 d {:.1} {:.1} {:.1} {:.1} re S [] 0 d",
+                value_x - 3.0,
+                self.y - box_height - 2.0,
+                value_width,
+                box_height + 2.0
+            );
+            let _ = writeln!(self.current, "0.75 0 0 rg");
+        }
+        let mut y = self.y;
+        for line in &lines {
+            self.show(value_x, y - BODY_SIZE, Font::Regular, BODY_SIZE, line);
+            y -= line_height;
+        }
+        if missing {
+            let _ = writeln!(self.current, "0 0 0 rg");
+        }
+        self.y -= box_height + 4.0;
+    }
+
+    /// Vertical space
+    pub fn gap(&mut self, points: f32) {
+        self.y -= points;
+    }
+
+    /// The finished document
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.current.is_empty() || self.pages.is_empty() {
+            self.pages.push(std::mem::take(&mut self.current));
+        }
+
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            Vec::new(), // page tree, once the page objects are numbered
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        ];
+        let mut info = b"<< /Producer (".to_vec();
+        info.extend(escape(concat!(
+            env!("CARGO_PKG_NAME"),
+            " ",
+            env!("CARGO_PKG_VERSION")
+        )));
+        info.extend(b") /Title (");
+        info.extend(escape(&self.title));
+        info.extend(b") >>");
+        objects.push(info);
+        let info_id = objects.len();
+
+        let page_count = self.pages.len();
+        let mut kids = Vec::new();
+        for (index, content) in self.pages.iter().enumerate() {
+            let mut stream = content.clone();
+            let _ = write!(stream, "BT /F1 8 Tf {:.1} {:.1} Td (", MARGIN, MARGIN / 2.0);
+            stream.extend(escape(&format!(
+                "{} - page {} of {}",
+                self.title,
+                index + 1,
+                page_count
+            )));
+            stream.extend(b") Tj ET\n");
+
+            let mut object = format!("<< /Length {} >>\nstream\n", stream.len()).into_bytes();
+            object.extend(stream);
+            object.extend(b"\nendstream");
+            objects.push(object);
+            let content_id = objects.len();
+
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH, PAGE_HEIGHT, content_id
+                )
+                .into_bytes(),
+            );
+            kids.push(format!("{} 0 R", objects.len()));
+        }
+        objects[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            page_count
+        )
+        .into_bytes();
+
+        let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+            pdf.extend(object);
+            pdf.extend(b"\nendobj\n");
+        }
+        let xref = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                info_id,
+                xref
+            )
+            .into_bytes(),
+        );
+        pdf
+    }
+
+    /// Start a new page unless `height` more points fit on this one
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < MARGIN && !self.current.is_empty() {
+            self.pages.push(std::mem::take(&mut self.current));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn show(&mut self, x: f32, y: f32, font: Font, size: f32, text: &str) {
+        let _ = write!(
+            self.current,
+            "BT /{} {} Tf {:.1} {:.1} Td (",
+            font.resource(),
+            size,
+            x,
+            y
+        );
+        self.current.extend(escape(text));
+        self.current.extend(b") Tj ET\n");
+    }
+}
+
+/// Width of `text` in Helvetica at `size` points
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match c as u32 {
+            code @ 32..=126 => HELVETICA_WIDTHS[(code - 32) as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// Break `text` into lines no wider than `width`, keeping explicit newlines
+fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(&candidate, size) <= width || line.is_empty() {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            }
+            // Words wider than the line (hashes, paths) are split anywhere
+            while text_width(&line, size) > width {
+                let mut head = String::new();
+                for c in line.chars() {
+                    head.push(c);
+                    if head.len() > 1 && text_width(&head, size) > width {
+                        head.pop();
+                        break;
+                    }
+                }
+                let rest = line[head.len()..].to_string();
+                lines.push(head);
+                line = rest;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// `text` as the bytes of a PDF literal string in WinAnsi encoding
+fn escape(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(c as u8);
+            }
+            ' '..='~' => out.push(c as u8),
+            '\u{a0}'..='\u{ff}' => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cross_reference_offsets_point_at_objects() {
+        let mut pdf = PdfWriter::new("Test (form)");
+        pdf.heading("Section", 13.0);
+        pdf.field("Name", "Jane Doe", false);
+        pdf.field("Title", "MANUAL ENTRY REQUIRED", true);
+        let bytes = pdf.finish();
+        let text = String::from_utf8_lossy(&bytes);
+
+        assert!(bytes.starts_with(b"%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        let xref = std::str::from_utf8(&bytes[startxref..]).unwrap();
+        assert!(xref.starts_with("xref\n"));
+
+        let entries: Vec<usize> = xref
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 7);
+        for (index, offset) in entries.iter().enumerate() {
+            assert!(bytes[*offset..].starts_with(format!("{} 0 obj", index + 1).as_bytes()));
+        }
+        assert!(text.contains("(Test \\(form\\) - page 1 of 1)"));
+    }
+
+    #[test]
+    fn test_long_content_flows_onto_more_pages() {
+        let mut pdf = PdfWriter::new("Long");
+        for i in 0..200 {
+            pdf.field(&format!("Row {}", i), "value", i % 2 == 0);
+        }
+        let text = String::from_utf8_lossy(&pdf.finish()).to_string();
+        let pages = text.matches("/Type /Page ").count();
+        assert!(pages > 1);
+        assert!(text.contains(&format!("/Count {}", pages)));
+    }
+
+    #[test]
+    fn test_wrap_respects_width() {
+        let text = "The quick brown fox jumps over the lazy dog ".repeat(8);
+        let lines = wrap(&text, 10.0, 200.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| text_width(line, 10.0) <= 200.0));
+
+        let hash = "ab".repeat(64);
+        let lines = wrap(&hash, 10.0, 120.0);
+        assert_eq!(lines.concat(), hash);
+        assert!(lines.iter().all(|line| text_width(line, 10.0) <= 120.0));
+
+        assert_eq!(wrap("one\ntwo", 10.0, 200.0), vec!["one", "two"]);
+    }
+
+    #[test]
+    fn test_escape_winansi() {
+        assert_eq!(escape("a(b)\\"), b"a\\(b\\)\\\\".to_vec());
+        assert_eq!(escape("°C"), vec![0xB0, b'C']);
+        assert_eq!(escape("✓"), b"?".to_vec());
+    }
+}
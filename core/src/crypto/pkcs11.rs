@@ -0,0 +1,711 @@
+// PKCS#11 certificate signing
+//
+// With `signing.pkcs11.module` set, certificates are signed by a key that
+// lives on an HSM, smart card or TPM-backed token instead of a file on the
+// wipe station. The module is the vendor's PKCS#11 library; the token is
+// picked by slot ID or token label (the only token present otherwise) and the
+// key by its CKA_LABEL. The user PIN comes from an environment variable or a
+// file named by `pin_source`; tokens with a protected authentication path
+// (PIN pad, TPM policy) log in without one.
+//
+// The private key is only ever used through C_Sign. Its CKA_VALUE is never
+// read, and keys the token would let out (CKA_EXTRACTABLE, or not
+// CKA_SENSITIVE) are refused. The public half is read from the public key
+// object with the same label:
+// - Ed25519 (CKK_EC_EDWARDS) keys sign the message with CKM_EDDSA
+// - P-256 (CKK_EC) keys sign its SHA-256 digest with CKM_ECDSA, and the raw
+//   r || s the token returns is re-encoded as the DER ECDSA-Sig-Value that
+//   certificates carry
+//
+// Every signature is checked against the public key before it is returned,
+// so a token that signs with the wrong key cannot issue certificates.
+
+use super::signing::{PublicKey, SignatureAlgorithm, Signer};
+use anyhow::{anyhow, bail, Context, Result};
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::error::{Error as CryptokiError, RvError};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::slot::Slot;
+use cryptoki::types::AuthPin;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+
+// DER CKA_EC_PARAMS of the supported curves
+const P256_EC_PARAMS: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const ED25519_EC_PARAMS_OID: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x70];
+/// PrintableString "edwards25519", which PKCS#11 3.0 allows in place of the OID
+const ED25519_EC_PARAMS_NAME: &[u8] = b"\x13\x0cedwards25519";
+
+/// PKCS#11 token configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pkcs11Config {
+    /// Path of the token's PKCS#11 library; keys are not on a token when unset
+    pub module: Option<PathBuf>,
+
+    /// Slot ID holding the token
+    pub slot: Option<u64>,
+
+    /// Label of the token, used when `slot` is unset
+    pub token_label: Option<String>,
+
+    /// CKA_LABEL of the signing key pair
+    pub key_label: Option<String>,
+
+    /// Where the user PIN is read from: `env:VARIABLE` or `file:PATH`; the
+    /// token's protected authentication path is used when unset
+    pub pin_source: Option<String>,
+}
+
+impl Pkcs11Config {
+    /// True if certificates are to be signed on a token
+    pub fn is_configured(&self) -> bool {
+        self.module.is_some()
+    }
+}
+
+/// Where the token's user PIN comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinSource {
+    /// Environment variable holding the PIN
+    Env(String),
+    /// File whose first line is the PIN
+    File(PathBuf),
+}
+
+impl FromStr for PinSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some(("env", name)) if !name.is_empty() => Ok(Self::Env(name.to_string())),
+            Some(("file", path)) if !path.is_empty() => Ok(Self::File(PathBuf::from(path))),
+            _ => bail!(
+                "expected env:VARIABLE or file:PATH (got {:?}); PINs are never \
+                 written into the configuration itself",
+                s
+            ),
+        }
+    }
+}
+
+impl PinSource {
+    /// Read the PIN
+    pub fn read(&self) -> Result<AuthPin> {
+        let pin = match self {
+            Self::Env(name) => std::env::var(name)
+                .with_context(|| format!("PKCS#11 PIN variable {} is not set", name))?,
+            Self::File(path) => {
+                let text = std::fs::read_to_string(path).with_context(|| {
+                    format!("Failed to read PKCS#11 PIN file {}", path.display())
+                })?;
+                text.lines().next().unwrap_or_default().to_string()
+            }
+        };
+        if pin.is_empty() {
+            bail!("PKCS#11 PIN from {} is empty", self);
+        }
+        Ok(AuthPin::new(pin))
+    }
+}
+
+impl fmt::Display for PinSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "env:{}", name),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+        }
+    }
+}
+
+/// Loaded and initialised PKCS#11 library for `module`
+///
+/// Libraries are finalised when their context is dropped, which would pull
+/// the token out from under any other signer using it, so one context per
+/// module is kept for the life of the process.
+fn context(module: &Path) -> Result<Pkcs11> {
+    static CONTEXTS: OnceLock<Mutex<HashMap<PathBuf, Pkcs11>>> = OnceLock::new();
+    let mut contexts = CONTEXTS
+        .get_or_init(Default::default)
+        .lock()
+        .map_err(|_| anyhow!("PKCS#11 module cache poisoned"))?;
+    if let Some(context) = contexts.get(module) {
+        return Ok(context.clone());
+    }
+
+    let context = Pkcs11::new(module)
+        .with_context(|| format!("Failed to load PKCS#11 module {}", module.display()))?;
+    match context.initialize(CInitializeArgs::OsThreads) {
+        Ok(()) | Err(CryptokiError::Pkcs11(RvError::CryptokiAlreadyInitialized, _)) => {}
+        Err(e) => {
+            return Err(e).with_context(|| {
+                format!("Failed to initialise PKCS#11 module {}", module.display())
+            })
+        }
+    }
+    contexts.insert(module.to_path_buf(), context.clone());
+    Ok(context)
+}
+
+/// Signing key held on a PKCS#11 token
+pub struct Pkcs11Signer {
+    module: PathBuf,
+    key_label: String,
+    /// PKCS#11 sessions must not be used from two threads at once
+    session: Mutex<Session>,
+    key: ObjectHandle,
+    public_key: PublicKey,
+}
+
+impl fmt::Debug for Pkcs11Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pkcs11Signer")
+            .field("module", &self.module)
+            .field("key_label", &self.key_label)
+            .field("algorithm", &self.public_key.algorithm())
+            .field("fingerprint", &self.public_key.fingerprint())
+            .finish()
+    }
+}
+
+impl Pkcs11Signer {
+    /// Log in to the configured token and find the signing key on it
+    pub fn open(config: &Pkcs11Config) -> Result<Self> {
+        let module = config
+            .module
+            .as_deref()
+            .ok_or_else(|| anyhow!("signing.pkcs11.module is not set"))?;
+        let key_label = config
+            .key_label
+            .as_deref()
+            .ok_or_else(|| anyhow!("signing.pkcs11.key_label is not set"))?;
+        let pin = config
+            .pin_source
+            .as_deref()
+            .map(|source| source.parse::<PinSource>()?.read())
+            .transpose()?;
+
+        let context = context(module)?;
+        let slot = find_slot(&context, config)?;
+        let session = context
+            .open_ro_session(slot)
+            .with_context(|| format!("Failed to open a session on PKCS#11 slot {}", slot))?;
+
+        if pin.is_none()
+            && !context
+                .get_token_info(slot)?
+                .protected_authentication_path()
+        {
+            bail!(
+                "The token in PKCS#11 slot {} has no protected authentication path; \
+                 set signing.pkcs11.pin_source",
+                slot
+            );
+        }
+        match session.login(UserType::User, pin.as_ref()) {
+            // Logins are per token, so another signer may have done it already
+            Ok(()) | Err(CryptokiError::Pkcs11(RvError::UserAlreadyLoggedIn, _)) => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to log in to PKCS#11 slot {}", slot))
+            }
+        }
+
+        let (key, public_key) = find_key(&session, key_label)
+            .with_context(|| format!("PKCS#11 signing key \"{}\"", key_label))?;
+        tracing::info!(
+            "Signing certificates with {} key \"{}\" on PKCS#11 slot {} ({})",
+            public_key.algorithm(),
+            key_label,
+            slot,
+            public_key.fingerprint()
+        );
+
+        Ok(Self {
+            module: module.to_path_buf(),
+            key_label: key_label.to_string(),
+            session: Mutex::new(session),
+            key,
+            public_key,
+        })
+    }
+}
+
+impl Signer for Pkcs11Signer {
+    fn algorithm(&self) -> SignatureAlgorithm {
+        self.public_key.algorithm()
+    }
+
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let session = self
+            .session
+            .lock()
+            .map_err(|_| anyhow!("PKCS#11 session lock poisoned"))?;
+        let signature = match self.public_key.algorithm() {
+            SignatureAlgorithm::Ed25519 => session.sign(&Mechanism::Eddsa, self.key, message)?,
+            SignatureAlgorithm::EcdsaP256Sha256 => {
+                let digest = Sha256::digest(message);
+                ecdsa_raw_to_der(&session.sign(&Mechanism::Ecdsa, self.key, &digest)?)?
+            }
+            SignatureAlgorithm::LegacySha256 => bail!("Legacy keys cannot sign"),
+        };
+        drop(session);
+
+        if !self.public_key.verify(message, &signature) {
+            bail!(
+                "PKCS#11 key \"{}\" returned a signature that does not verify",
+                self.key_label
+            );
+        }
+        Ok(signature)
+    }
+}
+
+/// Slot named in `config`, or the only slot holding a token
+fn find_slot(context: &Pkcs11, config: &Pkcs11Config) -> Result<Slot> {
+    let slots = context
+        .get_slots_with_token()
+        .context("Failed to list PKCS#11 slots")?;
+
+    if let Some(id) = config.slot {
+        return slots
+            .into_iter()
+            .find(|slot| slot.id() == id)
+            .ok_or_else(|| anyhow!("No token in PKCS#11 slot {}", id));
+    }
+    if let Some(label) = &config.token_label {
+        for slot in slots {
+            if context.get_token_info(slot)?.label().trim_end() == label {
+                return Ok(slot);
+            }
+        }
+        bail!("No PKCS#11 token labelled \"{}\"", label);
+    }
+    match slots.as_slice() {
+        [slot] => Ok(*slot),
+        [] => bail!("No PKCS#11 token present"),
+        _ => bail!(
+            "{} PKCS#11 tokens present; set signing.pkcs11.slot or signing.pkcs11.token_label",
+            slots.len()
+        ),
+    }
+}
+
+/// Private key labelled `label` and the public key that goes with it
+fn find_key(session: &Session, label: &str) -> Result<(ObjectHandle, PublicKey)> {
+    let key = find_one(session, ObjectClass::PRIVATE_KEY, label)?;
+    let attributes = session.get_attributes(
+        key,
+        &[
+            AttributeType::KeyType,
+            AttributeType::Sensitive,
+            AttributeType::Extractable,
+            AttributeType::Sign,
+        ],
+    )?;
+    let mut key_type = None;
+    for attribute in attributes {
+        match attribute {
+            Attribute::KeyType(kind) => key_type = Some(kind),
+            Attribute::Sensitive(false) => {
+                bail!("the private key is not CKA_SENSITIVE and could be read off the token")
+            }
+            Attribute::Extractable(true) => {
+                bail!("the private key is CKA_EXTRACTABLE and could be exported from the token")
+            }
+            Attribute::Sign(false) => bail!("the private key is not allowed to sign (CKA_SIGN)"),
+            _ => {}
+        }
+    }
+
+    let public = find_one(session, ObjectClass::PUBLIC_KEY, label)
+        .context("the token must also hold the public key under the same label")?;
+    let mut params = None;
+    let mut point = None;
+    for attribute in
+        session.get_attributes(public, &[AttributeType::EcParams, AttributeType::EcPoint])?
+    {
+        match attribute {
+            Attribute::EcParams(value) => params = Some(value),
+            Attribute::EcPoint(value) => point = Some(value),
+            _ => {}
+        }
+    }
+    let (params, point) = params
+        .zip(point)
+        .ok_or_else(|| anyhow!("the public key has no CKA_EC_PARAMS/CKA_EC_POINT"))?;
+
+    let algorithm = curve_algorithm(&params)?;
+    let expected_type = match algorithm {
+        SignatureAlgorithm::Ed25519 => KeyType::EC_EDWARDS,
+        _ => KeyType::EC,
+    };
+    if key_type != Some(expected_type) {
+        bail!(
+            "the private key type does not match its {} public key",
+            algorithm
+        );
+    }
+    let public_key = PublicKey::new(algorithm, unwrap_ec_point(&point)?)?;
+    Ok((key, public_key))
+}
+
+/// The single object of `class` labelled `label`
+fn find_one(session: &Session, class: ObjectClass, label: &str) -> Result<ObjectHandle> {
+    let kind = if class == ObjectClass::PRIVATE_KEY {
+        "private key"
+    } else {
+        "public key"
+    };
+    let objects = session.find_objects(&[
+        Attribute::Class(class),
+        Attribute::Label(label.as_bytes().to_vec()),
+    ])?;
+    match objects.as_slice() {
+        [object] => Ok(*object),
+        [] => bail!("no {} labelled \"{}\" on the token", kind, label),
+        _ => bail!(
+            "{} {}s labelled \"{}\" on the token",
+            objects.len(),
+            kind,
+            label
+        ),
+    }
+}
+
+/// Signature algorithm for a key with DER `CKA_EC_PARAMS` `params`
+fn curve_algorithm(params: &[u8]) -> Result<SignatureAlgorithm> {
+    if params == P256_EC_PARAMS {
+        Ok(SignatureAlgorithm::EcdsaP256Sha256)
+    } else if params == ED25519_EC_PARAMS_OID || params == ED25519_EC_PARAMS_NAME {
+        Ok(SignatureAlgorithm::Ed25519)
+    } else {
+        bail!("unsupported curve: only P-256 and Ed25519 keys can sign certificates")
+    }
+}
+
+/// Raw public key from `CKA_EC_POINT`
+///
+/// The standard wraps the point in a DER OCTET STRING, but some tokens
+/// return it bare.
+fn unwrap_ec_point(point: &[u8]) -> Result<&[u8]> {
+    match point {
+        [0x04, 0x20, rest @ ..] | [0x04, 0x41, rest @ ..]
+            if rest.len() == usize::from(point[1]) =>
+        {
+            Ok(rest)
+        }
+        _ if point.len() == 32 || point.len() == 65 => Ok(point),
+        _ => bail!("malformed CKA_EC_POINT ({} bytes)", point.len()),
+    }
+}
+
+/// DER ECDSA-Sig-Value for the raw `r || s` signature CKM_ECDSA produces
+fn ecdsa_raw_to_der(raw: &[u8]) -> Result<Vec<u8>> {
+    if raw.len() != 64 {
+        bail!("unexpected P-256 signature length {} from token", raw.len());
+    }
+    let mut body = Vec::with_capacity(72);
+    for half in raw.chunks(32) {
+        let trimmed = match half.iter().position(|&b| b != 0) {
+            Some(start) => &half[start..],
+            None => &half[31..],
+        };
+        body.push(0x02);
+        if trimmed[0] & 0x80 != 0 {
+            body.push(trimmed.len() as u8 + 1);
+            body.push(0x00);
+        } else {
+            body.push(trimmed.len() as u8);
+        }
+        body.extend_from_slice(trimmed);
+    }
+    let mut der = vec![0x30, body.len() as u8];
+    der.extend(body);
+    Ok(der)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::certificates::{
+        verify_signature, CertificateGenerator, VerificationResult, WipeDetails,
+    };
+    use crate::{DriveCapabilities, DriveInfo, DriveType, EncryptionStatus};
+    use chrono::Utc;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+    #[test]
+    fn test_raw_ecdsa_signatures_become_der() {
+        // Known-answer: high bit set in r (padded), leading zeros in s (trimmed)
+        let mut raw = vec![0x80; 32];
+        raw.extend([0x00, 0x00, 0x01]);
+        raw.extend([0x7f; 29]);
+        let der = ecdsa_raw_to_der(&raw).unwrap();
+        assert_eq!(&der[..5], &[0x30, 2 + 33 + 2 + 30, 0x02, 33, 0x00]);
+        assert_eq!(&der[37..40], &[0x02, 30, 0x01]);
+        assert_eq!(der.len(), 2 + 2 + 33 + 2 + 30);
+
+        // Round trip through ring: fixed signatures re-encoded must verify
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+            .unwrap();
+        let public_key = PublicKey::new(
+            SignatureAlgorithm::EcdsaP256Sha256,
+            pair.public_key().as_ref(),
+        )
+        .unwrap();
+        for i in 0..32u8 {
+            let message = [i; 16];
+            let fixed = pair.sign(&rng, &message).unwrap();
+            let der = ecdsa_raw_to_der(fixed.as_ref()).unwrap();
+            assert!(public_key.verify(&message, &der));
+        }
+
+        assert!(ecdsa_raw_to_der(&[0u8; 63]).is_err());
+    }
+
+    #[test]
+    fn test_ec_key_attributes_are_decoded() {
+        assert_eq!(
+            curve_algorithm(P256_EC_PARAMS).unwrap(),
+            SignatureAlgorithm::EcdsaP256Sha256
+        );
+        assert_eq!(
+            curve_algorithm(ED25519_EC_PARAMS_OID).unwrap(),
+            SignatureAlgorithm::Ed25519
+        );
+        assert_eq!(
+            curve_algorithm(ED25519_EC_PARAMS_NAME).unwrap(),
+            SignatureAlgorithm::Ed25519
+        );
+        // secp384r1
+        assert!(curve_algorithm(&[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22]).is_err());
+
+        let raw = [0x11u8; 32];
+        let wrapped = [&[0x04, 0x20][..], &raw].concat();
+        assert_eq!(unwrap_ec_point(&wrapped).unwrap(), &raw);
+        assert_eq!(unwrap_ec_point(&raw).unwrap(), &raw);
+
+        let mut point = vec![0x04];
+        point.extend([0x22; 64]);
+        let wrapped = [&[0x04, 0x41][..], &point].concat();
+        assert_eq!(unwrap_ec_point(&wrapped).unwrap(), point.as_slice());
+        assert_eq!(unwrap_ec_point(&point).unwrap(), point.as_slice());
+        assert!(unwrap_ec_point(&[0x04, 0x05, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_pin_sources() {
+        assert_eq!(
+            "env:HSM_PIN".parse::<PinSource>().unwrap(),
+            PinSource::Env("HSM_PIN".to_string())
+        );
+        assert_eq!(
+            "file:/etc/sayonara/hsm.pin".parse::<PinSource>().unwrap(),
+            PinSource::File(PathBuf::from("/etc/sayonara/hsm.pin"))
+        );
+        assert!("1234".parse::<PinSource>().is_err());
+        assert!("env:".parse::<PinSource>().is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pin");
+        std::fs::write(&path, "123456\n").unwrap();
+        assert!(PinSource::File(path.clone()).read().is_ok());
+        std::fs::write(&path, "\n").unwrap();
+        assert!(PinSource::File(path).read().is_err());
+    }
+
+    #[test]
+    fn test_missing_module_is_reported() {
+        let err = Pkcs11Signer::open(&Pkcs11Config {
+            module: Some("/nonexistent/libpkcs11.so".into()),
+            key_label: Some("sayonara".into()),
+            pin_source: Some("env:SAYONARA_TEST_UNSET_PIN".into()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("SAYONARA_TEST_UNSET_PIN"));
+
+        let err = Pkcs11Signer::open(&Pkcs11Config {
+            module: Some("/nonexistent/libpkcs11.so".into()),
+            key_label: Some("sayonara".into()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("Failed to load PKCS#11 module"));
+    }
+
+    fn drive() -> DriveInfo {
+        DriveInfo {
+            device_path: "/dev/sdz".to_string(),
+            model: "WDC WD10EZEX".to_string(),
+            serial: "WD-123".to_string(),
+            size: 1_000_204_886_016,
+            drive_type: DriveType::HDD,
+            encryption_status: EncryptionStatus::None,
+            capabilities: DriveCapabilities::default(),
+            health_status: None,
+            temperature_celsius: None,
+        }
+    }
+
+    /// Initialise a fresh SoftHSM2 token and create non-extractable key pairs
+    fn softhsm_token(module: &Path, dir: &Path, pin: &str) -> Slot {
+        let tokens = dir.join("tokens");
+        std::fs::create_dir(&tokens).unwrap();
+        let conf = dir.join("softhsm2.conf");
+        std::fs::write(
+            &conf,
+            format!(
+                "directories.tokendir = {}\nobjectstore.backend = file\n",
+                tokens.display()
+            ),
+        )
+        .unwrap();
+        std::env::set_var("SOFTHSM2_CONF", &conf);
+
+        let context = context(module).unwrap();
+        let slot = context.get_all_slots().unwrap()[0];
+        let so_pin = AuthPin::new("so-secret".to_string());
+        context.init_token(slot, &so_pin, "sayonara-test").unwrap();
+
+        let session = context.open_rw_session(slot).unwrap();
+        session.login(UserType::So, Some(&so_pin)).unwrap();
+        session.init_pin(&AuthPin::new(pin.to_string())).unwrap();
+        session.logout().unwrap();
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin.to_string())))
+            .unwrap();
+
+        for (label, mechanism, params) in [
+            ("p256", Mechanism::EccKeyPairGen, P256_EC_PARAMS),
+            (
+                "ed25519",
+                Mechanism::EccEdwardsKeyPairGen,
+                ED25519_EC_PARAMS_NAME,
+            ),
+        ] {
+            let label = label.as_bytes().to_vec();
+            session
+                .generate_key_pair(
+                    &mechanism,
+                    &[
+                        Attribute::Token(true),
+                        Attribute::Verify(true),
+                        Attribute::EcParams(params.to_vec()),
+                        Attribute::Label(label.clone()),
+                    ],
+                    &[
+                        Attribute::Token(true),
+                        Attribute::Private(true),
+                        Attribute::Sign(true),
+                        Attribute::Sensitive(true),
+                        Attribute::Extractable(false),
+                        Attribute::Label(label),
+                    ],
+                )
+                .unwrap();
+        }
+        // An extractable key must be refused
+        session
+            .generate_key_pair(
+                &Mechanism::EccKeyPairGen,
+                &[
+                    Attribute::Token(true),
+                    Attribute::EcParams(P256_EC_PARAMS.to_vec()),
+                    Attribute::Label(b"exportable".to_vec()),
+                ],
+                &[
+                    Attribute::Token(true),
+                    Attribute::Sign(true),
+                    Attribute::Sensitive(false),
+                    Attribute::Extractable(true),
+                    Attribute::Label(b"exportable".to_vec()),
+                ],
+            )
+            .unwrap();
+        session.logout().unwrap();
+        slot
+    }
+
+    /// End-to-end run against SoftHSM2
+    ///
+    /// Needs the SoftHSM2 module: set SAYONARA_SOFTHSM_MODULE if it is not at
+    /// /usr/lib/softhsm/libsofthsm2.so, then run with `--ignored`.
+    #[test]
+    #[ignore]
+    fn test_softhsm_signs_certificates() {
+        let module = PathBuf::from(
+            std::env::var("SAYONARA_SOFTHSM_MODULE")
+                .unwrap_or_else(|_| "/usr/lib/softhsm/libsofthsm2.so".to_string()),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let pin_file = dir.path().join("user.pin");
+        std::fs::write(&pin_file, "246810\n").unwrap();
+        let slot = softhsm_token(&module, dir.path(), "246810");
+
+        let config = |label: &str| Pkcs11Config {
+            module: Some(module.clone()),
+            slot: None,
+            token_label: Some("sayonara-test".into()),
+            key_label: Some(label.into()),
+            pin_source: Some(format!("file:{}", pin_file.display())),
+        };
+
+        for (label, algorithm) in [
+            ("p256", SignatureAlgorithm::EcdsaP256Sha256),
+            ("ed25519", SignatureAlgorithm::Ed25519),
+        ] {
+            let signer = Pkcs11Signer::open(&config(label)).unwrap();
+            assert_eq!(signer.algorithm(), algorithm);
+            let public_key = signer.public_key().clone();
+
+            let certificate = CertificateGenerator::with_signer(Box::new(signer))
+                .generate_certificate(
+                    &drive(),
+                    WipeDetails {
+                        algorithm_used: "Zero".to_string(),
+                        passes_completed: 1,
+                        duration_seconds: 60,
+                        operator_id: None,
+                        checkpoint_rejections: Vec::new(),
+                    },
+                    VerificationResult {
+                        verified: true,
+                        entropy_score: 0.0,
+                        recovery_test_passed: true,
+                        verification_timestamp: Utc::now(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(certificate.signature_algorithm, algorithm);
+            assert!(verify_signature(&certificate, &public_key)
+                .unwrap()
+                .is_valid());
+        }
+
+        let by_slot = Pkcs11Config {
+            slot: Some(slot.id()),
+            token_label: None,
+            ..config("ed25519")
+        };
+        assert!(Pkcs11Signer::open(&by_slot).is_ok());
+
+        let err = Pkcs11Signer::open(&config("exportable")).unwrap_err();
+        assert!(format!("{:#}", err).contains("could be"));
+        let err = Pkcs11Signer::open(&config("missing")).unwrap_err();
+        assert!(format!("{:#}", err).contains("no private key"));
+    }
+}
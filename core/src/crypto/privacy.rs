@@ -0,0 +1,348 @@
+// Privacy-preserving certificates
+//
+// With `signing.redact_identifiers` a certificate does not carry the drive's
+// serial number, device path or WWN. Each is replaced by `[redacted]`, and
+// `device_info.commitments` records a salted commitment to it instead:
+//
+//   commitment = SHA-256(salt || value), with a fresh 256-bit salt per field
+//
+// The commitments are signed with the rest of the certificate, and
+// `device_hash` is taken over the serial commitment rather than the serial,
+// so the public certificate still verifies but reveals nothing that a
+// guessed serial could be checked against. The same values are redacted
+// wherever else they appear in the certificate's evidence.
+//
+// The values and salts go to a disclosure file next to the certificate
+// (`<certificate>.disclosure.json`), which stays with the issuer. Handing
+// over the disclosure, or only some of its fields, lets the recipient check
+// that the certificate covers that serial: the value and salt hash to the
+// signed commitment.
+
+use super::certificates::WipeCertificate;
+use super::secure_rng::secure_random_bytes;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Placeholder written in place of a committed value
+pub const REDACTED: &str = "[redacted]";
+
+/// Shortest value `redact_text` will replace
+pub const MIN_REDACTED_LEN: usize = 4;
+
+/// Commitment construction, recorded so it can be changed later
+pub const COMMITMENT_SCHEME: &str = "sha256-salted";
+
+/// Salt length in bytes
+pub const SALT_LEN: usize = 32;
+
+/// Names of the committed fields
+pub const SERIAL: &str = "serial";
+pub const DEVICE_PATH: &str = "device_path";
+pub const WWN: &str = "wwn";
+
+/// Signed commitments to the identifying values a certificate leaves out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceCommitments {
+    pub scheme: String,
+    /// Hex commitment to the drive serial number
+    pub serial: String,
+    /// Hex commitment to the device path
+    pub device_path: String,
+    /// Hex commitment to the World Wide Name, when the drive reported one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wwn: Option<String>,
+}
+
+impl DeviceCommitments {
+    /// Commitment for the field named `field`
+    pub fn get(&self, field: &str) -> Option<&str> {
+        match field {
+            SERIAL => Some(&self.serial),
+            DEVICE_PATH => Some(&self.device_path),
+            WWN => self.wwn.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// A committed value and the salt that opens its commitment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisclosedValue {
+    pub value: String,
+    /// Hex salt
+    pub salt: String,
+}
+
+impl DisclosedValue {
+    /// Commit to `value` under a fresh salt
+    fn new(value: &str) -> Result<(String, Self)> {
+        let mut salt = [0u8; SALT_LEN];
+        secure_random_bytes(&mut salt).context("No randomness for commitment salts")?;
+        let disclosed = Self {
+            value: value.to_string(),
+            salt: hex::encode(salt),
+        };
+        Ok((disclosed.commitment()?, disclosed))
+    }
+
+    /// Commitment this value and salt open
+    pub fn commitment(&self) -> Result<String> {
+        let salt = hex::decode(&self.salt)
+            .ok()
+            .context("Disclosure salt is not hex")?;
+        Ok(commit(&self.value, &salt))
+    }
+}
+
+/// `SHA-256(salt || value)`, hex
+pub fn commit(value: &str, salt: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(value.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Salts and values behind a certificate's commitments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Disclosure {
+    pub certificate_id: String,
+    pub scheme: String,
+    /// Disclosed values by field name; remove entries to disclose less
+    pub fields: BTreeMap<String, DisclosedValue>,
+}
+
+/// Outcome of opening one commitment
+#[derive(Debug, Clone, Serialize)]
+pub struct DisclosureCheck {
+    pub field: String,
+    pub value: String,
+    /// The value and salt hash to the certificate's commitment
+    pub matches: bool,
+}
+
+impl Disclosure {
+    /// Commit to a drive's identifying values
+    ///
+    /// The certificate id is filled in once the certificate is issued.
+    pub fn commit(
+        serial: &str,
+        device_path: &str,
+        wwn: Option<&str>,
+    ) -> Result<(DeviceCommitments, Self)> {
+        let mut fields = BTreeMap::new();
+        let (serial, disclosed) = DisclosedValue::new(serial)?;
+        fields.insert(SERIAL.to_string(), disclosed);
+        let (device_path, disclosed) = DisclosedValue::new(device_path)?;
+        fields.insert(DEVICE_PATH.to_string(), disclosed);
+        let wwn = match wwn {
+            Some(wwn) => {
+                let (commitment, disclosed) = DisclosedValue::new(wwn)?;
+                fields.insert(WWN.to_string(), disclosed);
+                Some(commitment)
+            }
+            None => None,
+        };
+
+        let commitments = DeviceCommitments {
+            scheme: COMMITMENT_SCHEME.to_string(),
+            serial,
+            device_path,
+            wwn,
+        };
+        let disclosure = Self {
+            certificate_id: String::new(),
+            scheme: COMMITMENT_SCHEME.to_string(),
+            fields,
+        };
+        Ok((commitments, disclosure))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("{} is not a disclosure file", path.display()))
+    }
+
+    /// Write the disclosure readable by its owner only
+    pub fn save(&self, path: &Path) -> Result<()> {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        file.write_all(&serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Open each disclosed field against `certificate`'s commitments
+    pub fn check(&self, certificate: &WipeCertificate) -> Result<Vec<DisclosureCheck>> {
+        if self.certificate_id != certificate.certificate_id {
+            bail!(
+                "Disclosure is for certificate {}, not {}",
+                self.certificate_id,
+                certificate.certificate_id
+            );
+        }
+        let Some(commitments) = &certificate.device_info.commitments else {
+            bail!(
+                "Certificate {} has no commitments",
+                certificate.certificate_id
+            );
+        };
+        if self.scheme != commitments.scheme || commitments.scheme != COMMITMENT_SCHEME {
+            bail!("Unsupported commitment scheme '{}'", commitments.scheme);
+        }
+
+        self.fields
+            .iter()
+            .map(|(field, disclosed)| {
+                let committed = commitments
+                    .get(field)
+                    .with_context(|| format!("Certificate has no commitment to {}", field))?;
+                Ok(DisclosureCheck {
+                    field: field.clone(),
+                    value: disclosed.value.clone(),
+                    matches: disclosed.commitment()? == committed,
+                })
+            })
+            .collect()
+    }
+
+    /// The committed values, for redacting copies of them elsewhere
+    pub fn values(&self) -> Vec<&str> {
+        self.fields.values().map(|d| d.value.as_str()).collect()
+    }
+}
+
+/// Where the disclosure for a certificate at `path` is written
+pub fn disclosure_path(path: &str) -> String {
+    format!("{}.disclosure.json", path)
+}
+
+/// Replace every occurrence of `values` in the strings of `json`
+pub fn redact_json(json: &mut serde_json::Value, values: &[&str]) {
+    match json {
+        serde_json::Value::String(text) => *text = redact_text(text, values),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|v| redact_json(v, values)),
+        serde_json::Value::Object(members) => {
+            members.values_mut().for_each(|v| redact_json(v, values))
+        }
+        _ => {}
+    }
+}
+
+/// Replace every whole-token occurrence of `values` in `text`
+///
+/// A value only matches where it is not part of a longer word or number,
+/// so a serial of "123" leaves "1234" alone. Values shorter than
+/// `MIN_REDACTED_LEN` are skipped: they would match too much unrelated text.
+pub fn redact_text(text: &str, values: &[&str]) -> String {
+    // Longest first, so a value containing another is replaced whole
+    let mut values: Vec<_> = values
+        .iter()
+        .filter(|v| v.chars().count() >= MIN_REDACTED_LEN)
+        .collect();
+    values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+    values
+        .into_iter()
+        .fold(text.to_string(), |text, value| replace_tokens(&text, value))
+}
+
+fn replace_tokens(text: &str, value: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric());
+    let starts_word = is_word(value.chars().next());
+    let ends_word = is_word(value.chars().last());
+
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, _) in text.match_indices(value) {
+        if start < copied {
+            continue;
+        }
+        let end = start + value.len();
+        let joined_before = starts_word && is_word(text[..start].chars().last());
+        let joined_after = ends_word && is_word(text[end..].chars().next());
+        if joined_before || joined_after {
+            continue;
+        }
+        out.push_str(&text[copied..start]);
+        out.push_str(REDACTED);
+        copied = end;
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitments_open_only_with_their_salt() {
+        let (commitments, disclosure) =
+            Disclosure::commit("WD-123", "/dev/sdz", Some("0x5000c500a1b2c3d4")).unwrap();
+        let serial = &disclosure.fields[SERIAL];
+        assert_eq!(serial.commitment().unwrap(), commitments.serial);
+        assert_ne!(commitments.serial, commit("WD-123", &[]));
+
+        // Every field has its own salt
+        assert_ne!(serial.salt, disclosure.fields[DEVICE_PATH].salt);
+        assert_eq!(
+            commitments.get(WWN),
+            Some(disclosure.fields[WWN].commitment().unwrap().as_str())
+        );
+
+        let guessed = DisclosedValue {
+            value: "WD-124".to_string(),
+            salt: serial.salt.clone(),
+        };
+        assert_ne!(guessed.commitment().unwrap(), commitments.serial);
+    }
+
+    #[test]
+    fn test_redaction_replaces_longest_value_first() {
+        let mut json = serde_json::json!({
+            "device_path": "/dev/sdz",
+            "warnings": ["TRIM failed on /dev/sdz1 (WD-123)"],
+            "passes": 3,
+        });
+        redact_json(&mut json, &["/dev/sdz", "/dev/sdz1", "WD-123", ""]);
+        assert_eq!(json["device_path"], REDACTED);
+        assert_eq!(
+            json["warnings"][0],
+            "TRIM failed on [redacted] ([redacted])"
+        );
+        assert_eq!(json["passes"], 3);
+    }
+
+    #[test]
+    fn test_redaction_matches_whole_tokens_only() {
+        let log = "sector 1234 on ABC12 failed; ABC12-x retried; serial ABC12";
+        assert_eq!(
+            redact_text(log, &["ABC12", "123"]),
+            "sector 1234 on [redacted] failed; [redacted]-x retried; serial [redacted]"
+        );
+
+        // Too short to redact safely
+        assert_eq!(
+            redact_text("error 5 at 0x00", &["5", "0x0"]),
+            "error 5 at 0x00"
+        );
+
+        // A device path is still found next to punctuation, but not inside
+        // a longer name
+        assert_eq!(
+            redact_text("(/dev/sdz) /dev/sdzz", &["/dev/sdz"]),
+            "([redacted]) /dev/sdzz"
+        );
+    }
+}
@@ -0,0 +1,784 @@
+// RFC 3161 trusted timestamps
+//
+// A certificate's own `timestamp` is the issuing host's clock. To give
+// auditors a time they can rely on, the canonical certificate (signature
+// included, see `certificates::timestamp_payload`) is hashed and sent to a
+// Time-Stamp Authority; the TimeStampToken it returns is embedded in the
+// certificate and checked offline on verification:
+// - the token is CMS SignedData over a TSTInfo whose message imprint is the
+//   hash of the certificate
+// - the signer certificate is named by an ESS signing-certificate attribute
+//   and carries the critical, sole extended key usage id-kp-timeStamping
+// - the signer chains to a configured TSA trust anchor at the token's genTime
+//
+// Requests travel as HTTP POST bodies (`application/timestamp-query`, RFC 3161
+// section 3.4). `TimestampAuthority` is a minimal TSA for sites without one,
+// served over plain HTTP by `sayonara tsa serve`.
+
+use super::cms::{self, SignedEnvelope};
+use super::signing::{SigningConfig, SigningKey};
+use super::x509::{self, Certificate, TrustStore, ValidatedChain};
+use ::cms::content_info::ContentInfo;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use der::asn1::{BitString, GeneralizedTime, ObjectIdentifier, OctetString, Uint};
+use der::{Any, Decode, Encode, Sequence, Tag, Tagged};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::ExtendedKeyUsage;
+use x509_cert::ext::Extensions;
+use x509_cert::spki::AlgorithmIdentifierOwned;
+
+/// id-ct-TSTInfo, the content type of a time-stamp token
+pub const ID_CT_TST_INFO: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+/// id-kp-timeStamping
+pub const ID_KP_TIME_STAMPING: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.8");
+const ID_AA_SIGNING_CERTIFICATE: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.12");
+const ID_AA_SIGNING_CERTIFICATE_V2: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.47");
+
+const QUERY_CONTENT_TYPE: &str = "application/timestamp-query";
+const REPLY_CONTENT_TYPE: &str = "application/timestamp-reply";
+
+/// Largest request or response accepted over HTTP
+const MAX_MESSAGE_SIZE: u64 = 64 * 1024;
+
+/// How long to wait for a remote TSA
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+// PKIStatus values
+const STATUS_GRANTED: u8 = 0;
+const STATUS_GRANTED_WITH_MODS: u8 = 1;
+const STATUS_REJECTION: u8 = 2;
+
+// PKIFailureInfo bits
+const FAIL_BAD_ALG: usize = 0;
+const FAIL_BAD_REQUEST: usize = 2;
+const FAIL_BAD_DATA_FORMAT: usize = 5;
+const FAIL_UNACCEPTED_POLICY: usize = 15;
+const FAIL_SYSTEM_FAILURE: usize = 25;
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct MessageImprint {
+    hash_algorithm: AlgorithmIdentifierOwned,
+    hashed_message: OctetString,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct TimeStampReq {
+    version: u8,
+    message_imprint: MessageImprint,
+    #[asn1(optional = "true")]
+    req_policy: Option<ObjectIdentifier>,
+    #[asn1(optional = "true")]
+    nonce: Option<Uint>,
+    #[asn1(default = "Default::default")]
+    cert_req: bool,
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT", optional = "true")]
+    extensions: Option<Extensions>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct PkiStatusInfo {
+    status: u8,
+    #[asn1(optional = "true")]
+    status_string: Option<Vec<String>>,
+    #[asn1(optional = "true")]
+    fail_info: Option<BitString>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct TimeStampResp {
+    status: PkiStatusInfo,
+    #[asn1(optional = "true")]
+    time_stamp_token: Option<ContentInfo>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct Accuracy {
+    #[asn1(optional = "true")]
+    seconds: Option<u32>,
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT", optional = "true")]
+    millis: Option<u16>,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    micros: Option<u16>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct TstInfo {
+    version: u8,
+    policy: ObjectIdentifier,
+    message_imprint: MessageImprint,
+    serial_number: Uint,
+    /// GeneralizedTime, possibly with fractional seconds
+    gen_time: Any,
+    #[asn1(optional = "true")]
+    accuracy: Option<Accuracy>,
+    #[asn1(default = "Default::default")]
+    ordering: bool,
+    #[asn1(optional = "true")]
+    nonce: Option<Uint>,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT", optional = "true")]
+    tsa: Option<GeneralName>,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    extensions: Option<Extensions>,
+}
+
+// RFC 2634 / RFC 5035 ESS signing-certificate attributes
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct SigningCertificateV2 {
+    certs: Vec<EssCertIdV2>,
+    #[asn1(optional = "true")]
+    policies: Option<Any>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct EssCertIdV2 {
+    /// SHA-256 when absent
+    #[asn1(optional = "true")]
+    hash_algorithm: Option<AlgorithmIdentifierOwned>,
+    cert_hash: OctetString,
+    #[asn1(optional = "true")]
+    issuer_serial: Option<Any>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct SigningCertificate {
+    certs: Vec<EssCertId>,
+    #[asn1(optional = "true")]
+    policies: Option<Any>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct EssCertId {
+    /// SHA-1
+    cert_hash: OctetString,
+    #[asn1(optional = "true")]
+    issuer_serial: Option<Any>,
+}
+
+impl TstInfo {
+    fn gen_time(&self) -> Result<DateTime<Utc>> {
+        if self.gen_time.tag() != Tag::GeneralizedTime {
+            bail!("TSTInfo genTime is not a GeneralizedTime");
+        }
+        let text = std::str::from_utf8(self.gen_time.value())?;
+        let time = NaiveDateTime::parse_from_str(text, "%Y%m%d%H%M%S%.fZ")
+            .map_err(|e| anyhow!("Invalid TSTInfo genTime {}: {}", text, e))?;
+        Ok(time.and_utc())
+    }
+}
+
+/// What a valid time-stamp token attests
+#[derive(Debug, Clone, Serialize)]
+pub struct TimestampVerification {
+    /// Time at which the TSA saw the data
+    pub gen_time: DateTime<Utc>,
+    /// Subject of the TSA's signing certificate
+    pub tsa: String,
+    pub policy: String,
+    /// Serial number of the token (hex)
+    pub serial_number: String,
+    pub chain: ValidatedChain,
+}
+
+/// Check a DER TimeStampToken over `data` and its signer's chain to `trust`
+pub fn verify_token(
+    token: &[u8],
+    data: &[u8],
+    trust: &TrustStore,
+) -> Result<TimestampVerification> {
+    let (info, envelope) = check_token(token, data)?;
+    let gen_time = info.gen_time()?;
+    let chain = x509::validate_chain(
+        &envelope.signer_certificate,
+        &envelope.certificates,
+        trust,
+        gen_time,
+    )
+    .context("Time-stamp authority is not trusted")?;
+
+    Ok(TimestampVerification {
+        gen_time,
+        tsa: x509::subject_name(&envelope.signer_certificate),
+        policy: info.policy.to_string(),
+        serial_number: hex::encode(info.serial_number.as_bytes()),
+        chain,
+    })
+}
+
+/// Everything about a token that holds regardless of whom one trusts
+fn check_token(token: &[u8], data: &[u8]) -> Result<(TstInfo, SignedEnvelope)> {
+    let envelope =
+        cms::open_typed(token, None, ID_CT_TST_INFO).context("Invalid time-stamp token")?;
+    let info = TstInfo::from_der(&envelope.content)
+        .map_err(|e| anyhow!("Invalid TSTInfo in time-stamp token: {}", e))?;
+    if info.version != 1 {
+        bail!("Unsupported TSTInfo version {}", info.version);
+    }
+
+    let imprint = &info.message_imprint;
+    if imprint.hashed_message.as_bytes()
+        != cms::digest(imprint.hash_algorithm.oid, data)?.as_slice()
+    {
+        bail!("Time-stamp token is for different data");
+    }
+
+    check_tsa_certificate(&envelope.signer_certificate)?;
+    check_signing_certificate_attribute(&envelope)?;
+
+    Ok((info, envelope))
+}
+
+/// RFC 3161 section 2.3: id-kp-timeStamping must be the only extended key
+/// usage, and the extension must be critical
+fn check_tsa_certificate(certificate: &Certificate) -> Result<()> {
+    match certificate.tbs_certificate.get::<ExtendedKeyUsage>() {
+        Ok(Some((true, usage))) if usage.0 == [ID_KP_TIME_STAMPING] => Ok(()),
+        Ok(Some((false, _))) => bail!(
+            "\"{}\" has a non-critical extended key usage; a TSA certificate needs it critical",
+            x509::subject_name(certificate)
+        ),
+        Ok(_) => bail!(
+            "\"{}\" is not a time-stamping certificate (extended key usage must be \
+             timeStamping only)",
+            x509::subject_name(certificate)
+        ),
+        Err(e) => bail!("Invalid extended key usage: {}", e),
+    }
+}
+
+/// The token must name the certificate it was signed with, so a different
+/// certificate for the same key cannot be substituted
+fn check_signing_certificate_attribute(envelope: &SignedEnvelope) -> Result<()> {
+    let certificate = envelope.signer_certificate.to_der()?;
+
+    if let Some(value) = envelope.signed_attribute(ID_AA_SIGNING_CERTIFICATE_V2)? {
+        let attribute: SigningCertificateV2 = value
+            .decode_as()
+            .map_err(|e| anyhow!("Invalid ESS signing-certificate-v2 attribute: {}", e))?;
+        let id = attribute
+            .certs
+            .first()
+            .ok_or_else(|| anyhow!("ESS signing-certificate-v2 attribute is empty"))?;
+        let algorithm = id
+            .hash_algorithm
+            .as_ref()
+            .map_or(cms::ID_SHA256, |algorithm| algorithm.oid);
+        if id.cert_hash.as_bytes() != cms::digest(algorithm, &certificate)?.as_slice() {
+            bail!("Time-stamp token names a different signing certificate");
+        }
+    } else if let Some(value) = envelope.signed_attribute(ID_AA_SIGNING_CERTIFICATE)? {
+        let attribute: SigningCertificate = value
+            .decode_as()
+            .map_err(|e| anyhow!("Invalid ESS signing-certificate attribute: {}", e))?;
+        let id = attribute
+            .certs
+            .first()
+            .ok_or_else(|| anyhow!("ESS signing-certificate attribute is empty"))?;
+        let hash = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, &certificate);
+        if id.cert_hash.as_bytes() != hash.as_ref() {
+            bail!("Time-stamp token names a different signing certificate");
+        }
+    } else {
+        bail!("Time-stamp token does not identify its signing certificate");
+    }
+    Ok(())
+}
+
+/// Client for a remote Time-Stamp Authority
+#[derive(Debug, Clone)]
+pub struct TsaClient {
+    url: String,
+    policy: Option<ObjectIdentifier>,
+    /// Checked on receipt when non-empty
+    trust: TrustStore,
+}
+
+impl TsaClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            policy: None,
+            trust: TrustStore::default(),
+        }
+    }
+
+    /// Client for `signing.tsa_url`, if one is configured
+    pub fn from_config(config: &SigningConfig) -> Result<Option<Self>> {
+        let Some(url) = &config.tsa_url else {
+            return Ok(None);
+        };
+        let mut client = Self::new(url).with_trust(TrustStore::load(&config.tsa_trust_anchors)?);
+        if let Some(policy) = &config.tsa_policy {
+            client = client.with_policy(parse_policy(policy)?);
+        }
+        Ok(Some(client))
+    }
+
+    /// Ask for tokens under `policy`
+    pub fn with_policy(mut self, policy: ObjectIdentifier) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Refuse tokens whose signer does not chain to `trust`
+    pub fn with_trust(mut self, trust: TrustStore) -> Self {
+        self.trust = trust;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Obtain a DER TimeStampToken over `data`
+    pub fn timestamp(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let nonce = Uint::new(&random_positive(8)?)?;
+        let request = TimeStampReq {
+            version: 1,
+            message_imprint: MessageImprint {
+                hash_algorithm: AlgorithmIdentifierOwned {
+                    oid: cms::ID_SHA256,
+                    parameters: None,
+                },
+                hashed_message: OctetString::new(Sha256::digest(data).to_vec())?,
+            },
+            req_policy: self.policy,
+            nonce: Some(nonce.clone()),
+            // The certificate must travel with the token for offline checks
+            cert_req: true,
+            extensions: None,
+        };
+
+        let response = ureq::AgentBuilder::new()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .post(&self.url)
+            .set("Content-Type", QUERY_CONTENT_TYPE)
+            .send_bytes(&request.to_der()?)
+            .with_context(|| format!("Time-stamp request to {} failed", self.url))?;
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_MESSAGE_SIZE)
+            .read_to_end(&mut body)?;
+
+        let response = TimeStampResp::from_der(&body)
+            .map_err(|e| anyhow!("{} did not return a time-stamp response: {}", self.url, e))?;
+        let status = &response.status;
+        if status.status != STATUS_GRANTED && status.status != STATUS_GRANTED_WITH_MODS {
+            bail!(
+                "{} refused the time-stamp request (status {}{})",
+                self.url,
+                status.status,
+                status
+                    .status_string
+                    .as_ref()
+                    .map(|text| format!(": {}", text.join("; ")))
+                    .unwrap_or_default()
+            );
+        }
+        let token = response
+            .time_stamp_token
+            .ok_or_else(|| anyhow!("{} granted the request without a token", self.url))?
+            .to_der()?;
+
+        let (info, _) = check_token(&token, data)?;
+        if info.nonce.as_ref() != Some(&nonce) {
+            bail!("Time-stamp response does not answer this request (nonce mismatch)");
+        }
+        if self.policy.is_some_and(|policy| policy != info.policy) {
+            bail!("TSA issued the token under policy {}", info.policy);
+        }
+        if !self.trust.is_empty() {
+            verify_token(&token, data, &self.trust)?;
+        }
+
+        Ok(token)
+    }
+}
+
+/// Minimal Time-Stamp Authority
+///
+/// Signs TSTInfo structures with `key` under a single policy. Serial numbers
+/// are random 127-bit values, so no state is kept between requests. The host
+/// clock is the time source; run it on a machine whose clock is disciplined.
+#[derive(Debug)]
+pub struct TimestampAuthority {
+    key: SigningKey,
+    chain: Vec<Certificate>,
+    policy: ObjectIdentifier,
+}
+
+impl TimestampAuthority {
+    /// `chain` starts with the TSA certificate for `key`, followed by any
+    /// intermediates to hand out with tokens
+    pub fn new(key: SigningKey, chain: Vec<Certificate>, policy: ObjectIdentifier) -> Result<Self> {
+        let certificate = chain
+            .first()
+            .ok_or_else(|| anyhow!("A TSA needs its X.509 certificate"))?;
+        cms::check_signer_certificate(&key, certificate)?;
+        check_tsa_certificate(certificate)?;
+        Ok(Self { key, chain, policy })
+    }
+
+    /// Authority with the PKCS#8 key and PEM certificate chain at the given paths
+    pub fn load(key_path: &Path, certificate_path: &Path, policy: &str) -> Result<Self> {
+        Self::new(
+            SigningKey::load(key_path)?,
+            x509::load_certificates(certificate_path)?,
+            parse_policy(policy)?,
+        )
+    }
+
+    pub fn policy(&self) -> ObjectIdentifier {
+        self.policy
+    }
+
+    /// Subject of the TSA certificate
+    pub fn name(&self) -> String {
+        x509::subject_name(&self.chain[0])
+    }
+
+    /// Answer a DER TimeStampReq with a DER TimeStampResp
+    ///
+    /// Bad requests get a rejection response rather than an error, as the
+    /// protocol expects.
+    pub fn respond(&self, request: &[u8]) -> Vec<u8> {
+        let response = match self.issue(request) {
+            Ok(token) => TimeStampResp {
+                status: PkiStatusInfo {
+                    status: STATUS_GRANTED,
+                    status_string: None,
+                    fail_info: None,
+                },
+                time_stamp_token: Some(token),
+            },
+            Err((fail_bit, reason)) => {
+                tracing::warn!("Rejected time-stamp request: {}", reason);
+                TimeStampResp {
+                    status: PkiStatusInfo {
+                        status: STATUS_REJECTION,
+                        status_string: Some(vec![reason]),
+                        fail_info: failure_info(fail_bit),
+                    },
+                    time_stamp_token: None,
+                }
+            }
+        };
+        response
+            .to_der()
+            .expect("time-stamp response encodes to DER")
+    }
+
+    fn issue(&self, request: &[u8]) -> std::result::Result<ContentInfo, (usize, String)> {
+        let request = TimeStampReq::from_der(request)
+            .map_err(|e| (FAIL_BAD_DATA_FORMAT, format!("malformed request: {}", e)))?;
+        if request.version != 1 {
+            return Err((
+                FAIL_BAD_REQUEST,
+                format!("unsupported version {}", request.version),
+            ));
+        }
+        if request
+            .req_policy
+            .is_some_and(|policy| policy != self.policy)
+        {
+            return Err((
+                FAIL_UNACCEPTED_POLICY,
+                format!("only policy {} is offered", self.policy),
+            ));
+        }
+        if request.extensions.is_some() {
+            return Err((FAIL_BAD_REQUEST, "extensions are not supported".to_string()));
+        }
+
+        let imprint = &request.message_imprint;
+        let expected_length = match imprint.hash_algorithm.oid {
+            cms::ID_SHA256 => 32,
+            cms::ID_SHA384 => 48,
+            cms::ID_SHA512 => 64,
+            other => return Err((FAIL_BAD_ALG, format!("unsupported hash {}", other))),
+        };
+        if imprint.hashed_message.as_bytes().len() != expected_length {
+            return Err((
+                FAIL_BAD_DATA_FORMAT,
+                "hash length does not match the algorithm".to_string(),
+            ));
+        }
+
+        self.sign_tst_info(&request)
+            .map_err(|e| (FAIL_SYSTEM_FAILURE, e.to_string()))
+    }
+
+    fn sign_tst_info(&self, request: &TimeStampReq) -> Result<ContentInfo> {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+        let info = TstInfo {
+            version: 1,
+            policy: self.policy,
+            message_imprint: request.message_imprint.clone(),
+            serial_number: Uint::new(&random_positive(16)?)?,
+            gen_time: Any::encode_from(&GeneralizedTime::from_unix_duration(
+                Duration::from_secs(now.as_secs()),
+            )?)?,
+            accuracy: Some(Accuracy {
+                seconds: Some(1),
+                millis: None,
+                micros: None,
+            }),
+            ordering: false,
+            nonce: request.nonce.clone(),
+            tsa: Some(GeneralName::DirectoryName(
+                self.chain[0].tbs_certificate.subject.clone(),
+            )),
+            extensions: None,
+        };
+
+        let signing_certificate = SigningCertificateV2 {
+            certs: vec![EssCertIdV2 {
+                hash_algorithm: None,
+                cert_hash: OctetString::new(Sha256::digest(self.chain[0].to_der()?).to_vec())?,
+                issuer_serial: None,
+            }],
+            policies: None,
+        };
+
+        let token = cms::sign_typed(
+            &self.key,
+            &self.chain,
+            ID_CT_TST_INFO,
+            &info.to_der()?,
+            false,
+            request.cert_req,
+            vec![cms::attribute(
+                ID_AA_SIGNING_CERTIFICATE_V2,
+                &signing_certificate,
+            )?],
+        )?;
+        Ok(ContentInfo::from_der(&token)?)
+    }
+}
+
+/// HTTP front end for a [`TimestampAuthority`]
+pub struct TsaServer {
+    authority: TimestampAuthority,
+    server: tiny_http::Server,
+}
+
+impl TsaServer {
+    /// Listen on `address` (e.g. `127.0.0.1:3161`; port 0 picks a free port)
+    pub fn bind(authority: TimestampAuthority, address: &str) -> Result<Self> {
+        let server = tiny_http::Server::http(address)
+            .map_err(|e| anyhow!("Cannot listen on {}: {}", address, e))?;
+        Ok(Self { authority, server })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    pub fn authority(&self) -> &TimestampAuthority {
+        &self.authority
+    }
+
+    /// Answer requests until the listener fails
+    pub fn run(&self) -> Result<()> {
+        loop {
+            let request = self.server.recv()?;
+            if let Err(e) = self.handle(request) {
+                tracing::warn!("Time-stamp request failed: {}", e);
+            }
+        }
+    }
+
+    fn handle(&self, mut request: tiny_http::Request) -> Result<()> {
+        if *request.method() != tiny_http::Method::Post {
+            return Ok(request.respond(
+                tiny_http::Response::from_string("POST a time-stamp query\n").with_status_code(405),
+            )?);
+        }
+
+        let mut body = Vec::new();
+        request
+            .as_reader()
+            .take(MAX_MESSAGE_SIZE)
+            .read_to_end(&mut body)?;
+
+        let reply = self.authority.respond(&body);
+        let header = tiny_http::Header::from_bytes("Content-Type", REPLY_CONTENT_TYPE)
+            .map_err(|_| anyhow!("invalid header"))?;
+        Ok(request.respond(tiny_http::Response::from_data(reply).with_header(header))?)
+    }
+}
+
+/// Parse a dotted policy OID such as `1.3.6.1.4.1.32473.1`
+pub fn parse_policy(policy: &str) -> Result<ObjectIdentifier> {
+    ObjectIdentifier::new(policy).map_err(|e| anyhow!("Invalid TSA policy OID {}: {}", policy, e))
+}
+
+fn failure_info(bit: usize) -> Option<BitString> {
+    let mut bytes = vec![0u8; bit / 8 + 1];
+    bytes[bit / 8] = 0x80 >> (bit % 8);
+    let unused = 7 - (bit % 8) as u8;
+    BitString::new(unused, bytes).ok()
+}
+
+/// Random positive integer of `len` bytes without leading zeros
+fn random_positive(len: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("system random number generator unavailable"))?;
+    bytes[0] = (bytes[0] & 0x7f) | 0x40;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSA_ROOT: &str = include_str!("../../tests/fixtures/pki/tsa-root.pem");
+    const TSA_CERT: &str = include_str!("../../tests/fixtures/pki/tsa.pem");
+    const TSA_KEY: &str = include_str!("../../tests/fixtures/pki/tsa.key");
+    const ROOT: &str = include_str!("../../tests/fixtures/pki/root.pem");
+    const INTERMEDIATE: &str = include_str!("../../tests/fixtures/pki/intermediate.pem");
+    const SIGNER_CERT: &str = include_str!("../../tests/fixtures/pki/signer-p256.pem");
+    const SIGNER_KEY: &str = include_str!("../../tests/fixtures/pki/signer-p256.key");
+    const POLICY: &str = "1.3.6.1.4.1.32473.1";
+
+    fn authority() -> TimestampAuthority {
+        TimestampAuthority::new(
+            SigningKey::from_pem_or_der(TSA_KEY.as_bytes()).unwrap(),
+            x509::parse_certificates(TSA_CERT.as_bytes()).unwrap(),
+            parse_policy(POLICY).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn trust(pem: &str) -> TrustStore {
+        TrustStore::new(x509::parse_certificates(pem.as_bytes()).unwrap())
+    }
+
+    fn request(data: &[u8], cert_req: bool) -> TimeStampReq {
+        TimeStampReq {
+            version: 1,
+            message_imprint: MessageImprint {
+                hash_algorithm: AlgorithmIdentifierOwned {
+                    oid: cms::ID_SHA256,
+                    parameters: None,
+                },
+                hashed_message: OctetString::new(Sha256::digest(data).to_vec()).unwrap(),
+            },
+            req_policy: None,
+            nonce: Some(Uint::new(&[0x42, 0x01]).unwrap()),
+            cert_req,
+            extensions: None,
+        }
+    }
+
+    fn token_for(data: &[u8]) -> Vec<u8> {
+        let reply = authority().respond(&request(data, true).to_der().unwrap());
+        let response = TimeStampResp::from_der(&reply).unwrap();
+        assert_eq!(response.status.status, STATUS_GRANTED);
+        response.time_stamp_token.unwrap().to_der().unwrap()
+    }
+
+    #[test]
+    fn test_token_roundtrip() {
+        let before = Utc::now() - chrono::Duration::seconds(1);
+        let token = token_for(b"certificate");
+
+        let verified = verify_token(&token, b"certificate", &trust(TSA_ROOT)).unwrap();
+        assert!(verified.gen_time >= before && verified.gen_time <= Utc::now());
+        assert!(verified.tsa.contains("Sayonara Test TSA"));
+        assert_eq!(verified.policy, POLICY);
+        assert_eq!(verified.chain.subjects.len(), 2);
+
+        let err = verify_token(&token, b"other", &trust(TSA_ROOT)).unwrap_err();
+        assert!(err.to_string().contains("different data"), "{}", err);
+        assert!(verify_token(&token, b"certificate", &trust(ROOT)).is_err());
+    }
+
+    #[test]
+    fn test_rejections() {
+        let tsa = authority();
+        let status = |request: &[u8]| TimeStampResp::from_der(&tsa.respond(request)).unwrap();
+
+        let garbage = status(b"not a request");
+        assert_eq!(garbage.status.status, STATUS_REJECTION);
+        assert!(garbage.time_stamp_token.is_none());
+
+        let mut other_policy = request(b"x", true);
+        other_policy.req_policy = Some(parse_policy("1.2.3.4").unwrap());
+        let response = status(&other_policy.to_der().unwrap());
+        assert_eq!(response.status.status, STATUS_REJECTION);
+        assert_eq!(
+            response.status.fail_info,
+            failure_info(FAIL_UNACCEPTED_POLICY)
+        );
+
+        let mut short_hash = request(b"x", true);
+        short_hash.message_imprint.hashed_message = OctetString::new(vec![0u8; 20]).unwrap();
+        assert_eq!(
+            status(&short_hash.to_der().unwrap()).status.status,
+            STATUS_REJECTION
+        );
+    }
+
+    #[test]
+    fn test_token_without_certificate_cannot_be_checked_offline() {
+        let reply = authority().respond(&request(b"x", false).to_der().unwrap());
+        let token = TimeStampResp::from_der(&reply)
+            .unwrap()
+            .time_stamp_token
+            .unwrap()
+            .to_der()
+            .unwrap();
+        assert!(verify_token(&token, b"x", &trust(TSA_ROOT)).is_err());
+    }
+
+    #[test]
+    fn test_authority_needs_time_stamping_certificate() {
+        let chain = x509::parse_certificates(format!("{}{}", SIGNER_CERT, INTERMEDIATE).as_bytes())
+            .unwrap();
+        let err = TimestampAuthority::new(
+            SigningKey::from_pem_or_der(SIGNER_KEY.as_bytes()).unwrap(),
+            chain,
+            parse_policy(POLICY).unwrap(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("time-stamping"), "{}", err);
+    }
+
+    #[test]
+    fn test_client_against_local_server() {
+        let server = TsaServer::bind(authority(), "127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", server.local_addr().unwrap());
+        std::thread::spawn(move || server.run());
+
+        let client = TsaClient::new(&url)
+            .with_policy(parse_policy(POLICY).unwrap())
+            .with_trust(trust(TSA_ROOT));
+        let token = client.timestamp(b"payload").unwrap();
+        verify_token(&token, b"payload", &trust(TSA_ROOT)).unwrap();
+
+        // Untrusted TSA
+        let client = TsaClient::new(&url).with_trust(trust(ROOT));
+        assert!(client.timestamp(b"payload").is_err());
+
+        // Policy the TSA does not offer
+        let client = TsaClient::new(&url).with_policy(parse_policy("1.2.3.4").unwrap());
+        let err = client.timestamp(b"payload").unwrap_err();
+        assert!(err.to_string().contains("refused"), "{}", err);
+    }
+}
@@ -0,0 +1,902 @@
+// Append-only certificate transparency log
+//
+// Certificates are standalone files, so on their own nothing shows that one
+// was deleted or quietly regenerated. Every certificate a configured
+// `CertificateGenerator` issues is therefore appended to a Merkle tree kept in
+// the state directory, using the hashing of RFC 9162 (Certificate Transparency
+// 2.0):
+// - leaf hash = SHA-256(0x00 || entry), where the entry is the canonical JSON
+//   of a `LogEntry` naming the certificate and hashing its signed contents
+// - node hash = SHA-256(0x01 || left || right), splitting n leaves at the
+//   largest power of two below n
+//
+// After each append the log signs a tree head (size and root hash) with the
+// certificate signing key. An inclusion proof shows a certificate is one of
+// the leaves under a head; a consistency proof shows an older head's tree is
+// a prefix of the current one, so entries cannot be removed or rewritten
+// without invalidating every head handed out before.
+
+use super::canonical_json;
+use super::certificates::{certificate_hash, WipeCertificate};
+use super::signing::{PublicKey, SignatureAlgorithm, Signer};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// Log database file name inside the configured state directory
+pub const DB_FILE_NAME: &str = "transparency_log.db";
+
+/// SHA-256 tree hash
+pub type Hash = [u8; 32];
+
+/// Leaf data recorded for an issued certificate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub certificate_id: String,
+
+    /// Issue time recorded in the certificate
+    pub issued_at: DateTime<Utc>,
+
+    pub device_hash: String,
+
+    /// Hex SHA-256 of the signed certificate (`certificate_hash`)
+    pub certificate_hash: String,
+}
+
+impl LogEntry {
+    /// Entry for a signed certificate
+    pub fn for_certificate(certificate: &WipeCertificate) -> Result<Self> {
+        Ok(Self {
+            certificate_id: certificate.certificate_id.clone(),
+            issued_at: certificate.timestamp,
+            device_hash: certificate.device_info.device_hash.clone(),
+            certificate_hash: hex::encode(certificate_hash(certificate)?),
+        })
+    }
+
+    /// Canonical JSON bytes hashed into the leaf
+    pub fn leaf_data(&self) -> Result<Vec<u8>> {
+        canonical_json::to_canonical_vec(self)
+    }
+
+    pub fn leaf_hash(&self) -> Result<Hash> {
+        Ok(leaf_hash(&self.leaf_data()?))
+    }
+}
+
+/// Signed statement of the log's size and root hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTreeHead {
+    pub tree_size: u64,
+
+    /// Hex Merkle tree hash over the first `tree_size` entries
+    pub root_hash: String,
+
+    pub timestamp: DateTime<Utc>,
+    pub signature_algorithm: SignatureAlgorithm,
+
+    /// Hex SHA-256 of the signer's SubjectPublicKeyInfo
+    pub signer_fingerprint: String,
+
+    /// Hex signature over the canonical JSON of the other fields
+    pub signature: String,
+}
+
+impl SignedTreeHead {
+    /// Sign a head for a tree of `tree_size` entries with root `root`
+    pub fn sign(tree_size: u64, root: &Hash, key: &dyn Signer) -> Result<Self> {
+        let mut head = Self {
+            tree_size,
+            root_hash: hex::encode(root),
+            timestamp: Utc::now(),
+            signature_algorithm: key.algorithm(),
+            signer_fingerprint: key.public_key().fingerprint(),
+            signature: String::new(),
+        };
+        head.signature = hex::encode(&key.sign(&head.signing_payload()?)?);
+        Ok(head)
+    }
+
+    /// Bytes covered by the signature
+    pub fn signing_payload(&self) -> Result<Vec<u8>> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("signature");
+        }
+        Ok(canonical_json::to_canonical_string(&value)?.into_bytes())
+    }
+
+    pub fn root(&self) -> Result<Hash> {
+        parse_hash(&self.root_hash).context("Invalid tree head root hash")
+    }
+
+    /// Check the head was signed by `key`
+    pub fn verify(&self, key: &PublicKey) -> Result<()> {
+        if self.signer_fingerprint != key.fingerprint() {
+            bail!(
+                "Tree head signed by key {}, expected {}",
+                self.signer_fingerprint,
+                key.fingerprint()
+            );
+        }
+        let signature = hex::decode(&self.signature)
+            .ok()
+            .ok_or_else(|| anyhow!("Invalid signature"))?;
+        if self.signature_algorithm != key.algorithm()
+            || !key.verify(&self.signing_payload()?, &signature)
+        {
+            bail!("Tree head signature does not match its contents");
+        }
+        Ok(())
+    }
+}
+
+/// Proof that an entry is a leaf of the tree under `tree_head`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub leaf_index: u64,
+    pub entry: LogEntry,
+
+    /// Sibling hashes from the leaf up to the root (hex)
+    pub audit_path: Vec<String>,
+
+    pub tree_head: SignedTreeHead,
+}
+
+impl InclusionProof {
+    /// Check the audit path leads from the entry to the head's root
+    ///
+    /// The head's signature is checked separately with `SignedTreeHead::verify`.
+    pub fn verify(&self) -> Result<()> {
+        let path = parse_path(&self.audit_path)?;
+        if !verify_inclusion(
+            &self.entry.leaf_hash()?,
+            self.leaf_index,
+            self.tree_head.tree_size,
+            &path,
+            &self.tree_head.root()?,
+        ) {
+            bail!(
+                "Audit path does not lead from entry {} to the root of the {}-entry tree",
+                self.leaf_index,
+                self.tree_head.tree_size
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Proof that the tree of `old_size` entries is a prefix of `tree_head`'s
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    pub old_size: u64,
+
+    /// Subtree hashes (hex)
+    pub proof: Vec<String>,
+
+    pub tree_head: SignedTreeHead,
+}
+
+impl ConsistencyProof {
+    /// Check `old` is consistent with the proof's tree head
+    pub fn verify(&self, old: &SignedTreeHead) -> Result<()> {
+        if old.tree_size != self.old_size {
+            bail!(
+                "Proof is for a {}-entry tree, the head covers {}",
+                self.old_size,
+                old.tree_size
+            );
+        }
+        let proof = parse_path(&self.proof)?;
+        if !verify_consistency(
+            old.tree_size,
+            self.tree_head.tree_size,
+            &old.root()?,
+            &self.tree_head.root()?,
+            &proof,
+        ) {
+            bail!(
+                "The {}-entry tree is not a prefix of the current {}-entry tree",
+                old.tree_size,
+                self.tree_head.tree_size
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Persistent Merkle tree of issued certificates
+pub struct TransparencyLog {
+    conn: Connection,
+}
+
+impl TransparencyLog {
+    /// Open the log at `db_path`, or in the state directory by default
+    ///
+    /// Uses an in-memory database in test mode, like the undo journal.
+    pub fn open(db_path: Option<&str>) -> Result<Self> {
+        let is_test = cfg!(test) || std::env::var("SAYONARA_TEST_MODE").is_ok();
+
+        let db_path = match db_path {
+            Some(path) => PathBuf::from(path),
+            None if is_test => PathBuf::from(":memory:"),
+            None => Self::default_path(),
+        };
+
+        if db_path.to_str() != Some(":memory:") {
+            if let Some(parent) = db_path.parent() {
+                std::fs::create_dir_all(parent)
+                    .context("Failed to create transparency log directory")?;
+            }
+        }
+
+        let conn = Connection::open(&db_path).context("Failed to open transparency log")?;
+
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .context("Failed to set WAL mode")?;
+        conn.pragma_update(None, "synchronous", "FULL")
+            .context("Failed to set synchronous mode")?;
+
+        // The triggers only stop accidents; the signed heads are what make
+        // tampering detectable
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS log_entries (
+                leaf_index INTEGER PRIMARY KEY NOT NULL,
+                certificate_id TEXT NOT NULL UNIQUE,
+                entry TEXT NOT NULL,
+                logged_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS tree_heads (
+                tree_size INTEGER PRIMARY KEY NOT NULL,
+                head TEXT NOT NULL
+            );
+            CREATE TRIGGER IF NOT EXISTS log_entries_no_update BEFORE UPDATE ON log_entries
+                BEGIN SELECT RAISE(ABORT, 'transparency log is append-only'); END;
+            CREATE TRIGGER IF NOT EXISTS log_entries_no_delete BEFORE DELETE ON log_entries
+                BEGIN SELECT RAISE(ABORT, 'transparency log is append-only'); END;
+            CREATE TRIGGER IF NOT EXISTS tree_heads_no_update BEFORE UPDATE ON tree_heads
+                BEGIN SELECT RAISE(ABORT, 'transparency log is append-only'); END;
+            CREATE TRIGGER IF NOT EXISTS tree_heads_no_delete BEFORE DELETE ON tree_heads
+                BEGIN SELECT RAISE(ABORT, 'transparency log is append-only'); END;
+            "#,
+        )
+        .context("Failed to create transparency log schema")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Log location in the configured state directory
+    pub fn default_path() -> PathBuf {
+        crate::config::state_dir().join(DB_FILE_NAME)
+    }
+
+    /// Append a signed certificate and sign the new tree head with `key`
+    pub fn append(
+        &self,
+        certificate: &WipeCertificate,
+        key: &dyn Signer,
+    ) -> Result<SignedTreeHead> {
+        let entry = LogEntry::for_certificate(certificate)?;
+        let data = entry.leaf_data()?;
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .context("Failed to start transparency log transaction")?;
+
+        if self.find(&entry.certificate_id)?.is_some() {
+            bail!(
+                "Certificate {} is already in the transparency log",
+                entry.certificate_id
+            );
+        }
+
+        // Refuse to sign over entries that no longer match the last head
+        let mut leaves = self.leaf_hashes()?;
+        self.check_leaves(&leaves)?;
+
+        tx.execute(
+            r#"
+            INSERT INTO log_entries (leaf_index, certificate_id, entry, logged_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![
+                leaves.len() as i64,
+                entry.certificate_id,
+                String::from_utf8(data.clone())?,
+                Utc::now().to_rfc3339(),
+            ],
+        )
+        .context("Failed to write transparency log entry")?;
+        leaves.push(leaf_hash(&data));
+
+        let head = SignedTreeHead::sign(leaves.len() as u64, &root_hash(&leaves), key)?;
+        tx.execute(
+            "INSERT INTO tree_heads (tree_size, head) VALUES (?1, ?2)",
+            params![head.tree_size as i64, serde_json::to_string(&head)?],
+        )
+        .context("Failed to write transparency log tree head")?;
+
+        tx.commit()
+            .context("Failed to commit transparency log entry")?;
+
+        tracing::debug!(
+            certificate = %entry.certificate_id,
+            tree_size = head.tree_size,
+            root = %head.root_hash,
+            "Certificate logged"
+        );
+        Ok(head)
+    }
+
+    /// Number of entries
+    pub fn size(&self) -> Result<u64> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM log_entries", [], |row| row.get(0))
+            .context("Failed to count transparency log entries")?;
+        Ok(count as u64)
+    }
+
+    /// Most recent signed tree head, or None for an empty log
+    pub fn head(&self) -> Result<Option<SignedTreeHead>> {
+        let head: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT head FROM tree_heads ORDER BY tree_size DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to read transparency log tree head")?;
+        head.map(|json| serde_json::from_str(&json).context("Corrupt transparency log tree head"))
+            .transpose()
+    }
+
+    /// Check the stored entries still hash to the most recent head
+    pub fn verify_entries(&self) -> Result<()> {
+        self.check_leaves(&self.leaf_hashes()?)
+    }
+
+    /// Inclusion proof for `certificate` under the current head
+    ///
+    /// Fails if the certificate is not logged, or if it differs from the
+    /// certificate that was.
+    pub fn prove_inclusion(&self, certificate: &WipeCertificate) -> Result<InclusionProof> {
+        let head = self.current_head()?;
+        let (leaf_index, logged) = self.find(&certificate.certificate_id)?.ok_or_else(|| {
+            anyhow!(
+                "Certificate {} is not in the transparency log",
+                certificate.certificate_id
+            )
+        })?;
+
+        let entry = LogEntry::for_certificate(certificate)?;
+        if entry != logged {
+            bail!(
+                "Certificate {} does not match the logged entry (logged hash {}, certificate hash {})",
+                certificate.certificate_id,
+                logged.certificate_hash,
+                entry.certificate_hash
+            );
+        }
+
+        let leaves = self.leaf_hashes()?;
+        self.check_leaves(&leaves)?;
+        Ok(InclusionProof {
+            leaf_index,
+            entry,
+            audit_path: inclusion_path(&leaves, leaf_index as usize)
+                .iter()
+                .map(hex::encode)
+                .collect(),
+            tree_head: head,
+        })
+    }
+
+    /// Consistency proof from the tree of `old_size` entries to the current head
+    pub fn prove_consistency(&self, old_size: u64) -> Result<ConsistencyProof> {
+        let head = self.current_head()?;
+        if old_size == 0 || old_size > head.tree_size {
+            bail!(
+                "No consistency proof from {} entries to the current {}-entry tree",
+                old_size,
+                head.tree_size
+            );
+        }
+
+        let leaves = self.leaf_hashes()?;
+        self.check_leaves(&leaves)?;
+        Ok(ConsistencyProof {
+            old_size,
+            proof: consistency_path(&leaves, old_size as usize)
+                .iter()
+                .map(hex::encode)
+                .collect(),
+            tree_head: head,
+        })
+    }
+
+    fn current_head(&self) -> Result<SignedTreeHead> {
+        self.head()?
+            .ok_or_else(|| anyhow!("The transparency log is empty"))
+    }
+
+    fn find(&self, certificate_id: &str) -> Result<Option<(u64, LogEntry)>> {
+        let row: Option<(i64, String)> = self
+            .conn
+            .query_row(
+                "SELECT leaf_index, entry FROM log_entries WHERE certificate_id = ?1",
+                params![certificate_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .context("Failed to query transparency log")?;
+        row.map(|(index, json)| {
+            let entry = serde_json::from_str(&json).context("Corrupt transparency log entry")?;
+            Ok((index as u64, entry))
+        })
+        .transpose()
+    }
+
+    /// Leaf hashes in order, recomputed from the stored entries
+    fn leaf_hashes(&self) -> Result<Vec<Hash>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT leaf_index, entry FROM log_entries ORDER BY leaf_index")
+            .context("Failed to query transparency log")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .context("Failed to query transparency log")?;
+
+        let mut leaves = Vec::new();
+        for row in rows {
+            let (index, entry) = row.context("Failed to read transparency log entry")?;
+            if index as usize != leaves.len() {
+                bail!("Transparency log is missing entry {}", leaves.len());
+            }
+            leaves.push(leaf_hash(entry.as_bytes()));
+        }
+        Ok(leaves)
+    }
+
+    fn check_leaves(&self, leaves: &[Hash]) -> Result<()> {
+        let Some(head) = self.head()? else {
+            if leaves.is_empty() {
+                return Ok(());
+            }
+            bail!("Transparency log has entries but no tree head");
+        };
+        if head.tree_size != leaves.len() as u64 || head.root()? != root_hash(leaves) {
+            bail!(
+                "Transparency log entries do not match its last tree head \
+                 ({} entries, head covers {} with root {})",
+                leaves.len(),
+                head.tree_size,
+                head.root_hash
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Hash of a leaf holding `data`
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Largest power of two smaller than `n` (n >= 2)
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Merkle tree hash over `leaves`
+pub fn root_hash(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            let k = split(n);
+            node_hash(&root_hash(&leaves[..k]), &root_hash(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path for leaf `index` (RFC 9162 section 2.1.3.1)
+pub fn inclusion_path(leaves: &[Hash], index: usize) -> Vec<Hash> {
+    if leaves.len() <= 1 {
+        return Vec::new();
+    }
+    let k = split(leaves.len());
+    let mut path = if index < k {
+        inclusion_path(&leaves[..k], index)
+    } else {
+        inclusion_path(&leaves[k..], index - k)
+    };
+    path.push(if index < k {
+        root_hash(&leaves[k..])
+    } else {
+        root_hash(&leaves[..k])
+    });
+    path
+}
+
+/// Consistency proof from the first `old_size` leaves (RFC 9162 section 2.1.4.1)
+pub fn consistency_path(leaves: &[Hash], old_size: usize) -> Vec<Hash> {
+    fn subproof(m: usize, leaves: &[Hash], complete: bool) -> Vec<Hash> {
+        let n = leaves.len();
+        if m == n {
+            return if complete {
+                Vec::new()
+            } else {
+                vec![root_hash(leaves)]
+            };
+        }
+        let k = split(n);
+        let mut proof = if m <= k {
+            subproof(m, &leaves[..k], complete)
+        } else {
+            subproof(m - k, &leaves[k..], false)
+        };
+        proof.push(if m <= k {
+            root_hash(&leaves[k..])
+        } else {
+            root_hash(&leaves[..k])
+        });
+        proof
+    }
+
+    if old_size == 0 || old_size >= leaves.len() {
+        return Vec::new();
+    }
+    subproof(old_size, leaves, true)
+}
+
+/// Check an audit path (RFC 9162 section 2.1.3.2)
+pub fn verify_inclusion(leaf: &Hash, index: u64, size: u64, path: &[Hash], root: &Hash) -> bool {
+    if index >= size {
+        return false;
+    }
+    let (mut fn_, mut sn) = (index, size - 1);
+    let mut r = *leaf;
+    for p in path {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            r = node_hash(p, &r);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && r == *root
+}
+
+/// Check a consistency proof (RFC 9162 section 2.1.4.2)
+pub fn verify_consistency(
+    old_size: u64,
+    new_size: u64,
+    old_root: &Hash,
+    new_root: &Hash,
+    proof: &[Hash],
+) -> bool {
+    if old_size == 0 || old_size > new_size {
+        return false;
+    }
+    if old_size == new_size {
+        return proof.is_empty() && old_root == new_root;
+    }
+
+    let mut path = proof.to_vec();
+    if old_size.is_power_of_two() {
+        path.insert(0, *old_root);
+    }
+    let Some((first, rest)) = path.split_first() else {
+        return false;
+    };
+
+    let (mut fn_, mut sn) = (old_size - 1, new_size - 1);
+    while fn_ & 1 == 1 {
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    let (mut fr, mut sr) = (*first, *first);
+    for c in rest {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            fr = node_hash(c, &fr);
+            sr = node_hash(c, &sr);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            sr = node_hash(&sr, c);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && fr == *old_root && sr == *new_root
+}
+
+fn parse_path(hashes: &[String]) -> Result<Vec<Hash>> {
+    hashes
+        .iter()
+        .map(|h| parse_hash(h).ok_or_else(|| anyhow!("Invalid proof hash \"{}\"", h)))
+        .collect()
+}
+
+fn parse_hash(hex: &str) -> Option<Hash> {
+    hex::decode(hex).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::signing::SigningKey;
+    use crate::crypto::{CertificateGenerator, VerificationResult, WipeDetails};
+    use crate::{DriveCapabilities, DriveInfo, DriveType, EncryptionStatus};
+
+    /// Leaf inputs of the Certificate Transparency reference test vectors
+    const LEAVES: [&[u8]; 8] = [
+        b"",
+        &[0x00],
+        &[0x10],
+        &[0x20, 0x21],
+        &[0x30, 0x31],
+        &[0x40, 0x41, 0x42, 0x43],
+        &[0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57],
+        &[
+            0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6a, 0x6b, 0x6c, 0x6d,
+            0x6e, 0x6f,
+        ],
+    ];
+
+    fn leaves(n: usize) -> Vec<Hash> {
+        (0..n)
+            .map(|i| match LEAVES.get(i) {
+                Some(data) => leaf_hash(data),
+                None => leaf_hash(&i.to_be_bytes()),
+            })
+            .collect()
+    }
+
+    fn log_key() -> SigningKey {
+        SigningKey::generate(SignatureAlgorithm::Ed25519).unwrap().0
+    }
+
+    fn issue(generator: &CertificateGenerator, serial: &str) -> WipeCertificate {
+        let drive = DriveInfo {
+            device_path: "/dev/sdz".to_string(),
+            model: "Test Drive".to_string(),
+            serial: serial.to_string(),
+            size: 1 << 30,
+            drive_type: DriveType::SSD,
+            encryption_status: EncryptionStatus::None,
+            capabilities: DriveCapabilities::default(),
+            health_status: None,
+            temperature_celsius: None,
+        };
+        let details = WipeDetails {
+            algorithm_used: "Zero".to_string(),
+            passes_completed: 1,
+            duration_seconds: 1,
+            operator_id: None,
+            checkpoint_rejections: Vec::new(),
+        };
+        let verification = VerificationResult {
+            verified: true,
+            entropy_score: 0.0,
+            recovery_test_passed: true,
+            verification_timestamp: Utc::now(),
+        };
+        generator
+            .generate_certificate(&drive, details, verification)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reference_root_hashes() {
+        let expected = [
+            (
+                1,
+                "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            ),
+            (
+                2,
+                "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+            ),
+            (
+                3,
+                "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+            ),
+            (
+                4,
+                "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            ),
+            (
+                5,
+                "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+            ),
+            (
+                6,
+                "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+            ),
+            (
+                7,
+                "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+            ),
+            (
+                8,
+                "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+            ),
+        ];
+        for (n, root) in expected {
+            assert_eq!(
+                hex::encode(root_hash(&leaves(n))),
+                root,
+                "tree of {} leaves",
+                n
+            );
+        }
+        assert_eq!(
+            hex::encode(root_hash(&[])),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_inclusion_proofs_verify_and_reject_tampering() {
+        for n in 1..=20 {
+            let leaves = leaves(n);
+            let root = root_hash(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let path = inclusion_path(&leaves, i);
+                assert!(verify_inclusion(leaf, i as u64, n as u64, &path, &root));
+
+                // Wrong position, out-of-range index or sibling must fail
+                if n > 1 {
+                    let other = ((i + 1) % n) as u64;
+                    assert!(!verify_inclusion(leaf, other, n as u64, &path, &root));
+                }
+                assert!(!verify_inclusion(leaf, n as u64, n as u64, &path, &root));
+                assert!(!verify_inclusion(
+                    &leaf_hash(b"x"),
+                    i as u64,
+                    n as u64,
+                    &path,
+                    &root
+                ));
+                if let Some(first) = path.first() {
+                    let mut bad = path.clone();
+                    bad[0] = node_hash(first, first);
+                    assert!(!verify_inclusion(leaf, i as u64, n as u64, &bad, &root));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_consistency_proofs_verify_and_reject_rewrites() {
+        for n in 1..=20 {
+            let leaves = leaves(n);
+            let new_root = root_hash(&leaves);
+            for m in 1..=n {
+                let old_root = root_hash(&leaves[..m]);
+                let proof = consistency_path(&leaves, m);
+                assert!(
+                    verify_consistency(m as u64, n as u64, &old_root, &new_root, &proof),
+                    "{} -> {}",
+                    m,
+                    n
+                );
+
+                // An old tree whose entries differ is not a prefix
+                let mut rewritten = leaves[..m].to_vec();
+                rewritten[0] = leaf_hash(b"regenerated");
+                assert!(!verify_consistency(
+                    m as u64,
+                    n as u64,
+                    &root_hash(&rewritten),
+                    &new_root,
+                    &proof
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_log_appends_and_proves_certificates() {
+        let log = TransparencyLog::open(None).unwrap();
+        let generator = CertificateGenerator::with_key(log_key());
+        let signer = log_key();
+        let key = signer.public_key().clone();
+        assert!(log.head().unwrap().is_none());
+
+        let certificates: Vec<_> = (0..5)
+            .map(|i| issue(&generator, &format!("SN{}", i)))
+            .collect();
+        let mut heads = Vec::new();
+        for certificate in &certificates {
+            heads.push(log.append(certificate, &signer).unwrap());
+        }
+        assert_eq!(log.size().unwrap(), 5);
+        assert_eq!(log.head().unwrap().as_ref(), heads.last());
+        log.verify_entries().unwrap();
+
+        for certificate in &certificates {
+            let proof = log.prove_inclusion(certificate).unwrap();
+            proof.verify().unwrap();
+            proof.tree_head.verify(&key).unwrap();
+        }
+        for old in &heads {
+            old.verify(&key).unwrap();
+            log.prove_consistency(old.tree_size)
+                .unwrap()
+                .verify(old)
+                .unwrap();
+        }
+
+        assert!(log.append(&certificates[0], &signer).is_err());
+    }
+
+    #[test]
+    fn test_log_detects_regenerated_and_removed_certificates() {
+        let log = TransparencyLog::open(None).unwrap();
+        let generator = CertificateGenerator::with_key(log_key());
+        let signer = log_key();
+        let certificate = issue(&generator, "SN1");
+        log.append(&certificate, &signer).unwrap();
+        let old = log.head().unwrap().unwrap();
+
+        // Same ID, different contents
+        let mut regenerated = certificate.clone();
+        regenerated.wipe_details.passes_completed = 3;
+        assert!(log.prove_inclusion(&regenerated).is_err());
+
+        // Never logged
+        assert!(log.prove_inclusion(&issue(&generator, "SN2")).is_err());
+
+        // The schema refuses edits, and rewriting the file under it is caught
+        assert!(log.conn.execute("DELETE FROM log_entries", []).is_err());
+        log.conn
+            .execute_batch(
+                "DROP TRIGGER log_entries_no_update;
+                 UPDATE log_entries SET entry = '{}' WHERE leaf_index = 0;",
+            )
+            .unwrap();
+        assert!(log.verify_entries().is_err());
+        assert!(log.append(&issue(&generator, "SN3"), &signer).is_err());
+
+        // A head from another key is rejected
+        assert!(old.verify(log_key().public_key()).is_err());
+        let mut forged = old.clone();
+        forged.tree_size += 1;
+        assert!(forged.verify(signer.public_key()).is_err());
+    }
+}
@@ -0,0 +1,761 @@
+// Offline certificate verification
+//
+// Runs every check that can be made on a saved certificate without the
+// machine that issued it, and reports each by name so a failure says which
+// property does not hold:
+// - read               the file and any detached signature could be read
+// - cms_signature      envelope signature and message digest (CMS formats)
+// - certificate_chain  signer chain to a configured trust anchor (CMS formats)
+// - parse              the content is a wipe certificate
+// - signature          the certificate's own signature, by the CMS signer or
+//                      the configured public key
+// - cosignatures       every cosignature is intact and covers the certificate
+// - signature_policy   enough of the signers listed in `signing.policy` have
+//                      signed (only when the policy requires any)
+// - timestamp          the RFC 3161 token, if any, covers the certificate and
+//                      comes from a trusted TSA
+// - device_hash        `device_hash` matches the recorded model, serial (or
+//                      serial commitment), size
+// - device_match       a given drive is the one in the certificate
+// - anchor             the blockchain anchor exists on chain; the one online
+//                      check, made only when an RPC endpoint is given
+//
+// The format is detected from the content: JSON, a DER or PEM CMS envelope,
+// or JSON with a detached signature next to it (`<file>.p7s`).
+
+use super::anchor::{verify_anchor, ExpectedAnchor};
+use super::certificates::{
+    detached_signature_path, device_hash, verify_signature, verify_timestamp, SignatureStatus,
+    WipeCertificate,
+};
+use super::cms::{self, ChainTime};
+use super::cosign::{SignaturePolicy, SignerRecord, ISSUER_ROLE};
+use super::signing::{CertificateFormat, PublicKey, SigningConfig};
+use super::x509::{self, TrustStore};
+use crate::DriveInfo;
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// Outcome of one named check
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub check: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Result of verifying a certificate file
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateReport {
+    /// Every check that ran passed
+    pub valid: bool,
+    pub format: CertificateFormat,
+    /// Name of the first check that failed
+    pub failed_check: Option<&'static str>,
+    pub checks: Vec<CheckResult>,
+    /// Valid signatures on the certificate, by role
+    pub signers: Vec<SignerRecord>,
+    /// The certificate, once it could be parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<WipeCertificate>,
+}
+
+impl CertificateReport {
+    fn new(format: CertificateFormat) -> Self {
+        Self {
+            valid: false,
+            format,
+            failed_check: None,
+            checks: Vec::new(),
+            signers: Vec::new(),
+            certificate: None,
+        }
+    }
+
+    fn pass(&mut self, check: &'static str, detail: impl Into<String>) {
+        self.checks.push(CheckResult {
+            check,
+            passed: true,
+            detail: detail.into(),
+        });
+    }
+
+    fn fail(&mut self, check: &'static str, detail: impl Into<String>) {
+        self.failed_check.get_or_insert(check);
+        self.checks.push(CheckResult {
+            check,
+            passed: false,
+            detail: detail.into(),
+        });
+    }
+
+    fn finish(mut self) -> Self {
+        self.valid = self.failed_check.is_none() && !self.checks.is_empty();
+        self
+    }
+
+    /// Detail of the first failed check
+    pub fn failure(&self) -> Option<&CheckResult> {
+        self.checks.iter().find(|c| !c.passed)
+    }
+}
+
+/// Checks saved certificates against a public key or CA trust anchors
+#[derive(Debug, Clone, Default)]
+pub struct CertificateVerifier {
+    /// Key for plain JSON certificates
+    public_key: Option<PublicKey>,
+    /// Anchors for CMS signer chains
+    trust: TrustStore,
+    /// Anchors for time-stamp authorities
+    tsa_trust: TrustStore,
+    /// Ethereum JSON-RPC endpoint for checking blockchain anchors
+    anchor_rpc: Option<String>,
+    /// Contract, chain and issuer anchors must match
+    expected_anchor: Option<ExpectedAnchor>,
+    /// Signers that count towards a final certificate
+    policy: SignaturePolicy,
+}
+
+impl CertificateVerifier {
+    pub fn new(public_key: Option<PublicKey>, trust: TrustStore) -> Self {
+        Self {
+            public_key,
+            trust,
+            tsa_trust: TrustStore::default(),
+            anchor_rpc: None,
+            expected_anchor: None,
+            policy: SignaturePolicy::default(),
+        }
+    }
+
+    /// Verifier for the signing key and trust anchors in `config`
+    ///
+    /// A missing host public key is not an error here; JSON certificates then
+    /// fail the signature check with an explanation.
+    pub fn from_config(config: &SigningConfig) -> Result<Self> {
+        Ok(Self::new(
+            PublicKey::from_config(config).ok(),
+            TrustStore::from_config(config)?,
+        )
+        .with_tsa_trust(TrustStore::load(&config.tsa_trust_anchors)?)
+        .with_policy(SignaturePolicy::from_config(&config.policy)?))
+    }
+
+    /// Check JSON certificates against `public_key` instead
+    pub fn with_public_key(mut self, public_key: PublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    /// Check CMS signer chains against `trust` instead
+    pub fn with_trust(mut self, trust: TrustStore) -> Self {
+        self.trust = trust;
+        self
+    }
+
+    /// Check time-stamp tokens against `trust` instead
+    pub fn with_tsa_trust(mut self, trust: TrustStore) -> Self {
+        self.tsa_trust = trust;
+        self
+    }
+
+    /// Require signatures as `policy` says instead
+    pub fn with_policy(mut self, policy: SignaturePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Also check the certificate's blockchain anchor through `rpc_url`
+    pub fn with_anchor_rpc(mut self, rpc_url: &str) -> Self {
+        self.anchor_rpc = Some(rpc_url.to_string());
+        self
+    }
+
+    /// Accept only anchors in `expected`'s contract, chain and issuer
+    pub fn with_expected_anchor(mut self, expected: ExpectedAnchor) -> Self {
+        self.expected_anchor = Some(expected);
+        self
+    }
+
+    /// Verify the certificate at `path`
+    ///
+    /// A detached signature is taken from `detached_signature`, or from
+    /// `<path>.p7s` if that exists. With `drive`, its current details must
+    /// match the certificate.
+    pub fn verify_file(
+        &self,
+        path: &Path,
+        detached_signature: Option<&Path>,
+        drive: Option<&DriveInfo>,
+    ) -> CertificateReport {
+        let default_signature = detached_signature_path(&path.to_string_lossy());
+        let signature_path = detached_signature.or_else(|| {
+            Path::new(&default_signature)
+                .exists()
+                .then_some(Path::new(&default_signature))
+        });
+
+        let read = std::fs::read(path).and_then(|bytes| match signature_path {
+            Some(sig) => Ok((bytes, Some(std::fs::read(sig)?))),
+            None => Ok((bytes, None)),
+        });
+
+        match read {
+            Ok((bytes, signature)) => self.verify_bytes(&bytes, signature.as_deref(), drive),
+            Err(e) => {
+                let mut report = CertificateReport::new(CertificateFormat::Json);
+                report.fail("read", format!("{}: {}", path.display(), e));
+                report.finish()
+            }
+        }
+    }
+
+    /// Verify certificate bytes, with an optional detached CMS signature
+    pub fn verify_bytes(
+        &self,
+        bytes: &[u8],
+        detached_signature: Option<&[u8]>,
+        drive: Option<&DriveInfo>,
+    ) -> CertificateReport {
+        let format = match detached_signature {
+            Some(_) => CertificateFormat::CmsDetached,
+            None if looks_like_json(bytes) => CertificateFormat::Json,
+            None => CertificateFormat::Cms,
+        };
+        let mut report = CertificateReport::new(format);
+
+        // Unwrap the envelope; the signer certificate supplies the key
+        let (content, signer_key, envelope) = match format {
+            CertificateFormat::Json => (bytes.to_vec(), self.public_key.clone(), None),
+            CertificateFormat::Cms | CertificateFormat::CmsDetached => {
+                let envelope = decode_envelope(detached_signature.unwrap_or(bytes).to_vec())
+                    .and_then(|der| cms::open(&der, detached_signature.map(|_| bytes)));
+                let envelope = match envelope {
+                    Ok(envelope) => envelope,
+                    Err(e) => {
+                        report.fail("cms_signature", format!("{:#}", e));
+                        return report.finish();
+                    }
+                };
+                report.pass(
+                    "cms_signature",
+                    format!(
+                        "Signed by \"{}\"",
+                        x509::subject_name(&envelope.signer_certificate)
+                    ),
+                );
+
+                let key = x509::spki_der(&envelope.signer_certificate)
+                    .and_then(|der| PublicKey::from_spki_der(&der))
+                    .ok();
+                (envelope.content.clone(), key, Some(envelope))
+            }
+        };
+
+        let certificate: WipeCertificate = match serde_json::from_slice(&content) {
+            Ok(certificate) => certificate,
+            Err(e) => {
+                report.fail("parse", format!("Not a wipe certificate: {}", e));
+                return report.finish();
+            }
+        };
+        report.pass(
+            "parse",
+            format!(
+                "Certificate {} (schema {})",
+                certificate.certificate_id, certificate.schema_version
+            ),
+        );
+
+        // The signer's chain is checked at a trusted time, never at the
+        // signingTime the signer asserted
+        let stamp = verify_timestamp(&certificate, &self.tsa_trust);
+        if let Some(envelope) = &envelope {
+            let gen_time = match &stamp {
+                Ok(Some(stamp)) => Some(stamp.gen_time),
+                _ => None,
+            };
+            match envelope.validate_chain(&self.trust, gen_time) {
+                Ok((chain, source)) => report.pass(
+                    "certificate_chain",
+                    format!(
+                        "{} (valid {})",
+                        chain.subjects.join(" <- "),
+                        match source {
+                            ChainTime::Timestamp =>
+                                format!("at the time-stamp time {}", chain.checked_at.to_rfc3339()),
+                            ChainTime::Now => format!(
+                                "now, {}; no trusted time-stamp",
+                                chain.checked_at.to_rfc3339()
+                            ),
+                        }
+                    ),
+                ),
+                Err(e) => report.fail("certificate_chain", format!("{:#}", e)),
+            }
+        }
+
+        match signer_key {
+            None => report.fail(
+                "signature",
+                "No public key to check against (configure signing.key_path or pass one)",
+            ),
+            Some(key) => match verify_signature(&certificate, &key) {
+                Ok(SignatureStatus::Valid) => {
+                    report.pass(
+                        "signature",
+                        format!(
+                            "{} signature by key {}",
+                            certificate.signature_algorithm,
+                            key.fingerprint()
+                        ),
+                    );
+                    report.signers.push(SignerRecord {
+                        role: ISSUER_ROLE.to_string(),
+                        signer: certificate.wipe_details.operator_id.clone(),
+                        fingerprint: key.fingerprint(),
+                        trusted: self.policy.lists(ISSUER_ROLE, &key.fingerprint()),
+                    });
+                }
+                Ok(SignatureStatus::Legacy) => report.fail(
+                    "signature",
+                    "Legacy checksum only: the certificate is intact but its issuer cannot be established",
+                ),
+                Ok(SignatureStatus::KeyMismatch { expected, found }) => report.fail(
+                    "signature",
+                    format!("Signed by key {}, expected {}", found, expected),
+                ),
+                Ok(SignatureStatus::Invalid) => report.fail(
+                    "signature",
+                    "Signature does not match the certificate contents",
+                ),
+                Err(e) => report.fail("signature", format!("{:#}", e)),
+            },
+        }
+
+        self.check_cosignatures(&certificate, &mut report);
+
+        match stamp {
+            Ok(None) => {}
+            Ok(Some(stamp)) => report.pass(
+                "timestamp",
+                format!(
+                    "Existed at {} according to \"{}\" (token {})",
+                    stamp.gen_time.to_rfc3339(),
+                    stamp.tsa,
+                    stamp.serial_number
+                ),
+            ),
+            Err(e) => report.fail("timestamp", format!("{:#}", e)),
+        }
+
+        let info = &certificate.device_info;
+        let serial = info
+            .commitments
+            .as_ref()
+            .map_or(&info.serial, |c| &c.serial);
+        let recomputed = device_hash(&info.model, serial, info.size);
+        if recomputed == info.device_hash {
+            match info.commitments {
+                Some(_) => report.pass(
+                    "device_hash",
+                    format!("{} (serial, device path and WWN redacted)", recomputed),
+                ),
+                None => report.pass("device_hash", recomputed),
+            }
+        } else {
+            report.fail(
+                "device_hash",
+                format!(
+                    "Recorded {} but model, serial and size hash to {}",
+                    info.device_hash, recomputed
+                ),
+            );
+        }
+
+        if let Some(evidence) = &certificate.evidence {
+            if evidence.compliant {
+                report.pass("compliance", format!("Ran as {}", evidence.method.executed));
+            } else {
+                report.fail(
+                    "compliance",
+                    format!(
+                        "Wipe is not compliant: {}",
+                        evidence.compliance_problems().join("; ")
+                    ),
+                );
+            }
+        }
+
+        if let Some(drive) = drive {
+            match device_mismatches(&certificate, drive) {
+                Ok(()) => report.pass(
+                    "device_match",
+                    format!("{} is {} {}", drive.device_path, drive.model, drive.serial),
+                ),
+                Err(e) => report.fail("device_match", e.to_string()),
+            }
+        }
+
+        if let Some(rpc_url) = &self.anchor_rpc {
+            let verified = match &self.expected_anchor {
+                Some(expected) => verify_anchor(&certificate, rpc_url, expected),
+                None => Err(anyhow!(
+                    "No anchor contract configured (anchor.contract); the certificate's own is not trusted"
+                )),
+            };
+            match verified {
+                Ok(None) => report.fail("anchor", "Certificate has no blockchain anchor"),
+                Ok(Some(anchor)) => report.pass(
+                    "anchor",
+                    format!(
+                        "Anchored by {} in block {} of chain {} at {} ({} confirmations)",
+                        anchor.issuer,
+                        anchor.block_number,
+                        anchor.chain_id,
+                        anchor.block_time.to_rfc3339(),
+                        anchor.confirmations
+                    ),
+                ),
+                Err(e) => report.fail("anchor", format!("{:#}", e)),
+            }
+        }
+
+        report.certificate = Some(certificate);
+        report.finish()
+    }
+}
+
+fn looks_like_json(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'{')
+}
+
+/// DER envelope, from DER or PEM ("CMS" or "PKCS7")
+fn decode_envelope(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if !bytes.starts_with(b"-----") {
+        return Ok(bytes);
+    }
+    let pem = pem::parse(&bytes)?;
+    match pem.tag() {
+        "CMS" | "PKCS7" => Ok(pem.into_contents()),
+        tag => bail!("Expected a CMS PEM block, found \"{}\"", tag),
+    }
+}
+
+impl CertificateVerifier {
+    /// Check each cosignature, then the signature policy over every valid
+    /// signature
+    fn check_cosignatures(&self, certificate: &WipeCertificate, report: &mut CertificateReport) {
+        if !certificate.cosignatures.is_empty() {
+            let mut problems = Vec::new();
+            let mut seen: Vec<String> = Vec::new();
+            for cosignature in &certificate.cosignatures {
+                match cosignature.verify(certificate) {
+                    Ok(key)
+                        if certificate.signer_fingerprint.as_deref()
+                            == Some(key.fingerprint().as_str()) =>
+                    {
+                        problems.push(format!(
+                            "{} cosignature is by key {}, which issued the certificate",
+                            cosignature.role,
+                            key.fingerprint()
+                        ))
+                    }
+                    Ok(key) if seen.contains(&key.fingerprint()) => problems.push(format!(
+                        "{} cosignature is by key {}, which already cosigned",
+                        cosignature.role,
+                        key.fingerprint()
+                    )),
+                    Ok(key) => {
+                        seen.push(key.fingerprint());
+                        report.signers.push(SignerRecord {
+                            role: cosignature.role.clone(),
+                            signer: cosignature.signer.clone(),
+                            fingerprint: key.fingerprint(),
+                            trusted: self.policy.lists(&cosignature.role, &key.fingerprint()),
+                        })
+                    }
+                    Err(e) => problems.push(format!("{:#}", e)),
+                }
+            }
+            if problems.is_empty() {
+                let signed: Vec<_> = report.signers.iter().skip(1).map(describe_signer).collect();
+                report.pass("cosignatures", format!("Cosigned by {}", signed.join(", ")));
+            } else {
+                report.fail("cosignatures", problems.join("; "));
+            }
+        }
+
+        if self.policy.is_enforced() {
+            let outcome = self.policy.evaluate(&report.signers);
+            let detail = format!(
+                "{} of {} required signatures ({})",
+                outcome.satisfied.len(),
+                outcome.required,
+                if outcome.satisfied.is_empty() {
+                    "none".to_string()
+                } else {
+                    outcome.satisfied.join(", ")
+                }
+            );
+            if outcome.is_final() {
+                report.pass("signature_policy", detail);
+            } else {
+                report.fail(
+                    "signature_policy",
+                    format!(
+                        "{}; not yet signed by {}",
+                        detail,
+                        outcome.missing.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn describe_signer(record: &SignerRecord) -> String {
+    let key = &record.fingerprint[..record.fingerprint.len().min(16)];
+    match &record.signer {
+        Some(name) => format!("{} {} (key {})", record.role, name, key),
+        None => format!("{} (key {})", record.role, key),
+    }
+}
+
+fn device_mismatches(certificate: &WipeCertificate, drive: &DriveInfo) -> Result<()> {
+    let info = &certificate.device_info;
+    if info.commitments.is_some() {
+        bail!(
+            "The certificate's serial is redacted; check it against the drive with \
+             'cert disclose' and the disclosure file"
+        );
+    }
+
+    let mut mismatches = Vec::new();
+    if info.model != drive.model {
+        mismatches.push(format!(
+            "model \"{}\" (certificate) vs \"{}\" (drive)",
+            info.model, drive.model
+        ));
+    }
+    if info.serial != drive.serial {
+        mismatches.push(format!(
+            "serial \"{}\" (certificate) vs \"{}\" (drive)",
+            info.serial, drive.serial
+        ));
+    }
+    if info.size != drive.size {
+        mismatches.push(format!(
+            "size {} (certificate) vs {} (drive)",
+            info.size, drive.size
+        ));
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        bail!(
+            "{} does not match: {}",
+            drive.device_path,
+            mismatches.join("; ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::certificates::{
+        signing_payload, CertificateGenerator, VerificationResult, WipeDetails,
+    };
+    use crate::crypto::signing::{SignatureAlgorithm, SigningKey};
+    use crate::{DriveCapabilities, DriveType, EncryptionStatus};
+    use chrono::Utc;
+
+    const ROOT: &str = include_str!("../../tests/fixtures/pki/root.pem");
+    const OTHER_ROOT: &str = include_str!("../../tests/fixtures/pki/other-root.pem");
+    const INTERMEDIATE: &str = include_str!("../../tests/fixtures/pki/intermediate.pem");
+    const SIGNER_CERT: &str = include_str!("../../tests/fixtures/pki/signer-p256.pem");
+    const SIGNER_KEY: &str = include_str!("../../tests/fixtures/pki/signer-p256.key");
+
+    fn drive() -> DriveInfo {
+        DriveInfo {
+            device_path: "/dev/sdz".to_string(),
+            model: "WDC WD10EZEX".to_string(),
+            serial: "WD-123".to_string(),
+            size: 1_000_204_886_016,
+            drive_type: DriveType::HDD,
+            encryption_status: EncryptionStatus::None,
+            capabilities: DriveCapabilities::default(),
+            health_status: None,
+            temperature_celsius: None,
+        }
+    }
+
+    fn issue(key: SigningKey) -> WipeCertificate {
+        CertificateGenerator::with_key(key)
+            .generate_certificate(
+                &drive(),
+                WipeDetails {
+                    algorithm_used: "Zero".to_string(),
+                    passes_completed: 1,
+                    duration_seconds: 60,
+                    operator_id: None,
+                    checkpoint_rejections: Vec::new(),
+                },
+                VerificationResult {
+                    verified: true,
+                    entropy_score: 0.0,
+                    recovery_test_passed: true,
+                    verification_timestamp: Utc::now(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_pem_or_der(SIGNER_KEY.as_bytes()).unwrap()
+    }
+
+    fn trust(pem: &str) -> TrustStore {
+        TrustStore::new(x509::parse_certificates(pem.as_bytes()).unwrap())
+    }
+
+    fn checks(report: &CertificateReport) -> Vec<(&'static str, bool)> {
+        report.checks.iter().map(|c| (c.check, c.passed)).collect()
+    }
+
+    #[test]
+    fn test_json_certificate_passes() {
+        let certificate = issue(key());
+        let json = serde_json::to_vec_pretty(&certificate).unwrap();
+        let verifier = CertificateVerifier::default().with_public_key(key().public_key().clone());
+
+        let report = verifier.verify_bytes(&json, None, Some(&drive()));
+        assert!(report.valid, "{:?}", report.failure());
+        assert_eq!(report.format, CertificateFormat::Json);
+        assert_eq!(
+            checks(&report),
+            [
+                ("parse", true),
+                ("signature", true),
+                ("device_hash", true),
+                ("device_match", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failures_are_named() {
+        let verifier = CertificateVerifier::default().with_public_key(key().public_key().clone());
+
+        // Edited after signing
+        let mut certificate = issue(key());
+        certificate.wipe_details.passes_completed = 7;
+        let report = verifier.verify_bytes(&serde_json::to_vec(&certificate).unwrap(), None, None);
+        assert!(!report.valid);
+        assert_eq!(report.failed_check, Some("signature"));
+
+        // Other key
+        let (other, _) = SigningKey::generate(SignatureAlgorithm::Ed25519).unwrap();
+        let report = verifier.verify_bytes(&serde_json::to_vec(&issue(other)).unwrap(), None, None);
+        assert_eq!(report.failed_check, Some("signature"));
+        assert!(report.failure().unwrap().detail.contains("expected"));
+
+        // Validly signed, but the hash does not belong to the recorded drive
+        let mut certificate = issue(key());
+        certificate.device_info.device_hash = "00".repeat(32);
+        certificate.signature =
+            hex::encode(key().sign(&signing_payload(&certificate).unwrap()).unwrap());
+        let report = verifier.verify_bytes(&serde_json::to_vec(&certificate).unwrap(), None, None);
+        assert_eq!(report.failed_check, Some("device_hash"));
+
+        // Not a certificate at all
+        let report = verifier.verify_bytes(b"{\"hello\": 1}", None, None);
+        assert_eq!(report.failed_check, Some("parse"));
+    }
+
+    #[test]
+    fn test_device_mismatch_names_fields() {
+        let certificate = issue(key());
+        let verifier = CertificateVerifier::default().with_public_key(key().public_key().clone());
+
+        let mut other = drive();
+        other.serial = "WD-999".to_string();
+        let report = verifier.verify_bytes(
+            &serde_json::to_vec(&certificate).unwrap(),
+            None,
+            Some(&other),
+        );
+
+        assert_eq!(report.failed_check, Some("device_match"));
+        let detail = &report.failure().unwrap().detail;
+        assert!(
+            detail.contains("serial") && !detail.contains("model"),
+            "{}",
+            detail
+        );
+    }
+
+    #[test]
+    fn test_missing_public_key_fails_signature() {
+        let json = serde_json::to_vec(&issue(key())).unwrap();
+        let report = CertificateVerifier::default().verify_bytes(&json, None, None);
+        assert_eq!(report.failed_check, Some("signature"));
+    }
+
+    #[test]
+    fn test_cms_envelope_and_detached_files() {
+        let chain = x509::parse_certificates(format!("{}{}", SIGNER_CERT, INTERMEDIATE).as_bytes())
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        for format in [CertificateFormat::Cms, CertificateFormat::CmsDetached] {
+            let generator = CertificateGenerator::with_key(key())
+                .with_certificate_chain(chain.clone())
+                .unwrap()
+                .with_format(format)
+                .unwrap();
+            let certificate = issue(key());
+            let path = dir.path().join(format!("cert.{}", format.file_extension()));
+            generator
+                .save_certificate(&certificate, path.to_str().unwrap())
+                .unwrap();
+
+            // No public key needed: the envelope names the signer
+            let report = CertificateVerifier::new(None, trust(ROOT)).verify_file(&path, None, None);
+            assert!(report.valid, "{:?}", report.failure());
+            assert_eq!(report.format, format);
+            assert_eq!(
+                checks(&report)[..3],
+                [
+                    ("cms_signature", true),
+                    ("parse", true),
+                    ("certificate_chain", true)
+                ]
+            );
+            // Untimestamped, so the chain is checked now
+            assert!(report.checks[2].detail.contains("no trusted time-stamp"));
+
+            let report =
+                CertificateVerifier::new(None, trust(OTHER_ROOT)).verify_file(&path, None, None);
+            assert_eq!(report.failed_check, Some("certificate_chain"));
+        }
+    }
+
+    #[test]
+    fn test_unreadable_file() {
+        let report = CertificateVerifier::default().verify_file(
+            Path::new("/nonexistent/cert.json"),
+            None,
+            None,
+        );
+        assert!(!report.valid);
+        assert_eq!(report.failed_check, Some("read"));
+    }
+}
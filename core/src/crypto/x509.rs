@@ -0,0 +1,424 @@
+// X.509 certificate chains
+//
+// CMS-wrapped wipe certificates carry the signer's X.509 certificate and the
+// intermediates up to an internal CA. A chain is accepted when:
+// - every certificate is signed by the next one up
+// - every certificate is within its validity period at the time checked
+// - every issuer is a CA (basicConstraints cA, keyCertSign when keyUsage is
+//   present) and its pathLenConstraint allows the CAs below it
+// - the chain ends at one of the configured trust anchors
+//
+// Signatures are checked with ring: ECDSA P-256/P-384, Ed25519 and RSA
+// PKCS#1 v1.5 with SHA-256/384/512. Revocation is not checked.
+
+use super::signing::SigningConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use x509_cert::der::asn1::ObjectIdentifier;
+use x509_cert::der::{Decode, Encode};
+use x509_cert::ext::pkix::{BasicConstraints, KeyUsage};
+use x509_cert::spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+
+pub use x509_cert::Certificate;
+
+/// Longest chain followed from the signer to a trust anchor
+const MAX_CHAIN_LENGTH: usize = 8;
+
+const ID_EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const SECP256R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const SECP384R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+pub(crate) const ECDSA_WITH_SHA256: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const ECDSA_WITH_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.3");
+pub(crate) const ID_ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+const SHA256_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+const SHA384_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12");
+const SHA512_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.13");
+
+/// Parse one or more PEM ("CERTIFICATE") blocks, or a single DER certificate
+pub fn parse_certificates(bytes: &[u8]) -> Result<Vec<Certificate>> {
+    let certificates = if bytes.starts_with(b"-----") {
+        Certificate::load_pem_chain(bytes).map_err(|e| anyhow!("Invalid PEM certificate: {}", e))?
+    } else {
+        vec![Certificate::from_der(bytes).map_err(|e| anyhow!("Invalid DER certificate: {}", e))?]
+    };
+
+    if certificates.is_empty() {
+        bail!("No certificates found");
+    }
+    Ok(certificates)
+}
+
+/// Load a certificate file, keeping the order of its PEM blocks
+pub fn load_certificates(path: &Path) -> Result<Vec<Certificate>> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read certificate {}", path.display()))?;
+    parse_certificates(&bytes)
+        .with_context(|| format!("Failed to load certificate {}", path.display()))
+}
+
+/// Subject distinguished name in RFC 4514 form
+pub fn subject_name(certificate: &Certificate) -> String {
+    certificate.tbs_certificate.subject.to_string()
+}
+
+/// DER SubjectPublicKeyInfo of `certificate`
+pub fn spki_der(certificate: &Certificate) -> Result<Vec<u8>> {
+    certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|e| anyhow!("Failed to encode public key: {}", e))
+}
+
+/// CA certificates that chains must end at
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    anchors: Vec<Certificate>,
+}
+
+impl TrustStore {
+    pub fn new(anchors: Vec<Certificate>) -> Self {
+        Self { anchors }
+    }
+
+    /// Load every certificate in each of `paths`
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut anchors = Vec::new();
+        for path in paths {
+            anchors.extend(load_certificates(path)?);
+        }
+        Ok(Self::new(anchors))
+    }
+
+    /// Trust anchors named in `signing.trust_anchors`
+    pub fn from_config(config: &SigningConfig) -> Result<Self> {
+        Self::load(&config.trust_anchors)
+    }
+
+    pub fn anchors(&self) -> &[Certificate] {
+        &self.anchors
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+
+    fn contains(&self, certificate: &Certificate) -> bool {
+        self.anchors.iter().any(|anchor| anchor == certificate)
+    }
+}
+
+/// Chain that passed validation
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidatedChain {
+    /// Subjects from the signer up to and including the trust anchor
+    pub subjects: Vec<String>,
+    /// Time the validity periods were checked against
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Build and check the chain from `leaf` to a trust anchor
+///
+/// `intermediates` may hold extra or unrelated certificates; only those that
+/// link `leaf` to an anchor are used. Validity periods are checked at `at`,
+/// normally the time the document was signed.
+pub fn validate_chain(
+    leaf: &Certificate,
+    intermediates: &[Certificate],
+    trust: &TrustStore,
+    at: DateTime<Utc>,
+) -> Result<ValidatedChain> {
+    if trust.is_empty() {
+        bail!("No trust anchors configured");
+    }
+
+    check_validity(leaf, at)?;
+    check_signer_usage(leaf)?;
+
+    let mut chain = vec![leaf];
+    while chain.len() <= MAX_CHAIN_LENGTH {
+        let current = chain[chain.len() - 1];
+        if trust.contains(current) {
+            return Ok(ValidatedChain {
+                subjects: chain.iter().map(|c| subject_name(c)).collect(),
+                checked_at: at,
+            });
+        }
+
+        // Prefer an anchor, so a copy of the root shipped in the bag does
+        // not stand in for the configured one
+        let issuer = trust
+            .anchors
+            .iter()
+            .chain(intermediates)
+            .filter(|candidate| !chain.contains(candidate))
+            .find(|candidate| {
+                candidate.tbs_certificate.subject == current.tbs_certificate.issuer
+                    && verify_issued_by(current, candidate).is_ok()
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "No trusted issuer found for \"{}\" (issued by \"{}\")",
+                    subject_name(current),
+                    current.tbs_certificate.issuer
+                )
+            })?;
+
+        // CAs between the leaf and this issuer
+        check_ca(issuer, chain.len() - 1)?;
+        check_validity(issuer, at)?;
+        chain.push(issuer);
+    }
+
+    bail!(
+        "Certificate chain for \"{}\" is longer than {} certificates",
+        subject_name(leaf),
+        MAX_CHAIN_LENGTH
+    )
+}
+
+/// Check that `certificate` carries a valid signature by `issuer`
+pub fn verify_issued_by(certificate: &Certificate, issuer: &Certificate) -> Result<()> {
+    let tbs = certificate
+        .tbs_certificate
+        .to_der()
+        .map_err(|e| anyhow!("Failed to encode certificate: {}", e))?;
+    let signature = certificate
+        .signature
+        .as_bytes()
+        .ok_or_else(|| anyhow!("Certificate signature has unused bits"))?;
+
+    verify_with_spki(
+        &issuer.tbs_certificate.subject_public_key_info,
+        &certificate.signature_algorithm,
+        &tbs,
+        signature,
+    )
+    .with_context(|| {
+        format!(
+            "Signature of \"{}\" does not verify with the key of \"{}\"",
+            subject_name(certificate),
+            subject_name(issuer)
+        )
+    })
+}
+
+/// Verify `signature` over `message` with a SubjectPublicKeyInfo
+pub(crate) fn verify_with_spki(
+    spki: &SubjectPublicKeyInfoOwned,
+    algorithm: &AlgorithmIdentifierOwned,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    let curve = if spki.algorithm.oid == ID_EC_PUBLIC_KEY {
+        spki.algorithm
+            .parameters
+            .as_ref()
+            .and_then(|p| p.decode_as::<ObjectIdentifier>().ok())
+    } else {
+        None
+    };
+
+    let verifier: &'static dyn VerificationAlgorithm = match (algorithm.oid, curve) {
+        (ECDSA_WITH_SHA256, Some(SECP256R1)) => &signature::ECDSA_P256_SHA256_ASN1,
+        (ECDSA_WITH_SHA256, Some(SECP384R1)) => &signature::ECDSA_P384_SHA256_ASN1,
+        (ECDSA_WITH_SHA384, Some(SECP256R1)) => &signature::ECDSA_P256_SHA384_ASN1,
+        (ECDSA_WITH_SHA384, Some(SECP384R1)) => &signature::ECDSA_P384_SHA384_ASN1,
+        (ID_ED25519, _) if spki.algorithm.oid == ID_ED25519 => &signature::ED25519,
+        (SHA256_WITH_RSA, _) => &signature::RSA_PKCS1_2048_8192_SHA256,
+        (SHA384_WITH_RSA, _) => &signature::RSA_PKCS1_2048_8192_SHA384,
+        (SHA512_WITH_RSA, _) => &signature::RSA_PKCS1_2048_8192_SHA512,
+        (oid, _) => bail!(
+            "Unsupported signature algorithm {} for {} key",
+            oid,
+            spki.algorithm.oid
+        ),
+    };
+
+    UnparsedPublicKey::new(verifier, spki.subject_public_key.raw_bytes())
+        .verify(message, signature)
+        .map_err(|_| anyhow!("Signature verification failed"))
+}
+
+fn check_validity(certificate: &Certificate, at: DateTime<Utc>) -> Result<()> {
+    let validity = &certificate.tbs_certificate.validity;
+    let not_before = to_datetime(validity.not_before.to_unix_duration());
+    let not_after = to_datetime(validity.not_after.to_unix_duration());
+
+    if at < not_before {
+        bail!(
+            "Certificate \"{}\" is not valid before {} (checked at {})",
+            subject_name(certificate),
+            not_before,
+            at
+        );
+    }
+    if at > not_after {
+        bail!(
+            "Certificate \"{}\" expired at {} (checked at {})",
+            subject_name(certificate),
+            not_after,
+            at
+        );
+    }
+    Ok(())
+}
+
+fn check_ca(certificate: &Certificate, cas_below: usize) -> Result<()> {
+    let tbs = &certificate.tbs_certificate;
+    let constraints = tbs
+        .get::<BasicConstraints>()
+        .map_err(|e| anyhow!("Invalid basicConstraints: {}", e))?
+        .map(|(_, c)| c);
+
+    match constraints {
+        Some(c) if c.ca => {
+            if let Some(limit) = c.path_len_constraint {
+                if cas_below > limit as usize {
+                    bail!(
+                        "CA \"{}\" allows {} intermediate CAs below it, chain has {}",
+                        subject_name(certificate),
+                        limit,
+                        cas_below
+                    );
+                }
+            }
+        }
+        _ => bail!(
+            "Issuer \"{}\" is not a CA certificate",
+            subject_name(certificate)
+        ),
+    }
+
+    if let Some((_, usage)) = tbs
+        .get::<KeyUsage>()
+        .map_err(|e| anyhow!("Invalid keyUsage: {}", e))?
+    {
+        if !usage.key_cert_sign() {
+            bail!(
+                "CA \"{}\" may not sign certificates (keyUsage)",
+                subject_name(certificate)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_signer_usage(certificate: &Certificate) -> Result<()> {
+    if let Some((_, usage)) = certificate
+        .tbs_certificate
+        .get::<KeyUsage>()
+        .map_err(|e| anyhow!("Invalid keyUsage: {}", e))?
+    {
+        if !usage.digital_signature() && !usage.non_repudiation() {
+            bail!(
+                "Certificate \"{}\" may not sign documents (keyUsage)",
+                subject_name(certificate)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn to_datetime(since_epoch: std::time::Duration) -> DateTime<Utc> {
+    DateTime::from_timestamp(since_epoch.as_secs() as i64, 0).unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const ROOT: &str = include_str!("../../tests/fixtures/pki/root.pem");
+    const OTHER_ROOT: &str = include_str!("../../tests/fixtures/pki/other-root.pem");
+    const INTERMEDIATE: &str = include_str!("../../tests/fixtures/pki/intermediate.pem");
+    const SIGNER: &str = include_str!("../../tests/fixtures/pki/signer-ed25519.pem");
+
+    fn cert(pem: &str) -> Certificate {
+        parse_certificates(pem.as_bytes()).unwrap().remove(0)
+    }
+
+    fn year(y: i32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, 6, 1, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_chain_to_anchor() {
+        let trust = TrustStore::new(vec![cert(ROOT)]);
+        let chain =
+            validate_chain(&cert(SIGNER), &[cert(INTERMEDIATE)], &trust, year(2030)).unwrap();
+
+        assert_eq!(chain.subjects.len(), 3);
+        assert!(chain.subjects[0].contains("Wipe Station 1"));
+        assert!(chain.subjects[2].contains("Sayonara Test Root CA"));
+    }
+
+    #[test]
+    fn test_intermediate_as_anchor() {
+        let trust = TrustStore::new(vec![cert(INTERMEDIATE)]);
+        let chain = validate_chain(&cert(SIGNER), &[], &trust, year(2030)).unwrap();
+        assert_eq!(chain.subjects.len(), 2);
+    }
+
+    #[test]
+    fn test_untrusted_root_rejected() {
+        let trust = TrustStore::new(vec![cert(OTHER_ROOT)]);
+        let err = validate_chain(&cert(SIGNER), &[cert(INTERMEDIATE)], &trust, year(2030))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No trusted issuer"), "{}", err);
+
+        assert!(validate_chain(&cert(SIGNER), &[], &TrustStore::default(), year(2030)).is_err());
+    }
+
+    #[test]
+    fn test_missing_intermediate_rejected() {
+        let trust = TrustStore::new(vec![cert(ROOT)]);
+        assert!(validate_chain(&cert(SIGNER), &[], &trust, year(2030)).is_err());
+    }
+
+    #[test]
+    fn test_validity_period_enforced() {
+        let trust = TrustStore::new(vec![cert(ROOT)]);
+        let intermediates = [cert(INTERMEDIATE)];
+
+        // The signer certificate runs 2026-2036
+        let err = validate_chain(&cert(SIGNER), &intermediates, &trust, year(2040))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("expired"), "{}", err);
+
+        let err = validate_chain(&cert(SIGNER), &intermediates, &trust, year(2020))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("not valid before"), "{}", err);
+    }
+
+    #[test]
+    fn test_leaf_cannot_act_as_ca() {
+        // A certificate "issued" by the signer is refused even if the
+        // signature happened to match, since the signer is not a CA
+        let err = check_ca(&cert(SIGNER), 0).unwrap_err().to_string();
+        assert!(err.contains("not a CA"), "{}", err);
+        check_ca(&cert(INTERMEDIATE), 0).unwrap();
+
+        // The issuing CA has pathlen 0
+        assert!(check_ca(&cert(INTERMEDIATE), 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_der_and_pem_chain() {
+        let both = format!("{}{}", SIGNER, INTERMEDIATE);
+        let parsed = parse_certificates(both.as_bytes()).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0], cert(SIGNER));
+
+        let der = cert(ROOT).to_der().unwrap();
+        assert_eq!(parse_certificates(&der).unwrap()[0], cert(ROOT));
+        assert!(parse_certificates(b"not a certificate").is_err());
+    }
+}
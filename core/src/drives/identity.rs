@@ -0,0 +1,336 @@
+// Drive identity probing
+//
+// Records what physical drive sat behind a device path when an operation
+// started (serial, WWN, capacity, firmware), so a resumed operation can refuse
+// to continue on a different disk that has since been enumerated at the same
+// path.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::process::Command;
+
+/// Stable identifiers of the drive behind a device path
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriveIdentity {
+    /// Serial number as reported by udev/sysfs
+    pub serial: Option<String>,
+
+    /// Capacity in bytes
+    pub size: Option<u64>,
+
+    /// World Wide Name, when the transport reports one
+    #[serde(default)]
+    pub wwn: Option<String>,
+
+    /// Firmware revision
+    #[serde(default)]
+    pub firmware: Option<String>,
+}
+
+impl DriveIdentity {
+    /// Probe the drive currently at `device_path`
+    ///
+    /// Uses sysfs and udev only, so it is cheap enough to call when an
+    /// operation starts. Missing values are left as `None`.
+    pub fn probe(device_path: &str) -> Self {
+        let name = Path::new(device_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let serial = read_sysfs(&format!("/sys/block/{}/device/serial", name))
+            .or_else(|| udev_property(device_path, "ID_SERIAL_SHORT"));
+
+        // /sys/block/<dev>/size is always in 512-byte units
+        let size = read_sysfs(&format!("/sys/block/{}/size", name))
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(|sectors| sectors * 512);
+
+        let wwn = read_sysfs(&format!("/sys/block/{}/device/wwid", name))
+            .or_else(|| read_sysfs(&format!("/sys/block/{}/wwid", name)))
+            .or_else(|| udev_property(device_path, "ID_WWN"));
+
+        let firmware = read_sysfs(&format!("/sys/block/{}/device/firmware_rev", name))
+            .or_else(|| read_sysfs(&format!("/sys/block/{}/device/rev", name)))
+            .or_else(|| udev_property(device_path, "ID_REVISION"));
+
+        Self {
+            serial,
+            size,
+            wwn,
+            firmware,
+        }
+    }
+
+    /// SHA-256 over every identifier, as a hex string
+    ///
+    /// Unknown identifiers hash as empty, so two probes that could read the
+    /// same values produce the same fingerprint.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        for field in [
+            self.serial.clone(),
+            self.wwn.clone(),
+            self.size.map(|size| size.to_string()),
+            self.firmware.clone(),
+        ] {
+            hasher.update(field.unwrap_or_default().as_bytes());
+            hasher.update([0u8]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Describe every identifier that differs from `current`
+    ///
+    /// Identifiers unknown on either side are not compared.
+    pub fn mismatches(&self, current: &DriveIdentity) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let (Some(recorded), Some(found)) = (&self.serial, &current.serial) {
+            if recorded != found {
+                problems.push(format!(
+                    "serial changed (recorded {}, found {})",
+                    recorded, found
+                ));
+            }
+        }
+        if let (Some(recorded), Some(found)) = (&self.wwn, &current.wwn) {
+            if recorded != found {
+                problems.push(format!(
+                    "WWN changed (recorded {}, found {})",
+                    recorded, found
+                ));
+            }
+        }
+        if let (Some(recorded), Some(found)) = (self.size, current.size) {
+            if recorded != found {
+                problems.push(format!(
+                    "size changed (recorded {} bytes, found {} bytes)",
+                    recorded, found
+                ));
+            }
+        }
+        if let (Some(recorded), Some(found)) = (&self.firmware, &current.firmware) {
+            if recorded != found {
+                problems.push(format!(
+                    "firmware changed (recorded {}, found {})",
+                    recorded, found
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Like [`mismatches`](Self::mismatches), but also treats an identifier
+    /// that was recorded and can no longer be read as a problem
+    ///
+    /// Used where continuing on an unverifiable drive is not acceptable, such
+    /// as an operator resuming a checkpoint by hand.
+    pub fn strict_mismatches(&self, current: &DriveIdentity) -> Vec<String> {
+        let mut problems = self.mismatches(current);
+
+        if self.serial.is_some() && current.serial.is_none() {
+            problems.push("serial could not be read from the device".to_string());
+        }
+        if self.wwn.is_some() && current.wwn.is_none() {
+            problems.push("WWN could not be read from the device".to_string());
+        }
+        if self.size.is_some() && current.size.is_none() {
+            problems.push("size could not be read from the device".to_string());
+        }
+        if self.firmware.is_some() && current.firmware.is_none() {
+            problems.push("firmware could not be read from the device".to_string());
+        }
+
+        problems
+    }
+}
+
+/// Bus the drive at `device_path` is attached through (`sata`, `nvme`,
+/// `usb`, `sas`, ...), as lsblk reports it, or guessed from its sysfs path
+pub fn probe_transport(device_path: &str) -> Option<String> {
+    let lsblk = Command::new("lsblk")
+        .args(["-dno", "TRAN", device_path])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|transport| !transport.is_empty());
+
+    lsblk.or_else(|| {
+        let name = Path::new(device_path).file_name()?.to_str()?;
+        let path = std::fs::canonicalize(format!("/sys/block/{}", name)).ok()?;
+        transport_from_sysfs_path(&path.to_string_lossy())
+    })
+}
+
+/// Transport implied by a resolved `/sys/block/<dev>` path
+fn transport_from_sysfs_path(path: &str) -> Option<String> {
+    let transport = if path.contains("/usb") {
+        "usb"
+    } else if path.contains("/nvme") {
+        "nvme"
+    } else if path.contains("/ata") {
+        "sata"
+    } else if path.contains("/mmc") {
+        "mmc"
+    } else if path.contains("/virtio") {
+        "virtio"
+    } else if path.contains("/host") && path.contains("/target") {
+        "scsi"
+    } else {
+        return None;
+    };
+    Some(transport.to_string())
+}
+
+fn read_sysfs(path: &str) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn udev_property(device_path: &str, key: &str) -> Option<String> {
+    let output = Command::new("udevadm")
+        .args(["info", "--query=property", "--name", device_path])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_missing_device() {
+        let identity = DriveIdentity::probe("/dev/nonexistent_sayonara_test");
+        assert_eq!(identity.size, None);
+    }
+
+    #[test]
+    fn test_transport_from_sysfs_path() {
+        for (path, transport) in [
+            (
+                "/sys/devices/pci0000:00/0000:00:17.0/ata1/host0/target0:0:0/0:0:0:0/block/sda",
+                Some("sata"),
+            ),
+            (
+                "/sys/devices/pci0000:00/0000:00:1d.0/0000:3d:00.0/nvme/nvme0/nvme0n1",
+                Some("nvme"),
+            ),
+            (
+                "/sys/devices/pci0000:00/0000:00:14.0/usb2/2-1/2-1:1.0/host4/target4:0:0/4:0:0:0/block/sdb",
+                Some("usb"),
+            ),
+            (
+                "/sys/devices/pci0000:00/0000:00:03.0/0000:03:00.0/host2/port-2:0/end_device-2:0/target2:0:0/2:0:0:0/block/sdc",
+                Some("scsi"),
+            ),
+            ("/sys/devices/virtual/block/loop0", None),
+        ] {
+            assert_eq!(
+                transport_from_sysfs_path(path).as_deref(),
+                transport,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_matching_identity() {
+        let identity = DriveIdentity {
+            serial: Some("WD-123".to_string()),
+            size: Some(1_000_204_886_016),
+            ..Default::default()
+        };
+        assert!(identity.mismatches(&identity.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_mismatched_identity() {
+        let recorded = DriveIdentity {
+            serial: Some("WD-123".to_string()),
+            size: Some(1_000_204_886_016),
+            ..Default::default()
+        };
+        let current = DriveIdentity {
+            serial: Some("WD-456".to_string()),
+            size: Some(500_107_862_016),
+            ..Default::default()
+        };
+
+        let problems = recorded.mismatches(&current);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("serial"));
+        assert!(problems[1].contains("size"));
+    }
+
+    #[test]
+    fn test_unknown_values_not_compared() {
+        let recorded = DriveIdentity {
+            serial: None,
+            size: Some(1024),
+            ..Default::default()
+        };
+        let current = DriveIdentity {
+            serial: Some("WD-456".to_string()),
+            size: Some(1024),
+            ..Default::default()
+        };
+        assert!(recorded.mismatches(&current).is_empty());
+    }
+
+    #[test]
+    fn test_strict_mismatches_require_recorded_values() {
+        let recorded = DriveIdentity {
+            serial: Some("WD-123".to_string()),
+            size: Some(1024),
+            ..Default::default()
+        };
+        let current = DriveIdentity {
+            serial: None,
+            size: Some(1024),
+            ..Default::default()
+        };
+
+        assert!(recorded.mismatches(&current).is_empty());
+        let problems = recorded.strict_mismatches(&current);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("serial could not be read"));
+    }
+
+    #[test]
+    fn test_wwn_and_firmware_compared() {
+        let recorded = DriveIdentity {
+            serial: Some("WD-123".to_string()),
+            wwn: Some("naa.5000c500a1b2c3d4".to_string()),
+            firmware: Some("80.00A80".to_string()),
+            ..Default::default()
+        };
+        let current = DriveIdentity {
+            firmware: Some("81.00A81".to_string()),
+            ..recorded.clone()
+        };
+
+        let problems = recorded.mismatches(&current);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("firmware"));
+        assert_ne!(recorded.fingerprint(), current.fingerprint());
+        assert_eq!(recorded.fingerprint(), recorded.clone().fingerprint());
+    }
+}
@@ -10,14 +10,26 @@ use super::types::optane::OptaneDrive;
 use super::types::raid::RAIDArray;
 use super::types::smr::SMRDrive;
 use crate::crypto::secure_rng::secure_random_bytes;
-use crate::io::{IOConfig, IOHandle, OptimizedIO};
+use crate::error::{PassTracker, RangeSet};
+use crate::io::{IOConfig, IOError, IOHandle, OptimizedIO};
 use crate::ui::progress::ProgressBar;
 use anyhow::Result;
 
 // ==================== SMR DRIVE INTEGRATION ====================
 
 /// Wipe an SMR drive using OptimizedIO with proper zone handling
-pub fn wipe_smr_drive_integrated(smr_drive: &SMRDrive, algorithm: WipeAlgorithm) -> Result<()> {
+///
+/// `completed` holds the ranges of the zone sequence an interrupted wipe
+/// already wrote (empty for a fresh wipe); only the rest is written. When a
+/// `tracker` is given, written ranges are checkpointed so a later run can
+/// resume mid-pass, and chunks that hit a bad sector are skipped and reported
+/// to the tracker's bad sector handler instead of failing the pass.
+pub fn wipe_smr_drive_integrated(
+    smr_drive: &SMRDrive,
+    algorithm: WipeAlgorithm,
+    completed: &RangeSet,
+    tracker: Option<&PassTracker>,
+) -> Result<()> {
     println!("🔄 Starting SMR-aware integrated wipe");
     println!("   Drive: {}", smr_drive.device_path);
     println!("   Algorithm: {:?}", algorithm);
@@ -30,9 +42,30 @@ pub fn wipe_smr_drive_integrated(smr_drive: &SMRDrive, algorithm: WipeAlgorithm)
     let mut io_handle = OptimizedIO::open(&smr_drive.device_path, io_config)?;
 
     // Use SMR's built-in wipe function with OptimizedIO callbacks
-    smr_drive.wipe_smr_drive(|offset, size| {
-        write_pattern_to_zone(&mut io_handle, offset, size, &algorithm)
-    })?;
+    if completed.is_empty() {
+        let mut position = 0u64;
+        smr_drive.wipe_smr_drive(|offset, size| {
+            let zone = ZoneWrite {
+                offset,
+                size,
+                position,
+                sequential: smr_drive.is_sequential_zone_start(offset),
+            };
+            write_pattern_to_zone(&mut io_handle, &zone, &algorithm, tracker)?;
+            position += size;
+            Ok(())
+        })?;
+    } else {
+        smr_drive.resume_smr_wipe(completed, |offset, size, position| {
+            let zone = ZoneWrite {
+                offset,
+                size,
+                position,
+                sequential: smr_drive.is_sequential_zone_start(offset),
+            };
+            write_pattern_to_zone(&mut io_handle, &zone, &algorithm, tracker)
+        })?;
+    }
 
     // Validate
     smr_drive.validate_smr_wipe()?;
@@ -40,22 +73,55 @@ pub fn wipe_smr_drive_integrated(smr_drive: &SMRDrive, algorithm: WipeAlgorithm)
     // Print performance
     OptimizedIO::print_performance_report(&io_handle, None);
 
+    // Skipped ranges are carried into the certificate evidence, which is
+    // then marked non-compliant
+    if let Some(handler) = tracker.and_then(|tracker| tracker.bad_sectors()) {
+        let skipped = handler.skipped_ranges();
+        if !skipped.is_empty() {
+            println!(
+                "⚠️  SMR wipe incomplete: {} bytes in {} range(s) could not be written (bad sectors)",
+                skipped.covered(),
+                skipped.range_count()
+            );
+            return Ok(());
+        }
+    }
+
     println!("✅ SMR wipe completed successfully");
     Ok(())
 }
 
+/// One contiguous write inside a zone
+struct ZoneWrite {
+    /// Device offset to start writing at
+    offset: u64,
+    /// Bytes to write
+    size: u64,
+    /// Where `offset` lies within the whole pass; this is what gets
+    /// checkpointed
+    position: u64,
+    /// Sequential-write zone, which cannot continue past a failed write
+    sequential: bool,
+}
+
 /// Write pattern to a specific zone using OptimizedIO
+///
+/// Each chunk that reaches the device is recorded through `tracker`. A chunk
+/// that fails with a media error is left unwritten and handed to the bad
+/// sector handler; in a sequential zone the rest of the zone is skipped with
+/// it, since the write pointer cannot move past the failure.
 fn write_pattern_to_zone(
     io_handle: &mut IOHandle,
-    offset: u64,
-    size: u64,
+    zone: &ZoneWrite,
     algorithm: &WipeAlgorithm,
+    tracker: Option<&PassTracker>,
 ) -> Result<()> {
     let mut bytes_written = 0u64;
     let buffer_size = io_handle.acquire_buffer()?.as_slice().len() as u64;
+    let bad_sectors = tracker.and_then(|tracker| tracker.bad_sectors());
 
-    while bytes_written < size {
-        let write_size = (size - bytes_written).min(buffer_size);
+    while bytes_written < zone.size {
+        let write_size = (zone.size - bytes_written).min(buffer_size);
         let mut buffer = io_handle.acquire_buffer()?;
 
         // Fill buffer based on algorithm
@@ -75,18 +141,43 @@ fn write_pattern_to_zone(
         }
 
         // Write to zone
-        let written = io_handle.write_at(
-            &buffer.as_slice()[..write_size as usize],
-            offset + bytes_written,
-        )?;
+        let offset = zone.offset + bytes_written;
+        let written = match io_handle.write_at(&buffer.as_slice()[..write_size as usize], offset) {
+            Ok(written) => written as u64,
+            Err(e) => match bad_sectors.filter(|_| is_media_error(&e)) {
+                Some(handler) => {
+                    let skip = if zone.sequential {
+                        zone.size - bytes_written
+                    } else {
+                        write_size
+                    };
+                    handler.record_bad_range(offset, skip, e.to_string())?;
+                    bytes_written += skip;
+                    continue;
+                }
+                None => return Err(e.into()),
+            },
+        };
 
-        bytes_written += written as u64;
+        let start = zone.position + bytes_written;
+        bytes_written += written;
+
+        if let Some(tracker) = tracker {
+            tracker.record_range(start..zone.position + bytes_written, || {
+                Ok(io_handle.sync()?)
+            })?;
+        }
     }
 
     io_handle.sync()?;
     Ok(())
 }
 
+/// Write failed because the medium could not store the data
+fn is_media_error(error: &IOError) -> bool {
+    matches!(error, IOError::IoError(e) if e.raw_os_error() == Some(libc::EIO))
+}
+
 // ==================== OPTANE / 3D XPOINT INTEGRATION ====================
 
 /// Wipe Intel Optane drive using optimized operations
@@ -181,6 +272,25 @@ pub fn wipe_hybrid_drive_integrated(hybrid_drive: &mut HybridDrive) -> Result<()
 
     // Step 2: Wipe HDD portion with optimized I/O
     println!("\n   Step 2: Wiping HDD portion...");
+    let result = wipe_hybrid_hdd_portion(hybrid_drive);
+
+    // Step 3: Put the cache back the way it was, whether or not the wipe
+    // succeeded
+    println!("\n   Step 3: Re-enabling SSD cache...");
+    if let Err(e) = hybrid_drive.enable_cache() {
+        eprintln!(
+            "   ⚠️  Failed to restore cache setting ({}); run 'sayonara recover' to retry",
+            e
+        );
+    }
+
+    result?;
+    println!("✅ Hybrid drive wipe completed successfully");
+
+    Ok(())
+}
+
+fn wipe_hybrid_hdd_portion(hybrid_drive: &HybridDrive) -> Result<()> {
     let io_config = IOConfig::hdd_optimized();
     let mut io_handle = OptimizedIO::open(&hybrid_drive.device_path, io_config)?;
 
@@ -197,8 +307,6 @@ pub fn wipe_hybrid_drive_integrated(hybrid_drive: &mut HybridDrive) -> Result<()
     wipe_with_random_progress(&mut io_handle, size)?;
 
     OptimizedIO::print_performance_report(&io_handle, None);
-    println!("✅ Hybrid drive wipe completed successfully");
-
     Ok(())
 }
 
@@ -423,12 +531,19 @@ pub fn wipe_nvme_advanced_integrated(nvme_drive: &NVMeAdvanced, use_format: bool
     Ok(())
 }
 
-/// Format NVMe namespace using Format command
+/// Format NVMe namespace with Secure Erase Settings 1 (user data erase)
 fn format_nvme_namespace(controller_path: &str, nsid: u32) -> Result<()> {
     use std::process::Command;
 
     let output = Command::new("nvme")
-        .args(["format", controller_path, "-n", &nsid.to_string()])
+        .args([
+            "format",
+            controller_path,
+            "-n",
+            &nsid.to_string(),
+            "--ses=1",
+            "--force",
+        ])
         .output()?;
 
     if !output.status.success() {
@@ -6,9 +6,13 @@
 // - operations/: Drive operations (SMART, TRIM, HPA/DCO, SED)
 // - freeze/: Freeze detection and mitigation
 // - integrated_wipe.rs: OptimizedIO-integrated wipe operations for advanced drives
+// - identity.rs: Drive identity probing for checkpoint resume safety
+// - sanitization.rs: NIST SP 800-88 Clear/Purge technique selection
 
 // Core functionality
 pub mod detection;
+pub mod identity;
+pub mod sanitization;
 
 // Tests
 #[cfg(test)]
@@ -31,6 +35,10 @@ pub mod integrated_wipe;
 
 // Re-exports for backward compatibility and convenience
 pub use detection::DriveDetector;
+pub use identity::{probe_transport, DriveIdentity};
+pub use sanitization::{
+    check_pass_verification, plan_sanitization, SanitizationDecision, SanitizationPlan,
+};
 
 // Drive types
 pub use types::{
@@ -73,7 +81,10 @@ pub use types::{
 };
 
 // Operations
-pub use operations::{HPADCOManager, SEDManager, SMARTMonitor, TrimOperations};
+pub use operations::{
+    HPADCOManager, HpaRemoval, PendingUndo, ReplayOutcome, SEDManager, SMARTMonitor,
+    TrimOperations, UndoAction, UndoJournal,
+};
 
 // Freeze mitigation
 pub use freeze::{
@@ -20,6 +20,32 @@ pub struct DCOInfo {
     pub hidden_size_bytes: u64,
 }
 
+/// Whether an HPA removal lasts past the next power cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HpaRemoval {
+    /// Lifted until power-off, or until restored
+    Volatile,
+    /// Written to the drive, so the HPA does not come back
+    Permanent,
+}
+
+impl HpaRemoval {
+    fn name(self) -> &'static str {
+        match self {
+            HpaRemoval::Volatile => "volatile",
+            HpaRemoval::Permanent => "permanent",
+        }
+    }
+
+    /// What the certificate evidence records for this removal
+    pub fn evidence_action(self) -> &'static str {
+        match self {
+            HpaRemoval::Volatile => "HPA removed for the wipe, then restored",
+            HpaRemoval::Permanent => "permanently removed",
+        }
+    }
+}
+
 pub struct HPADCOManager;
 
 impl HPADCOManager {
@@ -92,20 +118,15 @@ impl HPADCOManager {
         Ok(None)
     }
 
-    /// Temporarily remove HPA (can be restored)
-    pub fn remove_hpa_temporary(device_path: &str) -> DriveResult<()> {
-        println!("Temporarily removing HPA on {}...", device_path);
+    /// Remove the HPA by raising the max address to the native max
+    pub fn remove_hpa(device_path: &str, removal: HpaRemoval) -> DriveResult<()> {
+        println!("Removing HPA on {} ({})...", device_path, removal.name());
 
         let native_max = Self::get_native_max_address(device_path)?;
 
         // Use hdparm to set max address to native max
         let output = Command::new("hdparm")
-            .args([
-                "--yes-i-know-what-i-am-doing",
-                "-N",
-                &format!("{}", native_max),
-                device_path,
-            ])
+            .args(Self::set_max_args(device_path, native_max, removal))
             .output()
             .map_err(|e| {
                 DriveError::HardwareCommandFailed(format!("Failed to remove HPA: {}", e))
@@ -119,10 +140,32 @@ impl HPADCOManager {
             )));
         }
 
-        println!("HPA temporarily removed. Full capacity now accessible.");
+        println!(
+            "HPA removed ({}). Full capacity now accessible.",
+            removal.name()
+        );
         Ok(())
     }
 
+    /// hdparm arguments setting the max address to `max_sectors`; the `p`
+    /// prefix makes the change survive a power cycle
+    pub(crate) fn set_max_args(
+        device_path: &str,
+        max_sectors: u64,
+        removal: HpaRemoval,
+    ) -> [String; 4] {
+        let max = match removal {
+            HpaRemoval::Volatile => max_sectors.to_string(),
+            HpaRemoval::Permanent => format!("p{}", max_sectors),
+        };
+        [
+            "--yes-i-know-what-i-am-doing".to_string(),
+            "-N".to_string(),
+            max,
+            device_path.to_string(),
+        ]
+    }
+
     /// Restore HPA to original settings
     pub fn restore_hpa(device_path: &str, original_max_sectors: u64) -> DriveResult<()> {
         println!(
@@ -131,12 +174,11 @@ impl HPADCOManager {
         );
 
         let output = Command::new("hdparm")
-            .args([
-                "--yes-i-know-what-i-am-doing",
-                "-N",
-                &format!("{}", original_max_sectors),
+            .args(Self::set_max_args(
                 device_path,
-            ])
+                original_max_sectors,
+                HpaRemoval::Volatile,
+            ))
             .output()
             .map_err(|e| {
                 DriveError::HardwareCommandFailed(format!("Failed to restore HPA: {}", e))
@@ -151,6 +151,29 @@ fn test_extract_number_from_line_hex() {
     assert!(num.is_some());
 }
 
+// ============================================================================
+// HPA Removal Tests
+// ============================================================================
+
+#[test]
+fn test_hpa_removal_evidence_matches_hdparm_call() {
+    // A permanent removal must use hdparm's `p` prefix, or the HPA returns
+    // on the next power cycle and the certificate would overstate it
+    let permanent = HPADCOManager::set_max_args("/dev/sda", 2000000, HpaRemoval::Permanent);
+    assert_eq!(
+        permanent,
+        ["--yes-i-know-what-i-am-doing", "-N", "p2000000", "/dev/sda"]
+    );
+    assert_eq!(
+        HpaRemoval::Permanent.evidence_action(),
+        "permanently removed"
+    );
+
+    let volatile = HPADCOManager::set_max_args("/dev/sda", 2000000, HpaRemoval::Volatile);
+    assert_eq!(volatile[2], "2000000");
+    assert!(!HpaRemoval::Volatile.evidence_action().contains("permanent"));
+}
+
 // ============================================================================
 // DCO Output Parsing Tests
 // ============================================================================
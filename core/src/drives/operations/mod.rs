@@ -6,12 +6,14 @@ pub mod hpa_dco; // Hidden Protected Area / Device Configuration Overlay
 pub mod sed; // Self-Encrypting Drive operations
 pub mod smart;
 pub mod trim; // TRIM/discard operations // SMART monitoring and health checks
+pub mod undo_journal; // Power-loss-safe undo of temporary drive configuration changes
 
 // Re-exports for convenience
-pub use hpa_dco::HPADCOManager;
+pub use hpa_dco::{HPADCOManager, HpaRemoval};
 pub use sed::SEDManager;
 pub use smart::SMARTMonitor;
 pub use trim::TrimOperations;
+pub use undo_journal::{PendingUndo, ReplayOutcome, UndoAction, UndoJournal};
 
 // Tests
 #[cfg(test)]
@@ -22,3 +24,6 @@ mod hpa_dco_tests;
 
 #[cfg(test)]
 mod trim_tests;
+
+#[cfg(test)]
+mod undo_journal_tests;
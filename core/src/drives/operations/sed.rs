@@ -1,3 +1,4 @@
+use super::undo_journal::{PendingUndo, UndoAction};
 use crate::SEDType;
 use crate::{DriveError, DriveResult};
 use anyhow::{anyhow, Result};
@@ -315,6 +316,15 @@ impl SEDManager {
         // Set temporary password and erase
         let password = "temporary_erase_pwd";
 
+        // Journal the password first so a power loss mid-erase cannot leave
+        // the drive locked with no record of the password
+        let undo = PendingUndo::begin(
+            device_path,
+            UndoAction::DisableAtaPassword {
+                password: password.to_string(),
+            },
+        )?;
+
         let output = Command::new("hdparm")
             .args([
                 "--user-master",
@@ -327,6 +337,7 @@ impl SEDManager {
             .map_err(|e| DriveError::CryptoEraseFailed(format!("Failed to set password: {}", e)))?;
 
         if !output.status.success() {
+            undo.complete()?;
             return Err(DriveError::CryptoEraseFailed(
                 "Failed to set security password".to_string(),
             ));
@@ -344,9 +355,14 @@ impl SEDManager {
             .map_err(|e| DriveError::CryptoEraseFailed(format!("Secure erase failed: {}", e)))?;
 
         if output.status.success() {
+            // A successful erase clears the password
+            undo.complete()?;
             println!("ATA Secure Erase completed");
             Ok(())
         } else {
+            if let Err(e) = undo.revert() {
+                eprintln!("Warning: failed to remove temporary password: {}", e);
+            }
             Err(DriveError::CryptoEraseFailed(
                 "ATA Secure Erase failed".to_string(),
             ))
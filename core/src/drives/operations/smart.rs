@@ -5,7 +5,7 @@ use std::io;
 use std::io::Write;
 use std::process::Command;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct SMARTHealth {
     pub overall_health: HealthStatus,
     pub temperature_celsius: Option<u32>,
@@ -24,7 +24,7 @@ pub struct SMARTHealth {
     pub attributes: HashMap<String, SMARTAttribute>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct SMARTAttribute {
     pub id: u8,
     pub name: String,
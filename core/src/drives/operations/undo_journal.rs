@@ -0,0 +1,568 @@
+// Undo journal for temporary drive configuration changes
+//
+// Some flows change the drive's own configuration for the duration of a wipe:
+// the HPA is lifted, an ATA security password is set for a secure erase, or
+// write caching is switched off. Each such change is journalled *before* it is
+// made, recording the original value and how to restore it, and the entry is
+// removed once the flow has put the drive back. Entries left behind by a crash
+// or power loss are replayed on the next start or with `sayonara recover`.
+//
+// Every entry names the process that made the change. That process holds an
+// exclusive flock on `undo_owners/<pid>.lock` next to the journal for as long
+// as it runs, so another sayonara started alongside it (a `list`, or a wipe
+// of another bay) can tell the change is still in use and leaves it alone.
+// Only entries whose owner has exited are replayed automatically; `recover
+// --force` is the one way to undo a change a live process still owns.
+//
+// The temporary ATA password is stored in the journal in plaintext, since
+// removing it again needs it. The journal is therefore created with mode
+// 0600 (SQLite gives its -wal and -shm files the same mode) and tightened to
+// 0600 if it already exists with a looser one.
+
+use super::hpa_dco::HPADCOManager;
+use crate::drives::DriveIdentity;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use uuid::Uuid;
+
+/// Journal database file name inside the configured state directory
+const DB_FILE_NAME: &str = "undo_journal.db";
+
+/// Directory, next to the journal, holding one lock file per owning process
+const OWNERS_DIR: &str = "undo_owners";
+
+/// How to put a drive back the way it was
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoAction {
+    /// Set the max address back to `max_sectors`, re-creating the HPA
+    RestoreHpa { max_sectors: u64 },
+
+    /// Remove the temporary ATA user password set for a secure erase
+    DisableAtaPassword { password: String },
+
+    /// Switch volatile write caching back to its original state
+    RestoreWriteCache { enabled: bool },
+}
+
+impl UndoAction {
+    /// One-line description for listings
+    pub fn description(&self) -> String {
+        match self {
+            UndoAction::RestoreHpa { max_sectors } => {
+                format!("restore HPA (max address {} sectors)", max_sectors)
+            }
+            UndoAction::DisableAtaPassword { .. } => {
+                "remove temporary ATA security password".to_string()
+            }
+            UndoAction::RestoreWriteCache { enabled } => format!(
+                "turn write cache {}",
+                if *enabled { "back on" } else { "back off" }
+            ),
+        }
+    }
+
+    /// Apply the undo to the drive at `device_path`
+    pub fn apply(&self, device_path: &str) -> Result<()> {
+        match self {
+            UndoAction::RestoreHpa { max_sectors } => {
+                HPADCOManager::restore_hpa(device_path, *max_sectors).map_err(|e| anyhow!("{}", e))
+            }
+            UndoAction::DisableAtaPassword { password } => {
+                // A completed erase clears the password itself
+                if ata_security_enabled(device_path) == Some(false) {
+                    return Ok(());
+                }
+
+                // A drive that lost power with the password set comes back
+                // locked; unlocking fails harmlessly if it is not
+                let _ = hdparm(&[
+                    "--user-master",
+                    "u",
+                    "--security-unlock",
+                    password,
+                    device_path,
+                ]);
+                hdparm(&[
+                    "--user-master",
+                    "u",
+                    "--security-disable",
+                    password,
+                    device_path,
+                ])
+            }
+            UndoAction::RestoreWriteCache { enabled } => set_write_cache(device_path, *enabled),
+        }
+    }
+}
+
+/// An outstanding change recorded in the journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoEntry {
+    /// Entry identifier (UUID)
+    pub id: String,
+
+    /// Device path the change was made through
+    pub device_path: String,
+
+    /// Drive behind `device_path` when the change was made
+    pub identity: DriveIdentity,
+
+    /// How to undo the change
+    pub action: UndoAction,
+
+    /// When the change was journalled
+    pub created_at: DateTime<Utc>,
+
+    /// Process that made the change; `None` for entries journalled before
+    /// owners were recorded
+    #[serde(default)]
+    pub owner_pid: Option<u32>,
+}
+
+/// Result of replaying one journal entry
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ReplayOutcome {
+    /// The drive was restored and the entry removed
+    Restored(UndoEntry),
+
+    /// A different drive (or none) is at the recorded path; the entry is kept
+    Skipped { entry: UndoEntry, reason: String },
+
+    /// Restoring failed; the entry is kept for another attempt
+    Failed { entry: UndoEntry, error: String },
+}
+
+/// Persistent journal of changes still to be undone
+pub struct UndoJournal {
+    conn: Connection,
+    /// Where owner lock files live
+    owners: PathBuf,
+}
+
+impl UndoJournal {
+    /// Open the journal at `db_path`, or in the state directory by default
+    ///
+    /// Uses an in-memory database in test mode, like the checkpoint store.
+    pub fn open(db_path: Option<&str>) -> Result<Self> {
+        let is_test = cfg!(test) || std::env::var("SAYONARA_TEST_MODE").is_ok();
+
+        let db_path = match db_path {
+            Some(path) => PathBuf::from(path),
+            None if is_test => PathBuf::from(":memory:"),
+            None => crate::config::state_dir().join(DB_FILE_NAME),
+        };
+        let owners = match db_path.parent() {
+            Some(parent) if db_path.to_str() != Some(":memory:") => parent.join(OWNERS_DIR),
+            _ => std::env::temp_dir().join("sayonara-undo-owners"),
+        };
+
+        if db_path.to_str() != Some(":memory:") {
+            if let Some(parent) = db_path.parent() {
+                std::fs::create_dir_all(parent)
+                    .context("Failed to create undo journal directory")?;
+            }
+            create_private(&db_path)?;
+        }
+
+        let conn = Connection::open(&db_path).context("Failed to open undo journal")?;
+
+        // Every entry must be on stable storage before the drive is touched
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .context("Failed to set WAL mode")?;
+        conn.pragma_update(None, "synchronous", "FULL")
+            .context("Failed to set synchronous mode")?;
+
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS undo_entries (
+                id TEXT PRIMARY KEY NOT NULL,
+                device_path TEXT NOT NULL,
+                identity TEXT NOT NULL,
+                action TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            "#,
+        )
+        .context("Failed to create undo journal schema")?;
+
+        // Journals written before owners were recorded lack the column
+        let has_owner: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('undo_entries') WHERE name = 'owner_pid'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .context("Failed to inspect undo journal schema")?
+            > 0;
+        if !has_owner {
+            conn.execute("ALTER TABLE undo_entries ADD COLUMN owner_pid INTEGER", [])
+                .context("Failed to upgrade undo journal schema")?;
+        }
+
+        Ok(Self { conn, owners })
+    }
+
+    /// Journal a change to `device_path` that is about to be made
+    ///
+    /// Returns the entry ID to pass to `complete` once the change is undone.
+    pub fn record(&self, device_path: &str, action: UndoAction) -> Result<String> {
+        self.record_for(device_path, DriveIdentity::probe(device_path), action)
+    }
+
+    pub(crate) fn record_for(
+        &self,
+        device_path: &str,
+        identity: DriveIdentity,
+        action: UndoAction,
+    ) -> Result<String> {
+        // Claim ownership before the entry can be seen by anyone else
+        let owner = std::process::id();
+        hold_owner_lock(&self.owners)?;
+
+        let id = Uuid::new_v4().to_string();
+        self.conn
+            .execute(
+                r#"
+            INSERT INTO undo_entries (id, device_path, identity, action, created_at, owner_pid)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+                params![
+                    id,
+                    device_path,
+                    serde_json::to_string(&identity)?,
+                    serde_json::to_string(&action)?,
+                    Utc::now().to_rfc3339(),
+                    owner,
+                ],
+            )
+            .context("Failed to write undo journal entry")?;
+
+        tracing::debug!(device = %device_path, entry = %id, action = ?action, "Undo entry recorded");
+        Ok(id)
+    }
+
+    /// Remove an entry once its change has been undone (or made moot)
+    pub fn complete(&self, id: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM undo_entries WHERE id = ?1", params![id])
+            .context("Failed to remove undo journal entry")?;
+        Ok(())
+    }
+
+    /// Outstanding entries, newest first (the order they must be undone in)
+    pub fn pending(&self) -> Result<Vec<UndoEntry>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r#"
+            SELECT id, device_path, identity, action, created_at, owner_pid
+            FROM undo_entries
+            ORDER BY created_at DESC, rowid DESC
+            "#,
+            )
+            .context("Failed to prepare undo journal query")?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<u32>>(5)?,
+                ))
+            })
+            .context("Failed to query undo journal")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to read undo journal")?;
+
+        rows.into_iter()
+            .map(
+                |(id, device_path, identity, action, created_at, owner_pid)| {
+                    Ok(UndoEntry {
+                        identity: serde_json::from_str(&identity).with_context(|| {
+                            format!("Undo entry {} has an unreadable identity", id)
+                        })?,
+                        action: serde_json::from_str(&action)
+                            .with_context(|| format!("Undo entry {} has an unknown action", id))?,
+                        created_at: DateTime::parse_from_rfc3339(&created_at)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                        id,
+                        device_path,
+                        owner_pid,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Whether the process that owns `entry` is still running
+    ///
+    /// Entries without an owner are treated as orphaned.
+    pub fn owner_alive(&self, entry: &UndoEntry) -> bool {
+        entry
+            .owner_pid
+            .is_some_and(|pid| owner_lock_held(&self.owners, pid))
+    }
+
+    /// Undo every outstanding change whose drive is still present
+    ///
+    /// Changes owned by a process that is still running are skipped unless
+    /// `include_live` is set.
+    pub fn replay(&self, include_live: bool) -> Result<Vec<ReplayOutcome>> {
+        self.replay_with(
+            DriveIdentity::probe,
+            |entry| entry.action.apply(&entry.device_path),
+            |entry| !include_live && self.owner_alive(entry),
+        )
+    }
+
+    pub(crate) fn replay_with<P, A, L>(
+        &self,
+        probe: P,
+        apply: A,
+        live: L,
+    ) -> Result<Vec<ReplayOutcome>>
+    where
+        P: Fn(&str) -> DriveIdentity,
+        A: Fn(&UndoEntry) -> Result<()>,
+        L: Fn(&UndoEntry) -> bool,
+    {
+        let mut outcomes = Vec::new();
+
+        for entry in self.pending()? {
+            // The owner may still be relying on the change
+            if live(&entry) {
+                outcomes.push(ReplayOutcome::Skipped {
+                    reason: format!(
+                        "in use by running process {}",
+                        entry.owner_pid.unwrap_or_default()
+                    ),
+                    entry,
+                });
+                continue;
+            }
+
+            // Never apply an undo to a different disk now at the same path.
+            // Lifting the HPA changes the reported size, so it cannot be
+            // used to recognise the drive for that action.
+            let mut recorded = entry.identity.clone();
+            if matches!(entry.action, UndoAction::RestoreHpa { .. }) {
+                recorded.size = None;
+            }
+            let problems = recorded.strict_mismatches(&probe(&entry.device_path));
+            if !problems.is_empty() {
+                outcomes.push(ReplayOutcome::Skipped {
+                    reason: format!("drive not present ({})", problems.join("; ")),
+                    entry,
+                });
+                continue;
+            }
+
+            match apply(&entry) {
+                Ok(()) => {
+                    self.complete(&entry.id)?;
+                    outcomes.push(ReplayOutcome::Restored(entry));
+                }
+                Err(e) => outcomes.push(ReplayOutcome::Failed {
+                    error: e.to_string(),
+                    entry,
+                }),
+            }
+        }
+
+        Ok(outcomes)
+    }
+}
+
+/// Lock files this process holds, by owners directory; never released
+/// before exit, so the lock covers every wipe the process runs
+fn held_locks() -> &'static Mutex<HashMap<PathBuf, File>> {
+    static HELD: OnceLock<Mutex<HashMap<PathBuf, File>>> = OnceLock::new();
+    HELD.get_or_init(Default::default)
+}
+
+fn owner_lock_path(owners: &Path, pid: u32) -> PathBuf {
+    owners.join(format!("{}.lock", pid))
+}
+
+/// Take this process's owner lock in `owners`, if not already held
+fn hold_owner_lock(owners: &Path) -> Result<()> {
+    let mut held = held_locks().lock().unwrap();
+    if held.contains_key(owners) {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(owners).context("Failed to create undo owner directory")?;
+    let path = owner_lock_path(owners, std::process::id());
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    if !try_lock(&file) {
+        return Err(anyhow!(
+            "Undo owner lock {} is held by another process",
+            path.display()
+        ));
+    }
+    held.insert(owners.to_path_buf(), file);
+    Ok(())
+}
+
+/// Whether process `pid` holds its owner lock in `owners`
+///
+/// A lock file left by a process that has exited is removed.
+fn owner_lock_held(owners: &Path, pid: u32) -> bool {
+    let path = owner_lock_path(owners, pid);
+    let Ok(file) = OpenOptions::new().write(true).open(&path) else {
+        return false;
+    };
+    if try_lock(&file) {
+        let _ = std::fs::remove_file(&path);
+        false
+    } else {
+        true
+    }
+}
+
+/// Create the journal at `path` readable by the owner only, or restrict an
+/// existing one to the owner
+fn create_private(path: &Path) -> Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .mode(0o600)
+        .open(path)
+        .context("Failed to create undo journal")?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .context("Failed to restrict undo journal permissions")
+}
+
+/// Non-blocking exclusive flock; locks belong to the open file, so a second
+/// open of a lock this process holds fails too
+fn try_lock(file: &File) -> bool {
+    // SAFETY: `file` is open for the whole call, so its descriptor is valid;
+    // flock only takes a lock on it and touches no memory
+    unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) == 0 }
+}
+
+/// A journalled change that has not been undone yet
+///
+/// Created right before the drive is modified; `complete` it once the flow
+/// has restored the drive, or `revert` to restore it now.
+#[derive(Debug)]
+pub struct PendingUndo {
+    id: String,
+    device_path: String,
+    action: UndoAction,
+}
+
+impl PendingUndo {
+    /// Journal `action` for `device_path` in the default journal
+    pub fn begin(device_path: &str, action: UndoAction) -> Result<Self> {
+        let id = UndoJournal::open(None)?.record(device_path, action.clone())?;
+        Ok(Self {
+            id,
+            device_path: device_path.to_string(),
+            action,
+        })
+    }
+
+    /// The change has been undone (or no longer needs undoing)
+    pub fn complete(self) -> Result<()> {
+        UndoJournal::open(None)?.complete(&self.id)
+    }
+
+    /// Undo the change now and drop the entry
+    ///
+    /// On failure the entry stays in the journal for a later replay.
+    pub fn revert(self) -> Result<()> {
+        self.action.apply(&self.device_path)?;
+        self.complete()
+    }
+}
+
+/// Current volatile write cache setting, if the drive reports it
+pub fn write_cache_enabled(device_path: &str) -> Option<bool> {
+    let output = Command::new("hdparm")
+        .args(["-W", device_path])
+        .output()
+        .ok()?;
+    parse_write_caching(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Turn volatile write caching on or off
+pub fn set_write_cache(device_path: &str, enabled: bool) -> Result<()> {
+    hdparm(&["-W", if enabled { "1" } else { "0" }, device_path])
+}
+
+/// Parse the `write-caching = 1 (on)` line printed by `hdparm -W`
+pub fn parse_write_caching(output: &str) -> Option<bool> {
+    let line = output
+        .lines()
+        .find(|line| line.trim_start().starts_with("write-caching"))?;
+    match line.split('=').nth(1)?.split_whitespace().next()? {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether an ATA security password is currently set, if the drive reports it
+pub fn ata_security_enabled(device_path: &str) -> Option<bool> {
+    let output = Command::new("hdparm")
+        .args(["-I", device_path])
+        .output()
+        .ok()?;
+    parse_security_enabled(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the `enabled` / `not enabled` line of the `Security:` section of
+/// `hdparm -I`
+pub fn parse_security_enabled(output: &str) -> Option<bool> {
+    output
+        .lines()
+        .skip_while(|line| line.trim() != "Security:")
+        .skip(1)
+        .take_while(|line| line.starts_with('\t') || line.starts_with(' '))
+        .find_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["enabled"] => Some(true),
+                ["not", "enabled"] => Some(false),
+                _ => None,
+            }
+        })
+}
+
+fn hdparm(args: &[&str]) -> Result<()> {
+    let output = Command::new("hdparm")
+        .args(args)
+        .output()
+        .context("Failed to run hdparm")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "hdparm {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
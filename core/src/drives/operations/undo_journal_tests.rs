@@ -0,0 +1,326 @@
+/// Tests for the undo journal
+///
+/// This test suite covers:
+/// - Recording, listing and completing entries
+/// - Replay order and outcomes
+/// - Refusing to replay onto a different drive
+/// - Output parsing from hdparm
+use super::undo_journal::*;
+use crate::drives::DriveIdentity;
+use anyhow::anyhow;
+use std::cell::RefCell;
+
+fn identity(serial: &str, size: u64) -> DriveIdentity {
+    DriveIdentity {
+        serial: Some(serial.to_string()),
+        size: Some(size),
+        ..Default::default()
+    }
+}
+
+fn journal() -> UndoJournal {
+    UndoJournal::open(None).unwrap()
+}
+
+// ============================================================================
+// Journal Tests
+// ============================================================================
+
+#[test]
+fn test_record_and_complete() {
+    let journal = journal();
+    assert!(journal.pending().unwrap().is_empty());
+
+    let id = journal
+        .record_for(
+            "/dev/sda",
+            identity("SN1", 1000),
+            UndoAction::RestoreHpa { max_sectors: 1900 },
+        )
+        .unwrap();
+
+    let pending = journal.pending().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, id);
+    assert_eq!(pending[0].device_path, "/dev/sda");
+    assert_eq!(pending[0].identity, identity("SN1", 1000));
+    assert_eq!(
+        pending[0].action,
+        UndoAction::RestoreHpa { max_sectors: 1900 }
+    );
+
+    journal.complete(&id).unwrap();
+    assert!(journal.pending().unwrap().is_empty());
+
+    // Completing twice is harmless
+    journal.complete(&id).unwrap();
+}
+
+#[test]
+fn test_journal_persists_across_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("undo.db");
+    let path = path.to_str().unwrap();
+
+    {
+        let journal = UndoJournal::open(Some(path)).unwrap();
+        journal
+            .record_for(
+                "/dev/sdb",
+                identity("SN2", 2000),
+                UndoAction::RestoreWriteCache { enabled: true },
+            )
+            .unwrap();
+    }
+
+    let reopened = UndoJournal::open(Some(path)).unwrap();
+    let pending = reopened.pending().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(
+        pending[0].action,
+        UndoAction::RestoreWriteCache { enabled: true }
+    );
+}
+
+#[test]
+fn test_journal_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("undo.db");
+    let mode = |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode();
+
+    // Entries can hold an ATA password
+    UndoJournal::open(Some(path.to_str().unwrap()))
+        .unwrap()
+        .record_for(
+            "/dev/sdb",
+            identity("SN2", 2000),
+            UndoAction::DisableAtaPassword {
+                password: "temporary".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(mode(&path) & 0o777, 0o600);
+    let wal = path.with_extension("db-wal");
+    if wal.exists() {
+        assert_eq!(mode(&wal) & 0o777, 0o600);
+    }
+
+    // A journal left readable by an older release is restricted on open
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+    UndoJournal::open(Some(path.to_str().unwrap())).unwrap();
+    assert_eq!(mode(&path) & 0o777, 0o600);
+}
+
+#[test]
+fn test_replay_undoes_newest_first() {
+    let journal = journal();
+    let drive = identity("SN1", 1000);
+    journal
+        .record_for(
+            "/dev/sda",
+            drive.clone(),
+            UndoAction::RestoreWriteCache { enabled: true },
+        )
+        .unwrap();
+    journal
+        .record_for(
+            "/dev/sda",
+            drive.clone(),
+            UndoAction::DisableAtaPassword {
+                password: "temp".to_string(),
+            },
+        )
+        .unwrap();
+
+    let applied = RefCell::new(Vec::new());
+    let outcomes = journal
+        .replay_with(
+            |_| drive.clone(),
+            |entry| {
+                applied.borrow_mut().push(entry.action.clone());
+                Ok(())
+            },
+            |_| false,
+        )
+        .unwrap();
+
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes
+        .iter()
+        .all(|o| matches!(o, ReplayOutcome::Restored(_))));
+    assert!(matches!(
+        applied.borrow()[0],
+        UndoAction::DisableAtaPassword { .. }
+    ));
+    assert_eq!(
+        applied.borrow()[1],
+        UndoAction::RestoreWriteCache { enabled: true }
+    );
+    assert!(journal.pending().unwrap().is_empty());
+}
+
+#[test]
+fn test_replay_skips_different_drive() {
+    let journal = journal();
+    journal
+        .record_for(
+            "/dev/sda",
+            identity("SN1", 1000),
+            UndoAction::RestoreWriteCache { enabled: true },
+        )
+        .unwrap();
+
+    let outcomes = journal
+        .replay_with(
+            |_| identity("OTHER", 1000),
+            |_| panic!("undo must not be applied to another drive"),
+            |_| false,
+        )
+        .unwrap();
+
+    assert!(matches!(outcomes[0], ReplayOutcome::Skipped { .. }));
+    assert_eq!(journal.pending().unwrap().len(), 1);
+}
+
+#[test]
+fn test_replay_hpa_ignores_size_change() {
+    let journal = journal();
+    journal
+        .record_for(
+            "/dev/sda",
+            identity("SN1", 1000),
+            UndoAction::RestoreHpa { max_sectors: 1 },
+        )
+        .unwrap();
+
+    // With the HPA still lifted the drive reports its native size
+    let outcomes = journal
+        .replay_with(|_| identity("SN1", 2000), |_| Ok(()), |_| false)
+        .unwrap();
+
+    assert!(matches!(outcomes[0], ReplayOutcome::Restored(_)));
+}
+
+#[test]
+fn test_replay_keeps_failed_entries() {
+    let journal = journal();
+    let drive = identity("SN1", 1000);
+    journal
+        .record_for(
+            "/dev/sda",
+            drive.clone(),
+            UndoAction::RestoreWriteCache { enabled: false },
+        )
+        .unwrap();
+
+    let outcomes = journal
+        .replay_with(
+            |_| drive.clone(),
+            |_| Err(anyhow!("device busy")),
+            |_| false,
+        )
+        .unwrap();
+
+    match &outcomes[0] {
+        ReplayOutcome::Failed { error, .. } => assert!(error.contains("device busy")),
+        other => panic!("expected failure, got {:?}", other),
+    }
+    assert_eq!(journal.pending().unwrap().len(), 1);
+}
+
+#[test]
+fn test_replay_leaves_entries_of_running_owners() {
+    let journal = journal();
+    let drive = identity("SN1", 1000);
+    journal
+        .record_for(
+            "/dev/sda",
+            drive.clone(),
+            UndoAction::RestoreHpa { max_sectors: 1900 },
+        )
+        .unwrap();
+
+    // This process recorded the entry and holds its owner lock
+    let pending = journal.pending().unwrap();
+    assert_eq!(pending[0].owner_pid, Some(std::process::id()));
+    assert!(journal.owner_alive(&pending[0]));
+
+    let outcomes = journal
+        .replay_with(
+            |_| drive.clone(),
+            |_| panic!("undo must not be applied while its owner runs"),
+            |entry| journal.owner_alive(entry),
+        )
+        .unwrap();
+    match &outcomes[0] {
+        ReplayOutcome::Skipped { reason, .. } => assert!(reason.contains("running process")),
+        other => panic!("expected skip, got {:?}", other),
+    }
+    assert_eq!(journal.pending().unwrap().len(), 1);
+}
+
+#[test]
+fn test_entries_of_exited_owners_are_orphaned() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("undo.db");
+    let journal = UndoJournal::open(Some(path.to_str().unwrap())).unwrap();
+    let entry = |owner_pid| UndoEntry {
+        id: "e".to_string(),
+        device_path: "/dev/sda".to_string(),
+        identity: identity("SN1", 1000),
+        action: UndoAction::RestoreWriteCache { enabled: true },
+        created_at: chrono::Utc::now(),
+        owner_pid,
+    };
+
+    // No lock file, a stale unlocked one, and no owner recorded at all
+    assert!(!journal.owner_alive(&entry(Some(u32::MAX))));
+    let owners = dir.path().join("undo_owners");
+    std::fs::create_dir_all(&owners).unwrap();
+    std::fs::write(owners.join("4242.lock"), b"").unwrap();
+    assert!(!journal.owner_alive(&entry(Some(4242))));
+    assert!(!owners.join("4242.lock").exists());
+    assert!(!journal.owner_alive(&entry(None)));
+}
+
+#[test]
+fn test_action_serialization() {
+    let action = UndoAction::DisableAtaPassword {
+        password: "pw".to_string(),
+    };
+    let json = serde_json::to_string(&action).unwrap();
+    assert!(json.contains("\"kind\":\"disable_ata_password\""));
+    assert_eq!(serde_json::from_str::<UndoAction>(&json).unwrap(), action);
+
+    assert!(UndoAction::RestoreHpa { max_sectors: 42 }
+        .description()
+        .contains("42"));
+}
+
+// ============================================================================
+// Parsing Tests
+// ============================================================================
+
+#[test]
+fn test_parse_write_caching() {
+    let on = "\n/dev/sda:\n write-caching =  1 (on)\n";
+    let off = "\n/dev/sda:\n write-caching =  0 (off)\n";
+    let unsupported = "\n/dev/sda:\n write-caching = not supported\n";
+
+    assert_eq!(parse_write_caching(on), Some(true));
+    assert_eq!(parse_write_caching(off), Some(false));
+    assert_eq!(parse_write_caching(unsupported), None);
+    assert_eq!(parse_write_caching(""), None);
+}
+
+#[test]
+fn test_parse_security_enabled() {
+    let set = "Security: \n\tMaster password revision code = 65534\n\t\tsupported\n\t\tenabled\n\t\tlocked\n\tnot\tfrozen\nLogical Unit WWN Device Identifier: 5000c500a1b2c3d4\n";
+    let clear = "Security: \n\t\tsupported\n\tnot\tenabled\n\tnot\tlocked\n\t\tfrozen\n";
+
+    assert_eq!(parse_security_enabled(set), Some(true));
+    assert_eq!(parse_security_enabled(clear), Some(false));
+    assert_eq!(parse_security_enabled("Commands/features:\n"), None);
+}
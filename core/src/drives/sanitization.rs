@@ -0,0 +1,611 @@
+// NIST SP 800-88 Rev. 1 Clear/Purge decisions
+//
+// `auto` picks its technique from the media tables of 800-88 Appendix A
+// rather than a fixed preference order: for the drive's media type, the
+// strongest Purge technique the drive reports support for, falling back to
+// a Clear overwrite where the media has no Purge technique available.
+//
+//   Media                    Purge, strongest first               Clear
+//   Magnetic (ATA/SCSI)      crypto erase, ATA secure erase       overwrite
+//   ATA flash SSD            crypto erase, ATA secure erase       overwrite
+//   NVMe flash SSD           crypto erase, sanitize, format NVM   overwrite
+//   USB removable flash      none                                 overwrite
+//   Others (SMR, Optane,     as their orchestrator path allows    overwrite
+//   hybrid, eMMC, RAID)
+//
+// With `--nist-level purge` a drive that cannot be purged is refused before
+// anything is written, and physical destruction is recommended instead; a
+// hardware Purge command that fails mid-wipe is refused the same way rather
+// than falling back to an overwrite. The decision, with the reasons for it,
+// is recorded in the certificate evidence.
+
+use crate::drives::NVMeAdvanced;
+use crate::{Algorithm, DriveInfo, DriveType, NistLevel, SEDType, SanitizeOption, WipeConfig};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// What to do with media that cannot be sanitized to the requested level
+pub const DESTROY_RECOMMENDATION: &str =
+    "Physically destroy the media instead (NIST SP 800-88 Destroy: shred, disintegrate, pulverize or incinerate)";
+
+/// The technique chosen for a drive, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizationDecision {
+    /// Level asked for with `--nist-level`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<NistLevel>,
+    /// Level the technique achieves on this media, if either
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<NistLevel>,
+    /// 800-88 Appendix A media type
+    pub media: String,
+    /// Appendix G method used, e.g. `Cryptographic Erase`
+    pub technique: String,
+    pub reasoning: Vec<String>,
+}
+
+impl SanitizationDecision {
+    /// The chosen hardware technique failed and the wipe is falling back to
+    /// a Clear overwrite
+    ///
+    /// Refuses when the target is Purge, since an overwrite cannot meet it.
+    pub fn fall_back_to_overwrite(&mut self, reason: &str) -> Result<()> {
+        self.reasoning.push(format!(
+            "{} failed ({}); an overwrite is a Clear technique",
+            self.technique, reason
+        ));
+        if self.target == Some(NistLevel::Purge) {
+            return Err(self.refusal(NistLevel::Purge));
+        }
+        self.level = Some(NistLevel::Clear);
+        self.technique = "Overwrite".to_string();
+        Ok(())
+    }
+
+    fn refusal(&self, target: NistLevel) -> anyhow::Error {
+        let reasons: Vec<_> = self
+            .reasoning
+            .iter()
+            .map(|reason| format!("  - {}", reason))
+            .collect();
+        anyhow!(
+            "NIST SP 800-88 {} cannot be achieved on this {}:\n{}\n{}",
+            target,
+            self.media,
+            reasons.join("\n"),
+            DESTROY_RECOMMENDATION
+        )
+    }
+}
+
+/// The algorithm to run and the decision behind it
+#[derive(Debug, Clone)]
+pub struct SanitizationPlan {
+    pub algorithm: Algorithm,
+    /// NVMe Sanitize action `Algorithm::Sanitize` runs, the one the decision
+    /// names; None when the drive reports none
+    pub sanitize_action: Option<SanitizeOption>,
+    pub decision: SanitizationDecision,
+}
+
+/// Decide how to sanitize `drive` under `config`
+///
+/// `auto` (`Algorithm::Auto`) gets the strongest technique from the
+/// media table; any other algorithm is classified as it stands. Returns an
+/// error explaining why when the result falls short of `config.nist_level`.
+pub fn plan_sanitization(drive: &DriveInfo, config: &WipeConfig) -> Result<SanitizationPlan> {
+    let media = media_type(&drive.drive_type);
+    let mut reasoning = Vec::new();
+    let (algorithm, level, technique) = if config.algorithm == Algorithm::Auto {
+        choose(drive, config, &mut reasoning)
+    } else {
+        classify(drive, config, &mut reasoning)
+    };
+
+    let decision = SanitizationDecision {
+        target: config.nist_level,
+        level,
+        media: media.to_string(),
+        technique,
+        reasoning,
+    };
+    if let Some(target) = config.nist_level {
+        if level.is_none_or(|level| level < target) {
+            return Err(decision.refusal(target));
+        }
+    }
+    let sanitize_action = match (&algorithm, &drive.drive_type) {
+        (Algorithm::Sanitize, DriveType::NVMe) => {
+            strongest_sanitize(&drive.capabilities.sanitize_options)
+        }
+        _ => None,
+    };
+    Ok(SanitizationPlan {
+        algorithm,
+        sanitize_action,
+        decision,
+    })
+}
+
+/// Refuse `--verify-passes` where the overwrite would not read passes back
+///
+/// Only the method registry's overwrite reads passes back; SMR, Optane,
+/// hybrid, eMMC and multi-namespace NVMe drives are overwritten by the
+/// orchestrator's own paths, so a certificate for them must not claim it.
+pub fn check_pass_verification(drive: &DriveInfo, config: &WipeConfig) -> Result<()> {
+    if !config.verify_passes {
+        return Ok(());
+    }
+    let orchestrated = match drive.drive_type {
+        DriveType::SMR | DriveType::Optane | DriveType::HybridSSHD | DriveType::EMMC => true,
+        DriveType::NVMe => {
+            NVMeAdvanced::detect_advanced_features(&drive.device_path).unwrap_or(false)
+        }
+        _ => false,
+    };
+    if orchestrated {
+        bail!(
+            "--verify-passes is not supported for {:?} drives: their wipe path does not read passes back",
+            drive.drive_type
+        );
+    }
+    Ok(())
+}
+
+fn media_type(drive_type: &DriveType) -> &'static str {
+    match drive_type {
+        DriveType::HDD => "ATA/SCSI hard disk drive",
+        DriveType::SMR => "shingled magnetic recording hard disk drive",
+        DriveType::SSD => "ATA solid state drive",
+        DriveType::NVMe => "NVMe solid state drive",
+        DriveType::USB => "USB removable flash media",
+        DriveType::Optane => "3D XPoint (Optane) drive",
+        DriveType::HybridSSHD => "hybrid hard disk drive with flash cache",
+        DriveType::EMMC | DriveType::UFS => "embedded flash device",
+        DriveType::RAID => "RAID array",
+        DriveType::Unknown => "drive of unknown media type",
+    }
+}
+
+type Choice = (Algorithm, Option<NistLevel>, String);
+
+/// Strongest technique for `auto`
+fn choose(drive: &DriveInfo, config: &WipeConfig, reasoning: &mut Vec<String>) -> Choice {
+    let caps = &drive.capabilities;
+    let overwrite = |reasoning: &mut Vec<String>, why: &str| -> Choice {
+        reasoning.push(why.to_string());
+        (
+            Algorithm::DoD5220,
+            Some(NistLevel::Clear),
+            "Overwrite".to_string(),
+        )
+    };
+
+    match drive.drive_type {
+        DriveType::HDD | DriveType::SSD | DriveType::NVMe => {
+            if let Some(choice) = crypto_erase(drive, config, reasoning) {
+                return choice;
+            }
+            if drive.drive_type == DriveType::NVMe {
+                if let Some(action) = strongest_sanitize(&caps.sanitize_options) {
+                    reasoning.push(format!(
+                        "The drive supports the NVMe Sanitize {} action, a Purge technique for flash media",
+                        sanitize_name(&action)
+                    ));
+                    return (
+                        Algorithm::Sanitize,
+                        Some(NistLevel::Purge),
+                        format!("Sanitize ({})", sanitize_name(&action)),
+                    );
+                }
+            }
+            if let Some(choice) = secure_erase(drive, config, reasoning) {
+                return choice;
+            }
+            reasoning.push(
+                "The drive reports no cryptographic erase, sanitize or secure erase support"
+                    .to_string(),
+            );
+            match drive.drive_type {
+                DriveType::HDD => overwrite(
+                    reasoning,
+                    "Overwriting all user-addressable sectors is Clear for magnetic media",
+                ),
+                _ => overwrite(
+                    reasoning,
+                    "Overwriting is Clear for flash media: remapped and over-provisioned blocks are out of its reach",
+                ),
+            }
+        }
+        DriveType::USB => overwrite(
+            reasoning,
+            "USB removable flash media support no sanitize commands, so an overwrite (Clear) is the strongest technique",
+        ),
+        DriveType::Optane if caps.crypto_erase || caps.secure_erase => {
+            instant_secure_erase(reasoning)
+        }
+        DriveType::SMR
+        | DriveType::Optane
+        | DriveType::HybridSSHD
+        | DriveType::EMMC
+        | DriveType::RAID
+        | DriveType::Unknown => overwrite(
+            reasoning,
+            &format!(
+                "No Purge technique is available for a {}; it is overwritten, which is Clear",
+                media_type(&drive.drive_type)
+            ),
+        ),
+        DriveType::UFS => unmap(reasoning),
+    }
+}
+
+/// Cryptographic erase, if the drive is a usable self-encrypting drive
+fn crypto_erase(
+    drive: &DriveInfo,
+    config: &WipeConfig,
+    reasoning: &mut Vec<String>,
+) -> Option<Choice> {
+    let caps = &drive.capabilities;
+    if !caps.crypto_erase {
+        return None;
+    }
+    let sed = match &caps.sed_type {
+        Some(SEDType::None) | None => "self-encrypting".to_string(),
+        Some(SEDType::Proprietary(name)) => name.clone(),
+        Some(sed) => format!("{:?}", sed),
+    };
+    if !config.sed_crypto_erase {
+        reasoning.push(format!(
+            "The drive ({}) supports cryptographic erase, but SED crypto erase is disabled",
+            sed
+        ));
+        return None;
+    }
+    reasoning.push(format!(
+        "The drive ({}) supports cryptographic erase, Purge for media whose user data is all encrypted",
+        sed
+    ));
+    Some((
+        Algorithm::CryptoErase,
+        Some(NistLevel::Purge),
+        "Cryptographic Erase".to_string(),
+    ))
+}
+
+/// ATA Secure Erase or Format NVM, if the drive supports it and is not
+/// frozen beyond mitigation
+fn secure_erase(
+    drive: &DriveInfo,
+    config: &WipeConfig,
+    reasoning: &mut Vec<String>,
+) -> Option<Choice> {
+    let caps = &drive.capabilities;
+    if !caps.secure_erase {
+        return None;
+    }
+    if caps.is_frozen && !config.freeze_mitigation {
+        reasoning.push(
+            "The drive supports secure erase but is frozen, and freeze mitigation is disabled"
+                .to_string(),
+        );
+        return None;
+    }
+    let technique = match (&drive.drive_type, caps.enhanced_erase) {
+        (DriveType::NVMe, _) => "Format NVM (Secure Erase)",
+        (_, true) => "ATA Enhanced Secure Erase",
+        (_, false) => "ATA Secure Erase",
+    };
+    reasoning.push(format!(
+        "The drive supports {}, a Purge technique for this media",
+        technique
+    ));
+    if drive.drive_type == DriveType::SSD {
+        reasoning.push(
+            "Rev. 1 accepts ATA Secure Erase as Purge for flash only where the drive implements it correctly; verify the result"
+                .to_string(),
+        );
+    }
+    Some((
+        Algorithm::SecureErase,
+        Some(NistLevel::Purge),
+        technique.to_string(),
+    ))
+}
+
+/// UNMAP, the only wipe a UFS device gets
+fn unmap(reasoning: &mut Vec<String>) -> Choice {
+    reasoning.push(
+        "UFS devices are wiped with UNMAP, which deallocates blocks but is neither Clear nor Purge"
+            .to_string(),
+    );
+    (Algorithm::SecureErase, None, "UNMAP".to_string())
+}
+
+/// Instant Secure Erase of an Optane drive
+fn instant_secure_erase(reasoning: &mut Vec<String>) -> Choice {
+    reasoning.push(
+        "The drive supports Instant Secure Erase, a cryptographic erase and so a Purge technique"
+            .to_string(),
+    );
+    (
+        Algorithm::SecureErase,
+        Some(NistLevel::Purge),
+        "Cryptographic Erase (Instant Secure Erase)".to_string(),
+    )
+}
+
+/// Strongest NVMe Sanitize action on offer
+fn strongest_sanitize(options: &[SanitizeOption]) -> Option<SanitizeOption> {
+    let has = |wanted: fn(&SanitizeOption) -> bool| options.iter().any(wanted);
+    if has(|o| {
+        matches!(
+            o,
+            SanitizeOption::CryptoErase | SanitizeOption::CryptoScramble
+        )
+    }) {
+        Some(SanitizeOption::CryptoErase)
+    } else if has(|o| matches!(o, SanitizeOption::BlockErase)) {
+        Some(SanitizeOption::BlockErase)
+    } else if has(|o| matches!(o, SanitizeOption::Overwrite)) {
+        Some(SanitizeOption::Overwrite)
+    } else {
+        None
+    }
+}
+
+fn sanitize_name(action: &SanitizeOption) -> &'static str {
+    match action {
+        SanitizeOption::CryptoErase | SanitizeOption::CryptoScramble => "Crypto Erase",
+        SanitizeOption::BlockErase => "Block Erase",
+        SanitizeOption::Overwrite => "Overwrite",
+    }
+}
+
+/// Level an explicitly requested algorithm achieves
+fn classify(drive: &DriveInfo, config: &WipeConfig, reasoning: &mut Vec<String>) -> Choice {
+    let algorithm = config.algorithm.clone();
+    let caps = &drive.capabilities;
+    let (level, technique) = match &algorithm {
+        Algorithm::CryptoErase if caps.crypto_erase => {
+            reasoning.push("Cryptographic erase of a self-encrypting drive is Purge".to_string());
+            (Some(NistLevel::Purge), "Cryptographic Erase".to_string())
+        }
+        Algorithm::Sanitize if drive.drive_type == DriveType::NVMe => {
+            match strongest_sanitize(&caps.sanitize_options) {
+                Some(action) => {
+                    reasoning.push(format!(
+                        "NVMe Sanitize is Purge; the strongest action the drive supports is {}",
+                        sanitize_name(&action)
+                    ));
+                    (
+                        Some(NistLevel::Purge),
+                        format!("Sanitize ({})", sanitize_name(&action)),
+                    )
+                }
+                None => {
+                    reasoning.push(
+                        "The drive reports no NVMe Sanitize action, so the wipe falls back to an overwrite, which is Clear"
+                            .to_string(),
+                    );
+                    (Some(NistLevel::Clear), "Overwrite".to_string())
+                }
+            }
+        }
+        Algorithm::CryptoErase | Algorithm::Sanitize => {
+            reasoning.push(format!(
+                "The drive does not support {:?}, so the wipe falls back to an overwrite, which is Clear",
+                algorithm
+            ));
+            (Some(NistLevel::Clear), "Overwrite".to_string())
+        }
+        Algorithm::SecureErase if drive.drive_type == DriveType::UFS => {
+            let (_, level, technique) = unmap(reasoning);
+            (level, technique)
+        }
+        Algorithm::SecureErase => {
+            let supported = match drive.drive_type {
+                DriveType::HDD | DriveType::SSD | DriveType::NVMe => {
+                    secure_erase(drive, config, reasoning)
+                }
+                DriveType::Optane if caps.crypto_erase || caps.secure_erase => {
+                    Some(instant_secure_erase(reasoning))
+                }
+                _ => None,
+            };
+            match supported {
+                Some((_, level, technique)) => (level, technique),
+                None => {
+                    reasoning.push(
+                        "Hardware secure erase is not available on this drive, so the wipe falls back to an overwrite, which is Clear"
+                            .to_string(),
+                    );
+                    (Some(NistLevel::Clear), "Overwrite".to_string())
+                }
+            }
+        }
+        Algorithm::TrimOnly => {
+            reasoning.push("TRIM deallocates blocks but is neither Clear nor Purge".to_string());
+            (None, "TRIM".to_string())
+        }
+        software => {
+            reasoning.push(format!(
+                "{} overwrites user-addressable storage, which is Clear; overwriting is not a Purge technique",
+                match software {
+                    Algorithm::Method(name) => name.clone(),
+                    builtin => format!("{:?}", builtin),
+                }
+            ));
+            (Some(NistLevel::Clear), "Overwrite".to_string())
+        }
+    };
+    (algorithm, level, technique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DriveCapabilities, EncryptionStatus};
+
+    fn drive(drive_type: DriveType, capabilities: DriveCapabilities) -> DriveInfo {
+        DriveInfo {
+            device_path: "/dev/sdz".to_string(),
+            model: "Test".to_string(),
+            serial: "T-1".to_string(),
+            size: 1 << 30,
+            drive_type,
+            encryption_status: EncryptionStatus::None,
+            capabilities,
+            health_status: None,
+            temperature_celsius: None,
+        }
+    }
+
+    fn auto(level: Option<NistLevel>) -> WipeConfig {
+        WipeConfig {
+            algorithm: Algorithm::Auto,
+            nist_level: level,
+            ..WipeConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_auto_prefers_strongest_purge() {
+        let sed = DriveCapabilities {
+            crypto_erase: true,
+            secure_erase: true,
+            sed_type: Some(SEDType::OPAL20),
+            sanitize_options: vec![SanitizeOption::BlockErase],
+            ..DriveCapabilities::default()
+        };
+        let plan = plan_sanitization(&drive(DriveType::NVMe, sed.clone()), &auto(None)).unwrap();
+        assert_eq!(plan.algorithm, Algorithm::CryptoErase);
+        assert_eq!(plan.decision.level, Some(NistLevel::Purge));
+
+        // Without crypto erase, NVMe sanitize beats format
+        let caps = DriveCapabilities {
+            crypto_erase: false,
+            ..sed
+        };
+        let plan = plan_sanitization(&drive(DriveType::NVMe, caps.clone()), &auto(None)).unwrap();
+        assert_eq!(plan.algorithm, Algorithm::Sanitize);
+        assert_eq!(plan.decision.technique, "Sanitize (Block Erase)");
+        assert_eq!(plan.sanitize_action, Some(SanitizeOption::BlockErase));
+
+        let caps = DriveCapabilities {
+            enhanced_erase: true,
+            ..caps
+        };
+        let plan =
+            plan_sanitization(&drive(DriveType::HDD, caps), &auto(Some(NistLevel::Purge))).unwrap();
+        assert_eq!(plan.algorithm, Algorithm::SecureErase);
+        assert_eq!(plan.decision.technique, "ATA Enhanced Secure Erase");
+    }
+
+    #[test]
+    fn test_explicit_secure_erase_is_not_rerouted() {
+        let config = WipeConfig {
+            algorithm: Algorithm::SecureErase,
+            ..WipeConfig::default()
+        };
+        // `auto` would pick crypto erase or sanitize on this drive
+        let caps = DriveCapabilities {
+            crypto_erase: true,
+            secure_erase: true,
+            sed_type: Some(SEDType::OPAL20),
+            sanitize_options: vec![SanitizeOption::BlockErase],
+            ..DriveCapabilities::default()
+        };
+        let plan = plan_sanitization(&drive(DriveType::NVMe, caps), &config).unwrap();
+        assert_eq!(plan.algorithm, Algorithm::SecureErase);
+        assert_eq!(plan.decision.technique, "Format NVM (Secure Erase)");
+        assert_eq!(plan.decision.level, Some(NistLevel::Purge));
+        assert_eq!(plan.sanitize_action, None);
+
+        // Without secure erase it stays secure erase, which falls back to an
+        // overwrite, rather than becoming a DoD overwrite from the table
+        let plan = plan_sanitization(
+            &drive(DriveType::HDD, DriveCapabilities::default()),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(plan.algorithm, Algorithm::SecureErase);
+        assert_eq!(plan.decision.technique, "Overwrite");
+        assert_eq!(plan.decision.level, Some(NistLevel::Clear));
+    }
+
+    #[test]
+    fn test_sanitize_records_the_action_it_runs() {
+        let config = WipeConfig {
+            algorithm: Algorithm::Sanitize,
+            ..WipeConfig::default()
+        };
+        let caps = DriveCapabilities {
+            sanitize_options: vec![SanitizeOption::BlockErase, SanitizeOption::CryptoScramble],
+            ..DriveCapabilities::default()
+        };
+        let plan = plan_sanitization(&drive(DriveType::NVMe, caps), &config).unwrap();
+        assert_eq!(plan.sanitize_action, Some(SanitizeOption::CryptoErase));
+        assert_eq!(plan.decision.technique, "Sanitize (Crypto Erase)");
+        assert_eq!(plan.decision.level, Some(NistLevel::Purge));
+
+        // No action to run means no Purge claim
+        let plan = plan_sanitization(
+            &drive(DriveType::NVMe, DriveCapabilities::default()),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(plan.sanitize_action, None);
+        assert_eq!(plan.decision.technique, "Overwrite");
+        assert_eq!(plan.decision.level, Some(NistLevel::Clear));
+    }
+
+    #[test]
+    fn test_purge_refused_where_only_clear_is_possible() {
+        let usb = drive(DriveType::USB, DriveCapabilities::default());
+        let plan = plan_sanitization(&usb, &auto(None)).unwrap();
+        assert_eq!(plan.algorithm, Algorithm::DoD5220);
+        assert_eq!(plan.decision.level, Some(NistLevel::Clear));
+        assert!(plan_sanitization(&usb, &auto(Some(NistLevel::Clear))).is_ok());
+
+        let refusal = plan_sanitization(&usb, &auto(Some(NistLevel::Purge)))
+            .unwrap_err()
+            .to_string();
+        assert!(refusal.contains("Purge cannot be achieved"));
+        assert!(refusal.contains("destroy"));
+
+        // An explicit overwrite is Clear whatever the drive supports
+        let config = WipeConfig {
+            algorithm: Algorithm::Gutmann,
+            nist_level: Some(NistLevel::Purge),
+            ..WipeConfig::default()
+        };
+        let hdd = drive(
+            DriveType::HDD,
+            DriveCapabilities {
+                secure_erase: true,
+                ..DriveCapabilities::default()
+            },
+        );
+        assert!(plan_sanitization(&hdd, &config).is_err());
+    }
+
+    #[test]
+    fn test_failed_purge_command_falls_back_only_without_purge_target() {
+        let caps = DriveCapabilities {
+            secure_erase: true,
+            ..DriveCapabilities::default()
+        };
+        let ssd = drive(DriveType::SSD, caps);
+
+        let mut decision = plan_sanitization(&ssd, &auto(None)).unwrap().decision;
+        decision.fall_back_to_overwrite("drive is frozen").unwrap();
+        assert_eq!(decision.level, Some(NistLevel::Clear));
+        assert_eq!(decision.technique, "Overwrite");
+
+        let mut decision = plan_sanitization(&ssd, &auto(Some(NistLevel::Purge)))
+            .unwrap()
+            .decision;
+        assert!(decision.fall_back_to_overwrite("drive is frozen").is_err());
+    }
+}
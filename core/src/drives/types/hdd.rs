@@ -1,3 +1,4 @@
+use crate::drives::operations::{PendingUndo, UndoAction};
 use crate::ui::progress::ProgressBar;
 use anyhow::Result;
 use std::process::Command;
@@ -28,6 +29,15 @@ impl HDDWipe {
     fn hardware_secure_erase(device_path: &str) -> Result<()> {
         println!("Running ATA secure erase...");
 
+        // journal the password before setting it so a power loss mid-erase
+        // cannot leave the drive locked with no record of the password
+        let undo = PendingUndo::begin(
+            device_path,
+            UndoAction::DisableAtaPassword {
+                password: "temp123".to_string(),
+            },
+        )?;
+
         // set password (blocking)
         let mut bar = ProgressBar::new(48);
         let mut set_cmd = Command::new("hdparm");
@@ -56,9 +66,14 @@ impl HDDWipe {
                 Some(status) => {
                     bar.render(100.0, None, None);
                     if status.success() {
+                        // a successful erase clears the password
+                        undo.complete()?;
                         println!("\nHardware secure erase completed successfully");
                         return Ok(());
                     } else {
+                        if let Err(e) = undo.revert() {
+                            eprintln!("Warning: failed to remove temporary password: {}", e);
+                        }
                         return Err(anyhow::anyhow!("Hardware secure erase failed"));
                     }
                 }
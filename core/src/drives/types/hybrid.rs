@@ -3,6 +3,7 @@
 // Hybrid drives combine HDD (magnetic) and SSD (flash) cache
 // Both portions must be wiped separately to ensure complete data destruction
 
+use crate::drives::operations::undo_journal::{self, PendingUndo, UndoAction, UndoJournal};
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::process::Command;
@@ -176,22 +177,43 @@ impl HybridDrive {
     }
 
     /// Temporarily disable cache with vendor-specific methods
+    ///
+    /// The original write cache setting is journalled first so it is restored
+    /// by `enable_cache`, or by recovery if the wipe never gets that far.
     pub fn disable_cache(&self) -> Result<()> {
         println!("Disabling SSD cache...");
 
+        let undo = match undo_journal::write_cache_enabled(&self.device_path) {
+            Some(enabled) => Some(PendingUndo::begin(
+                &self.device_path,
+                UndoAction::RestoreWriteCache { enabled },
+            )?),
+            None => None,
+        };
+
+        if self.try_disable_cache() {
+            return Ok(());
+        }
+
+        // Nothing was changed, so there is nothing to undo
+        if let Some(undo) = undo {
+            undo.complete()?;
+        }
+
+        println!("⚠️  Warning: Unable to disable cache completely");
+        Ok(()) // Non-fatal, continue anyway
+    }
+
+    fn try_disable_cache(&self) -> bool {
         // Try vendor-specific methods
         match self.manufacturer.as_str() {
-            "Seagate" => {
-                if self.try_seagate_cache_disable().is_ok() {
-                    println!("✅ Seagate cache disabled successfully");
-                    return Ok(());
-                }
+            "Seagate" if self.try_seagate_cache_disable().is_ok() => {
+                println!("✅ Seagate cache disabled successfully");
+                return true;
             }
-            "Western Digital" | "WDC" => {
-                if self.try_wd_cache_disable().is_ok() {
-                    println!("✅ WD cache disabled successfully");
-                    return Ok(());
-                }
+            "Western Digital" | "WDC" if self.try_wd_cache_disable().is_ok() => {
+                println!("✅ WD cache disabled successfully");
+                return true;
             }
             _ => {}
         }
@@ -199,11 +221,10 @@ impl HybridDrive {
         // Fall back to generic hdparm
         if self.try_generic_cache_disable().is_ok() {
             println!("✅ Cache disabled via hdparm");
-            return Ok(());
+            return true;
         }
 
-        println!("⚠️  Warning: Unable to disable cache completely");
-        Ok(()) // Non-fatal, continue anyway
+        false
     }
 
     /// Try Seagate-specific cache disable
@@ -255,12 +276,31 @@ impl HybridDrive {
     }
 
     /// Re-enable cache after wipe
+    ///
+    /// Restores the setting journalled by `disable_cache`, or turns write
+    /// caching on if none was recorded.
     pub fn enable_cache(&self) -> Result<()> {
-        let _ = Command::new("hdparm")
-            .arg("-W")
-            .arg("1") // Enable write caching
-            .arg(&self.device_path)
-            .output();
+        let journal = UndoJournal::open(None)?;
+        let recorded: Vec<_> = journal
+            .pending()?
+            .into_iter()
+            .filter(|entry| {
+                entry.device_path == self.device_path
+                    && matches!(entry.action, UndoAction::RestoreWriteCache { .. })
+            })
+            .collect();
+
+        if recorded.is_empty() {
+            let _ = undo_journal::set_write_cache(&self.device_path, true);
+            return Ok(());
+        }
+
+        // Newest first, so the oldest (original) setting is applied last
+        for entry in recorded {
+            if entry.action.apply(&self.device_path).is_ok() {
+                journal.complete(&entry.id)?;
+            }
+        }
 
         Ok(())
     }
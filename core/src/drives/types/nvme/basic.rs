@@ -1,4 +1,5 @@
 use crate::ui::progress::ProgressBar;
+use crate::SanitizeOption;
 use anyhow::{anyhow, Result};
 use std::process::Command;
 use std::thread;
@@ -13,13 +14,9 @@ impl NVMeWipe {
         let _device_info = Self::get_nvme_info(device_path)?;
 
         if Self::supports_format_nvm(device_path)? {
-            let mut cmd = Command::new("nvme");
-            cmd.args(["format", device_path, "--ses=1", "--force"]);
-            Self::run_command_with_bar(&mut cmd, "Format NVM secure erase")
+            Self::format_secure_erase(device_path)
         } else if Self::supports_sanitize(device_path)? {
-            let mut cmd = Command::new("nvme");
-            cmd.args(["sanitize", device_path, "--crypto-erase", "--force"]);
-            Self::run_command_with_bar(&mut cmd, "Sanitize crypto erase")
+            Self::sanitize(device_path, SanitizeOption::CryptoErase)
         } else {
             Err(anyhow!(
                 "No secure erase method available for this NVMe device"
@@ -27,6 +24,33 @@ impl NVMeWipe {
         }
     }
 
+    /// Format NVM with Secure Erase Settings 1 (user data erase)
+    pub fn format_secure_erase(device_path: &str) -> Result<()> {
+        let mut cmd = Command::new("nvme");
+        cmd.args(["format", device_path, "--ses=1", "--force"]);
+        Self::run_command_with_bar(&mut cmd, "Format NVM secure erase")
+    }
+
+    /// Sanitize with exactly `action`, never another one
+    pub fn sanitize(device_path: &str, action: SanitizeOption) -> Result<()> {
+        // Sanitize Action values from the NVMe base specification
+        let (sanact, label) = match action {
+            SanitizeOption::BlockErase => (2, "Sanitize block erase"),
+            SanitizeOption::Overwrite => (3, "Sanitize overwrite"),
+            SanitizeOption::CryptoErase | SanitizeOption::CryptoScramble => {
+                (4, "Sanitize crypto erase")
+            }
+        };
+        let mut cmd = Command::new("nvme");
+        cmd.args([
+            "sanitize",
+            device_path,
+            &format!("--sanact={}", sanact),
+            "--force",
+        ]);
+        Self::run_command_with_bar(&mut cmd, label)
+    }
+
     fn run_command_with_bar(cmd: &mut Command, label: &str) -> Result<()> {
         println!("Using {}...", label);
         let mut process = cmd.spawn()?;
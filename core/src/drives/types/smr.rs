@@ -3,6 +3,7 @@
 // SMR drives overlap magnetic tracks like roof shingles to increase capacity.
 // They require special handling during wipe operations due to sequential write requirements.
 
+use crate::error::RangeSet;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::process::Command;
@@ -527,6 +528,63 @@ impl SMRDrive {
         Ok(())
     }
 
+    /// Continue an interrupted SMR-aware wipe given the byte ranges of the
+    /// zone sequence that were already written
+    ///
+    /// `write_data_fn` receives `(offset, size, position)`, where `position`
+    /// is how far into the zone sequence `offset` lies. Zones that were fully
+    /// written are left alone. A conventional zone gets exactly its missing
+    /// ranges; a partially written sequential zone is reset and rewritten from
+    /// its start, because it only accepts writes at its write pointer.
+    pub fn resume_smr_wipe<F>(&self, completed: &RangeSet, mut write_data_fn: F) -> Result<()>
+    where
+        F: FnMut(u64, u64, u64) -> Result<()>, // (offset, size, position) -> Result
+    {
+        println!(
+            "Resuming SMR-aware wipe of {} ({} bytes already written)",
+            self.device_path,
+            completed.covered()
+        );
+
+        let mut position = 0u64;
+        for zone in &self.zones {
+            let zone_offset = zone.zone_start_lba * 512;
+            let zone_end = position + zone.zone_size;
+            let missing = completed.gaps(position..zone_end);
+
+            if !missing.is_empty() {
+                match zone.zone_type {
+                    ZoneType::Conventional => {
+                        for range in missing.iter() {
+                            write_data_fn(
+                                zone_offset + (range.start - position),
+                                range.end - range.start,
+                                range.start,
+                            )?;
+                        }
+                    }
+
+                    ZoneType::SequentialWriteRequired | ZoneType::SequentialWritePreferred => {
+                        self.reset_zone(zone.zone_number)?;
+                        write_data_fn(zone_offset, zone.zone_size, position)?;
+                    }
+                }
+            }
+
+            position = zone_end;
+        }
+
+        println!("SMR wipe completed successfully");
+        Ok(())
+    }
+
+    /// True if `offset` is the start of a sequential-write zone
+    pub fn is_sequential_zone_start(&self, offset: u64) -> bool {
+        self.zones.iter().any(|zone| {
+            zone.zone_start_lba * 512 == offset && zone.zone_type != ZoneType::Conventional
+        })
+    }
+
     /// Validate that SMR wipe was successful
     pub fn validate_smr_wipe(&self) -> Result<bool> {
         println!("Validating SMR wipe...");
@@ -583,4 +641,91 @@ mod tests {
 
         assert!(zone.is_writable());
     }
+
+    #[test]
+    fn test_resume_smr_wipe_skips_written_zones() {
+        let zone = |zone_number: u32| Zone {
+            zone_number,
+            zone_type: ZoneType::Conventional,
+            write_pointer: 0,
+            zone_start_lba: zone_number as u64 * 2048,
+            zone_size: 1024 * 1024,
+            zone_condition: ZoneCondition::Full,
+            zone_length: 0,
+        };
+        let drive = SMRDrive {
+            device_path: "/dev/null".to_string(),
+            zone_model: ZoneModel::HostAware,
+            zones: vec![zone(0), zone(1), zone(2)],
+            total_capacity: 3 * 1024 * 1024,
+            conventional_zone_count: 3,
+            sequential_zone_count: 0,
+            typical_zone_size: 1024 * 1024,
+        };
+
+        let mut writes = Vec::new();
+        drive
+            .resume_smr_wipe(
+                &RangeSet::from_range(0..1024 * 1024 + 4096),
+                |offset, size, position| {
+                    writes.push((offset, size, position));
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            writes,
+            vec![
+                (1024 * 1024 + 4096, 1024 * 1024 - 4096, 1024 * 1024 + 4096),
+                (2 * 1024 * 1024, 1024 * 1024, 2 * 1024 * 1024),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resume_smr_wipe_writes_only_missing_ranges() {
+        const MB: u64 = 1024 * 1024;
+        let zone = |zone_number: u32| Zone {
+            zone_number,
+            zone_type: ZoneType::Conventional,
+            write_pointer: 0,
+            zone_start_lba: zone_number as u64 * 4096,
+            zone_size: MB,
+            zone_condition: ZoneCondition::Full,
+            zone_length: 0,
+        };
+        let drive = SMRDrive {
+            device_path: "/dev/null".to_string(),
+            zone_model: ZoneModel::HostAware,
+            zones: vec![zone(0), zone(1), zone(2)],
+            total_capacity: 3 * MB,
+            conventional_zone_count: 3,
+            sequential_zone_count: 0,
+            typical_zone_size: MB,
+        };
+
+        // Zones were written out of order and one chunk was skipped
+        let completed: RangeSet = [0..MB + 64 * 1024, MB + 128 * 1024..2 * MB]
+            .into_iter()
+            .collect();
+
+        let mut writes = Vec::new();
+        drive
+            .resume_smr_wipe(&completed, |offset, size, position| {
+                writes.push((offset, size, position));
+                Ok(())
+            })
+            .unwrap();
+
+        // Zones are 2 MB apart on disk but contiguous in the zone sequence
+        assert_eq!(
+            writes,
+            vec![
+                (2 * MB + 64 * 1024, 64 * 1024, MB + 64 * 1024),
+                (4 * MB, MB, 2 * MB),
+            ]
+        );
+        assert!(!drive.is_sequential_zone_start(4 * MB));
+    }
 }
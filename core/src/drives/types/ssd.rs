@@ -1,3 +1,4 @@
+use crate::drives::operations::{PendingUndo, UndoAction};
 use crate::ui::progress::ProgressBar;
 use anyhow::{anyhow, Result};
 use std::process::Command;
@@ -15,7 +16,19 @@ impl SSDWipe {
         }
 
         Self::unfreeze_drive(device_path)?;
-        Self::set_security_password(device_path, "temp123")?;
+
+        // Journal the password first so a power loss mid-erase cannot leave
+        // the drive locked with no record of the password
+        let undo = PendingUndo::begin(
+            device_path,
+            UndoAction::DisableAtaPassword {
+                password: "temp123".to_string(),
+            },
+        )?;
+        if let Err(e) = Self::set_security_password(device_path, "temp123") {
+            undo.complete()?;
+            return Err(e);
+        }
 
         let mut cmd = Command::new("hdparm");
         cmd.args([
@@ -33,9 +46,14 @@ impl SSDWipe {
                 Some(status) => {
                     bar.render(100.0, None, None);
                     if status.success() {
+                        // A successful erase clears the password
+                        undo.complete()?;
                         println!("\nHardware secure erase completed successfully");
                         return Ok(());
                     } else {
+                        if let Err(e) = undo.revert() {
+                            eprintln!("Warning: failed to remove temporary password: {}", e);
+                        }
                         return Err(anyhow!("Secure erase failed"));
                     }
                 }
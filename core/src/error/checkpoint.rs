@@ -3,11 +3,21 @@
 /// This module provides atomic, persistent checkpoint storage using SQLite.
 /// Checkpoints are saved every 60 seconds OR every 1GB written, whichever comes first.
 /// All database operations use transactions for atomicity and must complete in <100ms.
+///
+/// Every stored row is sealed with an HMAC-SHA256 keyed from a per-host secret
+/// and bound to the fingerprint of the drive it was recorded for. Rows that fail
+/// either check are never resumed from; they are removed and logged as rejections.
+use super::range_set::RangeSet;
+use crate::drives::DriveIdentity;
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Duration, Utc};
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::types::ValueRef;
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use uuid::Uuid;
 
@@ -17,8 +27,35 @@ const DEFAULT_TIME_INTERVAL: std::time::Duration = std::time::Duration::from_sec
 /// Default checkpoint byte interval (1GB)
 const DEFAULT_BYTES_INTERVAL: u64 = 1024 * 1024 * 1024;
 
-/// Default database path
-const DEFAULT_DB_PATH: &str = "/var/lib/sayonara-wipe/checkpoints.db";
+/// Checkpoint database file name inside the configured state directory
+const DB_FILE_NAME: &str = "checkpoints.db";
+
+/// Host secret, kept out of the state directory
+///
+/// The path is fixed so that relocating `state_dir` (and with it the
+/// database) through configuration cannot also point the HMAC at a key the
+/// caller controls.
+pub const HOST_KEY_PATH: &str = "/etc/sayonara/checkpoint.key";
+
+/// Length of the host secret in bytes
+const KEY_LEN: usize = 32;
+
+/// Column list shared by every checkpoint query (order matches `Checkpoint::from_row`)
+///
+/// The first `SEALED_COLUMN_COUNT` columns are covered by the HMAC stored in `mac`.
+const CHECKPOINT_COLUMNS: &str = r#"
+    id, device_path, algorithm, operation_id,
+    current_pass, total_passes, bytes_written, total_size,
+    sectors_completed, state, config,
+    created_at, updated_at, error_count, last_error,
+    device_identity, mac
+"#;
+
+/// Number of leading `CHECKPOINT_COLUMNS` covered by the HMAC
+const SEALED_COLUMN_COUNT: usize = 16;
+
+/// Index of the `mac` column in `CHECKPOINT_COLUMNS`
+const MAC_COLUMN: usize = 16;
 
 /// Universal checkpoint structure supporting all algorithms
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -47,8 +84,9 @@ pub struct Checkpoint {
     /// Total device size in bytes
     pub total_size: u64,
 
-    /// List of completed sector offsets (for non-sequential algorithms)
-    pub sectors_completed: Vec<u64>,
+    /// Byte ranges of the current pass already written (positions within the
+    /// pass, so non-sequential work resumes with exactly the missing ranges)
+    pub sectors_completed: RangeSet,
 
     /// Algorithm-specific state (JSON)
     pub state: serde_json::Value,
@@ -67,6 +105,10 @@ pub struct Checkpoint {
 
     /// Last error message (if any)
     pub last_error: Option<String>,
+
+    /// Drive behind `device_path` when the checkpoint was first created
+    #[serde(default)]
+    pub device_identity: Option<DriveIdentity>,
 }
 
 impl Checkpoint {
@@ -88,13 +130,14 @@ impl Checkpoint {
             total_passes,
             bytes_written: 0,
             total_size,
-            sectors_completed: Vec::new(),
+            sectors_completed: RangeSet::new(),
             state: serde_json::Value::Null,
             config: serde_json::Value::Null,
             created_at: now,
             updated_at: now,
             error_count: 0,
             last_error: None,
+            device_identity: None,
         }
     }
 
@@ -120,6 +163,46 @@ impl Checkpoint {
         (self.bytes_written as f64 / self.total_size as f64) * 100.0
     }
 
+    /// Build a checkpoint from a row selected with `CHECKPOINT_COLUMNS`
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        let timestamp = |s: String| {
+            DateTime::parse_from_rfc3339(&s)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now)
+        };
+
+        Ok(Checkpoint {
+            id: row.get(0)?,
+            device_path: row.get(1)?,
+            algorithm: row.get(2)?,
+            operation_id: row.get(3)?,
+            current_pass: row.get::<_, i64>(4)? as usize,
+            total_passes: row.get::<_, i64>(5)? as usize,
+            bytes_written: row.get::<_, i64>(6)? as u64,
+            total_size: row.get::<_, i64>(7)? as u64,
+            sectors_completed: {
+                let json: String = row.get(8)?;
+                serde_json::from_str(&json).unwrap_or_default()
+            },
+            state: {
+                let json: String = row.get(9)?;
+                serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
+            },
+            config: {
+                let json: String = row.get(10)?;
+                serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
+            },
+            created_at: timestamp(row.get(11)?),
+            updated_at: timestamp(row.get(12)?),
+            error_count: row.get::<_, i64>(13)? as u32,
+            last_error: row.get(14)?,
+            device_identity: row
+                .get::<_, Option<String>>(15)?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+        })
+    }
+
     /// Get progress description
     pub fn progress_description(&self) -> String {
         format!(
@@ -133,6 +216,90 @@ impl Checkpoint {
     }
 }
 
+/// A checkpoint row together with what is needed to check its seal
+struct SealedRow {
+    checkpoint: Checkpoint,
+    /// Canonical encoding of the stored column values
+    sealed_bytes: Vec<u8>,
+    /// Hex HMAC tag stored with the row
+    mac: Option<String>,
+}
+
+impl SealedRow {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        let checkpoint = Checkpoint::from_row(row)?;
+
+        // MAC the values exactly as stored, so re-rendering timestamps or JSON
+        // on load can never change what is verified
+        let mut values = Vec::with_capacity(SEALED_COLUMN_COUNT + 1);
+        for idx in 0..SEALED_COLUMN_COUNT {
+            values.push(match row.get_ref(idx)? {
+                ValueRef::Null => serde_json::Value::Null,
+                ValueRef::Integer(i) => serde_json::Value::from(i),
+                ValueRef::Real(f) => serde_json::Value::from(f),
+                ValueRef::Text(t) => {
+                    serde_json::Value::from(String::from_utf8_lossy(t).into_owned())
+                }
+                ValueRef::Blob(b) => serde_json::Value::from(hex::encode(b)),
+            });
+        }
+        // Bind the row to the fingerprint of the drive it was recorded for
+        values.push(serde_json::Value::from(
+            checkpoint
+                .device_identity
+                .as_ref()
+                .map(DriveIdentity::fingerprint)
+                .unwrap_or_default(),
+        ));
+
+        Ok(Self {
+            checkpoint,
+            sealed_bytes: serde_json::to_vec(&values).unwrap_or_default(),
+            mac: row.get(MAC_COLUMN)?,
+        })
+    }
+
+    /// Check the stored tag against `key`, returning the reason on failure
+    fn verify(&self, key: &hmac::Key) -> std::result::Result<(), String> {
+        let tag = self
+            .mac
+            .as_deref()
+            .ok_or_else(|| "checkpoint has no integrity tag".to_string())?;
+        let tag = hex::decode(tag)
+            .ok()
+            .ok_or_else(|| "integrity tag is malformed".to_string())?;
+        hmac::verify(key, &self.sealed_bytes, &tag)
+            .map_err(|_| "integrity tag does not match (checkpoint was modified)".to_string())
+    }
+}
+
+/// A checkpoint that was refused on load
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CheckpointRejection {
+    /// ID of the refused checkpoint
+    pub checkpoint_id: String,
+
+    /// Algorithm the checkpoint was recorded for
+    pub algorithm: String,
+
+    /// Why the checkpoint was refused
+    pub reason: String,
+
+    /// When the checkpoint was refused
+    pub rejected_at: DateTime<Utc>,
+}
+
+/// A stored checkpoint as listed, with the reason it would be refused
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListedCheckpoint {
+    #[serde(flatten)]
+    pub checkpoint: Checkpoint,
+
+    /// Why the row fails its integrity check, if it does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejected: Option<String>,
+}
+
 /// Checkpoint database manager
 pub struct CheckpointManager {
     /// Database connection
@@ -152,6 +319,9 @@ pub struct CheckpointManager {
 
     /// Bytes written at last save
     last_bytes: u64,
+
+    /// HMAC key derived from the host secret
+    key: hmac::Key,
 }
 
 impl CheckpointManager {
@@ -159,15 +329,32 @@ impl CheckpointManager {
     ///
     /// Creates the database file and schema if it doesn't exist.
     /// Uses WAL mode for better concurrency and crash resilience.
+    /// Rows are sealed with the host secret at `HOST_KEY_PATH`; in test mode
+    /// an ephemeral secret is used instead.
     pub fn new(db_path: Option<&str>) -> Result<Self> {
         // Use in-memory database for tests to avoid permission issues
         // Check both cfg!(test) and environment variable for test detection
         let is_test = cfg!(test) || std::env::var("SAYONARA_TEST_MODE").is_ok();
 
+        let secret = if is_test {
+            random_key()?
+        } else {
+            load_host_key(Path::new(HOST_KEY_PATH))?
+        };
+
+        Self::open(db_path, is_test, secret)
+    }
+
+    /// Open the database at `db_path`, sealing rows with the secret at `key_path`
+    pub fn with_key_file(db_path: &str, key_path: &Path) -> Result<Self> {
+        Self::open(Some(db_path), false, load_host_key(key_path)?)
+    }
+
+    fn open(db_path: Option<&str>, is_test: bool, secret: [u8; KEY_LEN]) -> Result<Self> {
         let db_path = match db_path {
             Some(path) => PathBuf::from(path),
             None if is_test => PathBuf::from(":memory:"),
-            None => PathBuf::from(DEFAULT_DB_PATH),
+            None => crate::config::state_dir().join(DB_FILE_NAME),
         };
 
         // Ensure parent directory exists (skip for in-memory databases)
@@ -199,6 +386,7 @@ impl CheckpointManager {
             bytes_interval: DEFAULT_BYTES_INTERVAL,
             last_save: Instant::now(),
             last_bytes: 0,
+            key: hmac::Key::new(hmac::HMAC_SHA256, &secret),
         };
 
         manager.initialize_schema()?;
@@ -227,17 +415,45 @@ impl CheckpointManager {
                 updated_at TEXT NOT NULL,
                 error_count INTEGER NOT NULL DEFAULT 0,
                 last_error TEXT,
+                device_identity TEXT,
+                mac TEXT,
                 UNIQUE(device_path, algorithm, operation_id)
             );
 
+            CREATE TABLE IF NOT EXISTS checkpoint_rejections (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                checkpoint_id TEXT NOT NULL,
+                device_path TEXT NOT NULL,
+                algorithm TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                rejected_at TEXT NOT NULL
+            );
+
             CREATE INDEX IF NOT EXISTS idx_device ON checkpoints(device_path);
             CREATE INDEX IF NOT EXISTS idx_updated ON checkpoints(updated_at);
             CREATE INDEX IF NOT EXISTS idx_operation ON checkpoints(operation_id);
             CREATE INDEX IF NOT EXISTS idx_device_algo ON checkpoints(device_path, algorithm);
+            CREATE INDEX IF NOT EXISTS idx_rejection_device ON checkpoint_rejections(device_path);
             "#,
             )
             .context("Failed to create checkpoint schema")?;
 
+        // Databases created before identity tracking or sealing lack the columns
+        for column in ["device_identity", "mac"] {
+            let exists: bool = self
+                .conn
+                .prepare("SELECT 1 FROM pragma_table_info('checkpoints') WHERE name = ?1")?
+                .exists(params![column])?;
+            if !exists {
+                self.conn
+                    .execute(
+                        &format!("ALTER TABLE checkpoints ADD COLUMN {} TEXT", column),
+                        [],
+                    )
+                    .context("Failed to migrate checkpoint schema")?;
+            }
+        }
+
         Ok(())
     }
 
@@ -255,6 +471,12 @@ impl CheckpointManager {
             serde_json::to_string(&checkpoint.state).context("Failed to serialize state")?;
         let config_json =
             serde_json::to_string(&checkpoint.config).context("Failed to serialize config")?;
+        let identity_json = checkpoint
+            .device_identity
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("Failed to serialize device identity")?;
 
         // Use transaction for atomicity
         let tx = self
@@ -268,8 +490,9 @@ impl CheckpointManager {
                 id, device_path, algorithm, operation_id,
                 current_pass, total_passes, bytes_written, total_size,
                 sectors_completed, state, config,
-                created_at, updated_at, error_count, last_error
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                created_at, updated_at, error_count, last_error,
+                device_identity
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
             ON CONFLICT(device_path, algorithm, operation_id)
             DO UPDATE SET
                 id = excluded.id,
@@ -298,10 +521,33 @@ impl CheckpointManager {
                 checkpoint.updated_at.to_rfc3339(),
                 checkpoint.error_count as i64,
                 checkpoint.last_error,
+                identity_json,
             ],
         )
         .context("Failed to insert checkpoint")?;
 
+        // An update keeps the original creation time and identity, so seal what
+        // was actually stored rather than the caller's copy
+        let sealed = tx
+            .query_row(
+                &format!(
+                    r#"
+            SELECT {}
+            FROM checkpoints
+            WHERE device_path = ?1 AND algorithm = ?2 AND operation_id = ?3
+            "#,
+                    CHECKPOINT_COLUMNS
+                ),
+                params![
+                    checkpoint.device_path,
+                    checkpoint.algorithm,
+                    checkpoint.operation_id
+                ],
+                SealedRow::from_row,
+            )
+            .context("Failed to read back checkpoint")?;
+        seal(&tx, &self.key, &sealed)?;
+
         tx.commit()
             .context("Failed to commit checkpoint transaction")?;
 
@@ -321,127 +567,240 @@ impl CheckpointManager {
     }
 
     /// Load most recent checkpoint for device and algorithm
+    ///
+    /// Probes the drive at `device_path` for checkpoints that record an
+    /// identity. See `load_verified` for how invalid checkpoints are handled.
     pub fn load(&self, device_path: &str, algorithm: &str) -> Result<Option<Checkpoint>> {
-        let row = self
-            .conn
-            .query_row(
-                r#"
-            SELECT id, device_path, algorithm, operation_id,
-                   current_pass, total_passes, bytes_written, total_size,
-                   sectors_completed, state, config,
-                   created_at, updated_at, error_count, last_error
+        let mut current = None;
+        self.load_checked(device_path, algorithm, || {
+            current
+                .get_or_insert_with(|| DriveIdentity::probe(device_path))
+                .clone()
+        })
+    }
+
+    /// Load the most recent valid checkpoint for device and algorithm
+    ///
+    /// A checkpoint is valid when its HMAC verifies and every identifier it
+    /// recorded still matches `current`. Invalid checkpoints are deleted and
+    /// logged (see `rejections`); the newest valid one, if any, is returned.
+    /// When a recorded identifier cannot be read from `current`, nothing is
+    /// returned and the checkpoint is kept for a later attempt.
+    pub fn load_verified(
+        &self,
+        device_path: &str,
+        algorithm: &str,
+        current: &DriveIdentity,
+    ) -> Result<Option<Checkpoint>> {
+        self.load_checked(device_path, algorithm, || current.clone())
+    }
+
+    fn load_checked(
+        &self,
+        device_path: &str,
+        algorithm: &str,
+        mut current: impl FnMut() -> DriveIdentity,
+    ) -> Result<Option<Checkpoint>> {
+        let rows = {
+            let mut stmt = self
+                .conn
+                .prepare(&format!(
+                    r#"
+            SELECT {}
             FROM checkpoints
             WHERE device_path = ?1 AND algorithm = ?2
             ORDER BY updated_at DESC
-            LIMIT 1
             "#,
-                params![device_path, algorithm],
-                |row| {
-                    Ok(Checkpoint {
-                        id: row.get(0)?,
-                        device_path: row.get(1)?,
-                        algorithm: row.get(2)?,
-                        operation_id: row.get(3)?,
-                        current_pass: row.get::<_, i64>(4)? as usize,
-                        total_passes: row.get::<_, i64>(5)? as usize,
-                        bytes_written: row.get::<_, i64>(6)? as u64,
-                        total_size: row.get::<_, i64>(7)? as u64,
-                        sectors_completed: {
-                            let json: String = row.get(8)?;
-                            serde_json::from_str(&json).unwrap_or_default()
-                        },
-                        state: {
-                            let json: String = row.get(9)?;
-                            serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
-                        },
-                        config: {
-                            let json: String = row.get(10)?;
-                            serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
-                        },
-                        created_at: {
-                            let s: String = row.get(11)?;
-                            DateTime::parse_from_rfc3339(&s)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&Utc))
-                                .unwrap_or_else(Utc::now)
-                        },
-                        updated_at: {
-                            let s: String = row.get(12)?;
-                            DateTime::parse_from_rfc3339(&s)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&Utc))
-                                .unwrap_or_else(Utc::now)
-                        },
-                        error_count: row.get::<_, i64>(13)? as u32,
-                        last_error: row.get(14)?,
-                    })
-                },
-            )
-            .optional()
-            .context("Failed to load checkpoint")?;
+                    CHECKPOINT_COLUMNS
+                ))
+                .context("Failed to prepare load query")?;
+            let rows = stmt
+                .query_map(params![device_path, algorithm], SealedRow::from_row)
+                .context("Failed to load checkpoint")?
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to load checkpoint")?;
+            rows
+        };
 
-        Ok(row)
+        for row in rows {
+            if let Err(reason) = row.verify(&self.key) {
+                self.reject(&row.checkpoint, &reason)?;
+                continue;
+            }
+
+            let Some(recorded) = &row.checkpoint.device_identity else {
+                return Ok(Some(row.checkpoint));
+            };
+            let current = current();
+
+            let changed = recorded.mismatches(&current);
+            if !changed.is_empty() {
+                let reason = format!("drive identity changed: {}", changed.join("; "));
+                self.reject(&row.checkpoint, &reason)?;
+                continue;
+            }
+
+            // An identifier that cannot be read right now may be a transient
+            // probe failure: keep the checkpoint, but do not resume from it
+            let unreadable = recorded.strict_mismatches(&current);
+            if !unreadable.is_empty() {
+                tracing::warn!(
+                    device = %row.checkpoint.device_path,
+                    checkpoint = %row.checkpoint.id,
+                    reason = %unreadable.join("; "),
+                    "Drive identity cannot be verified; not resuming checkpoint"
+                );
+                return Ok(None);
+            }
+
+            return Ok(Some(row.checkpoint));
+        }
+
+        Ok(None)
     }
 
     /// Load checkpoint by ID
+    ///
+    /// Fails when the checkpoint's HMAC does not verify.
     pub fn load_by_id(&self, checkpoint_id: &str) -> Result<Option<Checkpoint>> {
         let row = self
             .conn
             .query_row(
-                r#"
-            SELECT id, device_path, algorithm, operation_id,
-                   current_pass, total_passes, bytes_written, total_size,
-                   sectors_completed, state, config,
-                   created_at, updated_at, error_count, last_error
-            FROM checkpoints
-            WHERE id = ?1
-            "#,
+                &format!(
+                    "SELECT {} FROM checkpoints WHERE id = ?1",
+                    CHECKPOINT_COLUMNS
+                ),
                 params![checkpoint_id],
-                |row| {
-                    Ok(Checkpoint {
-                        id: row.get(0)?,
-                        device_path: row.get(1)?,
-                        algorithm: row.get(2)?,
-                        operation_id: row.get(3)?,
-                        current_pass: row.get::<_, i64>(4)? as usize,
-                        total_passes: row.get::<_, i64>(5)? as usize,
-                        bytes_written: row.get::<_, i64>(6)? as u64,
-                        total_size: row.get::<_, i64>(7)? as u64,
-                        sectors_completed: {
-                            let json: String = row.get(8)?;
-                            serde_json::from_str(&json).unwrap_or_default()
-                        },
-                        state: {
-                            let json: String = row.get(9)?;
-                            serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
-                        },
-                        config: {
-                            let json: String = row.get(10)?;
-                            serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
-                        },
-                        created_at: {
-                            let s: String = row.get(11)?;
-                            DateTime::parse_from_rfc3339(&s)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&Utc))
-                                .unwrap_or_else(Utc::now)
-                        },
-                        updated_at: {
-                            let s: String = row.get(12)?;
-                            DateTime::parse_from_rfc3339(&s)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&Utc))
-                                .unwrap_or_else(Utc::now)
-                        },
-                        error_count: row.get::<_, i64>(13)? as u32,
-                        last_error: row.get(14)?,
-                    })
-                },
+                SealedRow::from_row,
             )
             .optional()
             .context("Failed to load checkpoint by ID")?;
 
-        Ok(row)
+        match row {
+            Some(row) => {
+                row.verify(&self.key)
+                    .map_err(|reason| anyhow!("Checkpoint {}: {}", checkpoint_id, reason))?;
+                Ok(Some(row.checkpoint))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Mark a checkpoint as the most recent for its device and algorithm
+    ///
+    /// `load` returns the newest checkpoint, so this makes an explicitly
+    /// chosen checkpoint the one the wipe algorithm resumes from. The
+    /// checkpoint must verify before it is re-sealed with the new timestamp.
+    pub fn touch(&mut self, checkpoint_id: &str) -> Result<()> {
+        if self.load_by_id(checkpoint_id)?.is_none() {
+            return Err(anyhow!("Checkpoint not found: {}", checkpoint_id));
+        }
+
+        let tx = self
+            .conn
+            .transaction()
+            .context("Failed to begin transaction")?;
+        tx.execute(
+            "UPDATE checkpoints SET updated_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), checkpoint_id],
+        )
+        .context("Failed to update checkpoint")?;
+        let sealed = tx
+            .query_row(
+                &format!(
+                    "SELECT {} FROM checkpoints WHERE id = ?1",
+                    CHECKPOINT_COLUMNS
+                ),
+                params![checkpoint_id],
+                SealedRow::from_row,
+            )
+            .context("Failed to read back checkpoint")?;
+        seal(&tx, &self.key, &sealed)?;
+        tx.commit()
+            .context("Failed to commit checkpoint transaction")?;
+
+        Ok(())
+    }
+
+    /// Delete an invalid checkpoint and log why it was refused
+    fn reject(&self, checkpoint: &Checkpoint, reason: &str) -> Result<()> {
+        tracing::warn!(
+            device = %checkpoint.device_path,
+            checkpoint = %checkpoint.id,
+            reason = reason,
+            "Rejecting checkpoint"
+        );
+
+        self.conn
+            .execute(
+                r#"
+            INSERT INTO checkpoint_rejections (
+                checkpoint_id, device_path, algorithm, reason, rejected_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+                params![
+                    checkpoint.id,
+                    checkpoint.device_path,
+                    checkpoint.algorithm,
+                    reason,
+                    Utc::now().to_rfc3339()
+                ],
+            )
+            .context("Failed to record checkpoint rejection")?;
+        self.conn
+            .execute(
+                "DELETE FROM checkpoints WHERE id = ?1",
+                params![checkpoint.id],
+            )
+            .context("Failed to delete rejected checkpoint")?;
+
+        Ok(())
+    }
+
+    /// Checkpoints refused for a device since its rejections were last cleared
+    pub fn rejections(&self, device_path: &str) -> Result<Vec<CheckpointRejection>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r#"
+            SELECT checkpoint_id, algorithm, reason, rejected_at
+            FROM checkpoint_rejections
+            WHERE device_path = ?1
+            ORDER BY id
+            "#,
+            )
+            .context("Failed to prepare rejection query")?;
+
+        let rejections = stmt
+            .query_map(params![device_path], |row| {
+                let rejected_at: String = row.get(3)?;
+                Ok(CheckpointRejection {
+                    checkpoint_id: row.get(0)?,
+                    algorithm: row.get(1)?,
+                    reason: row.get(2)?,
+                    rejected_at: DateTime::parse_from_rfc3339(&rejected_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })
+            .context("Failed to query checkpoint rejections")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect checkpoint rejections")?;
+
+        Ok(rejections)
+    }
+
+    /// Forget logged rejections for a device (once they have been reported)
+    pub fn clear_rejections(&mut self, device_path: &str) -> Result<usize> {
+        let deleted = self
+            .conn
+            .execute(
+                "DELETE FROM checkpoint_rejections WHERE device_path = ?1",
+                params![device_path],
+            )
+            .context("Failed to clear checkpoint rejections")?;
+
+        Ok(deleted)
     }
 
     /// Delete checkpoint (after successful completion)
@@ -461,77 +820,46 @@ impl CheckpointManager {
         Ok(())
     }
 
-    /// Delete checkpoint by device and algorithm
+    /// Delete checkpoint by device and algorithm (`"*"` matches every algorithm)
     pub fn delete_by_device(&mut self, device_path: &str, algorithm: &str) -> Result<usize> {
-        let deleted = self
-            .conn
-            .execute(
+        let deleted = if algorithm == "*" {
+            self.conn.execute(
+                "DELETE FROM checkpoints WHERE device_path = ?1",
+                params![device_path],
+            )
+        } else {
+            self.conn.execute(
                 "DELETE FROM checkpoints WHERE device_path = ?1 AND algorithm = ?2",
                 params![device_path, algorithm],
             )
-            .context("Failed to delete checkpoint by device")?;
+        }
+        .context("Failed to delete checkpoint by device")?;
 
         Ok(deleted)
     }
 
     /// List all checkpoints
-    pub fn list_all(&self) -> Result<Vec<Checkpoint>> {
+    ///
+    /// Every row is checked against its HMAC; rows that fail are listed with
+    /// the reason rather than removed, so listing never changes the database.
+    pub fn list_all(&self) -> Result<Vec<ListedCheckpoint>> {
         let mut stmt = self
             .conn
-            .prepare(
-                r#"
-            SELECT id, device_path, algorithm, operation_id,
-                   current_pass, total_passes, bytes_written, total_size,
-                   sectors_completed, state, config,
-                   created_at, updated_at, error_count, last_error
-            FROM checkpoints
-            ORDER BY updated_at DESC
-            "#,
-            )
+            .prepare(&format!(
+                "SELECT {} FROM checkpoints ORDER BY updated_at DESC",
+                CHECKPOINT_COLUMNS
+            ))
             .context("Failed to prepare list query")?;
 
         let checkpoints = stmt
-            .query_map([], |row| {
-                Ok(Checkpoint {
-                    id: row.get(0)?,
-                    device_path: row.get(1)?,
-                    algorithm: row.get(2)?,
-                    operation_id: row.get(3)?,
-                    current_pass: row.get::<_, i64>(4)? as usize,
-                    total_passes: row.get::<_, i64>(5)? as usize,
-                    bytes_written: row.get::<_, i64>(6)? as u64,
-                    total_size: row.get::<_, i64>(7)? as u64,
-                    sectors_completed: {
-                        let json: String = row.get(8)?;
-                        serde_json::from_str(&json).unwrap_or_default()
-                    },
-                    state: {
-                        let json: String = row.get(9)?;
-                        serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
-                    },
-                    config: {
-                        let json: String = row.get(10)?;
-                        serde_json::from_str(&json).unwrap_or(serde_json::Value::Null)
-                    },
-                    created_at: {
-                        let s: String = row.get(11)?;
-                        DateTime::parse_from_rfc3339(&s)
-                            .ok()
-                            .map(|dt| dt.with_timezone(&Utc))
-                            .unwrap_or_else(Utc::now)
-                    },
-                    updated_at: {
-                        let s: String = row.get(12)?;
-                        DateTime::parse_from_rfc3339(&s)
-                            .ok()
-                            .map(|dt| dt.with_timezone(&Utc))
-                            .unwrap_or_else(Utc::now)
-                    },
-                    error_count: row.get::<_, i64>(13)? as u32,
-                    last_error: row.get(14)?,
+            .query_map([], SealedRow::from_row)
+            .context("Failed to query checkpoints")?
+            .map(|row| {
+                row.map(|row| ListedCheckpoint {
+                    rejected: row.verify(&self.key).err(),
+                    checkpoint: row.checkpoint,
                 })
             })
-            .context("Failed to query checkpoints")?
             .collect::<Result<Vec<_>, _>>()
             .context("Failed to collect checkpoints")?;
 
@@ -592,6 +920,86 @@ impl CheckpointManager {
     }
 }
 
+/// Store the HMAC of a freshly written row
+fn seal(conn: &Connection, key: &hmac::Key, row: &SealedRow) -> Result<()> {
+    let tag = hmac::sign(key, &row.sealed_bytes);
+    conn.execute(
+        "UPDATE checkpoints SET mac = ?1 WHERE id = ?2",
+        params![hex::encode(tag.as_ref()), row.checkpoint.id],
+    )
+    .context("Failed to seal checkpoint")?;
+    Ok(())
+}
+
+/// Read the host secret, creating it (readable by the owner only) on first use
+///
+/// An existing secret must be owned by the current user and closed to group
+/// and others; anyone else able to write it could forge sealed rows.
+fn load_host_key(path: &Path) -> Result<[u8; KEY_LEN]> {
+    let read = |path: &Path| -> Result<[u8; KEY_LEN]> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let meta = std::fs::metadata(path)
+                .with_context(|| format!("Failed to stat checkpoint key {}", path.display()))?;
+            if meta.uid() != unsafe { libc::geteuid() } || meta.mode() & 0o077 != 0 {
+                return Err(anyhow!(
+                    "Checkpoint key {} must be owned by this user with mode 0600",
+                    path.display()
+                ));
+            }
+        }
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read checkpoint key {}", path.display()))?;
+        bytes.try_into().map_err(|_| {
+            anyhow!(
+                "Checkpoint key {} is corrupt (expected {} bytes)",
+                path.display(),
+                KEY_LEN
+            )
+        })
+    };
+
+    if path.exists() {
+        return read(path);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create checkpoint key directory")?;
+    }
+
+    let key = random_key()?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    match options.open(path) {
+        Ok(mut file) => {
+            file.write_all(&key)
+                .and_then(|()| file.sync_all())
+                .with_context(|| format!("Failed to write checkpoint key {}", path.display()))?;
+            Ok(key)
+        }
+        // Another process created it first
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => read(path),
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to create checkpoint key {}", path.display()))
+        }
+    }
+}
+
+fn random_key() -> Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| anyhow!("Failed to generate checkpoint key"))?;
+    Ok(key)
+}
+
 /// Database statistics
 #[derive(Debug, Clone)]
 pub struct CheckpointStats {
@@ -679,6 +1087,7 @@ mod tests {
 
         let all = manager.list_all().unwrap();
         assert_eq!(all.len(), 2);
+        assert!(all.iter().all(|listed| listed.rejected.is_none()));
     }
 
     #[test]
@@ -770,4 +1179,284 @@ mod tests {
             elapsed.as_millis()
         );
     }
+
+    #[test]
+    fn test_device_identity_roundtrip() {
+        let (mut manager, _temp) = create_test_manager();
+
+        let mut cp = Checkpoint::new("/dev/sda", "DoD", "op-id", 3, 4096);
+        cp.device_identity = Some(DriveIdentity {
+            serial: Some("WD-123".to_string()),
+            size: Some(4096),
+            ..Default::default()
+        });
+        manager.save(&cp).unwrap();
+
+        let loaded = manager.load_by_id(&cp.id).unwrap().unwrap();
+        assert_eq!(loaded.device_identity, cp.device_identity);
+    }
+
+    #[test]
+    fn test_completed_ranges_roundtrip() {
+        let (mut manager, _temp) = create_test_manager();
+
+        let mut cp = Checkpoint::new("/dev/sda", "Zero", "op-ranges", 1, 4 << 40);
+        cp.sectors_completed = [0..1 << 40, (2 << 40)..(3 << 40)].into_iter().collect();
+        manager.save(&cp).unwrap();
+
+        let loaded = manager.load_by_id(&cp.id).unwrap().unwrap();
+        assert_eq!(loaded.sectors_completed, cp.sectors_completed);
+
+        // Two terabyte-sized runs are stored as two gap/run pairs
+        let stored: String = manager
+            .conn
+            .query_row(
+                "SELECT sectors_completed FROM checkpoints WHERE id = ?1",
+                params![cp.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored, format!("[0,{0},{0},{0}]", 1u64 << 40));
+    }
+
+    #[test]
+    fn test_touch_makes_checkpoint_newest() {
+        let (mut manager, _temp) = create_test_manager();
+
+        let older = Checkpoint::new("/dev/sda", "DoD", "op-old", 3, 4096);
+        manager.save(&older).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let newer = Checkpoint::new("/dev/sda", "DoD", "op-new", 3, 4096);
+        manager.save(&newer).unwrap();
+
+        assert_eq!(
+            manager.load("/dev/sda", "DoD").unwrap().unwrap().id,
+            newer.id
+        );
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        manager.touch(&older.id).unwrap();
+        assert_eq!(
+            manager.load("/dev/sda", "DoD").unwrap().unwrap().id,
+            older.id
+        );
+
+        assert!(manager.touch("missing").is_err());
+    }
+
+    #[test]
+    fn test_delete_by_device_wildcard() {
+        let (mut manager, _temp) = create_test_manager();
+
+        manager
+            .save(&Checkpoint::new("/dev/sda", "DoD", "op-1", 3, 1024))
+            .unwrap();
+        manager
+            .save(&Checkpoint::new("/dev/sda", "Gutmann", "op-2", 35, 1024))
+            .unwrap();
+        manager
+            .save(&Checkpoint::new("/dev/sdb", "DoD", "op-3", 3, 1024))
+            .unwrap();
+
+        assert_eq!(manager.delete_by_device("/dev/sda", "*").unwrap(), 2);
+        assert_eq!(manager.list_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_schema_migration_adds_identity_column() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("old.db");
+
+        // Schema as created before device identity tracking
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE checkpoints (
+                id TEXT PRIMARY KEY NOT NULL,
+                device_path TEXT NOT NULL,
+                algorithm TEXT NOT NULL,
+                operation_id TEXT NOT NULL,
+                current_pass INTEGER NOT NULL,
+                total_passes INTEGER NOT NULL,
+                bytes_written INTEGER NOT NULL,
+                total_size INTEGER NOT NULL,
+                sectors_completed TEXT,
+                state TEXT,
+                config TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                error_count INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                UNIQUE(device_path, algorithm, operation_id)
+            );
+            INSERT INTO checkpoints VALUES (
+                'old-id', '/dev/sda', 'DoD', 'op', 1, 3, 512, 1024,
+                '[]', 'null', 'null', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z', 0, NULL
+            );
+            "#,
+        )
+        .unwrap();
+        drop(conn);
+
+        let manager = CheckpointManager::new(Some(db_path.to_str().unwrap())).unwrap();
+        let listed = manager.list_all().unwrap();
+        assert_eq!(listed[0].checkpoint.bytes_written, 512);
+        assert!(listed[0].checkpoint.device_identity.is_none());
+        assert_eq!(
+            listed[0].rejected.as_deref(),
+            Some("checkpoint has no integrity tag")
+        );
+
+        // Rows written before sealing carry no tag and are never trusted
+        let err = manager.load_by_id("old-id").unwrap_err();
+        assert!(err.to_string().contains("no integrity tag"));
+        assert!(manager.load("/dev/sda", "DoD").unwrap().is_none());
+        assert_eq!(manager.rejections("/dev/sda").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tampered_checkpoint_rejected() {
+        let (mut manager, _temp) = create_test_manager();
+
+        let mut cp = Checkpoint::new("/dev/sda", "DoD", "op-1", 3, 4096);
+        cp.update_progress(1, 2048);
+        manager.save(&cp).unwrap();
+
+        // Skip ahead by editing the database directly
+        manager
+            .conn
+            .execute(
+                "UPDATE checkpoints SET bytes_written = 4000 WHERE id = ?1",
+                params![cp.id],
+            )
+            .unwrap();
+
+        // Listing flags the row without removing it
+        let listed = manager.list_all().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0]
+            .rejected
+            .as_deref()
+            .unwrap()
+            .contains("does not match"));
+        assert!(manager.rejections("/dev/sda").unwrap().is_empty());
+
+        assert!(manager.load_by_id(&cp.id).is_err());
+        assert!(manager.load("/dev/sda", "DoD").unwrap().is_none());
+
+        let rejections = manager.rejections("/dev/sda").unwrap();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].checkpoint_id, cp.id);
+        assert!(rejections[0].reason.contains("does not match"));
+        assert!(manager.list_all().unwrap().is_empty());
+
+        assert_eq!(manager.clear_rejections("/dev/sda").unwrap(), 1);
+        assert!(manager.rejections("/dev/sda").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_verified_falls_back_past_other_drive() {
+        let (mut manager, _temp) = create_test_manager();
+        let drive = |serial: &str| DriveIdentity {
+            serial: Some(serial.to_string()),
+            size: Some(4096),
+            wwn: Some("0x5000c500a1b2c3d4".to_string()),
+            firmware: Some("CC43".to_string()),
+        };
+
+        let mut older = Checkpoint::new("/dev/sda", "DoD", "op-old", 3, 4096);
+        older.device_identity = Some(drive("CURRENT"));
+        manager.save(&older).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let mut newer = Checkpoint::new("/dev/sda", "DoD", "op-new", 3, 4096);
+        newer.device_identity = Some(drive("REMOVED"));
+        manager.save(&newer).unwrap();
+
+        let loaded = manager
+            .load_verified("/dev/sda", "DoD", &drive("CURRENT"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.id, older.id);
+
+        let rejections = manager.rejections("/dev/sda").unwrap();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].checkpoint_id, newer.id);
+        assert!(rejections[0].reason.contains("drive identity changed"));
+    }
+
+    #[test]
+    fn test_unreadable_identity_keeps_checkpoint() {
+        let (mut manager, _temp) = create_test_manager();
+        let recorded = DriveIdentity {
+            serial: Some("S1".to_string()),
+            size: Some(4096),
+            wwn: None,
+            firmware: None,
+        };
+
+        let mut cp = Checkpoint::new("/dev/sda", "DoD", "op-1", 3, 4096);
+        cp.device_identity = Some(recorded.clone());
+        manager.save(&cp).unwrap();
+
+        // The serial could not be read this time
+        let partial = DriveIdentity {
+            serial: None,
+            ..recorded.clone()
+        };
+        assert!(manager
+            .load_verified("/dev/sda", "DoD", &partial)
+            .unwrap()
+            .is_none());
+        assert!(manager.rejections("/dev/sda").unwrap().is_empty());
+        assert!(manager.load_by_id(&cp.id).unwrap().is_some());
+
+        // Once the drive answers again the checkpoint resumes
+        let loaded = manager
+            .load_verified("/dev/sda", "DoD", &recorded)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.id, cp.id);
+    }
+
+    #[test]
+    fn test_host_key_persists_across_managers() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("checkpoints.db");
+        let key_path = temp_dir.path().join("keys").join("checkpoint.key");
+        let open = || CheckpointManager::with_key_file(db_path.to_str().unwrap(), &key_path);
+
+        let cp = Checkpoint::new("/dev/sda", "DoD", "op-1", 3, 4096);
+        {
+            let mut manager = open().unwrap();
+            manager.save(&cp).unwrap();
+        }
+        assert_eq!(std::fs::read(&key_path).unwrap().len(), KEY_LEN);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let manager = open().unwrap();
+        assert!(manager.load_by_id(&cp.id).unwrap().is_some());
+
+        // A different host secret cannot vouch for the same rows
+        std::fs::write(&key_path, [7u8; KEY_LEN]).unwrap();
+        let manager = open().unwrap();
+        assert!(manager.load_by_id(&cp.id).is_err());
+
+        // Nor can a secret others are able to read or replace
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&key_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+            assert!(open().is_err());
+        }
+
+        // The database never picks up a key from its own directory
+        std::fs::write(temp_dir.path().join("checkpoint.key"), [7u8; KEY_LEN]).unwrap();
+        let manager = CheckpointManager::new(Some(db_path.to_str().unwrap())).unwrap();
+        assert!(manager.load_by_id(&cp.id).is_err());
+    }
 }
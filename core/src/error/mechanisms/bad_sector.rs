@@ -1,7 +1,10 @@
 /// Bad sector handling - skip bad sectors gracefully and log them
 ///
 /// This module handles bad sectors that cannot be written, skipping them
-/// while maintaining a record for verification and reporting.
+/// while maintaining a record for verification and reporting. Byte ranges a
+/// write had to skip are tracked as a `RangeSet`, so a resumed or targeted
+/// re-wipe can be given exactly what is still unwritten.
+use crate::error::range_set::RangeSet;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -18,6 +21,9 @@ pub struct BadSectorHandler {
     /// Set of bad sector offsets
     bad_sectors: Arc<Mutex<HashSet<u64>>>,
 
+    /// Byte ranges left unwritten because of bad sectors
+    skipped: Arc<Mutex<RangeSet>>,
+
     /// Maximum allowed bad sectors before abort
     max_bad_sectors: usize,
 
@@ -33,6 +39,7 @@ impl BadSectorHandler {
     pub fn new(device_path: impl Into<String>) -> Self {
         Self {
             bad_sectors: Arc::new(Mutex::new(HashSet::new())),
+            skipped: Arc::new(Mutex::new(RangeSet::new())),
             max_bad_sectors: DEFAULT_MAX_BAD_SECTORS,
             log_file: None,
             device_path: device_path.into(),
@@ -96,6 +103,28 @@ impl BadSectorHandler {
         Ok(())
     }
 
+    /// Record a bad sector that forced a write to skip `length` bytes
+    ///
+    /// The sector at `offset` counts towards the abort limit like any other;
+    /// the whole skipped range is remembered as unwritten.
+    pub fn record_bad_range(
+        &self,
+        offset: u64,
+        length: u64,
+        reason: impl Into<String>,
+    ) -> Result<()> {
+        self.skipped
+            .lock()
+            .unwrap()
+            .insert(offset..offset.saturating_add(length));
+        self.record_bad_sector(offset, reason)
+    }
+
+    /// Byte ranges left unwritten because of bad sectors
+    pub fn skipped_ranges(&self) -> RangeSet {
+        self.skipped.lock().unwrap().clone()
+    }
+
     /// Append entry to log file
     fn append_to_log(&self, log_path: &Path, sector: u64, reason: &str) -> Result<()> {
         // Ensure log directory exists
@@ -149,6 +178,7 @@ impl BadSectorHandler {
     pub fn clear(&self) {
         let mut sectors = self.bad_sectors.lock().unwrap();
         sectors.clear();
+        *self.skipped.lock().unwrap() = RangeSet::new();
     }
 
     /// Generate bad sector report
@@ -163,6 +193,7 @@ impl BadSectorHandler {
             max_bad_sectors: self.max_bad_sectors,
             percentage: (sector_list.len() as f64 / self.max_bad_sectors as f64) * 100.0,
             bad_sector_offsets: sector_list,
+            skipped_ranges: self.skipped_ranges(),
             log_file: self.log_file.clone(),
         }
     }
@@ -196,6 +227,10 @@ pub struct BadSectorReport {
     /// List of bad sector offsets
     pub bad_sector_offsets: Vec<u64>,
 
+    /// Byte ranges left unwritten because of bad sectors
+    #[serde(default)]
+    pub skipped_ranges: RangeSet,
+
     /// Log file location
     pub log_file: Option<PathBuf>,
 }
@@ -214,6 +249,14 @@ impl BadSectorReport {
             output.push_str(&format!("Log file: {}\n", log_file.display()));
         }
 
+        if !self.skipped_ranges.is_empty() {
+            output.push_str(&format!(
+                "Unwritten: {} bytes in {} range(s)\n",
+                self.skipped_ranges.covered(),
+                self.skipped_ranges.range_count()
+            ));
+        }
+
         if !self.bad_sector_offsets.is_empty() {
             output.push_str("\nBad sector offsets:\n");
             for (i, offset) in self.bad_sector_offsets.iter().enumerate() {
@@ -389,6 +432,30 @@ mod tests {
             .contains("bad_sectors__dev_nvme0n1.log"));
     }
 
+    #[test]
+    fn test_record_bad_range_tracks_unwritten_bytes() {
+        let handler = BadSectorHandler::new("/dev/sda");
+
+        handler.record_bad_range(4096, 4096, "EIO").unwrap();
+        handler.record_bad_range(8192, 4096, "EIO").unwrap();
+        handler.record_bad_range(65536, 512, "EIO").unwrap();
+
+        assert_eq!(handler.bad_sector_count(), 3);
+        assert_eq!(
+            handler.skipped_ranges().iter().collect::<Vec<_>>(),
+            vec![4096..12288, 65536..66048]
+        );
+
+        let report = handler.generate_report();
+        assert_eq!(report.skipped_ranges.range_count(), 2);
+        assert!(report
+            .format()
+            .contains("Unwritten: 8704 bytes in 2 range(s)"));
+
+        handler.clear();
+        assert!(handler.skipped_ranges().is_empty());
+    }
+
     #[test]
     fn test_duplicate_bad_sectors() {
         let handler = BadSectorHandler::new("/dev/sda");
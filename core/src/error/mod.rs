@@ -39,7 +39,7 @@
 /// # Usage Example
 ///
 /// ```rust,ignore
-/// use sayonara_wipe::error::{RecoveryCoordinator, Progress, ErrorContext};
+/// use sayonara_wipe::error::{RecoveryCoordinator, Progress, ErrorContext, RangeSet};
 /// use sayonara_wipe::WipeConfig;
 ///
 /// let config = WipeConfig::default();
@@ -73,6 +73,7 @@
 ///             current_pass: pass,
 ///             bytes_written: (pass as u64 + 1) * total_size,
 ///             state: serde_json::json!({"pass": pass}),
+///             completed: RangeSet::new(),
 ///         }
 ///     )?;
 /// }
@@ -84,15 +85,21 @@
 pub mod checkpoint;
 pub mod classification;
 pub mod mechanisms;
+pub mod range_set;
 pub mod recovery_coordinator;
 pub mod retry;
 
 // Re-export main types for convenience
-pub use checkpoint::{Checkpoint, CheckpointManager, CheckpointStats};
+pub use checkpoint::{
+    Checkpoint, CheckpointManager, CheckpointRejection, CheckpointStats, ListedCheckpoint,
+};
 pub use classification::{ClassifiedError, ErrorClass, ErrorClassifier, ErrorContext};
 pub use mechanisms::{
     AlternativeIO, BadSectorHandler, BadSectorReport, DegradedMode, DegradedModeManager,
     HealMethod, IOMethod, SelfHealer, WriteResult,
 };
-pub use recovery_coordinator::{Progress, RecoveryAction, RecoveryCoordinator, ResumeState};
+pub use range_set::RangeSet;
+pub use recovery_coordinator::{
+    PassTracker, Progress, RecoveryAction, RecoveryCoordinator, ResumeState, RESUME_ALIGNMENT,
+};
 pub use retry::{CircuitBreaker, ExponentialBackoff, RetryConfig, RetryStrategy};
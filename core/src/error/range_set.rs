@@ -0,0 +1,300 @@
+/// Compact byte-range tracking for non-sequential work
+///
+/// A `RangeSet` holds coalesced, half-open `[start, end)` ranges, so a pass that
+/// has written most of a multi-terabyte drive in a handful of runs costs a
+/// handful of entries. On disk it is run-length encoded as alternating gap and
+/// run lengths: `[gap0, run0, gap1, run1, ...]`.
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Set of byte ranges, kept sorted, disjoint and non-adjacent
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<u64>", into = "Vec<u64>")]
+pub struct RangeSet {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl RangeSet {
+    /// Empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set holding a single range
+    pub fn from_range(range: Range<u64>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+
+    /// True if no bytes are covered
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of disjoint ranges
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Total number of bytes covered
+    pub fn covered(&self) -> u64 {
+        self.ranges.iter().map(|(start, end)| end - start).sum()
+    }
+
+    /// Ranges in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.ranges.iter().map(|&(start, end)| start..end)
+    }
+
+    /// True if `offset` lies inside one of the ranges
+    pub fn contains(&self, offset: u64) -> bool {
+        let idx = self.ranges.partition_point(|&(_, end)| end <= offset);
+        self.ranges
+            .get(idx)
+            .is_some_and(|&(start, _)| start <= offset)
+    }
+
+    /// Add a range, merging it with any range it overlaps or touches
+    pub fn insert(&mut self, range: Range<u64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|&(_, end)| end < range.start);
+        let last = self
+            .ranges
+            .partition_point(|&(start, _)| start <= range.end);
+
+        let mut merged = (range.start, range.end);
+        if first < last {
+            merged.0 = merged.0.min(self.ranges[first].0);
+            merged.1 = merged.1.max(self.ranges[last - 1].1);
+        }
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// Remove a range, splitting any range it cuts through
+    pub fn remove(&mut self, range: Range<u64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|&(_, end)| end <= range.start);
+        let last = self.ranges.partition_point(|&(start, _)| start < range.end);
+        if first >= last {
+            return;
+        }
+
+        let mut kept = Vec::with_capacity(2);
+        let (head_start, _) = self.ranges[first];
+        let (_, tail_end) = self.ranges[last - 1];
+        if head_start < range.start {
+            kept.push((head_start, range.start));
+        }
+        if tail_end > range.end {
+            kept.push((range.end, tail_end));
+        }
+        self.ranges.splice(first..last, kept);
+    }
+
+    /// Bytes covered by either set
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.insert(range);
+        }
+        result
+    }
+
+    /// Bytes covered by this set but not by `other`
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.remove(range);
+        }
+        result
+    }
+
+    /// Bytes covered by both sets
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        self.difference(&self.difference(other))
+    }
+
+    /// Bytes of `within` that this set does not cover
+    pub fn gaps(&self, within: Range<u64>) -> RangeSet {
+        RangeSet::from_range(within).difference(self)
+    }
+
+    /// First range at or after `from` and below `limit` not covered by the set
+    pub fn next_unwritten(&self, from: u64, limit: u64) -> Option<Range<u64>> {
+        let mut idx = self.ranges.partition_point(|&(_, end)| end <= from);
+        let mut start = from;
+        if let Some(&(covered_start, covered_end)) = self.ranges.get(idx) {
+            if covered_start <= start {
+                start = covered_end;
+                idx += 1;
+            }
+        }
+
+        if start >= limit {
+            return None;
+        }
+
+        let end = self
+            .ranges
+            .get(idx)
+            .map_or(limit, |&(next_start, _)| next_start.min(limit));
+        Some(start..end)
+    }
+}
+
+impl From<RangeSet> for Vec<u64> {
+    fn from(set: RangeSet) -> Self {
+        let mut encoded = Vec::with_capacity(set.ranges.len() * 2);
+        let mut cursor = 0;
+        for (start, end) in set.ranges {
+            encoded.push(start - cursor);
+            encoded.push(end - start);
+            cursor = end;
+        }
+        encoded
+    }
+}
+
+impl TryFrom<Vec<u64>> for RangeSet {
+    type Error = String;
+
+    fn try_from(encoded: Vec<u64>) -> Result<Self, Self::Error> {
+        if !encoded.len().is_multiple_of(2) {
+            return Err("range encoding must hold gap/run pairs".to_string());
+        }
+
+        let mut set = RangeSet::new();
+        let mut cursor = 0u64;
+        for pair in encoded.chunks_exact(2) {
+            let start = cursor
+                .checked_add(pair[0])
+                .ok_or_else(|| "range encoding overflows".to_string())?;
+            let end = start
+                .checked_add(pair[1])
+                .ok_or_else(|| "range encoding overflows".to_string())?;
+            set.insert(start..end);
+            cursor = end;
+        }
+        Ok(set)
+    }
+}
+
+impl FromIterator<Range<u64>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = Range<u64>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<u64>]) -> RangeSet {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_insert_coalesces_overlapping_and_adjacent() {
+        let mut ranges = RangeSet::new();
+        ranges.insert(0..10);
+        ranges.insert(20..30);
+        assert_eq!(ranges.range_count(), 2);
+
+        // Touches the first range, overlaps the second
+        ranges.insert(10..25);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), vec![0..30]);
+
+        ranges.insert(40..50);
+        ranges.insert(5..8);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), vec![0..30, 40..50]);
+        assert_eq!(ranges.covered(), 40);
+    }
+
+    #[test]
+    fn test_remove_splits_ranges() {
+        let mut ranges = set(&[0..100, 200..300]);
+        ranges.remove(50..250);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), vec![0..50, 250..300]);
+
+        ranges.remove(0..50);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), vec![250..300]);
+
+        ranges.remove(260..270);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), vec![250..260, 270..300]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let ranges = set(&[10..20, 30..40]);
+        assert!(!ranges.contains(9));
+        assert!(ranges.contains(10));
+        assert!(ranges.contains(19));
+        assert!(!ranges.contains(20));
+        assert!(ranges.contains(35));
+        assert!(!ranges.contains(40));
+    }
+
+    #[test]
+    fn test_union_difference_intersection() {
+        let a = set(&[0..100, 200..300]);
+        let b = RangeSet::from_range(50..250);
+
+        assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![0..300]);
+        assert_eq!(
+            a.difference(&b).iter().collect::<Vec<_>>(),
+            vec![0..50, 250..300]
+        );
+        assert_eq!(
+            a.intersection(&b).iter().collect::<Vec<_>>(),
+            vec![50..100, 200..250]
+        );
+    }
+
+    #[test]
+    fn test_next_unwritten() {
+        let written = set(&[0..4096, 8192..12288]);
+
+        assert_eq!(written.next_unwritten(0, 16384), Some(4096..8192));
+        assert_eq!(written.next_unwritten(5000, 16384), Some(5000..8192));
+        assert_eq!(written.next_unwritten(8192, 16384), Some(12288..16384));
+        assert_eq!(written.next_unwritten(8192, 12288), None);
+        assert_eq!(RangeSet::new().next_unwritten(0, 10), Some(0..10));
+    }
+
+    #[test]
+    fn test_gaps() {
+        let written = set(&[100..200, 300..400]);
+        assert_eq!(
+            written.gaps(0..500).iter().collect::<Vec<_>>(),
+            vec![0..100, 200..300, 400..500]
+        );
+        assert!(written.gaps(120..180).is_empty());
+    }
+
+    #[test]
+    fn test_run_length_encoding_roundtrip() {
+        let ranges = set(&[4096..8192, 16384..1 << 40]);
+        let json = serde_json::to_string(&ranges).unwrap();
+        assert_eq!(json, format!("[4096,4096,8192,{}]", (1u64 << 40) - 16384));
+
+        let decoded: RangeSet = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, ranges);
+
+        assert!(serde_json::from_str::<RangeSet>("[1,2,3]").is_err());
+        assert_eq!(
+            serde_json::from_str::<RangeSet>("[]").unwrap(),
+            RangeSet::new()
+        );
+    }
+}
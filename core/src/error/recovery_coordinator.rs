@@ -11,13 +11,20 @@ use super::classification::{ClassifiedError, ErrorClass, ErrorClassifier, ErrorC
 use super::mechanisms::{
     AlternativeIO, BadSectorHandler, DegradedMode, DegradedModeManager, HealMethod, SelfHealer,
 };
+use super::range_set::RangeSet;
 use super::retry::{CircuitBreaker, RetryConfig};
+use crate::crypto::evidence::{self, PassRecord, PassVerification};
+use crate::drives::DriveIdentity;
 use crate::{DriveError, DriveResult, WipeConfig};
 use anyhow::Result;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+/// Resume offsets inside a pass are rounded down to this boundary so resumed
+/// writes stay aligned for Direct I/O
+pub const RESUME_ALIGNMENT: u64 = 4096;
+
 /// Progress information for checkpointing
 #[derive(Debug, Clone)]
 pub struct Progress {
@@ -29,6 +36,9 @@ pub struct Progress {
 
     /// Algorithm-specific state (JSON-serializable)
     pub state: serde_json::Value,
+
+    /// Byte ranges of the current pass already written
+    pub completed: RangeSet,
 }
 
 /// Recovery action to take
@@ -69,6 +79,159 @@ pub struct ResumeState {
     pub state: serde_json::Value,
 }
 
+impl ResumeState {
+    /// Byte ranges of the interrupted pass that already hold its data
+    ///
+    /// Checkpoints that predate range tracking only record how far the pass
+    /// got, which is treated as everything below `pass_offset`.
+    pub fn completed(&self, pass_size: u64) -> RangeSet {
+        if self.checkpoint.sectors_completed.is_empty() {
+            let completed = self.current_pass as u64 * pass_size;
+            let offset = self.bytes_written.saturating_sub(completed).min(pass_size);
+            return RangeSet::from_range(0..offset - offset % RESUME_ALIGNMENT);
+        }
+
+        self.checkpoint
+            .sectors_completed
+            .intersection(&RangeSet::from_range(0..pass_size))
+    }
+
+    /// Byte ranges of the interrupted pass still to be written
+    ///
+    /// Each range is widened to `RESUME_ALIGNMENT` so resumed writes stay
+    /// aligned for Direct I/O.
+    pub fn missing(&self, pass_size: u64) -> RangeSet {
+        self.completed(pass_size)
+            .gaps(0..pass_size)
+            .iter()
+            .map(|range| {
+                let start = range.start - range.start % RESUME_ALIGNMENT;
+                let end = range.end.div_ceil(RESUME_ALIGNMENT) * RESUME_ALIGNMENT;
+                start..end.min(pass_size)
+            })
+            .collect()
+    }
+
+    /// Offset inside the interrupted pass to continue writing from
+    ///
+    /// This is the start of the first missing range, so sequential passes
+    /// rewrite anything after it. Random passes simply continue with fresh
+    /// CSPRNG output from there; no stream position has to be restored.
+    pub fn pass_offset(&self, pass_size: u64) -> u64 {
+        self.missing(pass_size)
+            .iter()
+            .next()
+            .map_or(pass_size, |range| range.start)
+    }
+}
+
+/// Checkpoints positions inside one pass
+///
+/// Created with [`RecoveryCoordinator::pass_tracker`] and fed the ranges
+/// written so far; the checkpoint records their union.
+pub struct PassTracker<'a> {
+    coordinator: &'a RecoveryCoordinator,
+    algorithm: &'a str,
+    total_passes: usize,
+    pass: usize,
+    pass_size: u64,
+    state: serde_json::Value,
+    completed: Mutex<RangeSet>,
+    started_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl PassTracker<'_> {
+    /// Start from ranges an interrupted run of this pass already wrote
+    pub fn resuming(self, completed: RangeSet) -> Self {
+        *self.completed.lock().unwrap() = completed;
+        self
+    }
+
+    /// Checkpoint a sequential pass that has reached `offset`
+    pub fn record<F>(&self, offset: u64, flush: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<()>,
+    {
+        self.record_range(0..offset, flush)
+    }
+
+    /// Mark `range` of the pass as written and checkpoint if one is due
+    ///
+    /// `flush` runs first, and only when a checkpoint is actually saved, so
+    /// the recorded ranges never run ahead of data that reached the device.
+    pub fn record_range<F>(&self, range: std::ops::Range<u64>, flush: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<()>,
+    {
+        let completed = {
+            let mut completed = self.completed.lock().unwrap();
+            completed.insert(range);
+            completed.clone()
+        };
+
+        let bytes_written = self.pass as u64 * self.pass_size + completed.covered();
+        if !self.coordinator.checkpoint_due(bytes_written) {
+            return Ok(());
+        }
+
+        flush()?;
+
+        let mut state = match &self.state {
+            serde_json::Value::Object(map) => map.clone(),
+            _ => serde_json::Map::new(),
+        };
+        let pass_offset = completed
+            .next_unwritten(0, self.pass_size)
+            .map_or(self.pass_size, |gap| gap.start);
+        state.insert("pass_offset".to_string(), pass_offset.into());
+
+        self.coordinator.maybe_checkpoint(
+            self.algorithm,
+            self.total_passes,
+            self.pass_size * self.total_passes as u64,
+            &Progress {
+                current_pass: self.pass,
+                bytes_written,
+                state: serde_json::Value::Object(state),
+                completed,
+            },
+        )
+    }
+
+    /// Bad sector handler of the coordinator, for writes that have to skip
+    /// unwritable ranges
+    pub fn bad_sectors(&self) -> Option<&BadSectorHandler> {
+        self.coordinator.bad_sector_handler()
+    }
+
+    /// Record the pass as finished, having written `pattern`, for the
+    /// certificate's evidence
+    pub fn complete(&self, pattern: impl Into<String>) {
+        self.complete_verified(pattern, None);
+    }
+
+    /// Record the pass as finished, with what reading it back found
+    pub fn complete_verified(
+        &self,
+        pattern: impl Into<String>,
+        verification: Option<PassVerification>,
+    ) {
+        evidence::record_pass(
+            &self.coordinator.device_path,
+            PassRecord {
+                algorithm: self.algorithm.to_string(),
+                pass: self.pass + 1,
+                total_passes: self.total_passes,
+                pattern: pattern.into(),
+                bytes: self.pass_size,
+                started_at: self.started_at,
+                completed_at: chrono::Utc::now(),
+                verification,
+            },
+        );
+    }
+}
+
 /// Recovery coordinator - main orchestration
 pub struct RecoveryCoordinator {
     /// Error classifier
@@ -100,11 +263,17 @@ pub struct RecoveryCoordinator {
 
     /// Operation ID for this session
     operation_id: String,
+
+    /// Wipe configuration recorded in checkpoints (for `checkpoint resume`)
+    config: serde_json::Value,
+
+    /// Drive behind `device_path` when this operation started
+    device_identity: DriveIdentity,
 }
 
 impl RecoveryCoordinator {
     /// Create new recovery coordinator
-    pub fn new(device_path: impl Into<String>, _config: &WipeConfig) -> Result<Self> {
+    pub fn new(device_path: impl Into<String>, config: &WipeConfig) -> Result<Self> {
         let device_path = device_path.into();
         let operation_id = uuid::Uuid::new_v4().to_string();
 
@@ -116,15 +285,23 @@ impl RecoveryCoordinator {
                 .with_log_file(BadSectorHandler::default_log_file(&device_path)),
         );
 
+        // Site-wide retry limit from the layered configuration, if loaded
+        let retry_config = match crate::config::installed() {
+            Some(config) => RetryConfig::with_max_attempts(config.safety.max_retry_attempts),
+            None => RetryConfig::new(),
+        };
+
         Ok(Self {
             classifier: ErrorClassifier::new(),
             checkpoint_manager,
-            retry_config: RetryConfig::new(),
+            retry_config,
             circuit_breaker: CircuitBreaker::default_device(),
             bad_sector_handler,
             self_healer: SelfHealer::new(),
             degraded_mode: Arc::new(Mutex::new(DegradedModeManager::new())),
             alternative_io: Arc::new(Mutex::new(AlternativeIO::new())),
+            device_identity: DriveIdentity::probe(&device_path),
+            config: serde_json::to_value(config)?,
             device_path,
             operation_id,
         })
@@ -328,9 +505,41 @@ impl RecoveryCoordinator {
         }
     }
 
+    /// Whether a checkpoint would be saved at `bytes_written`
+    pub fn checkpoint_due(&self, bytes_written: u64) -> bool {
+        self.checkpoint_manager
+            .lock()
+            .unwrap()
+            .should_save(bytes_written)
+    }
+
+    /// Track positions within `pass` (0-based) of a wipe for mid-pass resume
+    ///
+    /// `state` is extra algorithm state stored with each checkpoint; the
+    /// offset reached is added to it as `pass_offset`.
+    pub fn pass_tracker<'a>(
+        &'a self,
+        algorithm: &'a str,
+        total_passes: usize,
+        pass: usize,
+        pass_size: u64,
+        state: serde_json::Value,
+    ) -> PassTracker<'a> {
+        PassTracker {
+            coordinator: self,
+            algorithm,
+            total_passes,
+            pass,
+            pass_size,
+            state,
+            completed: Mutex::new(RangeSet::new()),
+            started_at: chrono::Utc::now(),
+        }
+    }
+
     /// Save checkpoint if needed
     pub fn maybe_checkpoint(
-        &mut self,
+        &self,
         algorithm: &str,
         total_passes: usize,
         total_size: u64,
@@ -349,6 +558,9 @@ impl RecoveryCoordinator {
 
             checkpoint.update_progress(progress.current_pass, progress.bytes_written);
             checkpoint.state = progress.state.clone();
+            checkpoint.sectors_completed = progress.completed.clone();
+            checkpoint.config = self.config.clone();
+            checkpoint.device_identity = Some(self.device_identity.clone());
 
             manager.save(&checkpoint)?;
 
@@ -364,10 +576,19 @@ impl RecoveryCoordinator {
     }
 
     /// Load and resume from checkpoint
-    pub fn resume_from_checkpoint(&self, algorithm: &str) -> Result<Option<ResumeState>> {
+    ///
+    /// Checkpoints whose seal fails or that were recorded for a different
+    /// drive are rejected by the checkpoint manager and never resumed from. On
+    /// resume this coordinator adopts the checkpoint's operation ID so further
+    /// saves update the same record.
+    pub fn resume_from_checkpoint(&mut self, algorithm: &str) -> Result<Option<ResumeState>> {
         let manager = self.checkpoint_manager.lock().unwrap();
 
-        if let Some(checkpoint) = manager.load(&self.device_path, algorithm)? {
+        if let Some(checkpoint) =
+            manager.load_verified(&self.device_path, algorithm, &self.device_identity)?
+        {
+            self.operation_id = checkpoint.operation_id.clone();
+
             tracing::info!(
                 device = %self.device_path,
                 algorithm = algorithm,
@@ -434,6 +655,25 @@ impl RecoveryCoordinator {
     }
 }
 
+/// Hand degraded modes and bad sectors to the certificate evidence once the
+/// algorithm is done with the device, however it finished
+impl Drop for RecoveryCoordinator {
+    fn drop(&mut self) {
+        let modes = match self.degraded_mode.lock() {
+            Ok(manager) => manager.active_modes().to_vec(),
+            Err(_) => Vec::new(),
+        };
+        let bad_sectors = self
+            .bad_sector_handler
+            .as_ref()
+            .filter(|handler| handler.bad_sector_count() > 0)
+            .map(|handler| handler.generate_report());
+        if !modes.is_empty() || bad_sectors.is_some() {
+            evidence::record_recovery(&self.device_path, &modes, bad_sectors);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -461,6 +701,8 @@ mod tests {
             alternative_io: Arc::new(Mutex::new(AlternativeIO::new())),
             device_path,
             operation_id,
+            config: serde_json::Value::Null,
+            device_identity: DriveIdentity::default(),
         };
 
         (coordinator, temp_dir)
@@ -525,6 +767,7 @@ mod tests {
             current_pass: 5,
             bytes_written: 1024 * 1024 * 1024,
             state: serde_json::json!({"test": "value"}),
+            completed: RangeSet::new(),
         };
 
         assert_eq!(progress.current_pass, 5);
@@ -543,4 +786,202 @@ mod tests {
         };
         matches!(action, RecoveryAction::Skip { .. });
     }
+
+    #[test]
+    fn test_checkpoint_records_config_and_identity() {
+        let (mut coordinator, _temp) = create_test_coordinator();
+        coordinator.config = serde_json::to_value(WipeConfig::default()).unwrap();
+        coordinator.device_identity = DriveIdentity {
+            serial: Some("WD-123".to_string()),
+            size: Some(4096),
+            ..Default::default()
+        };
+        coordinator
+            .checkpoint_manager
+            .lock()
+            .unwrap()
+            .set_intervals(Duration::from_secs(0), 0);
+
+        let progress = Progress {
+            current_pass: 1,
+            bytes_written: 2048,
+            state: serde_json::Value::Null,
+            completed: RangeSet::new(),
+        };
+        coordinator
+            .maybe_checkpoint("DoD", 3, 4096, &progress)
+            .unwrap();
+
+        let resume = coordinator.resume_from_checkpoint("DoD").unwrap().unwrap();
+        let config: WipeConfig = serde_json::from_value(resume.checkpoint.config).unwrap();
+        assert_eq!(config.algorithm, crate::Algorithm::DoD5220);
+        assert_eq!(
+            resume.checkpoint.device_identity.unwrap().serial.as_deref(),
+            Some("WD-123")
+        );
+    }
+
+    #[test]
+    fn test_resume_adopts_operation_id() {
+        let (mut coordinator, _temp) = create_test_coordinator();
+
+        let checkpoint = Checkpoint::new("/dev/sda", "DoD", "earlier-op", 3, 4096);
+        coordinator
+            .checkpoint_manager
+            .lock()
+            .unwrap()
+            .save(&checkpoint)
+            .unwrap();
+
+        assert!(coordinator.resume_from_checkpoint("DoD").unwrap().is_some());
+        assert_eq!(coordinator.operation_id(), "earlier-op");
+    }
+
+    #[test]
+    fn test_resume_ignores_checkpoint_for_other_drive() {
+        let (mut coordinator, _temp) = create_test_coordinator();
+        coordinator.device_identity = DriveIdentity {
+            serial: Some("NEW-DRIVE".to_string()),
+            size: Some(4096),
+            ..Default::default()
+        };
+
+        let mut checkpoint = Checkpoint::new("/dev/sda", "DoD", "earlier-op", 3, 4096);
+        checkpoint.device_identity = Some(DriveIdentity {
+            serial: Some("OLD-DRIVE".to_string()),
+            size: Some(4096),
+            ..Default::default()
+        });
+        coordinator
+            .checkpoint_manager
+            .lock()
+            .unwrap()
+            .save(&checkpoint)
+            .unwrap();
+
+        assert!(coordinator.resume_from_checkpoint("DoD").unwrap().is_none());
+        assert_ne!(coordinator.operation_id(), "earlier-op");
+
+        let manager = coordinator.checkpoint_manager.lock().unwrap();
+        let rejections = manager.rejections("/dev/sda").unwrap();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].checkpoint_id, checkpoint.id);
+        assert!(rejections[0].reason.contains("serial"));
+    }
+
+    #[test]
+    fn test_resume_pass_offset() {
+        let resume = ResumeState {
+            checkpoint: Checkpoint::new("/dev/sda", "DoD", "op", 3, 3 * 1_000_000),
+            current_pass: 1,
+            bytes_written: 1_000_000 + 10_000,
+            state: serde_json::Value::Null,
+        };
+
+        // Rounded down to the resume alignment
+        assert_eq!(resume.pass_offset(1_000_000), 8192);
+
+        // A checkpoint taken at a pass boundary resumes at the pass start
+        let boundary = ResumeState {
+            bytes_written: 1_000_000,
+            ..resume
+        };
+        assert_eq!(boundary.pass_offset(1_000_000), 0);
+    }
+
+    #[test]
+    fn test_pass_tracker_checkpoints_mid_pass() {
+        let (mut coordinator, _temp) = create_test_coordinator();
+        coordinator
+            .checkpoint_manager
+            .lock()
+            .unwrap()
+            .set_intervals(Duration::from_secs(0), 0);
+
+        let mut flushed = false;
+        let tracker =
+            coordinator.pass_tracker("DoD", 3, 1, 1 << 20, serde_json::json!({"pass": 2}));
+        tracker
+            .record(64 * 1024, || {
+                flushed = true;
+                Ok(())
+            })
+            .unwrap();
+        assert!(flushed);
+
+        let resume = coordinator.resume_from_checkpoint("DoD").unwrap().unwrap();
+        assert_eq!(resume.current_pass, 1);
+        assert_eq!(resume.bytes_written, (1 << 20) + 64 * 1024);
+        assert_eq!(resume.pass_offset(1 << 20), 64 * 1024);
+        assert_eq!(resume.state["pass"], 2);
+        assert_eq!(resume.state["pass_offset"], 64 * 1024);
+    }
+
+    #[test]
+    fn test_pass_tracker_records_out_of_order_ranges() {
+        let (mut coordinator, _temp) = create_test_coordinator();
+        coordinator
+            .checkpoint_manager
+            .lock()
+            .unwrap()
+            .set_intervals(Duration::from_secs(0), 0);
+
+        // A resumed pass that had written its second half already
+        let tracker = coordinator
+            .pass_tracker("Zero", 1, 0, 1 << 20, serde_json::Value::Null)
+            .resuming(RangeSet::from_range(512 * 1024..1 << 20));
+        tracker.record_range(0..64 * 1024, || Ok(())).unwrap();
+
+        let resume = coordinator.resume_from_checkpoint("Zero").unwrap().unwrap();
+        assert_eq!(resume.bytes_written, 512 * 1024 + 64 * 1024);
+        assert_eq!(
+            resume.missing(1 << 20).iter().collect::<Vec<_>>(),
+            vec![64 * 1024..512 * 1024]
+        );
+        assert_eq!(resume.pass_offset(1 << 20), 64 * 1024);
+        assert_eq!(resume.state["pass_offset"], 64 * 1024);
+    }
+
+    #[test]
+    fn test_resume_missing_aligns_ranges() {
+        let mut checkpoint = Checkpoint::new("/dev/sda", "Zero", "op", 1, 100_000);
+        checkpoint.sectors_completed = [0..5000, 9000..20_000].into_iter().collect();
+        let resume = ResumeState {
+            checkpoint,
+            current_pass: 0,
+            bytes_written: 16_000,
+            state: serde_json::Value::Null,
+        };
+
+        // Widened outwards to the alignment, clipped to the pass
+        assert_eq!(
+            resume.missing(100_000).iter().collect::<Vec<_>>(),
+            vec![4096..12288, 16384..100_000]
+        );
+
+        // Checkpoints without ranges fall back to the linear offset
+        let legacy = ResumeState {
+            checkpoint: Checkpoint::new("/dev/sda", "Zero", "op", 1, 100_000),
+            ..resume
+        };
+        assert_eq!(
+            legacy.completed(100_000).iter().collect::<Vec<_>>(),
+            vec![0..12288]
+        );
+    }
+
+    #[test]
+    fn test_pass_tracker_skips_flush_when_not_due() {
+        let (coordinator, _temp) = create_test_coordinator();
+
+        let tracker = coordinator.pass_tracker("Zero", 1, 0, 1 << 20, serde_json::Value::Null);
+        tracker
+            .record(4096, || {
+                panic!("flush must not run without a due checkpoint")
+            })
+            .unwrap();
+
+        let manager = coordinator.checkpoint_manager.lock().unwrap();
+        assert!(manager.load("/dev/sda", "Zero").unwrap().is_none());
+    }
 }
@@ -56,6 +56,12 @@ impl ExponentialBackoff {
         self
     }
 
+    /// Override the maximum number of retry attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
     /// Preset for transient errors (fast retries)
     pub fn transient() -> Self {
         Self::new(Duration::from_millis(100), Duration::from_secs(30), 10)
@@ -356,6 +362,29 @@ impl RetryConfig {
         Self { strategies }
     }
 
+    /// Create retry configuration with every retrying class capped at `max_attempts`
+    ///
+    /// Used to apply `SafetyConfig::max_retry_attempts`. Backoff timings keep
+    /// their per-class presets.
+    pub fn with_max_attempts(max_attempts: u32) -> Self {
+        let mut config = Self::new();
+
+        config.set_strategy(
+            ErrorClass::Transient,
+            Box::new(ExponentialBackoff::transient().with_max_attempts(max_attempts)),
+        );
+        config.set_strategy(
+            ErrorClass::Recoverable,
+            Box::new(ExponentialBackoff::recoverable().with_max_attempts(max_attempts)),
+        );
+        config.set_strategy(
+            ErrorClass::Environmental,
+            Box::new(ExponentialBackoff::environmental().with_max_attempts(max_attempts)),
+        );
+
+        config
+    }
+
     /// Get retry strategy for error class
     pub fn get_strategy(&self, class: ErrorClass) -> &dyn RetryStrategy {
         self.strategies
@@ -549,6 +578,24 @@ mod tests {
         assert_eq!(fatal_strategy.max_attempts(), 0);
     }
 
+    #[test]
+    fn test_retry_config_with_max_attempts() {
+        let config = RetryConfig::with_max_attempts(3);
+
+        assert_eq!(config.get_strategy(ErrorClass::Transient).max_attempts(), 3);
+        assert_eq!(
+            config.get_strategy(ErrorClass::Recoverable).max_attempts(),
+            3
+        );
+        assert_eq!(
+            config
+                .get_strategy(ErrorClass::Environmental)
+                .max_attempts(),
+            3
+        );
+        assert_eq!(config.get_strategy(ErrorClass::Fatal).max_attempts(), 0);
+    }
+
     #[test]
     fn test_retry_strategy_should_retry() {
         let backoff =
@@ -5,12 +5,13 @@ use super::metrics::{IOMetrics, PerformanceTuner};
 use super::platform_specific::{get_platform_io, PlatformIO};
 use super::*;
 use crate::drives::operations::smart::SMARTMonitor;
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::sync::Arc;
 use std::time::Instant;
 
 /// I/O Configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IOConfig {
     /// Use Direct I/O (O_DIRECT) - bypasses OS cache
     pub use_direct_io: bool,
@@ -359,7 +360,33 @@ impl OptimizedIO {
     where
         F: FnMut(&mut PooledBuffer) -> IOResult<()>,
     {
-        let mut offset = 0u64;
+        Self::sequential_write_from(
+            handle,
+            0,
+            total_size,
+            |buffer, _offset| fill_buffer(buffer),
+            |_handle, _offset| Ok(()),
+        )
+    }
+
+    /// Sequentially write `[start_offset, total_size)`
+    ///
+    /// `fill_buffer` receives the device offset the buffer will be written to.
+    /// `on_written` runs after each write with the offset reached so far; every
+    /// byte below that offset has been handed to the device, which is what
+    /// mid-pass checkpoints record.
+    pub fn sequential_write_from<F, C>(
+        handle: &mut IOHandle,
+        start_offset: u64,
+        total_size: u64,
+        mut fill_buffer: F,
+        mut on_written: C,
+    ) -> IOResult<()>
+    where
+        F: FnMut(&mut PooledBuffer, u64) -> IOResult<()>,
+        C: FnMut(&IOHandle, u64) -> IOResult<()>,
+    {
+        let mut offset = start_offset;
         let buffer_size = handle.buffer_pool.stats().buffer_size as u64;
 
         while offset < total_size {
@@ -374,7 +401,7 @@ impl OptimizedIO {
             let mut buffer = handle.acquire_buffer()?;
 
             // Fill buffer with data
-            fill_buffer(&mut buffer)?;
+            fill_buffer(&mut buffer, offset)?;
 
             // Write only the needed portion to device
             let buffer_slice = &buffer.as_slice()[..write_size as usize];
@@ -389,6 +416,8 @@ impl OptimizedIO {
 
             offset += written as u64;
 
+            on_written(handle, offset)?;
+
             // Adaptive tuning if enabled
             if let Some(ref tuner) = handle.tuner {
                 let stats = handle.metrics.stats();
@@ -525,4 +554,44 @@ mod tests {
         let handle = OptimizedIO::open(path, config);
         assert!(handle.is_ok());
     }
+
+    #[test]
+    fn test_sequential_write_from_offset() {
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), vec![0xAAu8; 64 * 1024]).unwrap();
+        let path = temp.path().to_str().unwrap();
+
+        let config = IOConfig {
+            use_direct_io: false,
+            initial_buffer_size: 16 * 1024,
+            ..Default::default()
+        };
+        let mut handle = OptimizedIO::open(path, config).unwrap();
+
+        let mut fill_offsets = Vec::new();
+        let mut written_offsets = Vec::new();
+        OptimizedIO::sequential_write_from(
+            &mut handle,
+            32 * 1024,
+            64 * 1024,
+            |buffer, offset| {
+                fill_offsets.push(offset);
+                buffer.as_mut_slice().fill(0x00);
+                Ok(())
+            },
+            |_handle, offset| {
+                written_offsets.push(offset);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(fill_offsets, vec![32 * 1024, 48 * 1024]);
+        assert_eq!(written_offsets, vec![48 * 1024, 64 * 1024]);
+
+        // Data before the start offset is left untouched
+        let data = std::fs::read(temp.path()).unwrap();
+        assert!(data[..32 * 1024].iter().all(|&b| b == 0xAA));
+        assert!(data[32 * 1024..].iter().all(|&b| b == 0x00));
+    }
 }
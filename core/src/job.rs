@@ -0,0 +1,955 @@
+// Declarative batch job manifests
+//
+// A manifest (TOML or JSON) lists drive selectors with per-drive overrides:
+//
+//   [job]
+//   max_parallel = 2
+//   cert_dir = "./certificates"
+//
+//   [defaults]
+//   algorithm = "dod"
+//
+//   [[drive]]
+//   serial = "WD-WCC4N1234567"
+//   algorithm = "gutmann"
+//   verification = "level2"
+//
+//   [[drive]]
+//   model = "Samsung SSD 8*"
+//   hpa_dco = "remove-temp"
+//
+// `JobManifest::plan` resolves every selector against the detected drives and
+// rejects the whole job up front if anything is unknown, ambiguous or unsafe,
+// or a drive cannot reach the requested NIST level or read its passes back,
+// so a typo in bay 7 never leaves bays 1-6 half wiped.
+
+use crate::drives::{check_pass_verification, plan_sanitization, DriveDetector};
+use crate::verification::VerificationLevel;
+use crate::{Algorithm, DriveInfo, DriveType, HPADCOHandling, WipeConfig};
+use anyhow::{bail, Context, Result};
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Whole manifest as written by the operator
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobManifest {
+    #[serde(default)]
+    pub job: JobSettings,
+
+    /// Options applied to every drive entry that doesn't override them
+    #[serde(default)]
+    pub defaults: DriveOptions,
+
+    #[serde(default, rename = "drive")]
+    pub drives: Vec<DriveEntry>,
+}
+
+/// Global job settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobSettings {
+    /// Label used in the session summary
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Maximum number of drives wiped concurrently
+    #[serde(default = "default_max_parallel")]
+    pub max_parallel: usize,
+
+    /// Directory for certificates of drives without an explicit destination
+    #[serde(default = "default_cert_dir")]
+    pub cert_dir: PathBuf,
+
+    /// Session summary file (default: <cert_dir>/session_<id>.json)
+    #[serde(default)]
+    pub summary: Option<PathBuf>,
+
+    #[serde(default)]
+    pub operator_id: Option<String>,
+
+    /// Proceed on drives reporting poor health
+    #[serde(default)]
+    pub force: bool,
+
+    /// Confidence required by enhanced verification levels
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: f64,
+}
+
+impl Default for JobSettings {
+    fn default() -> Self {
+        Self {
+            name: None,
+            max_parallel: default_max_parallel(),
+            cert_dir: default_cert_dir(),
+            summary: None,
+            operator_id: None,
+            force: false,
+            min_confidence: default_min_confidence(),
+        }
+    }
+}
+
+fn default_max_parallel() -> usize {
+    1
+}
+
+fn default_cert_dir() -> PathBuf {
+    PathBuf::from("./certificates")
+}
+
+fn default_min_confidence() -> f64 {
+    95.0
+}
+
+/// Per-drive wipe options, as CLI-style names
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DriveOptions {
+    /// dod, gutmann, random, zero, secure, crypto, sanitize, trim, auto
+    #[serde(default)]
+    pub algorithm: Option<String>,
+
+    /// none, basic, level1 .. level4
+    #[serde(default)]
+    pub verification: Option<String>,
+
+    /// ignore, detect, remove-temp, remove-perm
+    #[serde(default)]
+    pub hpa_dco: Option<String>,
+}
+
+/// One `[[drive]]` entry: exactly one selector plus optional overrides
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DriveEntry {
+    #[serde(default)]
+    pub serial: Option<String>,
+    #[serde(default)]
+    pub wwn: Option<String>,
+    /// Glob matched case-insensitively against the model string
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Stable path such as /dev/disk/by-path/pci-0000:00:1f.2-ata-3
+    #[serde(default)]
+    pub by_path: Option<String>,
+
+    #[serde(default)]
+    pub algorithm: Option<String>,
+    #[serde(default)]
+    pub verification: Option<String>,
+    #[serde(default)]
+    pub hpa_dco: Option<String>,
+    /// Certificate destination for this drive
+    #[serde(default)]
+    pub certificate: Option<PathBuf>,
+}
+
+/// How a drive is identified in the manifest
+#[derive(Debug, Clone)]
+pub enum DriveSelector {
+    Serial(String),
+    Wwn(String),
+    Model(Pattern),
+    ByPath(String),
+}
+
+impl fmt::Display for DriveSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriveSelector::Serial(s) => write!(f, "serial \"{}\"", s),
+            DriveSelector::Wwn(w) => write!(f, "wwn \"{}\"", w),
+            DriveSelector::Model(p) => write!(f, "model \"{}\"", p.as_str()),
+            DriveSelector::ByPath(p) => write!(f, "by_path \"{}\"", p),
+        }
+    }
+}
+
+impl DriveSelector {
+    fn matches(&self, drive: &DriveInfo, env: &dyn DriveEnvironment) -> bool {
+        match self {
+            DriveSelector::Serial(serial) => drive.serial.trim() == serial.trim(),
+            DriveSelector::Wwn(wwn) => env
+                .wwn(&drive.device_path)
+                .map(|found| normalize_wwn(&found) == normalize_wwn(wwn))
+                .unwrap_or(false),
+            DriveSelector::Model(pattern) => pattern.matches_with(
+                drive.model.trim(),
+                MatchOptions {
+                    case_sensitive: false,
+                    ..Default::default()
+                },
+            ),
+            DriveSelector::ByPath(path) => env
+                .resolve_link(path)
+                .map(|target| target == drive.device_path)
+                .unwrap_or(false),
+        }
+    }
+
+    /// Selectors that name a single physical drive
+    fn is_unique(&self) -> bool {
+        !matches!(self, DriveSelector::Model(_))
+    }
+}
+
+/// Strip the common WWN spellings (0x5000..., naa.5000..., wwn-0x5000...)
+fn normalize_wwn(wwn: &str) -> String {
+    let lower = wwn.trim().to_lowercase();
+    let mut rest = lower.as_str();
+    for prefix in ["wwn-", "naa.", "eui.", "0x"] {
+        rest = rest.strip_prefix(prefix).unwrap_or(rest);
+    }
+    rest.to_string()
+}
+
+/// Post-wipe verification requested for a drive
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum VerificationMode {
+    None,
+    /// Recovery test used by `wipe`
+    Basic,
+    /// Mathematical verification used by `enhanced-wipe`
+    Enhanced(VerificationLevel),
+}
+
+impl FromStr for VerificationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "none" | "off" => VerificationMode::None,
+            "basic" => VerificationMode::Basic,
+            "level1" | "1" => VerificationMode::Enhanced(VerificationLevel::Level1RandomSampling),
+            "level2" | "2" => {
+                VerificationMode::Enhanced(VerificationLevel::Level2SystematicSampling)
+            }
+            "level3" | "3" => VerificationMode::Enhanced(VerificationLevel::Level3FullScan),
+            "level4" | "4" => VerificationMode::Enhanced(VerificationLevel::Level4ForensicScan),
+            _ => bail!("Unknown verification level: {}", s),
+        })
+    }
+}
+
+/// Host queries needed to resolve selectors and enforce safety rules
+pub trait DriveEnvironment {
+    fn is_system_drive(&self, device_path: &str) -> Result<bool>;
+    fn is_mounted(&self, device_path: &str) -> Result<bool>;
+    /// World Wide Name reported by the kernel, if any
+    fn wwn(&self, device_path: &str) -> Option<String>;
+    /// Canonical device path a symlink points to
+    fn resolve_link(&self, path: &str) -> Option<String>;
+}
+
+/// Live system: DriveDetector safety checks plus sysfs/udev lookups
+pub struct HostEnvironment;
+
+impl DriveEnvironment for HostEnvironment {
+    fn is_system_drive(&self, device_path: &str) -> Result<bool> {
+        DriveDetector::is_system_drive(device_path)
+    }
+
+    fn is_mounted(&self, device_path: &str) -> Result<bool> {
+        DriveDetector::is_mounted(device_path)
+    }
+
+    fn wwn(&self, device_path: &str) -> Option<String> {
+        let name = Path::new(device_path).file_name()?.to_str()?;
+        // SATA/SAS expose it under device/, NVMe namespaces directly
+        [
+            format!("/sys/block/{}/device/wwid", name),
+            format!("/sys/block/{}/wwid", name),
+        ]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|wwn| wwn.trim().to_string())
+        .filter(|wwn| !wwn.is_empty())
+    }
+
+    fn resolve_link(&self, path: &str) -> Option<String> {
+        std::fs::canonicalize(path)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+    }
+}
+
+/// A drive with its fully resolved settings
+#[derive(Debug, Clone)]
+pub struct PlannedWipe {
+    pub drive: DriveInfo,
+    pub config: WipeConfig,
+    pub verification: VerificationMode,
+    pub certificate: PathBuf,
+    /// Manifest selector that picked this drive
+    pub selector: String,
+}
+
+/// Validated job ready to execute
+#[derive(Debug, Clone)]
+pub struct JobPlan {
+    pub name: Option<String>,
+    pub wipes: Vec<PlannedWipe>,
+    pub max_parallel: usize,
+    pub force: bool,
+    pub min_confidence: f64,
+    pub operator_id: Option<String>,
+    pub cert_dir: PathBuf,
+    pub summary: Option<PathBuf>,
+}
+
+impl JobManifest {
+    /// Load a manifest; `.json` files are parsed as JSON, everything else as TOML
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read job manifest {}", path.display()))?;
+
+        let manifest = if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            Self::from_json_str(&contents)
+        } else {
+            Self::from_toml_str(&contents)
+        };
+        manifest.with_context(|| format!("Invalid job manifest {}", path.display()))
+    }
+
+    pub fn from_toml_str(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn from_json_str(contents: &str) -> Result<Self> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    /// Resolve selectors against `drives` and validate the whole job
+    ///
+    /// Every problem is reported at once. `allow_system` (from `--unsafe-mode`)
+    /// permits system and mounted drives.
+    pub fn plan(
+        &self,
+        drives: &[DriveInfo],
+        base: &WipeConfig,
+        env: &dyn DriveEnvironment,
+        allow_system: bool,
+    ) -> Result<JobPlan> {
+        let mut problems = Vec::new();
+
+        if self.job.max_parallel == 0 {
+            problems.push("job.max_parallel must be at least 1".to_string());
+        }
+        if !(0.0..=100.0).contains(&self.job.min_confidence) {
+            problems.push("job.min_confidence must be between 0 and 100".to_string());
+        }
+        if self.drives.is_empty() {
+            problems.push("manifest lists no [[drive]] entries".to_string());
+        }
+
+        let mut wipes: Vec<PlannedWipe> = Vec::new();
+        let mut claimed: HashMap<String, usize> = HashMap::new();
+
+        for (index, entry) in self.drives.iter().enumerate() {
+            let label = format!("drive #{}", index + 1);
+
+            let selector = match entry.selector() {
+                Ok(selector) => selector,
+                Err(e) => {
+                    problems.push(format!("{}: {}", label, e));
+                    continue;
+                }
+            };
+            let label = format!("{} ({})", label, selector);
+
+            let options = match self.resolve_options(entry, base) {
+                Ok(options) => options,
+                Err(e) => {
+                    problems.push(format!("{}: {}", label, e));
+                    continue;
+                }
+            };
+
+            let matched: Vec<&DriveInfo> =
+                drives.iter().filter(|d| selector.matches(d, env)).collect();
+
+            if matched.is_empty() {
+                problems.push(format!("{} matches no detected drive", label));
+                continue;
+            }
+            if selector.is_unique() && matched.len() > 1 {
+                problems.push(format!("{} matches {} drives", label, matched.len()));
+                continue;
+            }
+            if entry.certificate.is_some() && matched.len() > 1 {
+                problems.push(format!(
+                    "{} matches {} drives but names a single certificate file",
+                    label,
+                    matched.len()
+                ));
+                continue;
+            }
+
+            for drive in matched {
+                if let Some(previous) = claimed.insert(drive.device_path.clone(), index) {
+                    problems.push(format!(
+                        "{} selects {} which drive #{} already selected",
+                        label,
+                        drive.device_path,
+                        previous + 1
+                    ));
+                    continue;
+                }
+
+                if !allow_system {
+                    if env.is_system_drive(&drive.device_path).unwrap_or(true) {
+                        problems.push(format!(
+                            "{} selects system drive {}",
+                            label, drive.device_path
+                        ));
+                        continue;
+                    }
+                    if env.is_mounted(&drive.device_path).unwrap_or(true) {
+                        problems.push(format!(
+                            "{} selects mounted drive {}",
+                            label, drive.device_path
+                        ));
+                        continue;
+                    }
+                }
+
+                // The same checks a single wipe makes before writing, run
+                // for every drive before any of them is touched
+                if let Err(e) = check_capabilities(drive, &options.0)
+                    .and_then(|()| plan_sanitization(drive, &options.0).map(drop))
+                    .and_then(|()| check_pass_verification(drive, &options.0))
+                {
+                    problems.push(format!("{}: {}", label, e));
+                    continue;
+                }
+
+                let certificate = entry
+                    .certificate
+                    .clone()
+                    .unwrap_or_else(|| default_certificate_path(&self.job.cert_dir, drive));
+
+                wipes.push(PlannedWipe {
+                    drive: drive.clone(),
+                    config: options.0.clone(),
+                    verification: options.1,
+                    certificate,
+                    selector: selector.to_string(),
+                });
+            }
+        }
+
+        // Two drives must never overwrite each other's certificate
+        let mut destinations: HashMap<&Path, &str> = HashMap::new();
+        for wipe in &wipes {
+            if let Some(other) = destinations.insert(&wipe.certificate, &wipe.drive.device_path) {
+                problems.push(format!(
+                    "{} and {} share certificate destination {}",
+                    other,
+                    wipe.drive.device_path,
+                    wipe.certificate.display()
+                ));
+            }
+        }
+
+        if !problems.is_empty() {
+            bail!(
+                "Job manifest validation failed:\n  - {}",
+                problems.join("\n  - ")
+            );
+        }
+
+        Ok(JobPlan {
+            name: self.job.name.clone(),
+            wipes,
+            max_parallel: self.job.max_parallel,
+            force: self.job.force,
+            min_confidence: self.job.min_confidence,
+            operator_id: self.job.operator_id.clone(),
+            cert_dir: self.job.cert_dir.clone(),
+            summary: self.job.summary.clone(),
+        })
+    }
+
+    fn resolve_options(
+        &self,
+        entry: &DriveEntry,
+        base: &WipeConfig,
+    ) -> Result<(WipeConfig, VerificationMode)> {
+        let mut config = base.clone();
+
+        if let Some(algorithm) = entry
+            .algorithm
+            .as_ref()
+            .or(self.defaults.algorithm.as_ref())
+        {
+            config.algorithm = algorithm.parse()?;
+        }
+        if let Some(hpa_dco) = entry.hpa_dco.as_ref().or(self.defaults.hpa_dco.as_ref()) {
+            config.handle_hpa_dco = hpa_dco.parse()?;
+        }
+
+        let verification = match entry
+            .verification
+            .as_ref()
+            .or(self.defaults.verification.as_ref())
+        {
+            Some(level) => level.parse()?,
+            None if config.verify => VerificationMode::Basic,
+            None => VerificationMode::None,
+        };
+        config.verify = verification != VerificationMode::None;
+
+        if config.algorithm == Algorithm::TrimOnly
+            && config.handle_hpa_dco == HPADCOHandling::PermanentRemove
+        {
+            bail!("hpa_dco = \"remove-perm\" has no effect with algorithm \"trim\"");
+        }
+        // Enhanced verification wipes without touching hidden areas
+        if matches!(verification, VerificationMode::Enhanced(_))
+            && matches!(
+                config.handle_hpa_dco,
+                HPADCOHandling::TemporaryRemove | HPADCOHandling::PermanentRemove
+            )
+        {
+            bail!("hpa_dco removal is not supported with verification levels level1 to level4");
+        }
+
+        Ok((config, verification))
+    }
+}
+
+impl DriveEntry {
+    /// The single selector of this entry
+    pub fn selector(&self) -> Result<DriveSelector> {
+        let mut selectors = Vec::new();
+
+        if let Some(serial) = &self.serial {
+            selectors.push(DriveSelector::Serial(serial.clone()));
+        }
+        if let Some(wwn) = &self.wwn {
+            selectors.push(DriveSelector::Wwn(wwn.clone()));
+        }
+        if let Some(model) = &self.model {
+            let pattern =
+                Pattern::new(model).with_context(|| format!("invalid model glob \"{}\"", model))?;
+            selectors.push(DriveSelector::Model(pattern));
+        }
+        if let Some(path) = &self.by_path {
+            selectors.push(DriveSelector::ByPath(path.clone()));
+        }
+
+        match selectors.len() {
+            0 => bail!("no selector given (serial, wwn, model or by_path)"),
+            1 => Ok(selectors.remove(0)),
+            _ => bail!(
+                "conflicting selectors {}; use exactly one",
+                selectors
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// Reject explicit hardware methods the drive cannot perform
+fn check_capabilities(drive: &DriveInfo, config: &WipeConfig) -> Result<()> {
+    match config.algorithm {
+        Algorithm::CryptoErase if !drive.capabilities.crypto_erase => bail!(
+            "algorithm \"crypto\" requested but {} is not a self-encrypting drive",
+            drive.device_path
+        ),
+        Algorithm::Sanitize
+            if drive.drive_type != DriveType::NVMe
+                || drive.capabilities.sanitize_options.is_empty() =>
+        {
+            bail!(
+                "algorithm \"sanitize\" requested but {} does not support NVMe sanitize",
+                drive.device_path
+            )
+        }
+        Algorithm::TrimOnly if !drive.capabilities.trim_support => bail!(
+            "algorithm \"trim\" requested but {} does not support TRIM",
+            drive.device_path
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Certificate file used when an entry doesn't name one (same scheme as `wipe-all`)
+pub fn default_certificate_path(cert_dir: &Path, drive: &DriveInfo) -> PathBuf {
+    let cert_filename = drive.device_path.replace("/", "_").replace("dev_", "");
+    let format = crate::config::installed()
+        .map(|c| c.signing.format)
+        .unwrap_or_default();
+    cert_dir.join(format!(
+        "cert_{}.{}",
+        cert_filename,
+        format.file_extension()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DriveCapabilities, EncryptionStatus, NistLevel};
+    use std::collections::HashSet;
+
+    #[derive(Default)]
+    struct MockEnvironment {
+        system: HashSet<String>,
+        mounted: HashSet<String>,
+        wwns: HashMap<String, String>,
+        links: HashMap<String, String>,
+    }
+
+    impl DriveEnvironment for MockEnvironment {
+        fn is_system_drive(&self, device_path: &str) -> Result<bool> {
+            Ok(self.system.contains(device_path))
+        }
+
+        fn is_mounted(&self, device_path: &str) -> Result<bool> {
+            Ok(self.mounted.contains(device_path))
+        }
+
+        fn wwn(&self, device_path: &str) -> Option<String> {
+            self.wwns.get(device_path).cloned()
+        }
+
+        fn resolve_link(&self, path: &str) -> Option<String> {
+            self.links.get(path).cloned()
+        }
+    }
+
+    fn drive(path: &str, model: &str, serial: &str) -> DriveInfo {
+        DriveInfo {
+            device_path: path.to_string(),
+            model: model.to_string(),
+            serial: serial.to_string(),
+            size: 1024 * 1024 * 1024,
+            drive_type: DriveType::HDD,
+            encryption_status: EncryptionStatus::None,
+            capabilities: DriveCapabilities::default(),
+            health_status: None,
+            temperature_celsius: None,
+        }
+    }
+
+    fn bench() -> Vec<DriveInfo> {
+        vec![
+            drive("/dev/sda", "Samsung SSD 870 EVO", "S1"),
+            drive("/dev/sdb", "WDC WD10EZEX", "W1"),
+            drive("/dev/sdc", "WDC WD20EZRZ", "W2"),
+        ]
+    }
+
+    fn plan(manifest: &str, env: &MockEnvironment) -> Result<JobPlan> {
+        JobManifest::from_toml_str(manifest)?.plan(&bench(), &WipeConfig::default(), env, false)
+    }
+
+    #[test]
+    fn test_parse_full_manifest() {
+        let manifest = JobManifest::from_toml_str(
+            r#"
+            [job]
+            name = "bench-1"
+            max_parallel = 3
+            cert_dir = "/tmp/certs"
+            operator_id = "ops"
+
+            [defaults]
+            algorithm = "zero"
+
+            [[drive]]
+            serial = "S1"
+            verification = "level2"
+            certificate = "/tmp/certs/bay1.json"
+
+            [[drive]]
+            model = "WDC*"
+            hpa_dco = "remove-temp"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.job.max_parallel, 3);
+        assert_eq!(manifest.drives.len(), 2);
+        assert_eq!(manifest.defaults.algorithm.as_deref(), Some("zero"));
+    }
+
+    #[test]
+    fn test_unknown_field_rejected() {
+        let err = JobManifest::from_toml_str("[[drive]]\nserial = \"S1\"\nalgo = \"dod\"\n");
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_json_manifest() {
+        let manifest =
+            JobManifest::from_json_str(r#"{"drive": [{"serial": "S1", "algorithm": "random"}]}"#)
+                .unwrap();
+        assert_eq!(manifest.job.max_parallel, 1);
+        assert_eq!(manifest.drives[0].algorithm.as_deref(), Some("random"));
+    }
+
+    #[test]
+    fn test_plan_applies_overrides_and_defaults() {
+        let plan = plan(
+            r#"
+            [job]
+            cert_dir = "/tmp/certs"
+
+            [defaults]
+            algorithm = "zero"
+
+            [[drive]]
+            serial = "S1"
+            algorithm = "gutmann"
+            verification = "level2"
+            certificate = "/tmp/certs/bay1.json"
+
+            [[drive]]
+            model = "wdc*"
+            hpa_dco = "remove-temp"
+            verification = "none"
+            "#,
+            &MockEnvironment::default(),
+        )
+        .unwrap();
+
+        assert_eq!(plan.wipes.len(), 3);
+
+        let ssd = &plan.wipes[0];
+        assert_eq!(ssd.config.algorithm, Algorithm::Gutmann);
+        assert_eq!(
+            ssd.verification,
+            VerificationMode::Enhanced(VerificationLevel::Level2SystematicSampling)
+        );
+        assert_eq!(ssd.certificate, PathBuf::from("/tmp/certs/bay1.json"));
+
+        let wd = &plan.wipes[1];
+        assert_eq!(wd.config.algorithm, Algorithm::Zero);
+        assert_eq!(wd.config.handle_hpa_dco, HPADCOHandling::TemporaryRemove);
+        assert!(!wd.config.verify);
+        assert_eq!(wd.certificate, PathBuf::from("/tmp/certs/cert__sdb.json"));
+    }
+
+    #[test]
+    fn test_wwn_and_by_path_selectors() {
+        let mut env = MockEnvironment::default();
+        env.wwns
+            .insert("/dev/sdb".to_string(), "naa.5000C500A1B2C3D4".to_string());
+        env.links.insert(
+            "/dev/disk/by-path/pci-0000:00:1f.2-ata-3".to_string(),
+            "/dev/sdc".to_string(),
+        );
+
+        let plan = plan(
+            r#"
+            [[drive]]
+            wwn = "0x5000c500a1b2c3d4"
+
+            [[drive]]
+            by_path = "/dev/disk/by-path/pci-0000:00:1f.2-ata-3"
+            "#,
+            &env,
+        )
+        .unwrap();
+
+        let paths: Vec<_> = plan
+            .wipes
+            .iter()
+            .map(|w| w.drive.device_path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["/dev/sdb", "/dev/sdc"]);
+    }
+
+    #[test]
+    fn test_validation_reports_all_problems() {
+        let mut env = MockEnvironment::default();
+        env.system.insert("/dev/sda".to_string());
+
+        let err = plan(
+            r#"
+            [job]
+            max_parallel = 0
+
+            [[drive]]
+            serial = "S1"
+
+            [[drive]]
+            serial = "MISSING"
+
+            [[drive]]
+            serial = "W1"
+            model = "WDC*"
+
+            [[drive]]
+            serial = "W2"
+            algorithm = "shred"
+            "#,
+            &env,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("max_parallel"));
+        assert!(err.contains("system drive /dev/sda"));
+        assert!(err.contains("serial \"MISSING\"") && err.contains("matches no detected drive"));
+        assert!(err.contains("conflicting selectors"));
+        assert!(err.contains("Unknown algorithm"));
+    }
+
+    #[test]
+    fn test_duplicate_selection_rejected() {
+        let err = plan(
+            r#"
+            [[drive]]
+            serial = "W1"
+
+            [[drive]]
+            model = "WDC WD10*"
+            "#,
+            &MockEnvironment::default(),
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("already selected"));
+    }
+
+    #[test]
+    fn test_shared_certificate_rejected() {
+        let err = plan(
+            r#"
+            [[drive]]
+            serial = "W1"
+            certificate = "/tmp/same.json"
+
+            [[drive]]
+            serial = "W2"
+            certificate = "/tmp/same.json"
+            "#,
+            &MockEnvironment::default(),
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("share certificate destination"));
+    }
+
+    #[test]
+    fn test_mounted_drive_allowed_in_unsafe_mode() {
+        let mut env = MockEnvironment::default();
+        env.mounted.insert("/dev/sdb".to_string());
+        let manifest = JobManifest::from_toml_str("[[drive]]\nserial = \"W1\"\n").unwrap();
+
+        assert!(manifest
+            .plan(&bench(), &WipeConfig::default(), &env, false)
+            .is_err());
+        assert!(manifest
+            .plan(&bench(), &WipeConfig::default(), &env, true)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_capability_conflict_rejected() {
+        let err = plan(
+            "[[drive]]\nserial = \"W1\"\nalgorithm = \"crypto\"\n",
+            &MockEnvironment::default(),
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("not a self-encrypting drive"));
+    }
+
+    #[test]
+    fn test_hpa_dco_removal_rejected_with_enhanced_verification() {
+        let err = plan(
+            r#"
+            [defaults]
+            hpa_dco = "remove-temp"
+
+            [[drive]]
+            serial = "W1"
+            verification = "level2"
+            "#,
+            &MockEnvironment::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("hpa_dco removal is not supported"));
+
+        // Detection alone is fine
+        assert!(plan(
+            "[[drive]]\nserial = \"W1\"\nverification = \"level2\"\nhpa_dco = \"detect\"\n",
+            &MockEnvironment::default(),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_sanitization_and_readback_checked_up_front() {
+        let mut drives = bench();
+        drives[2].drive_type = DriveType::SMR;
+        let manifest =
+            JobManifest::from_toml_str("[[drive]]\nserial = \"W1\"\n[[drive]]\nserial = \"W2\"\n")
+                .unwrap();
+        let env = MockEnvironment::default();
+
+        // An overwrite only reaches Clear
+        let purge = WipeConfig {
+            nist_level: Some(NistLevel::Purge),
+            ..WipeConfig::default()
+        };
+        let err = manifest
+            .plan(&drives, &purge, &env, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("serial \"W1\"") && err.contains("cannot be achieved"));
+
+        // The SMR drive's wipe path cannot read passes back
+        let readback = WipeConfig {
+            verify_passes: true,
+            ..WipeConfig::default()
+        };
+        let err = manifest
+            .plan(&drives, &readback, &env, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("serial \"W2\"") && err.contains("--verify-passes"));
+        assert!(!err.contains("serial \"W1\""));
+    }
+
+    #[test]
+    fn test_verification_mode_parsing() {
+        assert_eq!(
+            "none".parse::<VerificationMode>().unwrap(),
+            VerificationMode::None
+        );
+        assert_eq!(
+            "basic".parse::<VerificationMode>().unwrap(),
+            VerificationMode::Basic
+        );
+        assert_eq!(
+            "level4".parse::<VerificationMode>().unwrap(),
+            VerificationMode::Enhanced(VerificationLevel::Level4ForensicScan)
+        );
+        assert!("level5".parse::<VerificationMode>().is_err());
+    }
+
+    #[test]
+    fn test_normalize_wwn() {
+        assert_eq!(normalize_wwn("0x5000C500A1B2C3D4"), "5000c500a1b2c3d4");
+        assert_eq!(normalize_wwn("naa.5000c500a1b2c3d4"), "5000c500a1b2c3d4");
+        assert_eq!(normalize_wwn("wwn-0x5000c500a1b2c3d4"), "5000c500a1b2c3d4");
+    }
+}
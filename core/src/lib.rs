@@ -4,10 +4,12 @@
 #![allow(clippy::type_complexity)]
 
 pub mod algorithms;
+pub mod config;
 pub mod crypto;
 pub mod drives;
 pub mod error;
 pub mod io;
+pub mod job;
 pub mod ui;
 pub mod verification;
 pub mod wipe_orchestrator;
@@ -17,6 +19,7 @@ pub use wipe_orchestrator::{wipe_drive, WipeOrchestrator};
 
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use thiserror::Error;
 
 // Global flag for handling Ctrl+C interrupts
@@ -125,6 +128,14 @@ pub struct WipeConfig {
     pub max_temperature_celsius: Option<u32>,
     pub freeze_mitigation: bool,
     pub sed_crypto_erase: bool,
+    /// NIST SP 800-88 level the wipe must reach; without one, `auto` takes
+    /// the strongest technique the drive supports
+    #[serde(default)]
+    pub nist_level: Option<NistLevel>,
+    /// Read every overwrite pass back in full and compare it with what was
+    /// written, rewriting blocks that read back wrong
+    #[serde(default)]
+    pub verify_passes: bool,
 }
 
 impl Default for WipeConfig {
@@ -141,10 +152,45 @@ impl Default for WipeConfig {
             max_temperature_celsius: Some(65),
             freeze_mitigation: true,
             sed_crypto_erase: true,
+            nist_level: None,
+            verify_passes: false,
         }
     }
 }
 
+/// NIST SP 800-88 Rev. 1 sanitization levels, weakest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NistLevel {
+    /// Logical techniques that resist simple, non-invasive recovery
+    Clear,
+    /// Physical or logical techniques that defeat state-of-the-art
+    /// laboratory recovery
+    Purge,
+}
+
+impl std::fmt::Display for NistLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            NistLevel::Clear => "Clear",
+            NistLevel::Purge => "Purge",
+        })
+    }
+}
+
+impl std::str::FromStr for NistLevel {
+    type Err = anyhow::Error;
+
+    /// Parse the command-line name (`clear`, `purge`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "clear" => NistLevel::Clear,
+            "purge" => NistLevel::Purge,
+            _ => return Err(anyhow::anyhow!("Unknown NIST level: {} (clear, purge)", s)),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum HPADCOHandling {
     Ignore,          // Don't check for HPA/DCO
@@ -153,6 +199,21 @@ pub enum HPADCOHandling {
     PermanentRemove, // Remove permanently (dangerous)
 }
 
+impl std::str::FromStr for HPADCOHandling {
+    type Err = anyhow::Error;
+
+    /// Parse the command-line name (`ignore`, `detect`, `remove-temp`, `remove-perm`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "ignore" => HPADCOHandling::Ignore,
+            "detect" => HPADCOHandling::Detect,
+            "remove-temp" => HPADCOHandling::TemporaryRemove,
+            "remove-perm" => HPADCOHandling::PermanentRemove,
+            _ => return Err(anyhow::anyhow!("Unknown HPA/DCO handling: {}", s)),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Algorithm {
     DoD5220,     // 3-pass DoD 5220.22-M
@@ -163,6 +224,62 @@ pub enum Algorithm {
     CryptoErase, // Cryptographic erase (SED)
     Sanitize,    // NVMe sanitize command
     TrimOnly,    // TRIM/discard only (SSD)
+    Auto,        // Strongest technique the NIST 800-88 decision allows
+    /// Software overwrite registered under this name (see
+    /// `algorithms::registry`)
+    Method(String),
+}
+
+impl Algorithm {
+    /// The software overwrite this algorithm runs, if it is one
+    pub fn wipe_method(&self) -> Option<Arc<dyn algorithms::WipeMethod>> {
+        algorithms::lookup_method(match self {
+            Algorithm::DoD5220 => "dod",
+            Algorithm::Gutmann => "gutmann",
+            Algorithm::Random => "random",
+            Algorithm::Zero => "zero",
+            Algorithm::Method(name) => {
+                // A scheme named in a saved configuration, e.g. a checkpoint
+                // resumed by a new process, loads on first use
+                if let Some(path) = name.strip_prefix(algorithms::scheme::SCHEME_PREFIX) {
+                    if let Err(e) = algorithms::scheme::register_scheme(path) {
+                        tracing::warn!("Cannot load {}: {:#}", name, e);
+                    }
+                }
+                name
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = anyhow::Error;
+
+    /// Parse the command-line name (`dod`, `gutmann`, ..., `auto`), the
+    /// name of a registered wipe method, or `scheme:<path>` to load a scheme
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix(algorithms::scheme::SCHEME_PREFIX) {
+            return Ok(Algorithm::Method(algorithms::scheme::register_scheme(
+                path,
+            )?));
+        }
+        Ok(match s.to_lowercase().as_str() {
+            "dod" => Algorithm::DoD5220,
+            "gutmann" => Algorithm::Gutmann,
+            "random" => Algorithm::Random,
+            "zero" => Algorithm::Zero,
+            "secure" => Algorithm::SecureErase,
+            "crypto" => Algorithm::CryptoErase,
+            "sanitize" => Algorithm::Sanitize,
+            "trim" => Algorithm::TrimOnly,
+            "auto" => Algorithm::Auto,
+            name => match algorithms::lookup_method(name) {
+                Some(method) => Algorithm::Method(method.name().to_lowercase()),
+                None => return Err(anyhow::anyhow!("Unknown algorithm: {}", s)),
+            },
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -248,7 +365,7 @@ pub enum SEDType {
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SanitizeOption {
     BlockErase,
     CryptoErase,
@@ -308,6 +425,8 @@ pub struct WipeSession {
     pub start_time: chrono::DateTime<chrono::Utc>,
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
     pub drives: Vec<DriveWipeRecord>,
+    /// Configuration the session started from; a drive whose options were
+    /// resolved separately records its own in `DriveWipeRecord::config`
     pub config: WipeConfig,
     pub operator_id: Option<String>,
 }
@@ -321,6 +440,9 @@ pub struct DriveWipeRecord {
     pub error_message: Option<String>,
     pub certificate_path: Option<String>,
     pub verification_passed: Option<bool>,
+    /// Configuration this drive was wiped with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<WipeConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -202,6 +202,8 @@ fn test_wipe_config_custom() {
         max_temperature_celsius: Some(70),
         freeze_mitigation: false,
         sed_crypto_erase: false,
+        nist_level: Some(NistLevel::Purge),
+        verify_passes: true,
     };
 
     assert_eq!(config.algorithm, Algorithm::Gutmann);
@@ -209,6 +211,14 @@ fn test_wipe_config_custom() {
     assert_eq!(config.multiple_passes, Some(7));
 }
 
+#[test]
+fn test_nist_level_from_str() {
+    assert_eq!("purge".parse::<NistLevel>().unwrap(), NistLevel::Purge);
+    assert_eq!("Clear".parse::<NistLevel>().unwrap(), NistLevel::Clear);
+    assert!("destroy".parse::<NistLevel>().is_err());
+    assert!(NistLevel::Purge > NistLevel::Clear);
+}
+
 // ==================== HPA/DCO HANDLING ENUM TESTS ====================
 
 #[test]
@@ -248,8 +258,106 @@ fn test_hpa_dco_handling_clone() {
     assert_eq!(handling, cloned);
 }
 
+#[test]
+fn test_hpa_dco_handling_from_str() {
+    assert_eq!(
+        "remove-temp".parse::<HPADCOHandling>().unwrap(),
+        HPADCOHandling::TemporaryRemove
+    );
+    assert_eq!(
+        "ignore".parse::<HPADCOHandling>().unwrap(),
+        HPADCOHandling::Ignore
+    );
+    assert!("remove".parse::<HPADCOHandling>().is_err());
+    assert!("remove-prem".parse::<HPADCOHandling>().is_err());
+}
+
 // ==================== ALGORITHM ENUM TESTS ====================
 
+#[test]
+fn test_algorithm_from_str() {
+    assert_eq!("dod".parse::<Algorithm>().unwrap(), Algorithm::DoD5220);
+    assert_eq!("Gutmann".parse::<Algorithm>().unwrap(), Algorithm::Gutmann);
+    assert_eq!("auto".parse::<Algorithm>().unwrap(), Algorithm::Auto);
+    assert!("shred".parse::<Algorithm>().is_err());
+}
+
+#[test]
+fn test_algorithm_registered_method() {
+    use crate::algorithms::{register_method, PassSource, WipeMethod, WipePass};
+
+    struct SiteWipe;
+    impl WipeMethod for SiteWipe {
+        fn name(&self) -> &str {
+            "Site-Triple"
+        }
+        fn description(&self) -> String {
+            "site triple-zero wipe".to_string()
+        }
+        fn checkpoint_label(&self) -> &str {
+            "SiteTriple"
+        }
+        fn passes(&self) -> Vec<WipePass> {
+            vec![WipePass::new(PassSource::Fixed(0x00), "Writing zeros"); 3]
+        }
+    }
+
+    assert!("site-triple".parse::<Algorithm>().is_err());
+    register_method(SiteWipe).unwrap();
+    let algo = "SITE-TRIPLE".parse::<Algorithm>().unwrap();
+    assert_eq!(algo, Algorithm::Method("site-triple".to_string()));
+    assert_eq!(algo.wipe_method().unwrap().passes().len(), 3);
+
+    // Built-in overwrites resolve through the registry too
+    assert_eq!(Algorithm::Zero.wipe_method().unwrap().name(), "zero");
+    assert!(Algorithm::SecureErase.wipe_method().is_none());
+}
+
+#[test]
+fn test_algorithm_scheme_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("client x.toml");
+    let path_str = path.to_str().unwrap();
+    std::fs::write(
+        &path,
+        "name = \"Client X\"\n[[pass]]\ntype = \"fixed\"\nbyte = 0x55\n[[pass]]\ntype = \"complement\"\n",
+    )
+    .unwrap();
+
+    let name = format!("scheme:{}", path_str);
+    let algo = name.parse::<Algorithm>().unwrap();
+    let canonical = std::fs::canonicalize(&path).unwrap();
+    assert_eq!(
+        algo,
+        Algorithm::Method(format!("scheme:{}", canonical.to_str().unwrap()))
+    );
+    let method = algo.wipe_method().unwrap();
+    let source = method.source().unwrap();
+    assert_eq!(source.name, "Client X");
+    assert_eq!(source.sha256.len(), 64);
+    assert_eq!(
+        method.expected_contents(),
+        crate::algorithms::PassSource::Fixed(0xAA)
+    );
+
+    // Loading the same file again is fine, under any spelling of its path;
+    // a changed file is refused
+    assert!(name.parse::<Algorithm>().is_ok());
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    let roundabout = dir.path().join("sub").join("..").join("client x.toml");
+    assert_eq!(
+        format!("scheme:{}", roundabout.to_str().unwrap())
+            .parse::<Algorithm>()
+            .unwrap(),
+        algo
+    );
+    std::fs::write(&path, "name = \"Client X\"\n[[pass]]\ntype = \"random\"\n").unwrap();
+    assert!(name.parse::<Algorithm>().is_err());
+    assert!("scheme:/nonexistent/scheme.toml"
+        .parse::<Algorithm>()
+        .is_err());
+}
+
 #[test]
 fn test_algorithm_dod5220() {
     let algo = Algorithm::DoD5220;
@@ -1,10 +1,36 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use sayonara_wipe::algorithms::{dod::DoDWipe, gutmann::GutmannWipe, random::RandomWipe};
-use sayonara_wipe::crypto::certificates::{CertificateGenerator, VerificationResult, WipeDetails};
+use sayonara_wipe::algorithms::{self, dod::DoDWipe};
+use sayonara_wipe::config::{
+    CliOverrides, ConfigLayer, ConfigLoader, EffectiveConfig, LoadedConfig,
+};
+use sayonara_wipe::crypto::anchor::{AnchorClient, ExpectedAnchor};
+use sayonara_wipe::crypto::bundle::{self, ArchiveFormat, EvidenceStore};
+use sayonara_wipe::crypto::certificates::{
+    attach_timestamp, detached_signature_path, CertificateGenerator, VerificationResult,
+    WipeCertificate, WipeDetails,
+};
+use sayonara_wipe::crypto::cosign;
+use sayonara_wipe::crypto::evidence::{
+    self, HiddenAreaEvidence, MethodEvidence, SchemeEvidence, WipeEvidence,
+};
+use sayonara_wipe::crypto::nist_form::SanitizationForm;
+use sayonara_wipe::crypto::privacy::{self, disclosure_path, Disclosure};
+use sayonara_wipe::crypto::signing::{self, PublicKey, Signer, SigningKey};
+use sayonara_wipe::crypto::timestamp::{TimestampAuthority, TsaClient, TsaServer};
+use sayonara_wipe::crypto::transparency::{SignedTreeHead, TransparencyLog};
+use sayonara_wipe::crypto::x509::TrustStore;
+use sayonara_wipe::crypto::{CertificateFormat, CertificateReport, CertificateVerifier};
+use sayonara_wipe::drives::operations::smart::SMARTHealth;
 use sayonara_wipe::drives::{
-    DriveDetector, FreezeMitigation, HDDWipe, HPADCOManager, NVMeWipe, SEDManager, SMARTMonitor,
-    SSDWipe, TrimOperations,
+    check_pass_verification, plan_sanitization, DriveDetector, DriveIdentity, FreezeMitigation,
+    HDDWipe, HPADCOManager, HpaRemoval, NVMeWipe, OptaneDrive, PendingUndo, ReplayOutcome,
+    SEDManager, SMARTMonitor, SSDWipe, SanitizationDecision, TrimOperations, UndoAction,
+    UndoJournal,
+};
+use sayonara_wipe::error::{BadSectorHandler, CheckpointManager, CheckpointRejection};
+use sayonara_wipe::job::{
+    default_certificate_path, HostEnvironment, JobManifest, JobPlan, PlannedWipe, VerificationMode,
 };
 use sayonara_wipe::verification::recovery_test::RecoveryTest;
 use sayonara_wipe::verification::{
@@ -13,6 +39,7 @@ use sayonara_wipe::verification::{
 };
 use sayonara_wipe::*;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
@@ -31,6 +58,14 @@ struct Cli {
     /// Disable safety checks (DANGEROUS!)
     #[arg(long, global = true)]
     unsafe_mode: bool,
+
+    /// Directory for checkpoints and other persistent state
+    #[arg(long, global = true)]
+    state_dir: Option<String>,
+
+    /// Operator recorded in wipe certificates
+    #[arg(long, global = true, env = "SAYONARA_OPERATOR")]
+    operator: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -51,9 +86,9 @@ enum Commands {
         /// Device path (e.g., /dev/sda)
         device: String,
 
-        /// Wiping algorithm (dod, gutmann, random, zero, secure, crypto, sanitize, trim, auto)
-        #[arg(short, long, default_value = "auto")]
-        algorithm: String,
+        /// Wiping algorithm (dod, gutmann, random, zero, secure, crypto, sanitize, trim, auto, a registered method, or scheme:<file>)
+        #[arg(short, long)]
+        algorithm: Option<String>,
 
         /// Skip verification
         #[arg(long)]
@@ -63,9 +98,26 @@ enum Commands {
         #[arg(short, long)]
         cert_output: Option<String>,
 
+        /// Certificate format (json, cms, cms-detached)
+        #[arg(long)]
+        cert_format: Option<String>,
+
+        /// Commit to the serial, device path and WWN instead of recording them
+        /// (salts go to <certificate>.disclosure.json)
+        #[arg(long)]
+        redact_identifiers: bool,
+
         /// Handle HPA/DCO (ignore, detect, remove-temp, remove-perm)
-        #[arg(long, default_value = "detect")]
-        hpa_dco: String,
+        #[arg(long)]
+        hpa_dco: Option<String>,
+
+        /// NIST SP 800-88 level to reach (clear, purge); refuses drives that cannot
+        #[arg(long)]
+        nist_level: Option<String>,
+
+        /// Read every pass back in full and rewrite blocks that read back wrong
+        #[arg(long)]
+        verify_passes: bool,
 
         /// Skip TRIM after wipe
         #[arg(long)]
@@ -75,9 +127,9 @@ enum Commands {
         #[arg(long)]
         no_temp_check: bool,
 
-        /// Maximum temperature in Celsius
-        #[arg(long, default_value = "65")]
-        max_temp: u32,
+        /// Maximum temperature in Celsius (default: 65)
+        #[arg(long)]
+        max_temp: Option<u32>,
 
         /// Skip freeze mitigation
         #[arg(long)]
@@ -91,8 +143,8 @@ enum Commands {
     /// Wipe ALL drives (EXTREMELY DANGEROUS!)
     WipeAll {
         /// Wiping algorithm
-        #[arg(short, long, default_value = "auto")]
-        algorithm: String,
+        #[arg(short, long)]
+        algorithm: Option<String>,
 
         /// Skip verification
         #[arg(long)]
@@ -102,13 +154,30 @@ enum Commands {
         #[arg(short, long, default_value = "./certificates")]
         cert_dir: String,
 
+        /// Certificate format (json, cms, cms-detached)
+        #[arg(long)]
+        cert_format: Option<String>,
+
+        /// Commit to the serial, device path and WWN instead of recording them
+        /// (salts go to <certificate>.disclosure.json)
+        #[arg(long)]
+        redact_identifiers: bool,
+
         /// Exclude specific drives (comma-separated)
         #[arg(long)]
         exclude: Option<String>,
 
         /// Handle HPA/DCO
-        #[arg(long, default_value = "detect")]
-        hpa_dco: String,
+        #[arg(long)]
+        hpa_dco: Option<String>,
+
+        /// NIST SP 800-88 level to reach (clear, purge); refuses drives that cannot
+        #[arg(long)]
+        nist_level: Option<String>,
+
+        /// Read every pass back in full and rewrite blocks that read back wrong
+        #[arg(long)]
+        verify_passes: bool,
 
         /// Skip TRIM after wipe
         #[arg(long)]
@@ -119,6 +188,16 @@ enum Commands {
         force: bool,
     },
 
+    /// Run a batch job manifest (per-drive selectors and overrides)
+    Run {
+        /// Job manifest (TOML, or JSON with a .json extension)
+        manifest: String,
+
+        /// Validate and print the plan without wiping
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Verify a previous wipe
     Verify {
         /// Device path to verify
@@ -157,14 +236,23 @@ enum Commands {
         /// Device path (e.g., /dev/sda)
         device: String,
 
-        /// Wiping algorithm (dod, gutmann, random, zero, secure, crypto, sanitize, trim, auto)
-        #[arg(short, long, default_value = "auto")]
-        algorithm: String,
+        /// Wiping algorithm (dod, gutmann, random, zero, secure, crypto, sanitize, trim, auto, a registered method, or scheme:<file>)
+        #[arg(short, long)]
+        algorithm: Option<String>,
 
         /// Output certificate path
         #[arg(short, long)]
         cert_output: Option<String>,
 
+        /// Certificate format (json, cms, cms-detached)
+        #[arg(long)]
+        cert_format: Option<String>,
+
+        /// Commit to the serial, device path and WWN instead of recording them
+        /// (salts go to <certificate>.disclosure.json)
+        #[arg(long)]
+        redact_identifiers: bool,
+
         /// Sample percentage for verification (0.1-10.0)
         #[arg(long, default_value = "1.0")]
         sample_percent: f64,
@@ -182,8 +270,16 @@ enum Commands {
         verification_level: String,
 
         /// Handle HPA/DCO (ignore, detect, remove-temp, remove-perm)
-        #[arg(long, default_value = "detect")]
-        hpa_dco: String,
+        #[arg(long)]
+        hpa_dco: Option<String>,
+
+        /// NIST SP 800-88 level to reach (clear, purge); refuses drives that cannot
+        #[arg(long)]
+        nist_level: Option<String>,
+
+        /// Read every pass back in full and rewrite blocks that read back wrong
+        #[arg(long)]
+        verify_passes: bool,
 
         /// Skip TRIM after wipe
         #[arg(long)]
@@ -215,6 +311,353 @@ enum Commands {
     },
 
     Custom,
+
+    /// Inspect the layered configuration (defaults, /etc, user file, env, CLI)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Manage checkpoints of interrupted wipe operations
+    Checkpoint {
+        #[command(subcommand)]
+        action: CheckpointAction,
+    },
+
+    /// Undo drive configuration changes left behind by an interrupted run
+    /// (lifted HPA, temporary ATA password, disabled write cache)
+    Recover {
+        /// Only list the outstanding changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also undo changes owned by a sayonara process that is still running
+        #[arg(long)]
+        force: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Work with saved wipe certificates
+    Cert {
+        #[command(subcommand)]
+        action: CertAction,
+    },
+
+    /// Run an RFC 3161 Time-Stamp Authority
+    Tsa {
+        #[command(subcommand)]
+        action: TsaAction,
+    },
+
+    /// Inspect the transparency log of issued certificates
+    Log {
+        #[command(subcommand)]
+        action: LogAction,
+    },
+
+    /// Bundle the artifacts of a wipe for auditors
+    Evidence {
+        #[command(subcommand)]
+        action: EvidenceAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Show configuration values
+    Show {
+        /// Show every merged value and the layer that set it
+        #[arg(long)]
+        effective: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CheckpointAction {
+    /// List stored checkpoints with their progress
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the full recorded state and configuration of a checkpoint
+    Inspect {
+        /// Checkpoint ID
+        id: String,
+    },
+
+    /// Resume an interrupted wipe from its checkpoint
+    Resume {
+        /// Checkpoint ID
+        id: String,
+
+        /// Certificate output path
+        #[arg(short, long)]
+        cert_output: Option<String>,
+
+        /// Certificate format (json, cms, cms-detached)
+        #[arg(long)]
+        cert_format: Option<String>,
+
+        /// Commit to the serial, device path and WWN instead of recording them
+        /// (salts go to <certificate>.disclosure.json)
+        #[arg(long)]
+        redact_identifiers: bool,
+
+        /// Force operation even if drive is unhealthy
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Delete stale checkpoints
+    Purge {
+        /// Remove checkpoints not updated within this period (e.g. 7d, 12h)
+        #[arg(long, default_value = "7d")]
+        older_than: String,
+
+        /// Remove every checkpoint regardless of age
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CertAction {
+    /// Check a certificate's signature, device hash and (optionally) drive
+    Verify {
+        /// Certificate file (JSON, CMS envelope, or JSON with a .p7s signature)
+        file: String,
+
+        /// Public key for JSON certificates (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Trust anchor for CMS signer chains (repeatable; default: signing.trust_anchors)
+        #[arg(long)]
+        trust_anchor: Vec<String>,
+
+        /// Trust anchor for time-stamp tokens (repeatable; default: signing.tsa_trust_anchors)
+        #[arg(long)]
+        tsa_trust_anchor: Vec<String>,
+
+        /// Detached CMS signature (default: <file>.p7s when present)
+        #[arg(long)]
+        signature: Option<String>,
+
+        /// Also check that this drive's model, serial and size match
+        #[arg(long)]
+        device: Option<String>,
+
+        /// Also check the blockchain anchor through this Ethereum JSON-RPC endpoint
+        #[arg(long)]
+        anchor_rpc: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Add an RFC 3161 timestamp to a saved JSON certificate
+    Timestamp {
+        /// Certificate file (JSON)
+        file: String,
+
+        /// Time-Stamp Authority URL (default: signing.tsa_url)
+        #[arg(long)]
+        tsa_url: Option<String>,
+    },
+
+    /// Countersign a saved JSON certificate, e.g. as the supervisor who
+    /// witnessed the wipe
+    Cosign {
+        /// Certificate file (JSON)
+        file: String,
+
+        /// Role to sign as
+        #[arg(long, default_value = cosign::DEFAULT_COSIGNER_ROLE)]
+        role: String,
+
+        /// Cosigner's private key file, PKCS#8 (default: the configured signing key)
+        #[arg(long)]
+        key: Option<String>,
+
+        /// Name of the person signing (default: --operator)
+        #[arg(long)]
+        signer: Option<String>,
+
+        /// Public key of the issuing signature (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+    },
+
+    /// Anchor saved JSON certificates on chain (several go in one batch)
+    Anchor {
+        /// Certificate files (JSON)
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Ethereum JSON-RPC endpoint (default: anchor.rpc_url)
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// WipeCertificate contract address (default: anchor.contract)
+        #[arg(long)]
+        contract: Option<String>,
+
+        /// Issuer's secp256k1 private key file, hex (default: anchor.key_path)
+        #[arg(long)]
+        key: Option<String>,
+    },
+
+    /// Print the configured signing key's public key (PEM) for verifiers
+    PublicKey {
+        /// Write it to this file instead of standard output
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Prove what a redacted certificate covers, from its disclosure file
+    Disclose {
+        /// Certificate file (JSON, CMS envelope, or JSON with a .p7s signature)
+        file: String,
+
+        /// Disclosure file (default: <file>.disclosure.json)
+        #[arg(long)]
+        disclosure: Option<String>,
+
+        /// Also require the certificate to cover this serial number
+        #[arg(long)]
+        serial: Option<String>,
+
+        /// Public key for JSON certificates (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Render a certificate as a NIST SP 800-88 Appendix G Certificate of Sanitization
+    Render {
+        /// Certificate file (JSON, CMS envelope, or JSON with a .p7s signature)
+        file: String,
+
+        /// Output format (html, pdf; default: from --output, else html)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Output file (default: the certificate path with a .html or .pdf extension)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Public key for JSON certificates (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LogAction {
+    /// Show and check the current signed tree head
+    Head {
+        /// Public key the log signs with (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Prove a certificate is included under the current tree head
+    Prove {
+        /// Certificate file (JSON or CMS envelope)
+        file: String,
+
+        /// Public key the log signs with (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Output the proof as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check that an earlier tree head is a prefix of the current one
+    Consistency {
+        /// Earlier tree head, as saved from `log head --json`
+        old_head: String,
+
+        /// Public key the log signs with (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Output the proof as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum EvidenceAction {
+    /// Export a certificate's artifacts with a signed manifest as one archive
+    Export {
+        /// Certificate ID
+        cert_id: String,
+
+        /// Archive format (tar, zip; default: from --output, else tar)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Output file (default: sayonara-evidence-<cert-id>.tar or .zip)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Check that a bundle is complete, unmodified and signed
+    Verify {
+        /// Bundle file (tar or zip)
+        bundle: String,
+
+        /// Public key the manifest and certificate are signed with (default: the configured signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum TsaAction {
+    /// Answer time-stamp requests over HTTP
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:3161")]
+        listen: String,
+
+        /// TSA private key (PKCS#8 PEM or DER)
+        #[arg(long)]
+        key: String,
+
+        /// TSA certificate (PEM, extended key usage timeStamping), then intermediates
+        #[arg(long)]
+        certificate: String,
+
+        /// Policy OID stamped into every token
+        #[arg(long)]
+        policy: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -239,22 +682,14 @@ enum SedAction {
 /// Generate enhanced certificate with verification details
 fn generate_enhanced_certificate(
     drive_info: &DriveInfo,
-    config: &WipeConfig,
+    wipe_details: WipeDetails,
     verification_report: &VerificationReport,
-    duration: Duration,
+    evidence: WipeEvidence,
     cert_path: &str,
-) -> Result<()> {
-    use crate::crypto::certificates::{CertificateGenerator, VerificationResult, WipeDetails};
+) -> Result<(WipeCertificate, Option<Disclosure>)> {
+    use crate::crypto::certificates::{CertificateGenerator, VerificationResult};
 
-    let cert_gen = CertificateGenerator::new();
-
-    // Create enhanced wipe details
-    let wipe_details = WipeDetails {
-        algorithm_used: format!("{:?}", config.algorithm),
-        passes_completed: 1,
-        duration_seconds: duration.as_secs(),
-        operator_id: None,
-    };
+    let cert_gen = CertificateGenerator::configured()?;
 
     // Create enhanced verification result
     let verification_result = VerificationResult {
@@ -264,18 +699,34 @@ fn generate_enhanced_certificate(
         verification_timestamp: verification_report.timestamp,
     };
 
-    let certificate =
-        cert_gen.generate_certificate(drive_info, wipe_details, verification_result)?;
+    let evidence = evidence.with_verification_report(verification_report.clone());
+    let (certificate, disclosure) = cert_gen.generate_certificate_with_disclosure(
+        drive_info,
+        wipe_details,
+        verification_result,
+        Some(evidence),
+    )?;
 
-    // Add enhanced verification data to certificate
+    // Add enhanced verification data to certificate (unsigned; the signed
+    // copy is in the evidence)
     let mut enhanced_cert = serde_json::to_value(&certificate)?;
-    enhanced_cert["enhanced_verification"] = serde_json::to_value(verification_report)?;
+    let mut enhanced_verification = serde_json::to_value(verification_report)?;
+    if let Some(disclosure) = &disclosure {
+        privacy::redact_json(&mut enhanced_verification, &disclosure.values());
+    }
+    enhanced_cert["enhanced_verification"] = enhanced_verification;
 
     // Save enhanced certificate
-    let cert_json = serde_json::to_string_pretty(&enhanced_cert)?;
-    std::fs::write(cert_path, cert_json)?;
+    cert_gen.save_document(&enhanced_cert, cert_path)?;
+    if cert_gen.format() == CertificateFormat::CmsDetached {
+        println!(
+            "   Detached CMS signature: {}",
+            detached_signature_path(cert_path)
+        );
+    }
+    save_disclosure(disclosure.as_ref(), cert_path)?;
 
-    Ok(())
+    Ok((certificate, disclosure))
 }
 
 #[tokio::main]
@@ -285,7 +736,24 @@ async fn main() -> Result<()> {
     setup_signal_handlers()?;
 
     // Check for root privileges
-    if !cli.unsafe_mode && !is_root() {
+    let needs_root = !matches!(
+        cli.command,
+        Commands::Config { .. }
+            | Commands::Custom
+            | Commands::Cert {
+                action: CertAction::Verify { device: None, .. }
+                    | CertAction::Timestamp { .. }
+                    | CertAction::Cosign { .. }
+                    | CertAction::Anchor { .. }
+                    | CertAction::PublicKey { .. }
+                    | CertAction::Render { .. }
+                    | CertAction::Disclose { .. }
+            }
+            | Commands::Tsa { .. }
+            | Commands::Log { .. }
+            | Commands::Evidence { .. }
+    );
+    if needs_root && !cli.unsafe_mode && !is_root() {
         eprintln!("Error: This program requires root privileges.");
         eprintln!("Please run with sudo or as root user.");
         std::process::exit(1);
@@ -296,6 +764,19 @@ async fn main() -> Result<()> {
         env_logger::init();
     }
 
+    // Merge system/user config files, SAYONARA_* env vars and explicit flags
+    let mut overrides = command_overrides(&cli.command)?;
+    overrides.state_dir = cli.state_dir.as_ref().map(std::path::PathBuf::from);
+    let loaded = ConfigLoader::new().with_overrides(overrides).load()?;
+    sayonara_wipe::config::install(loaded.config.clone());
+    let effective = &loaded.config;
+
+    // Undo whatever an interrupted run left changed on the drives before
+    // touching them again
+    if needs_root && is_root() && !matches!(cli.command, Commands::Recover { .. }) {
+        replay_undo_journal();
+    }
+
     match &cli.command {
         Commands::List {
             detailed,
@@ -305,54 +786,41 @@ async fn main() -> Result<()> {
         }
         Commands::Wipe {
             device,
-            algorithm,
-            no_verify,
             cert_output,
-            hpa_dco,
-            no_trim,
-            no_temp_check,
-            max_temp,
-            no_unfreeze,
             force,
+            ..
         } => {
-            let config = build_wipe_config(
-                algorithm,
-                !no_verify,
-                hpa_dco,
-                !no_trim,
-                !no_temp_check,
-                *max_temp,
-                !no_unfreeze,
-            )?;
             wipe_drive(
                 device,
-                config,
+                effective.wipe.clone(),
+                &effective.safety,
                 cert_output.as_deref(),
                 *force,
                 cli.unsafe_mode,
+                cli.operator.as_deref(),
             )
             .await?;
         }
         Commands::WipeAll {
-            algorithm,
-            no_verify,
             cert_dir,
             exclude,
-            hpa_dco,
-            no_trim,
             force,
+            ..
         } => {
-            let config =
-                build_wipe_config(algorithm, !no_verify, hpa_dco, !no_trim, true, 65, true)?;
             wipe_all_drives(
-                config,
+                effective.wipe.clone(),
+                &effective.safety,
                 cert_dir,
                 exclude.as_deref(),
                 cli.unsafe_mode,
                 *force,
+                cli.operator.as_deref(),
             )
             .await?;
         }
+        Commands::Run { manifest, dry_run } => {
+            run_job(manifest, effective, cli.unsafe_mode, *dry_run).await?;
+        }
         Commands::Verify {
             device,
             check_hidden,
@@ -371,15 +839,13 @@ async fn main() -> Result<()> {
         }
         Commands::EnhancedWipe {
             device,
-            algorithm,
             cert_output,
             sample_percent,
             skip_pre_tests,
             min_confidence,
             verification_level,
-            hpa_dco,
-            no_trim,
             force,
+            ..
         } => {
             let drives = DriveDetector::detect_all_drives()?;
             let drive_info = drives
@@ -422,12 +888,8 @@ async fn main() -> Result<()> {
                 }
             };
 
-            // Build config
-            let config = build_wipe_config(
-                algorithm, true, // Always verify in enhanced mode
-                hpa_dco, !no_trim, true, // Temperature monitoring
-                65, true, // Freeze mitigation
-            )?;
+            // Layered config (verification is always forced on in enhanced mode)
+            let config = effective.wipe.clone();
 
             // Safety confirmation with level info
             if !cli.unsafe_mode {
@@ -480,6 +942,8 @@ async fn main() -> Result<()> {
                     min_confidence: *min_confidence,
                     verification_level: level,
                     skip_pre_tests: *skip_pre_tests,
+                    operator_id: cli.operator.clone(),
+                    interactive: true,
                 },
             )
             .await?;
@@ -551,33 +1015,254 @@ async fn main() -> Result<()> {
         Commands::Custom => {
             print_customizations()?;
         }
-    }
-
-    Ok(())
-}
-
-/// Verification options for enhanced wipe
-struct VerificationOptions<'a> {
-    cert_output: Option<&'a str>,
-    _sample_percent: f64,
-    min_confidence: f64,
-    verification_level: VerificationLevel,
-    skip_pre_tests: bool,
-}
-
-/// Enhanced wipe with multi-level verification
-async fn enhanced_wipe_with_verification(
-    device: &str,
-    drive_info: &DriveInfo,
-    config: WipeConfig,
+        Commands::Config { action } => match action {
+            ConfigAction::Show { effective, json } => {
+                show_config(&loaded, *effective, *json)?;
+            }
+        },
+        Commands::Checkpoint { action } => {
+            handle_checkpoint(
+                action,
+                &effective.safety,
+                cli.unsafe_mode,
+                cli.operator.as_deref(),
+            )
+            .await?;
+        }
+        Commands::Recover {
+            dry_run,
+            force,
+            json,
+        } => {
+            recover(*dry_run, *force, *json)?;
+        }
+        Commands::Cert { action } => match action {
+            CertAction::Verify {
+                file,
+                public_key,
+                trust_anchor,
+                tsa_trust_anchor,
+                signature,
+                device,
+                anchor_rpc,
+                json,
+            } => {
+                let mut verifier = CertificateVerifier::from_config(&effective.signing)?;
+                if let Some(url) = anchor_rpc {
+                    verifier = verifier.with_anchor_rpc(url);
+                    if let Some(expected) = ExpectedAnchor::from_config(&effective.anchor)? {
+                        verifier = verifier.with_expected_anchor(expected);
+                    }
+                }
+                if let Some(path) = public_key {
+                    verifier = verifier.with_public_key(PublicKey::load(Path::new(path))?);
+                }
+                if !trust_anchor.is_empty() {
+                    let paths: Vec<PathBuf> = trust_anchor.iter().map(PathBuf::from).collect();
+                    verifier = verifier.with_trust(TrustStore::load(&paths)?);
+                }
+                if !tsa_trust_anchor.is_empty() {
+                    let paths: Vec<PathBuf> = tsa_trust_anchor.iter().map(PathBuf::from).collect();
+                    verifier = verifier.with_tsa_trust(TrustStore::load(&paths)?);
+                }
+                verify_certificate_file(
+                    &verifier,
+                    file,
+                    signature.as_deref(),
+                    device.as_deref(),
+                    *json,
+                )?;
+            }
+            CertAction::Disclose {
+                file,
+                disclosure,
+                serial,
+                public_key,
+                json,
+            } => {
+                let mut verifier = CertificateVerifier::from_config(&effective.signing)?;
+                if let Some(path) = public_key {
+                    verifier = verifier.with_public_key(PublicKey::load(Path::new(path))?);
+                }
+                disclose_certificate(
+                    &verifier,
+                    file,
+                    disclosure.as_deref(),
+                    serial.as_deref(),
+                    *json,
+                )?;
+            }
+            CertAction::Render {
+                file,
+                format,
+                output,
+                public_key,
+            } => {
+                let mut verifier = CertificateVerifier::from_config(&effective.signing)?;
+                if let Some(path) = public_key {
+                    verifier = verifier.with_public_key(PublicKey::load(Path::new(path))?);
+                }
+                render_certificate_form(&verifier, file, format.as_deref(), output.as_deref())?;
+            }
+            CertAction::Timestamp { file, tsa_url } => {
+                let mut signing = effective.signing.clone();
+                if tsa_url.is_some() {
+                    signing.tsa_url = tsa_url.clone();
+                }
+                let client = TsaClient::from_config(&signing)?.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No Time-Stamp Authority: pass --tsa-url or set signing.tsa_url"
+                    )
+                })?;
+                timestamp_certificate_file(file, &client)?;
+            }
+            CertAction::Cosign {
+                file,
+                role,
+                key,
+                signer,
+                public_key,
+            } => {
+                let mut verifier = CertificateVerifier::from_config(&effective.signing)?;
+                if let Some(path) = public_key {
+                    verifier = verifier.with_public_key(PublicKey::load(Path::new(path))?);
+                }
+                let key: Box<dyn Signer> = match key {
+                    Some(path) => Box::new(SigningKey::load(Path::new(path))?),
+                    None => signing::signer_from_config(&effective.signing)?,
+                };
+                let name = signer.as_deref().or(cli.operator.as_deref());
+                cosign_certificate_file(&verifier, file, role, name, key.as_ref())?;
+            }
+            CertAction::Anchor {
+                files,
+                rpc_url,
+                contract,
+                key,
+            } => {
+                let mut config = effective.anchor.clone();
+                if rpc_url.is_some() {
+                    config.rpc_url = rpc_url.clone();
+                }
+                if contract.is_some() {
+                    config.contract = contract.clone();
+                }
+                if let Some(key) = key {
+                    config.key_path = Some(PathBuf::from(key));
+                }
+                let client = AnchorClient::from_config(&config)?.ok_or_else(|| {
+                    anyhow::anyhow!("No Ethereum node: pass --rpc-url or set anchor.rpc_url")
+                })?;
+                anchor_certificate_files(files, &client)?;
+            }
+            CertAction::PublicKey { output } => {
+                let key = PublicKey::from_config(&effective.signing)?;
+                match output {
+                    Some(path) => {
+                        std::fs::write(path, key.to_pem())
+                            .with_context(|| format!("Failed to write {}", path))?;
+                        println!("✅ {} public key written to {}", key.algorithm(), path);
+                        println!("   Fingerprint: {}", key.fingerprint());
+                    }
+                    None => print!("{}", key.to_pem()),
+                }
+            }
+        },
+        Commands::Tsa { action } => match action {
+            TsaAction::Serve {
+                listen,
+                key,
+                certificate,
+                policy,
+            } => {
+                let authority =
+                    TimestampAuthority::load(Path::new(key), Path::new(certificate), policy)?;
+                let server = TsaServer::bind(authority, listen)?;
+                println!("🕒 Time-Stamp Authority \"{}\"", server.authority().name());
+                println!("   Policy:    {}", server.authority().policy());
+                println!("   Listening: http://{}/", listen);
+                tokio::task::spawn_blocking(move || server.run()).await??;
+            }
+        },
+        Commands::Log { action } => {
+            let log = open_transparency_log()?;
+            let key_path = match action {
+                LogAction::Head { public_key, .. }
+                | LogAction::Prove { public_key, .. }
+                | LogAction::Consistency { public_key, .. } => public_key,
+            };
+            let key = match key_path {
+                Some(path) => PublicKey::load(Path::new(path))?,
+                None => PublicKey::from_config(&effective.signing)?,
+            };
+            match action {
+                LogAction::Head { json, .. } => show_log_head(&log, &key, *json)?,
+                LogAction::Prove { file, json, .. } => {
+                    let verifier = CertificateVerifier::from_config(&effective.signing)?;
+                    prove_certificate_inclusion(&log, &key, &verifier, file, *json)?;
+                }
+                LogAction::Consistency { old_head, json, .. } => {
+                    check_log_consistency(&log, &key, old_head, *json)?;
+                }
+            }
+        }
+        Commands::Evidence { action } => match action {
+            EvidenceAction::Export {
+                cert_id,
+                format,
+                output,
+            } => {
+                export_evidence_bundle(effective, cert_id, format.as_deref(), output.as_deref())?;
+            }
+            EvidenceAction::Verify {
+                bundle,
+                public_key,
+                json,
+            } => {
+                let key = match public_key {
+                    Some(path) => PublicKey::load(Path::new(path))?,
+                    None => PublicKey::from_config(&effective.signing)?,
+                };
+                let verifier = CertificateVerifier::from_config(&effective.signing)?
+                    .with_public_key(key.clone());
+                verify_evidence_bundle(&key, &verifier, bundle, *json)?;
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Verification options for enhanced wipe
+struct VerificationOptions<'a> {
+    cert_output: Option<&'a str>,
+    _sample_percent: f64,
+    min_confidence: f64,
+    verification_level: VerificationLevel,
+    skip_pre_tests: bool,
+    operator_id: Option<String>,
+    /// Ask on the terminal before going on past failed pre-wipe tests;
+    /// unattended runs fail the drive instead
+    interactive: bool,
+}
+
+/// Enhanced wipe with multi-level verification
+///
+/// Returns whether the forensic verification met the required confidence
+async fn enhanced_wipe_with_verification(
+    device: &str,
+    drive_info: &DriveInfo,
+    config: WipeConfig,
     options: VerificationOptions<'_>,
-) -> Result<()> {
+) -> Result<bool> {
     let VerificationOptions {
         cert_output,
         _sample_percent,
         min_confidence,
         verification_level,
         skip_pre_tests,
+        operator_id,
+        interactive,
     } = options;
     println!("\n🚀 Starting Enhanced Secure Wipe with Forensic Verification");
     println!(
@@ -588,7 +1273,13 @@ async fn enhanced_wipe_with_verification(
     println!("Verification Level: {:?}", verification_level);
     println!("{}", "=".repeat(70));
 
+    // Refuse a drive that cannot reach the requested NIST level, or whose
+    // passes cannot be read back as asked, before writing anything to it
+    plan_sanitization(drive_info, &config)?;
+    check_pass_verification(drive_info, &config)?;
+
     let start_time = Instant::now();
+    let smart_snapshot = SMARTMonitor::get_health(device).ok();
 
     // ===== STAGE 1: PRE-WIPE VERIFICATION CAPABILITY TEST =====
     let pre_wipe_results = if !skip_pre_tests {
@@ -633,6 +1324,9 @@ async fn enhanced_wipe_with_verification(
 
         if !results.test_pattern_detection || !results.recovery_tool_simulation {
             eprintln!("\n⚠️  Warning: Verification system tests failed!");
+            if !interactive {
+                anyhow::bail!("Pre-wipe verification system tests failed");
+            }
             print!("Do you want to continue anyway? [y/N]: ");
             io::stdout().flush()?;
 
@@ -662,7 +1356,8 @@ async fn enhanced_wipe_with_verification(
 
     // Execute the wipe
     println!("  └─ Executing wipe algorithm...");
-    select_and_execute_wipe(device, drive_info, &config).await?;
+    evidence::clear_recorded(device);
+    let method = select_and_execute_wipe(device, drive_info, &config).await?;
 
     let wipe_duration = start_time.elapsed();
     println!(
@@ -761,16 +1456,41 @@ async fn enhanced_wipe_with_verification(
     }
 
     // ===== STAGE 5: CERTIFICATE GENERATION =====
+    let checkpoint_rejections = take_checkpoint_rejections(device);
+    let wipe_evidence = WipeEvidence::collect(
+        drive_info,
+        &config,
+        method,
+        HiddenAreaEvidence::unchecked(config.handle_hpa_dco.clone()),
+        None,
+        Vec::new(),
+    );
+    warn_if_non_compliant(&wipe_evidence);
     if let Some(cert_path) = cert_output {
         println!("\n🏆 Stage 5: Generating Enhanced Certificate");
-        generate_enhanced_certificate(
+        let wipe_details = WipeDetails {
+            algorithm_used: algorithm_name(&config.algorithm),
+            passes_completed: wipe_evidence.passes.len().max(1) as u32,
+            duration_seconds: wipe_duration.as_secs(),
+            operator_id,
+            checkpoint_rejections,
+        };
+        let (certificate, disclosure) = generate_enhanced_certificate(
             drive_info,
-            &config,
+            wipe_details,
             &verification_report,
-            wipe_duration,
+            wipe_evidence,
             cert_path,
         )?;
         println!("✅ Certificate saved to: {}", cert_path);
+        keep_wipe_evidence(
+            &certificate,
+            disclosure.as_ref(),
+            device,
+            cert_path,
+            smart_snapshot.as_ref(),
+            &verification_report,
+        );
     }
 
     // ===== STAGE 6: HEAT MAP VISUALIZATION =====
@@ -825,7 +1545,7 @@ async fn enhanced_wipe_with_verification(
         start_time.elapsed().as_secs_f64()
     );
 
-    Ok(())
+    Ok(verification_report.confidence_level >= min_confidence)
 }
 
 /// Display enhanced post-wipe analysis results
@@ -1068,6 +1788,11 @@ fn print_customizations() -> Result<()> {
     println!("  sanitize   - NVMe sanitize command");
     println!("  trim       - TRIM/discard only (for SSDs)");
     println!("  auto       - Automatically select best algorithm (default)");
+    for method in algorithms::registry::registry().methods() {
+        if !matches!(method.name(), "dod" | "gutmann" | "random" | "zero") {
+            println!("  {:<10} - {}", method.name(), method.description());
+        }
+    }
 
     // HPA/DCO HANDLING
     println!("\n🔒 HPA/DCO (Hidden Protected Area / Device Configuration Overlay) HANDLING");
@@ -1082,6 +1807,16 @@ fn print_customizations() -> Result<()> {
     println!("{}", "-".repeat(80));
     println!("  --debug       - Enable verbose debug logging");
     println!("  --unsafe-mode - Disable all safety checks (EXTREMELY DANGEROUS)");
+    println!("  --state-dir   - Directory for checkpoints (default: /var/lib/sayonara-wipe)");
+
+    // CONFIGURATION FILES
+    println!("\n🗂️  CONFIGURATION LAYERS (later layers win)");
+    println!("{}", "-".repeat(80));
+    println!("  1. Built-in defaults");
+    println!("  2. System file:  /etc/sayonara/config.toml");
+    println!("  3. User file:    ~/.config/sayonara/config.toml");
+    println!("  4. Environment:  SAYONARA_<SECTION>__<KEY> (e.g. SAYONARA_WIPE__MAX_TEMPERATURE_CELSIUS=60)");
+    println!("  5. Command-line flags");
 
     // COMMAND-SPECIFIC FLAGS
     println!("\n⚙️  COMMAND-SPECIFIC CUSTOMIZATIONS");
@@ -1111,6 +1846,10 @@ fn print_customizations() -> Result<()> {
     println!("    --no-trim          - Skip TRIM operations");
     println!("    --force            - Force operation on unhealthy drives");
 
+    println!("\n  RUN Command:");
+    println!("    <manifest>         - Job manifest with [[drive]] selectors (serial, wwn, model, by_path)");
+    println!("    --dry-run          - Validate the manifest and print the plan only");
+
     println!("\n  VERIFY Command:");
     println!("    --check-hidden     - Check for hidden areas (HPA/DCO)");
 
@@ -1135,6 +1874,27 @@ fn print_customizations() -> Result<()> {
     println!("    --no-trim            - Skip TRIM operation");
     println!("    --force              - Force operation on unhealthy drives");
 
+    println!("\n  CONFIG Command:");
+    println!("    show               - Show values overriding the built-in defaults");
+    println!("      --effective      - Show every merged value and the layer that set it");
+    println!("      --json           - Output as JSON");
+
+    println!("\n  CHECKPOINT Command:");
+    println!("    list               - List interrupted operations and their progress");
+    println!("      --json           - Output as JSON");
+    println!("    inspect <id>       - Show full recorded state and configuration");
+    println!("    resume <id>        - Resume with the recorded configuration");
+    println!("      -c, --cert-output - Certificate output path");
+    println!("      --force          - Force operation on unhealthy drives");
+    println!("    purge              - Delete stale checkpoints");
+    println!("      --older-than     - Age threshold (default: 7d)");
+    println!("      --all            - Delete every checkpoint");
+
+    println!("\n  RECOVER Command:");
+    println!("    Undo HPA, ATA password and write cache changes left by an interrupted run");
+    println!("    --dry-run            - Only list the outstanding changes");
+    println!("    --json               - Output as JSON");
+
     println!("\n  LIVE-VERIFY Command:");
     println!("    --report-to          - Remote endpoint for verification report");
     println!("    --sample-percent     - Verification sampling percentage (default: 1.0)");
@@ -1215,7 +1975,7 @@ fn print_customizations() -> Result<()> {
     println!("\n🌡️  TEMPERATURE MANAGEMENT");
     println!("{}", "-".repeat(80));
     println!("  - Default max temperature: 65°C");
-    println!("  - Configurable via --max-temp flag");
+    println!("  - Configurable via --max-temp flag or wipe.max_temperature_celsius");
     println!("  - Automatic pausing when temperature exceeds threshold");
     println!("  - Continuous monitoring during operations");
     println!("  - Warning/Critical threshold detection");
@@ -1244,6 +2004,16 @@ fn print_customizations() -> Result<()> {
     println!("  Wipe with certificate and custom temperature:");
     println!("    sudo sayonara-wipe wipe /dev/sdb -c cert.json --max-temp 70");
     println!();
+    println!("  Batch job from a manifest:");
+    println!("    sudo sayonara-wipe run job.toml --dry-run");
+    println!();
+    println!("  Resume an interrupted wipe:");
+    println!("    sudo sayonara-wipe checkpoint list");
+    println!("    sudo sayonara-wipe checkpoint resume <id>");
+    println!();
+    println!("  Restore drive settings after a crash or power loss:");
+    println!("    sudo sayonara-wipe recover --dry-run");
+    println!();
     println!("  List all drives with details:");
     println!("    sudo sayonara-wipe list --detailed");
     println!();
@@ -1265,49 +2035,159 @@ fn is_root() -> bool {
     unsafe { libc::geteuid() == 0 }
 }
 
-fn build_wipe_config(
-    algorithm: &str,
-    verify: bool,
-    hpa_dco: &str,
-    use_trim: bool,
-    temp_monitoring: bool,
-    max_temp: u32,
-    freeze_mitigation: bool,
-) -> Result<WipeConfig> {
-    let algorithm = match algorithm.to_lowercase().as_str() {
-        "dod" => Algorithm::DoD5220,
-        "gutmann" => Algorithm::Gutmann,
-        "random" => Algorithm::Random,
-        "zero" => Algorithm::Zero,
-        "secure" => Algorithm::SecureErase,
-        "crypto" => Algorithm::CryptoErase,
-        "sanitize" => Algorithm::Sanitize,
-        "trim" => Algorithm::TrimOnly,
-        "auto" => Algorithm::SecureErase, // Will fallback based on capabilities
-        _ => return Err(anyhow::anyhow!("Unknown algorithm: {}", algorithm)),
-    };
+/// Collect the wipe settings passed explicitly on the command line
+///
+/// Flags left at their defaults are not overrides, so values pinned in
+/// /etc/sayonara/config.toml or SAYONARA_* variables still apply.
+fn command_overrides(command: &Commands) -> Result<CliOverrides> {
+    let mut overrides = CliOverrides::default();
 
-    let hpa_dco_handling = match hpa_dco {
-        "ignore" => HPADCOHandling::Ignore,
-        "detect" => HPADCOHandling::Detect,
-        "remove-temp" => HPADCOHandling::TemporaryRemove,
-        "remove-perm" => HPADCOHandling::PermanentRemove,
-        _ => HPADCOHandling::Detect,
-    };
+    match command {
+        Commands::Wipe {
+            algorithm,
+            no_verify,
+            hpa_dco,
+            no_trim,
+            no_temp_check,
+            max_temp,
+            no_unfreeze,
+            nist_level,
+            verify_passes,
+            cert_format,
+            redact_identifiers,
+            ..
+        } => {
+            overrides.algorithm = algorithm.as_deref().map(parse_algorithm).transpose()?;
+            overrides.nist_level = nist_level.as_deref().map(str::parse).transpose()?;
+            overrides.verify_passes = verify_passes.then_some(true);
+            overrides.verify = no_verify.then_some(false);
+            overrides.handle_hpa_dco = hpa_dco.as_deref().map(str::parse).transpose()?;
+            overrides.use_trim_after = no_trim.then_some(false);
+            overrides.temperature_monitoring = no_temp_check.then_some(false);
+            overrides.max_temperature_celsius = *max_temp;
+            overrides.freeze_mitigation = no_unfreeze.then_some(false);
+            overrides.cert_format = parse_cert_format(cert_format)?;
+            overrides.redact_identifiers = redact_identifiers.then_some(true);
+        }
+        Commands::WipeAll {
+            algorithm,
+            no_verify,
+            hpa_dco,
+            no_trim,
+            nist_level,
+            verify_passes,
+            cert_format,
+            redact_identifiers,
+            ..
+        } => {
+            overrides.algorithm = algorithm.as_deref().map(parse_algorithm).transpose()?;
+            overrides.nist_level = nist_level.as_deref().map(str::parse).transpose()?;
+            overrides.verify_passes = verify_passes.then_some(true);
+            overrides.verify = no_verify.then_some(false);
+            overrides.handle_hpa_dco = hpa_dco.as_deref().map(str::parse).transpose()?;
+            overrides.use_trim_after = no_trim.then_some(false);
+            overrides.cert_format = parse_cert_format(cert_format)?;
+            overrides.redact_identifiers = redact_identifiers.then_some(true);
+        }
+        Commands::EnhancedWipe {
+            algorithm,
+            hpa_dco,
+            no_trim,
+            nist_level,
+            verify_passes,
+            cert_format,
+            redact_identifiers,
+            ..
+        } => {
+            overrides.algorithm = algorithm.as_deref().map(parse_algorithm).transpose()?;
+            overrides.nist_level = nist_level.as_deref().map(str::parse).transpose()?;
+            overrides.verify_passes = verify_passes.then_some(true);
+            overrides.verify = Some(true); // Always verify in enhanced mode
+            overrides.handle_hpa_dco = hpa_dco.as_deref().map(str::parse).transpose()?;
+            overrides.use_trim_after = no_trim.then_some(false);
+            overrides.cert_format = parse_cert_format(cert_format)?;
+            overrides.redact_identifiers = redact_identifiers.then_some(true);
+        }
+        Commands::Checkpoint {
+            action:
+                CheckpointAction::Resume {
+                    cert_format,
+                    redact_identifiers,
+                    ..
+                },
+        } => {
+            overrides.cert_format = parse_cert_format(cert_format)?;
+            overrides.redact_identifiers = redact_identifiers.then_some(true);
+        }
+        _ => {}
+    }
 
-    Ok(WipeConfig {
-        algorithm,
-        verify,
-        multiple_passes: None,
-        preserve_partition_table: false,
-        unlock_encrypted: false,
-        handle_hpa_dco: hpa_dco_handling,
-        use_trim_after: use_trim,
-        temperature_monitoring: temp_monitoring,
-        max_temperature_celsius: Some(max_temp),
-        freeze_mitigation,
-        sed_crypto_erase: true,
-    })
+    Ok(overrides)
+}
+
+fn parse_algorithm(algorithm: &str) -> Result<Algorithm> {
+    algorithm.parse()
+}
+
+fn parse_cert_format(format: &Option<String>) -> Result<Option<CertificateFormat>> {
+    format.as_deref().map(str::parse).transpose()
+}
+
+fn show_config(loaded: &LoadedConfig, effective: bool, json: bool) -> Result<()> {
+    // Without --effective only values changed from the built-in defaults are shown
+    let entries: Vec<_> = loaded
+        .entries()?
+        .into_iter()
+        .filter(|(_, _, layer)| effective || *layer != ConfigLayer::Default)
+        .collect();
+
+    if json {
+        let values: serde_json::Map<String, serde_json::Value> = entries
+            .into_iter()
+            .map(|(key, value, layer)| (key, serde_json::json!({ "value": value, "layer": layer })))
+            .collect();
+        let files: Vec<_> = loaded
+            .files
+            .iter()
+            .map(|(layer, path)| serde_json::json!({ "layer": layer, "path": path }))
+            .collect();
+
+        let output = serde_json::json!({ "files": files, "values": values });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("Configuration files:");
+    if loaded.files.is_empty() {
+        println!(
+            "  (none found, checked {})",
+            sayonara_wipe::config::SYSTEM_CONFIG_PATH
+        );
+    }
+    for (layer, path) in &loaded.files {
+        println!("  {:<8} {}", layer, path.display());
+    }
+
+    if entries.is_empty() {
+        println!("\nAll values are at their built-in defaults.");
+        println!("Use --effective to show the full merged configuration.");
+        return Ok(());
+    }
+
+    println!(
+        "\n{}",
+        if effective {
+            "Effective configuration:"
+        } else {
+            "Values overriding built-in defaults:"
+        }
+    );
+    println!("  {:<42} {:<24} LAYER", "KEY", "VALUE");
+    for (key, value, layer) in entries {
+        println!("  {:<42} {:<24} {}", key, value.to_string(), layer);
+    }
+
+    Ok(())
 }
 
 async fn list_drives(detailed: bool, include_system: bool) -> Result<()> {
@@ -1428,9 +2308,11 @@ fn print_drive_detailed(drive: &DriveInfo) -> Result<()> {
 async fn wipe_drive(
     device: &str,
     config: WipeConfig,
+    safety: &SafetyConfig,
     cert_output: Option<&str>,
     force: bool,
     unsafe_mode: bool,
+    operator: Option<&str>,
 ) -> Result<()> {
     // Detect the specific drive
     let drives = DriveDetector::detect_all_drives()?;
@@ -1439,7 +2321,7 @@ async fn wipe_drive(
         .find(|d| d.device_path == device)
         .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", device))?;
 
-    // Safety checks
+    // Safety checks; only --unsafe-mode skips these, never configuration
     if !unsafe_mode {
         if DriveDetector::is_system_drive(device)? {
             eprintln!("Error: {} appears to be a system drive.", device);
@@ -1471,8 +2353,13 @@ async fn wipe_drive(
         }
     }
 
+    // Refuse a drive that cannot reach the requested NIST level before
+    // asking for confirmation
+    plan_sanitization(&drive_info, &config)?;
+    check_pass_verification(&drive_info, &config)?;
+
     // Confirmation
-    if !unsafe_mode {
+    if !unsafe_mode && safety.require_confirmation {
         println!(
             "\nWARNING: This will permanently erase ALL data on {}",
             device
@@ -1509,13 +2396,16 @@ async fn wipe_drive(
         end_time: None,
         drives: vec![],
         config: config.clone(),
-        operator_id: None,
+        operator_id: operator.map(str::to_string),
     };
 
     // Perform the wipe
-    wipe_single_drive(device, &drive_info, config, cert_output, session, force).await
+    wipe_single_drive(device, &drive_info, config, cert_output, session, force).await?;
+    Ok(())
 }
 
+/// Returns whether the post-wipe verification passed, or `None` when the
+/// configuration skips it
 async fn wipe_single_drive(
     device: &str,
     drive_info: &DriveInfo,
@@ -1523,7 +2413,7 @@ async fn wipe_single_drive(
     cert_output: Option<&str>,
     mut session: WipeSession,
     force: bool,
-) -> Result<()> {
+) -> Result<Option<bool>> {
     println!(
         "\nStarting wipe of {} ({}, {})",
         device, drive_info.model, drive_info.serial
@@ -1531,51 +2421,95 @@ async fn wipe_single_drive(
 
     let start_time = Instant::now();
     let mut warnings = Vec::new();
+    evidence::clear_recorded(device);
 
     // Phase 1: Preparation
     println!("\nPhase 1: Preparation");
 
+    // Kept for the evidence bundle
+    let smart_snapshot = SMARTMonitor::get_health(device).ok();
+
     // Handle freeze mitigation
+    let mut freeze_mitigation = None;
     if config.freeze_mitigation && drive_info.capabilities.is_frozen {
         println!("Drive is frozen, attempting mitigation...");
         match FreezeMitigation::unfreeze_drive(device) {
-            Ok(_) => println!("✓ Drive unfrozen successfully"),
+            Ok(_) => {
+                println!("✓ Drive unfrozen successfully");
+                freeze_mitigation = Some("frozen; unfrozen before wiping".to_string());
+            }
             Err(e) => {
                 let msg = format!("Failed to unfreeze: {}", e);
                 warnings.push(msg.clone());
                 eprintln!("⚠ {}", msg);
+                freeze_mitigation = Some(format!("frozen; {}", msg));
             }
         }
+    } else if drive_info.capabilities.is_frozen {
+        freeze_mitigation = Some("frozen; mitigation disabled".to_string());
     }
 
     // Handle HPA/DCO
-    let mut hpa_original = None;
+    let mut hpa_undo = None;
+    let mut hidden_areas = HiddenAreaEvidence::unchecked(config.handle_hpa_dco.clone());
     match config.handle_hpa_dco {
         HPADCOHandling::Detect => {
             if let Ok((hpa, dco)) = HPADCOManager::check_hidden_areas(device) {
                 if hpa.is_some() || dco.is_some() {
                     warnings.push("Hidden areas detected but not removed".to_string());
                 }
+                hidden_areas.checked = true;
+                hidden_areas.action = if hpa.is_some() || dco.is_some() {
+                    "detected, left in place".to_string()
+                } else {
+                    "none found".to_string()
+                };
+                hidden_areas.hpa_hidden_sectors = hpa.map(|hpa| hpa.hidden_sectors);
+                hidden_areas.dco_hidden_sectors = dco.map(|dco| dco.hidden_sectors);
             }
         }
         HPADCOHandling::TemporaryRemove => {
-            if let Ok(Some(hpa)) = HPADCOManager::detect_hpa(device) {
-                hpa_original = Some(hpa.current_max_sectors);
+            let detected = HPADCOManager::detect_hpa(device);
+            if let Ok(hpa) = &detected {
+                hidden_areas.checked = true;
+                hidden_areas.hpa_hidden_sectors = hpa.as_ref().map(|hpa| hpa.hidden_sectors);
+                hidden_areas.action = "no HPA found".to_string();
+            }
+            if let Ok(Some(hpa)) = detected {
+                // Journal the original max address first so a crash cannot
+                // leave the HPA lifted without a record of how to restore it
+                let undo = PendingUndo::begin(
+                    device,
+                    UndoAction::RestoreHpa {
+                        max_sectors: hpa.current_max_sectors,
+                    },
+                )?;
                 println!("Temporarily removing HPA...");
-                HPADCOManager::remove_hpa_temporary(device)?;
+                if let Err(e) = HPADCOManager::remove_hpa(device, HpaRemoval::Volatile) {
+                    undo.complete()?;
+                    return Err(e.into());
+                }
+                hpa_undo = Some(undo);
                 println!("✓ HPA temporarily removed");
+                hidden_areas.action = HpaRemoval::Volatile.evidence_action().to_string();
             }
         }
         HPADCOHandling::PermanentRemove => {
-            if HPADCOManager::detect_hpa(device)?.is_some() {
+            hidden_areas.checked = true;
+            hidden_areas.action = "none found".to_string();
+            if let Some(hpa) = HPADCOManager::detect_hpa(device)? {
                 println!("Permanently removing HPA...");
-                HPADCOManager::remove_hpa_temporary(device)?;
+                HPADCOManager::remove_hpa(device, HpaRemoval::Permanent)?;
                 println!("✓ HPA permanently removed");
+                hidden_areas.hpa_hidden_sectors = Some(hpa.hidden_sectors);
+                hidden_areas.action = HpaRemoval::Permanent.evidence_action().to_string();
             }
-            if HPADCOManager::detect_dco(device)?.is_some() {
+            if let Some(dco) = HPADCOManager::detect_dco(device)? {
                 println!("Removing DCO...");
                 HPADCOManager::remove_dco(device)?;
                 println!("✓ DCO removed");
+                hidden_areas.dco_hidden_sectors = Some(dco.hidden_sectors);
+                hidden_areas.action = "permanently removed".to_string();
             }
         }
         _ => {}
@@ -1622,25 +2556,36 @@ async fn wipe_single_drive(
     println!("\nPhase 2: Wiping");
 
     let wipe_result = match select_and_execute_wipe(device, drive_info, &config).await {
-        Ok(_) => Ok(()),
+        Ok(method) => Ok(method),
         Err(e) => {
             warnings.push(format!("Wipe error: {}", e));
 
             // Check if this was a user interrupt
             if e.to_string().contains("interrupted") || e.to_string().contains("Interrupted") {
                 eprintln!("\n❌ Wipe operation cancelled by user");
-                return Err(e);
+            } else {
+                eprintln!("\n⚠️  Wipe failed, skipping post-wipe operations");
             }
 
             Err(e)
         }
     };
 
-    // If wipe failed (not interrupted), continue to cleanup but skip verification
-    if wipe_result.is_err() {
-        eprintln!("\n⚠️  Wipe failed, skipping post-wipe operations");
-        return wipe_result;
-    }
+    // If wipe failed, put the HPA back but skip verification
+    let method = match wipe_result {
+        Ok(method) => method,
+        Err(e) => {
+            if let Some(undo) = hpa_undo.take() {
+                if let Err(e) = undo.revert() {
+                    eprintln!(
+                        "⚠ Failed to restore HPA ({}); run 'sayonara recover' to retry",
+                        e
+                    );
+                }
+            }
+            return Err(e);
+        }
+    };
 
     // Phase 3: Post-wipe operations
     println!("\nPhase 3: Post-wipe operations");
@@ -1655,9 +2600,9 @@ async fn wipe_single_drive(
     }
 
     // Restore HPA if needed
-    if let Some(original_sectors) = hpa_original {
+    if let Some(undo) = hpa_undo {
         println!("Restoring original HPA configuration...");
-        HPADCOManager::restore_hpa(device, original_sectors)?;
+        undo.revert()?;
         println!("✓ HPA restored");
     }
 
@@ -1689,35 +2634,67 @@ async fn wipe_single_drive(
     };
 
     // Generate certificate
+    let checkpoint_rejections = take_checkpoint_rejections(device);
+    let wipe_evidence = WipeEvidence::collect(
+        drive_info,
+        &config,
+        method,
+        hidden_areas,
+        freeze_mitigation,
+        warnings.clone(),
+    );
+    warn_if_non_compliant(&wipe_evidence);
     if let Some(cert_path) = cert_output {
         println!("\nGenerating certificate...");
-        let cert_gen = CertificateGenerator::new();
+        let cert_gen = CertificateGenerator::configured()?;
         let wipe_details = WipeDetails {
-            algorithm_used: format!("{:?}", config.algorithm),
-            passes_completed: 1,
+            algorithm_used: algorithm_name(&config.algorithm),
+            passes_completed: wipe_evidence.passes.len().max(1) as u32,
             duration_seconds: wipe_duration.as_secs(),
             operator_id: session.operator_id.clone(),
+            checkpoint_rejections,
         };
 
-        let certificate =
-            cert_gen.generate_certificate(drive_info, wipe_details, verification_result.clone())?;
+        let (certificate, disclosure) = cert_gen.generate_certificate_with_disclosure(
+            drive_info,
+            wipe_details,
+            verification_result.clone(),
+            Some(wipe_evidence),
+        )?;
         cert_gen.save_certificate(&certificate, cert_path)?;
         println!("✓ Certificate saved to: {}", cert_path);
+        if cert_gen.format() == CertificateFormat::CmsDetached {
+            println!(
+                "  Detached CMS signature: {}",
+                detached_signature_path(cert_path)
+            );
+        }
+        save_disclosure(disclosure.as_ref(), cert_path)?;
+        println!(
+            "  Signed with {} key {}",
+            certificate.signature_algorithm,
+            cert_gen.public_key().fingerprint()
+        );
+        keep_wipe_evidence(
+            &certificate,
+            disclosure.as_ref(),
+            device,
+            cert_path,
+            smart_snapshot.as_ref(),
+            &verification_result,
+        );
     }
 
     // Update session
     session.drives.push(DriveWipeRecord {
         drive_info: drive_info.clone(),
-        status: if wipe_result.is_ok() {
-            WipeStatus::Completed
-        } else {
-            WipeStatus::Failed
-        },
+        status: WipeStatus::Completed,
         start_time: chrono::Utc::now() - chrono::Duration::seconds(wipe_duration.as_secs() as i64),
         end_time: Some(chrono::Utc::now()),
-        error_message: wipe_result.err().map(|e| e.to_string()),
+        error_message: None,
         certificate_path: cert_output.map(|s| s.to_string()),
-        verification_passed: Some(verification_result.verified),
+        verification_passed: config.verify.then_some(verification_result.verified),
+        config: Some(config.clone()),
     });
 
     if !warnings.is_empty() {
@@ -1728,14 +2705,118 @@ async fn wipe_single_drive(
     }
 
     println!("\n✓ Operation completed successfully!");
-    Ok(())
+    Ok(config.verify.then_some(verification_result.verified))
 }
 
-async fn select_and_execute_wipe(
+/// What the orchestrator writes to an Optane drive without ISE
+const OPTANE_OVERWRITE: &str = "Optane overwrite (0x00, 0xFF, random)";
+
+/// Technique the orchestrator runs on NVMe drives with several or zoned
+/// namespaces
+const NVME_NAMESPACE_FORMAT: &str = "Format NVM (Secure Erase)";
+
+/// Show the NIST 800-88 decision before the wipe starts
+fn print_sanitization_decision(decision: &SanitizationDecision) {
+    match decision.level {
+        Some(level) => println!(
+            "NIST SP 800-88 {}: {} ({})",
+            level, decision.technique, decision.media
+        ),
+        None => println!(
+            "NIST SP 800-88: {} reaches neither Clear nor Purge ({})",
+            decision.technique, decision.media
+        ),
+    }
+    for reason in &decision.reasoning {
+        println!("  - {}", reason);
+    }
+}
+
+/// Run the DoD overwrite in place of a technique that could not run
+///
+/// Refuses instead when the wipe must reach NIST Purge, which an overwrite
+/// cannot.
+fn fall_back_to_overwrite(
+    method: &mut MethodEvidence,
+    reason: impl Into<String>,
     device: &str,
     drive_info: &DriveInfo,
     config: &WipeConfig,
 ) -> Result<()> {
+    let reason = reason.into();
+    if let Some(nist) = method.nist.as_mut() {
+        nist.fall_back_to_overwrite(&reason)?;
+    }
+    println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...");
+    println!("   This will take longer but will securely wipe the drive.\n");
+    method.fall_back(reason, "DoD5220");
+    DoDWipe::wipe_drive(
+        device,
+        drive_info.size,
+        drive_info.drive_type.clone(),
+        config,
+    )
+}
+
+/// Name of `algorithm` for method evidence
+fn algorithm_name(algorithm: &Algorithm) -> String {
+    match algorithm {
+        Algorithm::Method(name) => name.clone(),
+        builtin => format!("{:?}", builtin),
+    }
+}
+
+/// Formal name of the standard `algorithm` implements
+fn standard_name(algorithm: &Algorithm) -> Option<String> {
+    algorithm
+        .wipe_method()
+        .and_then(|method| method.standard().map(str::to_string))
+}
+
+/// The scheme file `algorithm` runs, with its hash and passes
+fn scheme_evidence(algorithm: &Algorithm) -> Option<SchemeEvidence> {
+    let wipe_method = algorithm.wipe_method()?;
+    let source = wipe_method.source()?;
+    Some(SchemeEvidence {
+        name: source.name,
+        path: source.path,
+        sha256: source.sha256,
+        passes: wipe_method
+            .passes()
+            .iter()
+            .map(|pass| pass.source.name())
+            .collect(),
+    })
+}
+
+/// Point out what makes a wipe's certificate non-compliant
+fn warn_if_non_compliant(evidence: &WipeEvidence) {
+    if evidence.compliant {
+        return;
+    }
+    eprintln!("\n⚠️  This wipe is NOT compliance-safe; its certificate will say so:");
+    for problem in evidence.compliance_problems() {
+        eprintln!("   - {}", problem);
+    }
+}
+
+async fn select_and_execute_wipe(
+    device: &str,
+    drive_info: &DriveInfo,
+    config: &WipeConfig,
+) -> Result<MethodEvidence> {
+    let mut method = MethodEvidence::new(algorithm_name(&config.algorithm));
+    method.scheme = scheme_evidence(&config.algorithm);
+
+    // NIST 800-88 technique for this media; refuses a level it cannot reach
+    let plan = plan_sanitization(drive_info, config)?;
+    check_pass_verification(drive_info, config)?;
+    print_sanitization_decision(&plan.decision);
+    method.nist = Some(plan.decision);
+    // `auto` runs the technique the NIST decision chose
+    let mut algorithm = plan.algorithm;
+    let overwrite = algorithm.wipe_method().is_some();
+
     // Check if this is an advanced drive type that needs specialized handling
     match drive_info.drive_type {
         DriveType::SMR
@@ -1750,8 +2831,28 @@ async fn select_and_execute_wipe(
             );
             println!("Using specialized wipe strategy...\n");
 
+            // The decision counted on Instant Secure Erase; make sure the
+            // drive offers it before anything is written
+            let mut executed = None;
+            if drive_info.drive_type == DriveType::Optane
+                && !overwrite
+                && !OptaneDrive::get_configuration(device).is_ok_and(|optane| optane.supports_ise)
+            {
+                let reason = "the drive does not report Instant Secure Erase support";
+                if let Some(nist) = method.nist.as_mut() {
+                    nist.fall_back_to_overwrite(reason)?;
+                }
+                algorithm = Algorithm::DoD5220;
+                method.fall_back(reason, OPTANE_OVERWRITE);
+                executed = Some(OPTANE_OVERWRITE.to_string());
+            }
+
             use sayonara_wipe::WipeOrchestrator;
-            let mut orchestrator = WipeOrchestrator::new(device.to_string(), config.clone())
+            let orchestrated = WipeConfig {
+                algorithm: algorithm.clone(),
+                ..config.clone()
+            };
+            let mut orchestrator = WipeOrchestrator::new(device.to_string(), orchestrated)
                 .map_err(|e| anyhow::anyhow!("Orchestrator initialization failed: {}", e))?;
 
             orchestrator
@@ -1759,7 +2860,21 @@ async fn select_and_execute_wipe(
                 .await
                 .map_err(|e| anyhow::anyhow!("Advanced wipe failed: {}", e))?;
 
-            return Ok(());
+            // What the orchestrator runs for each media
+            let executed = executed.unwrap_or_else(|| match drive_info.drive_type {
+                DriveType::SMR if overwrite => algorithm_name(&algorithm),
+                DriveType::SMR => "SMR zone overwrite (random)".to_string(),
+                DriveType::Optane if overwrite => OPTANE_OVERWRITE.to_string(),
+                DriveType::Optane => "Optane Instant Secure Erase".to_string(),
+                DriveType::HybridSSHD => "Hybrid overwrite (0x00, 0xFF, random)".to_string(),
+                DriveType::EMMC => "eMMC overwrite (random, boot partitions 0x00)".to_string(),
+                _ => "UFS UNMAP".to_string(),
+            });
+            method = method.executing(executed);
+            if drive_info.drive_type == DriveType::SMR && overwrite {
+                method.standard = standard_name(&algorithm);
+            }
+            return Ok(method);
         }
         DriveType::NVMe => {
             // Check if it's an advanced NVMe (ZNS, multi-namespace, etc.)
@@ -1769,7 +2884,11 @@ async fn select_and_execute_wipe(
                 println!("Using specialized wipe strategy...\n");
 
                 use sayonara_wipe::WipeOrchestrator;
-                let mut orchestrator = WipeOrchestrator::new(device.to_string(), config.clone())
+                let orchestrated = WipeConfig {
+                    algorithm: algorithm.clone(),
+                    ..config.clone()
+                };
+                let mut orchestrator = WipeOrchestrator::new(device.to_string(), orchestrated)
                     .map_err(|e| anyhow::anyhow!("Orchestrator initialization failed: {}", e))?;
 
                 orchestrator
@@ -1777,7 +2896,22 @@ async fn select_and_execute_wipe(
                     .await
                     .map_err(|e| anyhow::anyhow!("Advanced NVMe wipe failed: {}", e))?;
 
-                return Ok(());
+                if overwrite {
+                    return Ok(method.executing("Advanced NVMe overwrite (0x00, 0xFF, random)"));
+                }
+                // Namespaces are formatted one by one whatever hardware
+                // technique was chosen; record the one that ran
+                if let Some(nist) = method.nist.as_mut() {
+                    if nist.technique != NVME_NAMESPACE_FORMAT {
+                        nist.reasoning.push(format!(
+                            "Drives with several or zoned namespaces are formatted namespace by namespace, so {} ran instead of {}",
+                            NVME_NAMESPACE_FORMAT, nist.technique
+                        ));
+                        nist.technique = NVME_NAMESPACE_FORMAT.to_string();
+                        nist.level = Some(NistLevel::Purge);
+                    }
+                }
+                return Ok(method.executing("Advanced NVMe Format NVM (Secure Erase)"));
             }
             // Otherwise fall through to standard NVMe handling below
         }
@@ -1786,60 +2920,28 @@ async fn select_and_execute_wipe(
         }
     }
 
-    // Auto-select best method based on capabilities and config
-    let algorithm = if config.algorithm == Algorithm::SecureErase {
-        // Auto-select based on drive capabilities
-        if drive_info.capabilities.crypto_erase && config.sed_crypto_erase {
-            Algorithm::CryptoErase
-        } else if drive_info.drive_type == DriveType::NVMe
-            && !drive_info.capabilities.sanitize_options.is_empty()
-        {
-            Algorithm::Sanitize
-        } else if drive_info.capabilities.secure_erase {
-            Algorithm::SecureErase
-        } else {
-            Algorithm::DoD5220
-        }
-    } else {
-        config.algorithm.clone()
-    };
-
     println!("Using algorithm: {:?}", algorithm);
+    method = method.executing(algorithm_name(&algorithm));
+    method.standard = standard_name(&algorithm);
 
     match algorithm {
-        Algorithm::DoD5220 => {
-            DoDWipe::wipe_drive(
-                device,
-                drive_info.size,
-                drive_info.drive_type.clone(),
-                config,
-            )?;
-        }
-        Algorithm::Gutmann => {
-            GutmannWipe::wipe_drive(
-                device,
-                drive_info.size,
-                drive_info.drive_type.clone(),
-                config,
-            )?;
-        }
-        Algorithm::Random => {
-            RandomWipe::wipe_drive(
+        Algorithm::DoD5220
+        | Algorithm::Gutmann
+        | Algorithm::Random
+        | Algorithm::Zero
+        | Algorithm::Method(_) => {
+            // Software overwrite from the method registry
+            let wipe_method = algorithm
+                .wipe_method()
+                .ok_or_else(|| anyhow::anyhow!("No wipe method for {:?}", algorithm))?;
+            algorithms::method::execute(
+                wipe_method.as_ref(),
                 device,
                 drive_info.size,
                 drive_info.drive_type.clone(),
                 config,
             )?;
         }
-        Algorithm::Zero => {
-            // Simple zero overwrite
-            DoDWipe::wipe_drive(
-                device,
-                drive_info.size,
-                drive_info.drive_type.clone(),
-                config,
-            )?; // Reuse with zero pattern
-        }
         Algorithm::SecureErase => {
             // Try hardware secure erase with graceful fallback to software methods
             match drive_info.drive_type {
@@ -1850,31 +2952,28 @@ async fn select_and_execute_wipe(
                     Err(e) => {
                         println!("\n⚠️  Hardware secure erase failed: {}", e);
                         println!("   Reason: Drive may not support ATA secure erase or is frozen");
-                        println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...");
-                        println!("   This will take longer but will securely wipe the drive.\n");
-                        DoDWipe::wipe_drive(
+                        fall_back_to_overwrite(
+                            &mut method,
+                            e.to_string(),
                             device,
-                            drive_info.size,
-                            drive_info.drive_type.clone(),
+                            drive_info,
                             config,
                         )?;
                     }
                 },
-                DriveType::NVMe => match NVMeWipe::secure_erase(device) {
+                // The decision chose Format NVM; Sanitize is `Algorithm::Sanitize`
+                DriveType::NVMe => match NVMeWipe::format_secure_erase(device) {
                     Ok(_) => {
                         println!("✅ Hardware secure erase completed successfully");
                     }
                     Err(e) => {
                         println!("\n⚠️  Hardware secure erase failed: {}", e);
-                        println!(
-                            "   Reason: Drive may not support Format NVM or Sanitize commands"
-                        );
-                        println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...");
-                        println!("   This will take longer but will securely wipe the drive.\n");
-                        DoDWipe::wipe_drive(
+                        println!("   Reason: Drive may not support the Format NVM command");
+                        fall_back_to_overwrite(
+                            &mut method,
+                            e.to_string(),
                             device,
-                            drive_info.size,
-                            drive_info.drive_type.clone(),
+                            drive_info,
                             config,
                         )?;
                     }
@@ -1886,23 +2985,22 @@ async fn select_and_execute_wipe(
                     Err(e) => {
                         println!("\n⚠️  Hardware secure erase failed: {}", e);
                         println!("   Reason: Drive may not support ATA secure erase or is frozen");
-                        println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...");
-                        println!("   This will take longer but will securely wipe the drive.\n");
-                        DoDWipe::wipe_drive(
+                        fall_back_to_overwrite(
+                            &mut method,
+                            e.to_string(),
                             device,
-                            drive_info.size,
-                            drive_info.drive_type.clone(),
+                            drive_info,
                             config,
                         )?;
                     }
                 },
                 _ => {
                     println!("ℹ️  Hardware secure erase not available for this drive type");
-                    println!("   Using DoD 5220.22-M (3-pass software wipe)...\n");
-                    DoDWipe::wipe_drive(
+                    fall_back_to_overwrite(
+                        &mut method,
+                        "hardware secure erase not available",
                         device,
-                        drive_info.size,
-                        drive_info.drive_type.clone(),
+                        drive_info,
                         config,
                     )?;
                 }
@@ -1918,59 +3016,46 @@ async fn select_and_execute_wipe(
                     Err(e) => {
                         println!("\n⚠️  Cryptographic erase failed: {}", e);
                         println!("   Reason: Drive may be locked or does not support crypto erase");
-                        println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...");
-                        println!("   This will take longer but will securely wipe the drive.\n");
-                        DoDWipe::wipe_drive(
+                        fall_back_to_overwrite(
+                            &mut method,
+                            e.to_string(),
                             device,
-                            drive_info.size,
-                            drive_info.drive_type.clone(),
+                            drive_info,
                             config,
                         )?;
                     }
                 }
             } else {
                 println!("\n⚠️  Self-Encrypting Drive (SED) not detected");
-                println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...\n");
-                DoDWipe::wipe_drive(
+                fall_back_to_overwrite(
+                    &mut method,
+                    "self-encrypting drive not detected",
                     device,
-                    drive_info.size,
-                    drive_info.drive_type.clone(),
+                    drive_info,
                     config,
                 )?;
             }
         }
         Algorithm::Sanitize => {
-            // Try NVMe sanitize with fallback to DoD
-            if drive_info.drive_type == DriveType::NVMe {
-                match NVMeWipe::secure_erase(device) {
-                    Ok(_) => {
-                        println!("✅ NVMe sanitize completed successfully");
-                    }
-                    Err(e) => {
-                        println!("\n⚠️  NVMe sanitize failed: {}", e);
-                        println!(
-                            "   Reason: Drive may not support Sanitize or Format NVM commands"
-                        );
-                        println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...");
-                        println!("   This will take longer but will securely wipe the drive.\n");
-                        DoDWipe::wipe_drive(
-                            device,
-                            drive_info.size,
-                            drive_info.drive_type.clone(),
-                            config,
-                        )?;
-                    }
+            // Run the sanitize action the decision names, with fallback to DoD
+            let sanitized = match (&drive_info.drive_type, plan.sanitize_action) {
+                (DriveType::NVMe, Some(action)) => NVMeWipe::sanitize(device, action),
+                (DriveType::NVMe, None) => {
+                    Err(anyhow::anyhow!("the drive reports no sanitize action"))
                 }
-            } else {
-                println!("\n⚠️  Sanitize command only available for NVMe drives");
-                println!("   Falling back to DoD 5220.22-M (3-pass software wipe)...\n");
-                DoDWipe::wipe_drive(
-                    device,
-                    drive_info.size,
-                    drive_info.drive_type.clone(),
-                    config,
-                )?;
-            }
+                _ => Err(anyhow::anyhow!(
+                    "sanitize is only available for NVMe drives"
+                )),
+            };
+            match sanitized {
+                Ok(_) => {
+                    println!("✅ NVMe sanitize completed successfully");
+                }
+                Err(e) => {
+                    println!("\n⚠️  NVMe sanitize not run: {}", e);
+                    fall_back_to_overwrite(&mut method, e.to_string(), device, drive_info, config)?;
+                }
+            }
         }
         Algorithm::TrimOnly => {
             if drive_info.capabilities.trim_support {
@@ -1979,17 +3064,28 @@ async fn select_and_execute_wipe(
                 return Err(anyhow::anyhow!("TRIM not supported on this drive"));
             }
         }
+        // The plan resolves `auto` to the technique it chose
+        Algorithm::Auto => {
+            return Err(anyhow::anyhow!("auto was not resolved to a technique"));
+        }
     }
 
-    Ok(())
+    // Every fallback above is to the DoD overwrite
+    if !method.fallbacks.is_empty() {
+        method.standard = standard_name(&Algorithm::DoD5220);
+    }
+
+    Ok(method)
 }
 
 async fn wipe_all_drives(
     config: WipeConfig,
+    safety: &SafetyConfig,
     cert_dir: &str,
     exclude: Option<&str>,
     unsafe_mode: bool,
     force: bool,
+    operator: Option<&str>,
 ) -> Result<()> {
     let drives = DriveDetector::detect_all_drives()?;
 
@@ -2036,7 +3132,7 @@ async fn wipe_all_drives(
         );
     }
 
-    if !unsafe_mode {
+    if !unsafe_mode && safety.require_confirmation {
         println!("\n⚠ WARNING: This action is IRREVERSIBLE!");
         print!("Type 'DESTROY_ALL_DATA' to confirm: ");
         io::stdout().flush()?;
@@ -2060,7 +3156,7 @@ async fn wipe_all_drives(
         end_time: None,
         drives: vec![],
         config: config.clone(),
-        operator_id: None,
+        operator_id: operator.map(str::to_string),
     };
 
     let total_drives = drives_to_wipe.len();
@@ -2078,8 +3174,9 @@ async fn wipe_all_drives(
         );
         println!("{}", "=".repeat(60));
 
-        let cert_filename = drive.device_path.replace("/", "_").replace("dev_", "");
-        let cert_path = format!("{}/cert_{}.json", cert_dir, cert_filename);
+        let cert_path = default_certificate_path(std::path::Path::new(cert_dir), drive)
+            .to_string_lossy()
+            .to_string();
 
         let result = wipe_single_drive(
             &drive.device_path,
@@ -2419,69 +3516,1194 @@ fn truncate_string(s: &str, max_len: usize) -> String {
 }
 
 // Additional helper functions for parallel operations
-#[allow(dead_code)]
-async fn wipe_drives_parallel(
-    drives: Vec<DriveInfo>,
-    config: WipeConfig,
-    cert_dir: &str,
-    max_parallel: usize,
+async fn run_job(
+    manifest_path: &str,
+    effective: &sayonara_wipe::config::EffectiveConfig,
+    unsafe_mode: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let manifest = JobManifest::from_path(manifest_path)?;
+
+    println!("Detecting drives...");
+    let drives = DriveDetector::detect_all_drives()?;
+    let plan = manifest.plan(&drives, &effective.wipe, &HostEnvironment, unsafe_mode)?;
+
+    println!(
+        "\nJob {}: {} drive(s), up to {} in parallel",
+        plan.name.as_deref().unwrap_or(manifest_path),
+        plan.wipes.len(),
+        plan.max_parallel
+    );
+    for wipe in &plan.wipes {
+        println!(
+            "  - {} ({}, {}) <- {}",
+            wipe.drive.device_path, wipe.drive.model, wipe.drive.serial, wipe.selector
+        );
+        println!(
+            "      algorithm: {:?}, verification: {:?}, HPA/DCO: {:?}",
+            wipe.config.algorithm, wipe.verification, wipe.config.handle_hpa_dco
+        );
+        println!("      certificate: {}", wipe.certificate.display());
+    }
+
+    if dry_run {
+        println!("\n✅ Manifest is valid (dry run, nothing was wiped)");
+        return Ok(());
+    }
+
+    if !unsafe_mode && effective.safety.require_confirmation {
+        println!("\n⚠ WARNING: This action is IRREVERSIBLE!");
+        print!("Type 'DESTROY_ALL_DATA' to confirm: ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if input.trim() != "DESTROY_ALL_DATA" {
+            println!("Operation cancelled.");
+            return Ok(());
+        }
+    }
+
+    std::fs::create_dir_all(&plan.cert_dir)?;
+    for wipe in &plan.wipes {
+        if let Some(parent) = wipe.certificate.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let mut session = WipeSession {
+        session_id: Uuid::new_v4().to_string(),
+        start_time: chrono::Utc::now(),
+        end_time: None,
+        drives: vec![],
+        config: effective.wipe.clone(),
+        operator_id: plan.operator_id.clone(),
+    };
+
+    let summary_path = plan.summary.clone().unwrap_or_else(|| {
+        plan.cert_dir
+            .join(format!("session_{}.json", session.session_id))
+    });
+
+    session.drives = wipe_drives_parallel(&plan).await?;
+    session.end_time = Some(chrono::Utc::now());
+
+    if let Some(parent) = summary_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&summary_path, serde_json::to_string_pretty(&session)?)?;
+
+    let completed = session
+        .drives
+        .iter()
+        .filter(|r| matches!(r.status, WipeStatus::Completed))
+        .count();
+    println!("\n{}", "=".repeat(70));
+    println!(
+        "Job finished: {} completed, {} failed",
+        completed,
+        session.drives.len() - completed
+    );
+    for record in session.drives.iter().filter(|r| r.error_message.is_some()) {
+        println!(
+            "  ✗ {}: {}",
+            record.drive_info.device_path,
+            record.error_message.as_deref().unwrap_or_default()
+        );
+    }
+    println!("Session summary: {}", summary_path.display());
+
+    Ok(())
+}
+
+/// Wipe the planned drives, at most `max_parallel` at a time
+///
+/// The wipe paths do blocking I/O, so each drive runs on its own blocking
+/// thread; a semaphore holds back the rest until a slot frees up.
+async fn wipe_drives_parallel(plan: &JobPlan) -> Result<Vec<DriveWipeRecord>> {
+    let plan = std::sync::Arc::new(plan.clone());
+    let slots = std::sync::Arc::new(tokio::sync::Semaphore::new(plan.max_parallel));
+    let runtime = tokio::runtime::Handle::current();
+
+    let mut tasks = Vec::with_capacity(plan.wipes.len());
+    for wipe in plan.wipes.iter().cloned() {
+        let slot = slots.clone().acquire_owned().await?;
+        let plan = plan.clone();
+        let runtime = runtime.clone();
+        tasks.push(tokio::task::spawn_blocking(move || {
+            let record = runtime.block_on(wipe_planned_drive(wipe, &plan));
+            drop(slot);
+            record
+        }));
+    }
+
+    let mut records = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        records.push(task.await?);
+    }
+    Ok(records)
+}
+
+async fn wipe_planned_drive(wipe: PlannedWipe, plan: &JobPlan) -> DriveWipeRecord {
+    let PlannedWipe {
+        drive,
+        config,
+        verification,
+        certificate,
+        ..
+    } = wipe;
+    let cert_path = certificate.to_string_lossy().to_string();
+    let start_time = chrono::Utc::now();
+    let planned = config.clone();
+
+    let result = match verification {
+        VerificationMode::Enhanced(level) => enhanced_wipe_with_verification(
+            &drive.device_path,
+            &drive,
+            config,
+            VerificationOptions {
+                cert_output: Some(&cert_path),
+                _sample_percent: 1.0,
+                min_confidence: plan.min_confidence,
+                verification_level: level,
+                skip_pre_tests: false,
+                operator_id: plan.operator_id.clone(),
+                // Drives run in parallel, with nobody at the terminal
+                interactive: false,
+            },
+        )
+        .await
+        .map(Some),
+        VerificationMode::None | VerificationMode::Basic => {
+            let session = WipeSession {
+                session_id: Uuid::new_v4().to_string(),
+                start_time,
+                end_time: None,
+                drives: vec![],
+                config: config.clone(),
+                operator_id: plan.operator_id.clone(),
+            };
+
+            wipe_single_drive(
+                &drive.device_path,
+                &drive,
+                config,
+                Some(&cert_path),
+                session,
+                plan.force,
+            )
+            .await
+        }
+    };
+
+    match result {
+        Ok(verification_passed) => DriveWipeRecord {
+            drive_info: drive,
+            status: WipeStatus::Completed,
+            start_time,
+            end_time: Some(chrono::Utc::now()),
+            error_message: None,
+            certificate_path: Some(cert_path),
+            verification_passed,
+            config: Some(planned),
+        },
+        Err(e) => DriveWipeRecord {
+            drive_info: drive,
+            status: WipeStatus::Failed,
+            start_time,
+            end_time: Some(chrono::Utc::now()),
+            error_message: Some(e.to_string()),
+            certificate_path: None,
+            verification_passed: Some(false),
+            config: Some(planned),
+        },
+    }
+}
+
+async fn handle_checkpoint(
+    action: &CheckpointAction,
+    safety: &SafetyConfig,
+    unsafe_mode: bool,
+    operator: Option<&str>,
+) -> Result<()> {
+    match action {
+        CheckpointAction::List { json } => list_checkpoints(*json),
+        CheckpointAction::Inspect { id } => inspect_checkpoint(id),
+        CheckpointAction::Resume {
+            id,
+            cert_output,
+            force,
+            ..
+        } => {
+            resume_checkpoint(
+                id,
+                safety,
+                cert_output.as_deref(),
+                *force,
+                unsafe_mode,
+                operator,
+            )
+            .await
+        }
+        CheckpointAction::Purge { older_than, all } => purge_checkpoints(older_than, *all),
+    }
+}
+
+fn list_checkpoints(json: bool) -> Result<()> {
+    let manager = CheckpointManager::new(None)?;
+    let checkpoints = manager.list_all()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&checkpoints)?);
+        return Ok(());
+    }
+
+    if checkpoints.is_empty() {
+        println!("No checkpoints stored.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<36} {:<15} {:<12} {:<40} {:<20} {:<6}",
+        "ID", "Device", "Algorithm", "Progress", "Updated", "Errors"
+    );
+    println!("{}", "-".repeat(134));
+
+    for listed in &checkpoints {
+        let checkpoint = &listed.checkpoint;
+        println!(
+            "{:<36} {:<15} {:<12} {:<40} {:<20} {:<6}",
+            checkpoint.id,
+            checkpoint.device_path,
+            truncate_string(&checkpoint.algorithm, 12),
+            checkpoint.progress_description(),
+            checkpoint.updated_at.format("%Y-%m-%d %H:%M:%S"),
+            checkpoint.error_count
+        );
+        // A row that fails its integrity check is refused on resume
+        if let Some(reason) = &listed.rejected {
+            println!("{:<36} ⚠️  INVALID, will not resume: {}", "", reason);
+        }
+    }
+
+    let stats = manager.stats()?;
+    println!(
+        "\n{} checkpoint(s) in {}",
+        stats.total_checkpoints,
+        stats.database_path.display()
+    );
+    let invalid = checkpoints.iter().filter(|c| c.rejected.is_some()).count();
+    if invalid > 0 {
+        println!(
+            "⚠️  {} checkpoint(s) failed verification and will be refused on resume",
+            invalid
+        );
+    }
+
+    Ok(())
+}
+
+fn inspect_checkpoint(id: &str) -> Result<()> {
+    let manager = CheckpointManager::new(None)?;
+    let checkpoint = manager
+        .load_by_id(id)?
+        .ok_or_else(|| anyhow::anyhow!("Checkpoint not found: {}", id))?;
+
+    println!("{}", serde_json::to_string_pretty(&checkpoint)?);
+    Ok(())
+}
+
+async fn resume_checkpoint(
+    id: &str,
+    safety: &SafetyConfig,
+    cert_output: Option<&str>,
     force: bool,
-) -> Result<Vec<DriveWipeRecord>> {
-    use futures::stream::{self, StreamExt};
-
-    let results = stream::iter(drives)
-        .map(|drive| {
-            let config = config.clone();
-            let cert_dir = cert_dir.to_string();
-            async move {
-                let cert_filename = drive.device_path.replace("/", "_").replace("dev_", "");
-                let cert_path = format!("{}/cert_{}.json", cert_dir, cert_filename);
-
-                let session = WipeSession {
-                    session_id: Uuid::new_v4().to_string(),
-                    start_time: chrono::Utc::now(),
-                    end_time: None,
-                    drives: vec![],
-                    config: config.clone(),
-                    operator_id: None,
+    unsafe_mode: bool,
+    operator: Option<&str>,
+) -> Result<()> {
+    let mut manager = CheckpointManager::new(None)?;
+    let checkpoint = manager
+        .load_by_id(id)?
+        .ok_or_else(|| anyhow::anyhow!("Checkpoint not found: {}", id))?;
+
+    if checkpoint.config.is_null() {
+        anyhow::bail!(
+            "Checkpoint {} has no recorded wipe configuration and cannot be resumed; \
+             start a new wipe of {} instead",
+            id,
+            checkpoint.device_path
+        );
+    }
+    let config: WipeConfig = serde_json::from_value(checkpoint.config.clone())
+        .with_context(|| format!("Checkpoint {} has an unreadable wipe configuration", id))?;
+
+    // Refuse to continue on a different disk enumerated at the same path
+    let recorded = checkpoint.device_identity.as_ref().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Checkpoint {} does not record which drive it belongs to and cannot be resumed safely",
+            id
+        )
+    })?;
+    let current = DriveIdentity::probe(&checkpoint.device_path);
+    let problems = recorded.strict_mismatches(&current);
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "Refusing to resume checkpoint {}: {} no longer holds the recorded drive ({})",
+            id,
+            checkpoint.device_path,
+            problems.join("; ")
+        );
+    }
+
+    println!("🔁 Resuming checkpoint {}", checkpoint.id);
+    println!("   Device:    {}", checkpoint.device_path);
+    println!("   Algorithm: {}", checkpoint.algorithm);
+    println!("   Progress:  {}", checkpoint.progress_description());
+
+    // Make this the most recent checkpoint for the device so the wipe picks it up
+    manager.touch(&checkpoint.id)?;
+    drop(manager);
+
+    wipe_drive(
+        &checkpoint.device_path,
+        config,
+        safety,
+        cert_output,
+        force,
+        unsafe_mode,
+        operator,
+    )
+    .await
+}
+
+/// Collect (and forget) checkpoints refused while wiping `device`
+///
+/// The rejections belong in the certificate of the wipe that hit them, so
+/// they are cleared once taken.
+fn take_checkpoint_rejections(device: &str) -> Vec<CheckpointRejection> {
+    let result = CheckpointManager::new(None).and_then(|mut manager| {
+        let rejections = manager.rejections(device)?;
+        manager.clear_rejections(device)?;
+        Ok(rejections)
+    });
+
+    let rejections = match result {
+        Ok(rejections) => rejections,
+        Err(e) => {
+            println!("⚠️  Could not read checkpoint rejections: {}", e);
+            return Vec::new();
+        }
+    };
+
+    for rejection in &rejections {
+        println!(
+            "⚠️  Refused checkpoint {} ({}): {}",
+            rejection.checkpoint_id, rejection.algorithm, rejection.reason
+        );
+    }
+
+    rejections
+}
+
+fn purge_checkpoints(older_than: &str, all: bool) -> Result<()> {
+    let mut manager = CheckpointManager::new(None)?;
+
+    let deleted = if all {
+        let checkpoints = manager.list_all()?;
+        for listed in &checkpoints {
+            manager.delete(&listed.checkpoint.id)?;
+        }
+        checkpoints.len()
+    } else {
+        let max_age = humantime::parse_duration(older_than)
+            .with_context(|| format!("Invalid duration: {}", older_than))?;
+        manager.cleanup_stale(chrono::Duration::from_std(max_age)?)?
+    };
+
+    manager.vacuum()?;
+    println!("🧹 Removed {} checkpoint(s)", deleted);
+
+    Ok(())
+}
+
+/// Replay outstanding undo journal entries at startup
+///
+/// Failures are reported but never stop the requested command.
+fn replay_undo_journal() {
+    let replayed = UndoJournal::open(None).and_then(|journal| {
+        let outcomes = journal.replay(false)?;
+        // Changes another running sayonara still relies on are not news
+        Ok(outcomes
+            .into_iter()
+            .filter(|outcome| {
+                !matches!(outcome, ReplayOutcome::Skipped { entry, .. } if journal.owner_alive(entry))
+            })
+            .collect::<Vec<_>>())
+    });
+    let outcomes = match replayed {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            eprintln!("⚠️  Could not read undo journal: {}", e);
+            return;
+        }
+    };
+
+    if outcomes.is_empty() {
+        return;
+    }
+
+    println!("♻️  Undoing drive changes left by an interrupted run:");
+    print_replay_outcomes(&outcomes);
+}
+
+fn recover(dry_run: bool, force: bool, json: bool) -> Result<()> {
+    let journal = UndoJournal::open(None)?;
+
+    if dry_run {
+        let pending = journal.pending()?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&pending)?);
+        } else if pending.is_empty() {
+            println!("No outstanding drive changes.");
+        } else {
+            for entry in &pending {
+                let owner = match entry.owner_pid {
+                    Some(pid) if journal.owner_alive(entry) => format!(", in use by pid {}", pid),
+                    _ => String::new(),
                 };
+                println!(
+                    "{}  {:<15} {} (recorded {}{})",
+                    entry.id,
+                    entry.device_path,
+                    entry.action.description(),
+                    entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    owner
+                );
+            }
+        }
+        return Ok(());
+    }
 
-                match wipe_single_drive(
-                    &drive.device_path,
-                    &drive,
-                    config,
-                    Some(&cert_path),
-                    session,
-                    force,
-                )
-                .await
-                {
-                    Ok(_) => DriveWipeRecord {
-                        drive_info: drive.clone(),
-                        status: WipeStatus::Completed,
-                        start_time: chrono::Utc::now(),
-                        end_time: Some(chrono::Utc::now()),
-                        error_message: None,
-                        certificate_path: Some(cert_path),
-                        verification_passed: Some(true),
-                    },
-                    Err(e) => DriveWipeRecord {
-                        drive_info: drive.clone(),
-                        status: WipeStatus::Failed,
-                        start_time: chrono::Utc::now(),
-                        end_time: Some(chrono::Utc::now()),
-                        error_message: Some(e.to_string()),
-                        certificate_path: None,
-                        verification_passed: Some(false),
-                    },
-                }
+    let outcomes = journal.replay(force)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&outcomes)?);
+    } else if outcomes.is_empty() {
+        println!("No outstanding drive changes.");
+    } else {
+        print_replay_outcomes(&outcomes);
+    }
+
+    if outcomes
+        .iter()
+        .any(|outcome| !matches!(outcome, ReplayOutcome::Restored(_)))
+    {
+        return Err(anyhow::anyhow!(
+            "Some drive changes could not be undone; they remain in the journal \
+             (changes in use by a running sayonara are only undone with --force)"
+        ));
+    }
+
+    Ok(())
+}
+
+fn verify_certificate_file(
+    verifier: &CertificateVerifier,
+    file: &str,
+    signature: Option<&str>,
+    device: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let drive = match device {
+        Some(device) => Some(
+            DriveDetector::detect_all_drives()?
+                .into_iter()
+                .find(|d| d.device_path == device)
+                .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", device))?,
+        ),
+        None => None,
+    };
+
+    let report = verifier.verify_file(Path::new(file), signature.map(Path::new), drive.as_ref());
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_certificate_report(file, &report);
+    }
+
+    // Scripts rely on the exit status as much as on the output
+    if !report.valid {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Write the Appendix G form for the certificate at `file`
+///
+/// Forms are rendered even for certificates that fail verification, with a
+/// warning, so a damaged certificate can still be inspected on paper.
+fn render_certificate_form(
+    verifier: &CertificateVerifier,
+    file: &str,
+    format: Option<&str>,
+    output: Option<&str>,
+) -> Result<()> {
+    let pdf = match format.or_else(|| output.and_then(|o| Path::new(o).extension()?.to_str())) {
+        None | Some("html") | Some("htm") => false,
+        Some("pdf") => true,
+        Some(other) => anyhow::bail!("Unknown form format '{}' (expected html or pdf)", other),
+    };
+    let output = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(file).with_extension(if pdf { "pdf" } else { "html" }));
+
+    let report = verifier.verify_file(Path::new(file), None, None);
+    let certificate = report.certificate.as_ref().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Cannot read {}: {}",
+            file,
+            report.failure().map_or("", |c| c.detail.as_str())
+        )
+    })?;
+    if let Some(check) = report.failure() {
+        eprintln!(
+            "⚠️  Certificate failed the {} check ({}); the form is not evidence of sanitization",
+            check.check, check.detail
+        );
+    }
+
+    // Enhanced certificates from before schema 3 keep their forensic report
+    // outside the signature only
+    let signed_report = certificate
+        .evidence
+        .as_ref()
+        .is_some_and(|e| e.verification_report.is_some());
+    let legacy_report: Option<VerificationReport> = std::fs::read(file)
+        .ok()
+        .filter(|_| !signed_report)
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+        .and_then(|document| {
+            serde_json::from_value(document["enhanced_verification"].clone()).ok()
+        });
+
+    let form = SanitizationForm::new(certificate, legacy_report.as_ref());
+    if pdf {
+        std::fs::write(&output, form.to_pdf())
+    } else {
+        std::fs::write(&output, form.to_html())
+    }
+    .with_context(|| format!("Cannot write {}", output.display()))?;
+
+    println!(
+        "✓ Certificate of Sanitization written to {}",
+        output.display()
+    );
+    let manual: Vec<_> = form.manual_fields().collect();
+    if !manual.is_empty() {
+        println!("  {} fields need completing by hand:", manual.len());
+        for (section, label) in manual {
+            println!("    - {}: {}", section, label);
+        }
+    }
+    Ok(())
+}
+
+/// Saved JSON certificate that may still be changed outside its signature
+///
+/// `addition` names what is being added, for the error messages.
+fn read_amendable_certificate(
+    file: &str,
+    addition: &str,
+) -> Result<(serde_json::Value, WipeCertificate)> {
+    if Path::new(&detached_signature_path(file)).exists() {
+        anyhow::bail!(
+            "{} has a detached CMS signature, which {} would invalidate",
+            file,
+            addition
+        );
+    }
+    let bytes = std::fs::read(file).with_context(|| format!("Cannot read {}", file))?;
+    let document: serde_json::Value = serde_json::from_slice(&bytes).with_context(|| {
+        format!(
+            "{} is not a JSON certificate; CMS envelopes cannot take {} after signing",
+            file, addition
+        )
+    })?;
+    let certificate: WipeCertificate = serde_json::from_value(document.clone())
+        .with_context(|| format!("{} is not a wipe certificate", file))?;
+    Ok((document, certificate))
+}
+
+fn timestamp_certificate_file(file: &str, tsa: &TsaClient) -> Result<()> {
+    let (mut document, mut certificate) = read_amendable_certificate(file, "a timestamp")?;
+
+    attach_timestamp(&mut certificate, tsa)?;
+
+    // Keep members this release does not model, such as enhanced_verification
+    document["timestamp_token"] = serde_json::to_value(&certificate.timestamp_token)?;
+    std::fs::write(file, serde_json::to_string_pretty(&document)?)?;
+
+    println!("🕒 Timestamped {} by {}", file, tsa.url());
+    Ok(())
+}
+
+/// Add a cosignature to the certificate at `file` once its signatures check
+/// out; on failure the process exits with status 1
+fn cosign_certificate_file(
+    verifier: &CertificateVerifier,
+    file: &str,
+    role: &str,
+    name: Option<&str>,
+    key: &dyn Signer,
+) -> Result<()> {
+    let report = verifier.verify_file(Path::new(file), None, None);
+    let signed = report
+        .checks
+        .iter()
+        .filter(|c| matches!(c.check, "signature" | "cosignatures"))
+        .all(|c| c.passed);
+    if report.certificate.is_none() || !signed {
+        print_certificate_report(file, &report);
+        std::process::exit(1);
+    }
+
+    let (mut document, mut certificate) = read_amendable_certificate(file, "a cosignature")?;
+    cosign::cosign(&mut certificate, role, name, key)?;
+
+    // Keep members this release does not model, such as enhanced_verification
+    document["cosignatures"] = serde_json::to_value(&certificate.cosignatures)?;
+    std::fs::write(file, serde_json::to_string_pretty(&document)?)?;
+
+    println!(
+        "✍  Cosigned {} as {} with key {}",
+        file,
+        role,
+        key.public_key().fingerprint()
+    );
+    let report = verifier.verify_file(Path::new(file), None, None);
+    if let Some(policy) = report.checks.iter().find(|c| c.check == "signature_policy") {
+        if policy.passed {
+            println!("   Final: {}", policy.detail);
+        } else {
+            println!("   Not final yet: {}", policy.detail);
+        }
+    }
+    Ok(())
+}
+
+fn anchor_certificate_files(files: &[String], client: &AnchorClient) -> Result<()> {
+    let mut documents = Vec::new();
+    let mut certificates = Vec::new();
+    for file in files {
+        let (document, certificate) = read_amendable_certificate(file, "an anchor")?;
+        if let Some(anchor) = &certificate.anchor {
+            anyhow::bail!(
+                "{} is already anchored (transaction {})",
+                file,
+                anchor.transaction_hash
+            );
+        }
+        documents.push(document);
+        certificates.push(certificate);
+    }
+
+    client.anchor_batch(&mut certificates)?;
+
+    // Keep members this release does not model, such as enhanced_verification
+    for ((file, mut document), certificate) in files.iter().zip(documents).zip(&certificates) {
+        document["anchor"] = serde_json::to_value(&certificate.anchor)?;
+        std::fs::write(file, serde_json::to_string_pretty(&document)?)?;
+    }
+
+    if let Some(anchor) = certificates.first().and_then(|c| c.anchor.as_ref()) {
+        println!(
+            "⛓  Anchored {} certificate(s) on chain {}",
+            certificates.len(),
+            anchor.chain_id
+        );
+        println!("   Contract:    {}", anchor.contract);
+        println!("   Transaction: {}", anchor.transaction_hash);
+        println!("   Block:       {}", anchor.block_number);
+        println!("   Hash:        {}", anchor.anchored_hash);
+    }
+    Ok(())
+}
+
+fn print_certificate_report(file: &str, report: &CertificateReport) {
+    println!("Certificate: {} ({})", file, report.format);
+    if let Some(certificate) = &report.certificate {
+        println!("   ID:     {}", certificate.certificate_id);
+        println!(
+            "   Drive:  {} {} ({} GB)",
+            certificate.device_info.model,
+            certificate.device_info.serial,
+            certificate.device_info.size / (1024 * 1024 * 1024)
+        );
+        if let Some(evidence) = &certificate.evidence {
+            println!(
+                "   Method: {} ({} passes recorded)",
+                evidence.method.executed,
+                evidence.passes.len()
+            );
+        }
+    }
+    for signer in &report.signers {
+        println!(
+            "   Signed: {:<10} {}{}{}",
+            signer.role,
+            signer
+                .signer
+                .as_deref()
+                .map(|name| format!("{}, ", name))
+                .unwrap_or_default(),
+            signer.fingerprint,
+            if signer.trusted {
+                ""
+            } else {
+                " (not in policy)"
             }
-        })
-        .buffer_unordered(max_parallel)
-        .collect::<Vec<_>>()
-        .await;
+        );
+    }
+    for check in &report.checks {
+        let mark = if check.passed { "PASS" } else { "FAIL" };
+        println!("   [{}] {:<17} {}", mark, check.check, check.detail);
+    }
+
+    match report.failed_check {
+        None => println!("\n✅ PASS: certificate is valid"),
+        Some(check) => println!("\n❌ FAIL: {} check failed", check),
+    }
+}
+
+/// Write the disclosure of a redacted certificate saved at `cert_path`
+fn save_disclosure(disclosure: Option<&Disclosure>, cert_path: &str) -> Result<()> {
+    if let Some(disclosure) = disclosure {
+        let path = disclosure_path(cert_path);
+        disclosure.save(Path::new(&path))?;
+        println!("  Serial, device path and WWN redacted; salts in {}", path);
+        println!(
+            "  Keep the disclosure private; share it only to prove what the certificate covers"
+        );
+    }
+    Ok(())
+}
+
+/// Open the commitments of the redacted certificate at `file`; on failure
+/// the process exits with status 1
+///
+/// With `serial`, the disclosure must also open the serial commitment to
+/// exactly that serial.
+fn disclose_certificate(
+    verifier: &CertificateVerifier,
+    file: &str,
+    disclosure: Option<&str>,
+    serial: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    // A non-compliant wipe is still a genuine certificate of that wipe
+    let report = verifier.verify_file(Path::new(file), None, None);
+    let genuine = report
+        .checks
+        .iter()
+        .all(|c| c.passed || c.check == "compliance");
+    let Some(certificate) = report.certificate.as_ref().filter(|_| genuine) else {
+        print_certificate_report(file, &report);
+        std::process::exit(1);
+    };
+
+    let disclosure_file = disclosure
+        .map(str::to_string)
+        .unwrap_or_else(|| disclosure_path(file));
+    let checks = Disclosure::load(Path::new(&disclosure_file))?.check(certificate)?;
+    let serial_check = serial.map(|serial| {
+        checks
+            .iter()
+            .any(|c| c.field == privacy::SERIAL && c.matches && c.value == serial)
+    });
+    let valid = checks.iter().all(|c| c.matches) && serial_check != Some(false);
 
-    Ok(results)
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "valid": valid,
+                "certificate_id": certificate.certificate_id,
+                "fields": checks,
+                "serial_matches": serial_check,
+            }))?
+        );
+    } else {
+        println!("Certificate: {} ({})", file, certificate.certificate_id);
+        println!("Disclosure:  {}", disclosure_file);
+        for check in &checks {
+            let mark = if check.matches { "PASS" } else { "FAIL" };
+            println!("   [{}] {:<12} {}", mark, check.field, check.value);
+        }
+        match (valid, serial) {
+            (true, Some(serial)) => {
+                println!("\n✅ PASS: certificate covers serial {}", serial)
+            }
+            (true, None) => println!("\n✅ PASS: disclosed values match the certificate"),
+            (false, Some(serial)) if serial_check == Some(false) => {
+                println!("\n❌ FAIL: certificate does not cover serial {}", serial)
+            }
+            (false, _) => println!("\n❌ FAIL: disclosure does not match the certificate"),
+        }
+    }
+
+    if !valid {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Keep the artifacts of a wipe for `sayonara evidence export`
+///
+/// Checkpoints are deleted once a wipe completes, so their history is
+/// captured here: whatever is still stored for the device, and the refused
+/// checkpoints the certificate records. For a redacted certificate the
+/// committed values are redacted from every artifact too; the disclosure is
+/// never kept. Failures only warn; the wipe and its certificate stand
+/// without a bundle.
+fn keep_wipe_evidence(
+    certificate: &WipeCertificate,
+    disclosure: Option<&Disclosure>,
+    device: &str,
+    cert_path: &str,
+    smart: Option<&SMARTHealth>,
+    verification: &impl serde::Serialize,
+) {
+    let store = EvidenceStore::new(EvidenceStore::default_path());
+    let id = &certificate.certificate_id;
+    let redacted = disclosure.map(Disclosure::values).unwrap_or_default();
+    let add_json = |name: &str, value: serde_json::Value| {
+        let mut value = value;
+        privacy::redact_json(&mut value, &redacted);
+        store.add_json(id, name, &value)
+    };
+
+    let result = (|| -> Result<()> {
+        let extension = Path::new(cert_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("json");
+        let name = format!("certificate.{}", extension);
+        store.add_file(id, &name, Path::new(cert_path))?;
+        let signature = detached_signature_path(cert_path);
+        if Path::new(&signature).exists() {
+            store.add_file(id, &format!("{}.p7s", name), Path::new(&signature))?;
+        }
+
+        add_json("verification.json", serde_json::to_value(verification)?)?;
+        if let Some(smart) = smart {
+            add_json("smart.json", serde_json::to_value(smart)?)?;
+        }
+
+        let bad_sectors = BadSectorHandler::default_log_file(device);
+        if bad_sectors.exists() {
+            let log = std::fs::read_to_string(&bad_sectors)?;
+            store.add_bytes(
+                id,
+                "bad_sectors.log",
+                privacy::redact_text(&log, &redacted).as_bytes(),
+            )?;
+        }
+
+        let checkpoints: Vec<_> = CheckpointManager::new(None)
+            .and_then(|manager| manager.list_all())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|c| c.checkpoint.device_path == device)
+            .collect();
+        add_json(
+            "checkpoints.json",
+            serde_json::json!({
+                "checkpoints": checkpoints,
+                "rejections": certificate.wipe_details.checkpoint_rejections,
+            }),
+        )
+    })();
+
+    match result {
+        Ok(()) => println!(
+            "  Evidence kept for 'sayonara evidence export {}'",
+            certificate.certificate_id
+        ),
+        Err(e) => eprintln!("⚠️  Could not keep wipe evidence: {:#}", e),
+    }
+}
+
+/// Write the evidence bundle for `cert_id`, with a fresh inclusion proof
+/// from the transparency log when it has the certificate
+fn export_evidence_bundle(
+    config: &EffectiveConfig,
+    cert_id: &str,
+    format: Option<&str>,
+    output: Option<&str>,
+) -> Result<()> {
+    let format = match (format, output) {
+        (Some(format), _) => format.parse()?,
+        (None, Some(output)) => ArchiveFormat::from_path(Path::new(output)).unwrap_or_default(),
+        (None, None) => ArchiveFormat::default(),
+    };
+    let output = output.map(PathBuf::from).unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "sayonara-evidence-{}.{}",
+            cert_id,
+            format.file_extension()
+        ))
+    });
+
+    let store = EvidenceStore::new(EvidenceStore::default_path());
+    let mut files = store.files(cert_id)?;
+
+    let verifier = CertificateVerifier::from_config(&config.signing)?;
+    let certificate = files
+        .iter()
+        .find(|(name, _)| name.starts_with("certificate.") && !name.ends_with(".p7s"))
+        .and_then(|(name, bytes)| {
+            let signature = files
+                .iter()
+                .find(|(other, _)| *other == format!("{}.p7s", name))
+                .map(|(_, bytes)| bytes.as_slice());
+            verifier.verify_bytes(bytes, signature, None).certificate
+        });
+    match (certificate, open_transparency_log()) {
+        (Some(certificate), Ok(log)) => match log.prove_inclusion(&certificate) {
+            Ok(proof) => {
+                files.push((
+                    "transparency_proof.json".to_string(),
+                    serde_json::to_vec_pretty(&proof)?,
+                ));
+                files.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+            Err(e) => eprintln!("⚠️  No transparency log inclusion proof: {}", e),
+        },
+        (None, _) => eprintln!("⚠️  Stored certificate could not be read; exporting it as is"),
+        (_, Err(e)) => eprintln!("⚠️  No transparency log inclusion proof: {}", e),
+    }
+
+    let signer = signing::signer_from_config(&config.signing)?;
+    let manifest = bundle::write_bundle(&output, format, cert_id, &files, signer.as_ref())?;
+
+    println!("✅ Evidence bundle written to {}", output.display());
+    for entry in &manifest.files {
+        println!(
+            "   {:<28} {:>10} bytes  {}",
+            entry.path, entry.size, entry.sha256
+        );
+    }
+    println!(
+        "   Manifest signed with {} key {}",
+        manifest.signature_algorithm, manifest.signer_fingerprint
+    );
+    Ok(())
+}
+
+/// Check an evidence bundle; on failure the process exits with status 1
+fn verify_evidence_bundle(
+    key: &PublicKey,
+    verifier: &CertificateVerifier,
+    path: &str,
+    json: bool,
+) -> Result<()> {
+    let report = bundle::verify_bundle(Path::new(path), key, verifier);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("\n🔍 Evidence Bundle Verification");
+        println!("{}", "=".repeat(70));
+        println!("File:        {}", path);
+        if let Some(id) = &report.certificate_id {
+            println!("Certificate: {}", id);
+        }
+        println!();
+        for check in &report.checks {
+            let mark = if check.passed { "PASS" } else { "FAIL" };
+            println!("   [{}] {:<12} {}", mark, check.check, check.detail);
+        }
+        match report.failed_check {
+            None => println!("\n✅ PASS: bundle is complete and unmodified"),
+            Some(check) => println!("\n❌ FAIL: {} check failed", check),
+        }
+    }
+
+    if !report.valid {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// The state directory's transparency log, which must already exist
+fn open_transparency_log() -> Result<TransparencyLog> {
+    let path = TransparencyLog::default_path();
+    if !path.exists() {
+        anyhow::bail!(
+            "No transparency log at {} (no certificates issued yet?)",
+            path.display()
+        );
+    }
+    TransparencyLog::open(Some(&path.to_string_lossy()))
+}
+
+/// Print a log check; on failure the process exits with status 1
+fn report_log_check(result: Result<()>, success: &str) {
+    match result {
+        Ok(()) => println!("\n✅ PASS: {}", success),
+        Err(e) => {
+            println!("\n❌ FAIL: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_tree_head(head: &SignedTreeHead) {
+    println!("   Entries:   {}", head.tree_size);
+    println!("   Root hash: {}", head.root_hash);
+    println!("   Signed:    {}", head.timestamp.to_rfc3339());
+    println!(
+        "   Signer:    {} ({})",
+        head.signer_fingerprint, head.signature_algorithm
+    );
+}
+
+fn show_log_head(log: &TransparencyLog, key: &PublicKey, json: bool) -> Result<()> {
+    let head = log
+        .head()?
+        .ok_or_else(|| anyhow::anyhow!("The transparency log is empty"))?;
+    let result = head.verify(key).and_then(|_| log.verify_entries());
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&head)?);
+        if let Err(e) = result {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    println!(
+        "🌳 Transparency log: {}",
+        TransparencyLog::default_path().display()
+    );
+    print_tree_head(&head);
+    report_log_check(result, "tree head is signed and matches the logged entries");
+    Ok(())
+}
+
+fn prove_certificate_inclusion(
+    log: &TransparencyLog,
+    key: &PublicKey,
+    verifier: &CertificateVerifier,
+    file: &str,
+    json: bool,
+) -> Result<()> {
+    // Only the contents matter here; `cert verify` judges the signature
+    let report = verifier.verify_file(Path::new(file), None, None);
+    let certificate = report.certificate.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Cannot read a certificate from {}: {}",
+            file,
+            report
+                .checks
+                .iter()
+                .find(|c| !c.passed)
+                .map(|c| c.detail.as_str())
+                .unwrap_or("unknown error")
+        )
+    })?;
+
+    let proof = log.prove_inclusion(&certificate)?;
+    let result = proof.tree_head.verify(key).and_then(|_| proof.verify());
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&proof)?);
+        if let Err(e) = result {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    println!("Certificate: {}", file);
+    println!("   ID:        {}", proof.entry.certificate_id);
+    println!(
+        "   Entry:     {} of {}",
+        proof.leaf_index, proof.tree_head.tree_size
+    );
+    println!("   Hash:      {}", proof.entry.certificate_hash);
+    println!("   Path:      {} hashes", proof.audit_path.len());
+    print_tree_head(&proof.tree_head);
+    report_log_check(
+        result,
+        "certificate is included under the current tree head",
+    );
+    Ok(())
+}
+
+fn check_log_consistency(
+    log: &TransparencyLog,
+    key: &PublicKey,
+    old_head: &str,
+    json: bool,
+) -> Result<()> {
+    let bytes = std::fs::read(old_head).with_context(|| format!("Cannot read {}", old_head))?;
+    let old: SignedTreeHead = serde_json::from_slice(&bytes)
+        .with_context(|| format!("{} is not a signed tree head", old_head))?;
+
+    // A head the log never signed proves nothing either way
+    old.verify(key)
+        .with_context(|| format!("{} was not signed by the log", old_head))?;
+    let proof = log.prove_consistency(old.tree_size)?;
+    let result = proof.tree_head.verify(key).and_then(|_| proof.verify(&old));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&proof)?);
+        if let Err(e) = result {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    println!("Earlier head: {}", old_head);
+    print_tree_head(&old);
+    println!("Current head:");
+    print_tree_head(&proof.tree_head);
+    println!("   Proof:     {} hashes", proof.proof.len());
+    report_log_check(
+        result,
+        "earlier tree is a prefix of the current one; no entries were removed or changed",
+    );
+    Ok(())
+}
+
+fn print_replay_outcomes(outcomes: &[ReplayOutcome]) {
+    for outcome in outcomes {
+        match outcome {
+            ReplayOutcome::Restored(entry) => {
+                println!("   ✓ {}: {}", entry.device_path, entry.action.description());
+            }
+            ReplayOutcome::Skipped { entry, reason } => {
+                eprintln!(
+                    "   ⚠ {}: kept '{}' - {}",
+                    entry.device_path,
+                    entry.action.description(),
+                    reason
+                );
+            }
+            ReplayOutcome::Failed { entry, error } => {
+                eprintln!(
+                    "   ✗ {}: could not {} - {}",
+                    entry.device_path,
+                    entry.action.description(),
+                    error
+                );
+            }
+        }
+    }
 }
 
 // Signal handler for graceful shutdown
@@ -3,8 +3,10 @@
 // This module acts as the main entry point for wipe operations, detecting the drive
 // type and routing to the appropriate specialized wipe implementation.
 
+use crate::algorithms::PassSource;
 use crate::drives::types::emmc::EMMCDevice;
 use crate::{
+    crypto::evidence,
     drives::{
         integrated_wipe::{
             wipe_emmc_drive_integrated, wipe_hybrid_drive_integrated,
@@ -13,13 +15,18 @@ use crate::{
         },
         HybridDrive, NVMeAdvanced, OptaneDrive, SMRDrive,
     },
-    error::{ErrorContext, RecoveryCoordinator},
-    Algorithm, DriveError, DriveInfo, DriveResult, DriveType, WipeConfig,
+    error::{ErrorContext, PassTracker, RangeSet, RecoveryCoordinator, ResumeState},
+    DriveError, DriveInfo, DriveResult, DriveType, WipeConfig,
 };
 use anyhow::Result;
+use serde_json::json;
+use std::cmp::Ordering;
 use std::fs::OpenOptions;
 use std::io::{Seek, SeekFrom, Write};
 
+/// Chunk size for software overwrites of a region
+const REGION_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
 /// Main wipe orchestrator with integrated error recovery
 pub struct WipeOrchestrator {
     device_path: String,
@@ -95,57 +102,78 @@ impl WipeOrchestrator {
         println!("Conventional Zones: {}", smr.conventional_zone_count);
         println!();
 
-        // Handle multi-pass algorithms (DoD, Gutmann) by executing multiple passes
-        match self.config.algorithm {
-            Algorithm::DoD5220 => {
-                println!("Using DoD 5220.22-M (3-pass wipe)");
-                let passes = [
-                    (WipeAlgorithm::Zeros, "Pass 1/3: Writing zeros"),
-                    (WipeAlgorithm::Ones, "Pass 2/3: Writing ones"),
-                    (WipeAlgorithm::Random, "Pass 3/3: Writing random data"),
-                ];
-
-                for (pass_num, (algorithm, description)) in passes.iter().enumerate() {
-                    println!("{}", description);
-                    let context = ErrorContext::new(
-                        format!("smr_wipe_pass_{}", pass_num + 1),
-                        &self.device_path,
-                    );
-
-                    self.recovery_coordinator
-                        .execute_with_recovery("wipe_smr_drive", context, || -> DriveResult<()> {
-                            wipe_smr_drive_integrated(&smr, algorithm.clone()).map_err(|e| {
-                                DriveError::IoError(std::io::Error::other(format!("{}", e)))
-                            })?;
-                            Ok(())
-                        })
-                        .map_err(|e| {
-                            DriveError::IoError(std::io::Error::other(format!("{}", e)))
-                        })?;
-                }
-            }
-            Algorithm::Gutmann => {
-                return Err(DriveError::Unsupported(
-                    "Gutmann 35-pass wipe not yet supported for SMR drives. Use DoD or Random instead.".to_string()
-                ));
+        // Software methods run pass by pass; anything else is one random pass
+        let label = self.checkpoint_label("smr");
+        let pass_size: u64 = smr.zones.iter().map(|zone| zone.zone_size).sum();
+
+        let passes: Vec<(WipeAlgorithm, String)> = match self.config.algorithm.wipe_method() {
+            Some(method) => {
+                let passes = method.passes();
+                println!("Using {} ({} passes)", method.description(), passes.len());
+                passes
+                    .into_iter()
+                    .map(|pass| match zone_algorithm(&pass.source) {
+                        Some(algorithm) => Ok((algorithm, pass.description)),
+                        None => Err(DriveError::Unsupported(format!(
+                            "{} writes multi-byte patterns, which SMR zone writes do not support yet. Use DoD or Random instead.",
+                            method.description()
+                        ))),
+                    })
+                    .collect::<DriveResult<_>>()?
             }
-            _ => {
-                // Single-pass algorithms (Zero, Random, etc.)
-                let wipe_algorithm = self.convert_to_wipe_algorithm();
-                let context = ErrorContext::new("smr_wipe", &self.device_path);
+            None => vec![(
+                self.convert_to_wipe_algorithm(),
+                "Writing random data".to_string(),
+            )],
+        };
+        let resume = self.resume_ranges(&label, pass_size)?;
+
+        for (pass_num, (algorithm, description)) in passes.iter().enumerate() {
+            let completed = match pass_completed(pass_num, &resume) {
+                Some(completed) => completed,
+                None => continue,
+            };
+
+            println!("Pass {}/{}: {}", pass_num + 1, passes.len(), description);
+            let context =
+                ErrorContext::new(format!("smr_wipe_pass_{}", pass_num + 1), &self.device_path);
+            let tracker = self
+                .recovery_coordinator
+                .pass_tracker(
+                    &label,
+                    passes.len(),
+                    pass_num,
+                    pass_size,
+                    json!({"pass": pass_num + 1}),
+                )
+                .resuming(completed.clone());
 
-                self.recovery_coordinator
-                    .execute_with_recovery("wipe_smr_drive", context, || -> DriveResult<()> {
-                        wipe_smr_drive_integrated(&smr, wipe_algorithm.clone()).map_err(|e| {
+            self.recovery_coordinator
+                .execute_with_recovery("wipe_smr_drive", context, || -> DriveResult<()> {
+                    wipe_smr_drive_integrated(&smr, algorithm.clone(), &completed, Some(&tracker))
+                        .map_err(|e| {
                             DriveError::IoError(std::io::Error::other(format!("{}", e)))
                         })?;
-                        Ok(())
-                    })
-                    .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))?;
-            }
+                    Ok(())
+                })
+                .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))?;
+            tracker.complete(pattern_name(algorithm));
         }
 
-        println!("✅ SMR drive wipe completed successfully");
+        self.finish_checkpoints()?;
+        let unwritten = self
+            .recovery_coordinator
+            .bad_sector_handler()
+            .map(|handler| handler.skipped_ranges().covered())
+            .unwrap_or(0);
+        if unwritten > 0 {
+            println!(
+                "⚠️  SMR drive wipe finished with {} bytes unwritten; the certificate will not be compliant",
+                unwritten
+            );
+        } else {
+            println!("✅ SMR drive wipe completed successfully");
+        }
         Ok(())
     }
 
@@ -174,8 +202,8 @@ impl WipeOrchestrator {
         );
         println!();
 
-        // Prefer hardware ISE if available
-        let use_ise = optane.supports_ise;
+        // ISE unless an overwrite was chosen for the drive
+        let use_ise = optane.supports_ise && self.config.algorithm.wipe_method().is_none();
 
         // Create error context
         let context = ErrorContext::new("optane_wipe", &self.device_path);
@@ -321,8 +349,8 @@ impl WipeOrchestrator {
             println!("ZNS Support: {}", nvme_advanced.zns_support);
             println!();
 
-            // Prefer hardware format, but can fall back to software
-            let use_format = true;
+            // Format NVM unless an overwrite was chosen for the drive
+            let use_format = self.config.algorithm.wipe_method().is_none();
 
             // Create error context
             let context = ErrorContext::new("nvme_advanced_wipe", &self.device_path);
@@ -387,22 +415,53 @@ impl WipeOrchestrator {
                 println!("\n⚠️  NVMe sanitize failed: {}", e);
                 println!("   Reason: Drive may not support Sanitize or Format NVM commands");
                 println!("   Falling back to software overwrite (this will take longer)...\n");
+                evidence::record_fallback(
+                    &self.device_path,
+                    "NVMe sanitize",
+                    e.to_string(),
+                    "software overwrite (zeros, ones, random)",
+                );
 
                 // Fall back to software overwrite using write_pattern_to_region
                 let size = self.drive_info.size;
                 let context = ErrorContext::new("nvme_software_fallback", &self.device_path);
+                let label = self.checkpoint_label("nvme");
+                let resume = self.resume_point(&label, size)?;
+                let trackers: Vec<PassTracker> = (0..3)
+                    .map(|pass| {
+                        self.recovery_coordinator.pass_tracker(
+                            &label,
+                            3,
+                            pass,
+                            size,
+                            json!({"pass": pass + 1}),
+                        )
+                    })
+                    .collect();
 
                 self.recovery_coordinator
                     .execute_with_recovery("nvme_software_wipe", context, || {
-                        println!("   Pass 1/3: Writing zeros...");
-                        self.write_pattern_to_region(0, size)?;
-
-                        println!("   Pass 2/3: Writing ones...");
-                        let pattern = vec![0xFF; 4096 * 1024]; // 4MB of 0xFF
-                        self.write_custom_pattern(0, size, &pattern)?;
-
-                        println!("   Pass 3/3: Writing random data...");
-                        self.write_random_data_to_region(0, size)?;
+                        if let Some(resume_from) = pass_start(0, resume) {
+                            println!("   Pass 1/3: Writing zeros...");
+                            self.write_pattern_to_region(0, size, resume_from, &trackers[0])?;
+                        }
+
+                        if let Some(resume_from) = pass_start(1, resume) {
+                            println!("   Pass 2/3: Writing ones...");
+                            let pattern = vec![0xFF; 4096 * 1024]; // 4MB of 0xFF
+                            self.write_custom_pattern(
+                                0,
+                                size,
+                                &pattern,
+                                resume_from,
+                                &trackers[1],
+                            )?;
+                        }
+
+                        if let Some(resume_from) = pass_start(2, resume) {
+                            println!("   Pass 3/3: Writing random data...");
+                            self.write_random_data_to_region(0, size, resume_from, &trackers[2])?;
+                        }
 
                         Ok(())
                     })
@@ -412,7 +471,13 @@ impl WipeOrchestrator {
                             e
                         )))
                     })?;
+                for (pass, pattern) in ["0x00", "0xFF", "random"].into_iter().enumerate() {
+                    if pass_start(pass, resume).is_some() {
+                        trackers[pass].complete(pattern);
+                    }
+                }
 
+                self.finish_checkpoints()?;
                 println!("✅ NVMe software wipe completed successfully");
                 Ok(())
             }
@@ -430,14 +495,25 @@ impl WipeOrchestrator {
         let device_path = self.device_path.clone();
         let size = self.drive_info.size;
         let trim_support = self.drive_info.capabilities.trim_support;
+        let label = self.checkpoint_label("ssd");
+        let resume = self.resume_point(&label, size)?;
+        let tracker = self
+            .recovery_coordinator
+            .pass_tracker(&label, 1, 0, size, json!({}));
 
         // Execute with recovery coordinator
         self.recovery_coordinator
             .execute_with_recovery("wipe_ssd_drive", context, || {
                 // Perform basic overwrite
-                self.write_pattern_to_region(0, size).map_err(|e| {
-                    DriveError::IoError(std::io::Error::other(format!("SSD wipe failed: {}", e)))
-                })?;
+                if let Some(resume_from) = pass_start(0, resume) {
+                    self.write_pattern_to_region(0, size, resume_from, &tracker)
+                        .map_err(|e| {
+                            DriveError::IoError(std::io::Error::other(format!(
+                                "SSD wipe failed: {}",
+                                e
+                            )))
+                        })?;
+                }
 
                 // Then TRIM if supported
                 if trim_support {
@@ -449,7 +525,9 @@ impl WipeOrchestrator {
                 Ok(())
             })
             .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))?;
+        tracker.complete(self.pattern_name());
 
+        self.finish_checkpoints()?;
         println!("✅ SSD wipe completed successfully");
         Ok(())
     }
@@ -463,16 +541,29 @@ impl WipeOrchestrator {
         let context = ErrorContext::new("hdd_wipe", &self.device_path);
 
         let size = self.drive_info.size;
+        let label = self.checkpoint_label("hdd");
+        let resume = self.resume_point(&label, size)?;
+        let tracker = self
+            .recovery_coordinator
+            .pass_tracker(&label, 1, 0, size, json!({}));
 
         // Execute with recovery coordinator
-        self.recovery_coordinator
-            .execute_with_recovery("wipe_hdd_drive", context, || {
-                self.write_pattern_to_region(0, size).map_err(|e| {
-                    DriveError::IoError(std::io::Error::other(format!("HDD wipe failed: {}", e)))
+        if let Some(resume_from) = pass_start(0, resume) {
+            self.recovery_coordinator
+                .execute_with_recovery("wipe_hdd_drive", context, || {
+                    self.write_pattern_to_region(0, size, resume_from, &tracker)
+                        .map_err(|e| {
+                            DriveError::IoError(std::io::Error::other(format!(
+                                "HDD wipe failed: {}",
+                                e
+                            )))
+                        })
                 })
-            })
-            .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))?;
+                .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))?;
+            tracker.complete(self.pattern_name());
+        }
 
+        self.finish_checkpoints()?;
         println!("✅ HDD wipe completed successfully");
         Ok(())
     }
@@ -521,76 +612,160 @@ impl WipeOrchestrator {
 
     /// Convert WipeConfig algorithm to WipeAlgorithm for integrated wipe functions
     ///
-    /// NOTE: This only handles single-pass algorithms. Multi-pass algorithms (DoD, Gutmann)
-    /// must be handled separately by the caller executing multiple passes.
+    /// NOTE: This is what a single pass writes: the final contents of a
+    /// software method, or random data for anything else. Multi-pass methods
+    /// must be run pass by pass by the caller.
     pub(crate) fn convert_to_wipe_algorithm(&self) -> WipeAlgorithm {
-        match self.config.algorithm {
-            Algorithm::Zero => WipeAlgorithm::Zeros,
-            Algorithm::Random => WipeAlgorithm::Random,
-            // DoD and Gutmann should be handled with multiple passes by the caller
-            // Fallback to random for safety if called incorrectly
-            Algorithm::DoD5220 => WipeAlgorithm::Random,
-            Algorithm::Gutmann => WipeAlgorithm::Random,
-            _ => WipeAlgorithm::Random, // Default to random for security
-        }
+        zone_algorithm(&self.expected_contents()).unwrap_or(WipeAlgorithm::Random)
     }
 
-    /// Write pattern to a specific region (used by SMR and other specialized wipers)
-    fn write_pattern_to_region(&self, offset: u64, size: u64) -> Result<()> {
-        let mut file = OpenOptions::new().write(true).open(&self.device_path)?;
+    /// Checkpoint label for a software overwrite path, e.g. `hdd:DoD5220`
+    fn checkpoint_label(&self, path: &str) -> String {
+        format!("{}:{:?}", path, self.config.algorithm)
+    }
 
-        file.seek(SeekFrom::Start(offset))?;
+    /// Where an interrupted overwrite recorded under `label` left off
+    ///
+    /// Returns the pass to continue and the offset inside it; `(0, 0)` when
+    /// there is nothing to resume.
+    fn resume_point(&mut self, label: &str, pass_size: u64) -> DriveResult<(usize, u64)> {
+        Ok(match self.load_resume(label)? {
+            Some(resume) => {
+                let offset = resume.pass_offset(pass_size);
+                println!(
+                    "Resuming pass {} at byte {} (checkpoint found)",
+                    resume.current_pass + 1,
+                    offset
+                );
+                (resume.current_pass, offset)
+            }
+            None => (0, 0),
+        })
+    }
 
-        // Generate pattern based on algorithm
-        let pattern = self.generate_pattern(size as usize)?;
-        file.write_all(&pattern)?;
-        file.sync_all()?;
+    /// Like `resume_point`, but returns the byte ranges of the interrupted
+    /// pass already written, for paths that can fill holes out of order
+    fn resume_ranges(&mut self, label: &str, pass_size: u64) -> DriveResult<(usize, RangeSet)> {
+        Ok(match self.load_resume(label)? {
+            Some(resume) => {
+                let completed = resume.completed(pass_size);
+                println!(
+                    "Resuming pass {} with {} of {} bytes written (checkpoint found)",
+                    resume.current_pass + 1,
+                    completed.covered(),
+                    pass_size
+                );
+                (resume.current_pass, completed)
+            }
+            None => (0, RangeSet::new()),
+        })
+    }
 
-        Ok(())
+    fn load_resume(&mut self, label: &str) -> DriveResult<Option<ResumeState>> {
+        self.recovery_coordinator
+            .resume_from_checkpoint(label)
+            .map_err(|e| {
+                DriveError::IoError(std::io::Error::other(format!(
+                    "Failed to load checkpoint: {}",
+                    e
+                )))
+            })
     }
 
-    /// Write a custom pattern to a specific region
-    fn write_custom_pattern(&self, offset: u64, size: u64, pattern: &[u8]) -> Result<()> {
-        let mut file = OpenOptions::new().write(true).open(&self.device_path)?;
+    /// Drop checkpoints once the wipe has completed
+    fn finish_checkpoints(&self) -> DriveResult<()> {
+        self.recovery_coordinator
+            .delete_checkpoint()
+            .map_err(|e| DriveError::IoError(std::io::Error::other(format!("{}", e))))
+    }
 
-        file.seek(SeekFrom::Start(offset))?;
+    /// Write pattern to a specific region (used by SMR and other specialized wipers)
+    ///
+    /// Writing starts `resume_from` bytes into the region; progress is
+    /// checkpointed through `tracker`.
+    fn write_pattern_to_region(
+        &self,
+        offset: u64,
+        size: u64,
+        resume_from: u64,
+        tracker: &PassTracker,
+    ) -> Result<()> {
+        let contents = self.expected_contents();
+        self.write_region_chunks(offset, size, resume_from, tracker, |chunk, position| {
+            contents.fill(chunk, position)
+        })
+    }
 
+    /// Write a custom pattern to a specific region
+    fn write_custom_pattern(
+        &self,
+        offset: u64,
+        size: u64,
+        pattern: &[u8],
+        resume_from: u64,
+        tracker: &PassTracker,
+    ) -> Result<()> {
         let pattern_len = pattern.len() as u64;
-        let mut written = 0u64;
 
         // Write pattern repeatedly until size is reached
-        while written < size {
-            let to_write = std::cmp::min(pattern_len, size - written);
-            file.write_all(&pattern[..to_write as usize])?;
-            written += to_write;
-        }
-
-        file.sync_all()?;
-        Ok(())
+        self.write_region_chunks(offset, size, resume_from, tracker, |chunk, position| {
+            for (i, byte) in chunk.iter_mut().enumerate() {
+                *byte = pattern[((position + i as u64) % pattern_len) as usize];
+            }
+            Ok(())
+        })
     }
 
     /// Write cryptographically secure random data to a specific region
     /// This method explicitly uses SecureRNG regardless of the configured algorithm
-    fn write_random_data_to_region(&self, offset: u64, size: u64) -> Result<()> {
+    fn write_random_data_to_region(
+        &self,
+        offset: u64,
+        size: u64,
+        resume_from: u64,
+        tracker: &PassTracker,
+    ) -> Result<()> {
         use crate::crypto::secure_rng::SecureRNG;
 
+        let mut rng = SecureRNG::new()?;
+
+        // Fill with cryptographically secure random data
+        self.write_region_chunks(offset, size, resume_from, tracker, |chunk, _position| {
+            rng.fill_bytes(chunk)
+        })
+    }
+
+    /// Write `[resume_from, size)` of the region at `offset` chunk by chunk
+    ///
+    /// `fill` receives each chunk and its position within the region. Written
+    /// data is flushed before a checkpoint records it.
+    fn write_region_chunks<F>(
+        &self,
+        offset: u64,
+        size: u64,
+        resume_from: u64,
+        tracker: &PassTracker,
+        mut fill: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut [u8], u64) -> Result<()>,
+    {
         let mut file = OpenOptions::new().write(true).open(&self.device_path)?;
 
-        file.seek(SeekFrom::Start(offset))?;
+        file.seek(SeekFrom::Start(offset + resume_from))?;
 
-        let mut rng = SecureRNG::new()?;
-        let chunk_size = 4 * 1024 * 1024; // 4MB chunks
-        let mut written = 0u64;
+        let mut position = resume_from;
+        let mut chunk = Vec::new();
 
-        while written < size {
-            let to_write = std::cmp::min(chunk_size, (size - written) as usize);
-            let mut buffer = vec![0u8; to_write];
+        while position < size {
+            let to_write = REGION_CHUNK_SIZE.min(size - position) as usize;
+            chunk.resize(to_write, 0);
 
-            // Fill with cryptographically secure random data
-            rng.fill_bytes(&mut buffer)?;
+            fill(&mut chunk, position)?;
+            file.write_all(&chunk)?;
+            position += to_write as u64;
 
-            file.write_all(&buffer)?;
-            written += to_write as u64;
+            tracker.record(position, || Ok(file.sync_data()?))?;
         }
 
         file.sync_all()?;
@@ -635,39 +810,67 @@ impl WipeOrchestrator {
         })
     }
 
+    /// What a single-pass overwrite leaves on the drive
+    fn expected_contents(&self) -> PassSource {
+        self.config
+            .algorithm
+            .wipe_method()
+            .map(|method| method.expected_contents())
+            .unwrap_or(PassSource::Random)
+    }
+
+    /// What `generate_pattern` writes, for pass records
+    fn pattern_name(&self) -> String {
+        self.expected_contents().name()
+    }
+
     /// Generate wipe pattern based on configured algorithm
+    #[cfg(test)]
     pub(crate) fn generate_pattern(&self, size: usize) -> Result<Vec<u8>> {
-        use crate::crypto::secure_rng::SecureRNG;
+        let mut data = vec![0u8; size];
+        self.expected_contents().fill(&mut data, 0)?;
+        Ok(data)
+    }
+}
 
-        match self.config.algorithm {
-            Algorithm::Random => {
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
-            }
-            Algorithm::Zero => Ok(vec![0u8; size]),
-            Algorithm::DoD5220 => {
-                // DoD uses multiple passes, for now just use first pass pattern
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
-            }
-            Algorithm::Gutmann => {
-                // Gutmann uses 35 passes, this is simplified
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
-            }
-            _ => {
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
-            }
-        }
+/// Zone write for a pass source; integrated writes take single bytes only
+fn zone_algorithm(source: &PassSource) -> Option<WipeAlgorithm> {
+    match source {
+        PassSource::Fixed(0x00) => Some(WipeAlgorithm::Zeros),
+        PassSource::Fixed(0xFF) => Some(WipeAlgorithm::Ones),
+        PassSource::Fixed(byte) => Some(WipeAlgorithm::Pattern(*byte)),
+        PassSource::Random => Some(WipeAlgorithm::Random),
+        PassSource::Pattern(_) => None,
+    }
+}
+
+/// What an integrated SMR pass writes, for pass records
+fn pattern_name(algorithm: &WipeAlgorithm) -> String {
+    match algorithm {
+        WipeAlgorithm::Zeros => "0x00".to_string(),
+        WipeAlgorithm::Ones => "0xFF".to_string(),
+        WipeAlgorithm::Random => "random".to_string(),
+        WipeAlgorithm::Pattern(byte) => format!("0x{:02X}", byte),
+    }
+}
+
+/// Offset to start `pass` from given a resume point, or `None` if the pass
+/// already completed
+fn pass_start(pass: usize, (resume_pass, resume_offset): (usize, u64)) -> Option<u64> {
+    match pass.cmp(&resume_pass) {
+        Ordering::Less => None,
+        Ordering::Equal => Some(resume_offset),
+        Ordering::Greater => Some(0),
+    }
+}
+
+/// Ranges of `pass` already written given a resume point, or `None` if the
+/// pass already completed
+fn pass_completed(pass: usize, (resume_pass, completed): &(usize, RangeSet)) -> Option<RangeSet> {
+    match pass.cmp(resume_pass) {
+        Ordering::Less => None,
+        Ordering::Equal => Some(completed.clone()),
+        Ordering::Greater => Some(RangeSet::new()),
     }
 }
 
@@ -683,6 +886,7 @@ pub async fn wipe_drive(device_path: &str, config: WipeConfig) -> DriveResult<()
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Algorithm;
 
     #[test]
     fn test_orchestrator_creation() {
@@ -426,6 +426,54 @@ fn test_pattern_generation_small_size() -> Result<()> {
     Ok(())
 }
 
+// ==================== RESUME TESTS ====================
+
+#[test]
+fn test_pass_start_from_resume_point() {
+    let resume = (1, 8192);
+
+    assert_eq!(pass_start(0, resume), None);
+    assert_eq!(pass_start(1, resume), Some(8192));
+    assert_eq!(pass_start(2, resume), Some(0));
+}
+
+#[test]
+fn test_pass_completed_from_resume_ranges() {
+    let written: RangeSet = [0..4096, 8192..12288].into_iter().collect();
+    let resume = (1, written.clone());
+
+    assert_eq!(pass_completed(0, &resume), None);
+    assert_eq!(pass_completed(1, &resume), Some(written));
+    assert_eq!(pass_completed(2, &resume), Some(RangeSet::new()));
+}
+
+#[test]
+fn test_write_pattern_to_region_resumes_mid_region() -> Result<()> {
+    let temp = tempfile::NamedTempFile::new()?;
+    std::fs::write(temp.path(), vec![0xAA; 64 * 1024])?;
+
+    let config = WipeConfig {
+        algorithm: Algorithm::Zero,
+        ..Default::default()
+    };
+    let orchestrator = WipeOrchestrator::new(temp.path().to_str().unwrap().to_string(), config)?;
+    let tracker = orchestrator.recovery_coordinator.pass_tracker(
+        "test",
+        1,
+        0,
+        64 * 1024,
+        serde_json::json!({}),
+    );
+
+    orchestrator.write_pattern_to_region(0, 64 * 1024, 32 * 1024, &tracker)?;
+
+    // Only the part after the resume offset is rewritten
+    let data = std::fs::read(temp.path())?;
+    assert!(data[..32 * 1024].iter().all(|&b| b == 0xAA));
+    assert!(data[32 * 1024..].iter().all(|&b| b == 0x00));
+    Ok(())
+}
+
 // ==================== ALGORITHM ENUM COVERAGE TESTS ====================
 
 #[test]
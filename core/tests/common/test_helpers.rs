@@ -69,6 +69,24 @@ pub fn calculate_file_entropy(path: &std::path::Path) -> std::io::Result<f64> {
     Ok(entropy)
 }
 
+/// Overwrite a file with each pass of a wipe method in turn
+pub fn apply_passes(
+    path: &std::path::Path,
+    passes: &[sayonara_wipe::algorithms::WipePass],
+) -> anyhow::Result<()> {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    let mut buffer = vec![0u8; fs::metadata(path)?.len() as usize];
+    for pass in passes {
+        pass.source.fill(&mut buffer, 0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&buffer)?;
+    }
+    file.sync_all()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +123,20 @@ mod tests {
         let entropy = calculate_file_entropy(temp.path()).unwrap();
         assert!(entropy < 0.1);
     }
+
+    #[test]
+    fn test_apply_passes() {
+        use sayonara_wipe::algorithms::{PassSource, WipePass};
+
+        let mut temp = NamedTempFile::new().unwrap();
+        temp.write_all(&vec![0xAAu8; 1024]).unwrap();
+        temp.flush().unwrap();
+
+        let passes = [
+            WipePass::new(PassSource::Random, "Writing random data"),
+            WipePass::new(PassSource::Fixed(0x00), "Writing 0x00"),
+        ];
+        apply_passes(temp.path(), &passes).unwrap();
+        assert!(verify_all_zeros(temp.path()).unwrap());
+    }
 }
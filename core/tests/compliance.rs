@@ -8,6 +8,7 @@ mod common; // Common test utilities
 mod compliance {
     pub mod certificate_validation;
     pub mod dod_5220_22m;
+    pub mod international_standards;
     pub mod nist_800_88;
     pub mod statistical_suite;
 }
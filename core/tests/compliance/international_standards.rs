@@ -0,0 +1,281 @@
+use crate::common::test_helpers::{apply_passes, calculate_file_entropy, verify_pattern};
+use anyhow::Result;
+/// International Overwrite Standards Compliance Tests
+///
+/// These tests validate the overwrite standards beyond DoD 5220.22-M and
+/// Gutmann against one table of what each standard requires.
+///
+/// Standard Requirements:
+/// - DoD 5220.22-M ECE: E (0x00, 0xFF, random, verified), C (0x96), E again
+/// - HMG IS5 Baseline: 1 pass of 0x00, verified
+/// - HMG IS5 Enhanced: 0x00, 0xFF, then random data, verified
+/// - BSI VSITR: 0x00 and 0xFF alternately for six passes, then 0xAA
+/// - RCMP TSSIT OPS-II: 0x00 and 0xFF alternately for six passes, then
+///   random data, verified
+/// - GOST R 50739-95: 0x00, then random data
+/// - Schneier: 0xFF, 0x00, then five passes of random data
+/// - NAVSO P-5239-26 (RLL): 0x01, the 0x27FFFFFF pattern, then random
+///   data, verified
+/// - VSITR, GOST and Schneier prescribe no read-back
+use sayonara_wipe::algorithms::{
+    method, DoDEceWipe, GostWipe, HmgIs5BaselineWipe, HmgIs5EnhancedWipe, NavsoWipe, PassSource,
+    RcmpOps2Wipe, SchneierWipe, VsitrWipe, WipeMethod,
+};
+use sayonara_wipe::crypto::evidence;
+use sayonara_wipe::{Algorithm, DriveType, WipeConfig};
+use tempfile::NamedTempFile;
+
+/// What one standard requires
+struct Standard {
+    method: &'static dyn WipeMethod,
+    /// Name it is selected by with `--algorithm`
+    selected_as: &'static str,
+    /// Formal name recorded in the certificate
+    formal_name: &'static str,
+    sources: Vec<PassSource>,
+    /// Which passes the standard has read back
+    verified: &'static [bool],
+}
+
+fn standards() -> Vec<Standard> {
+    use PassSource::{Fixed, Pattern, Random};
+
+    vec![
+        Standard {
+            method: &DoDEceWipe,
+            selected_as: "dod-ece",
+            formal_name: "DoD 5220.22-M ECE",
+            sources: vec![
+                Fixed(0x00),
+                Fixed(0xFF),
+                Random,
+                Fixed(0x96),
+                Fixed(0x00),
+                Fixed(0xFF),
+                Random,
+            ],
+            verified: &[false, false, true, false, false, false, true],
+        },
+        Standard {
+            method: &HmgIs5BaselineWipe,
+            selected_as: "hmg-is5-baseline",
+            formal_name: "HMG Infosec Standard No. 5, Baseline",
+            sources: vec![Fixed(0x00)],
+            verified: &[true],
+        },
+        Standard {
+            method: &HmgIs5EnhancedWipe,
+            selected_as: "hmg-is5-enhanced",
+            formal_name: "HMG Infosec Standard No. 5, Enhanced",
+            sources: vec![Fixed(0x00), Fixed(0xFF), Random],
+            verified: &[false, false, true],
+        },
+        Standard {
+            method: &VsitrWipe,
+            selected_as: "VSITR",
+            formal_name: "BSI VSITR (Richtlinien zur Sicherheit von Verschlusssachen in der IT)",
+            sources: [0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0xAA]
+                .map(Fixed)
+                .to_vec(),
+            verified: &[false; 7],
+        },
+        Standard {
+            method: &RcmpOps2Wipe,
+            selected_as: "rcmp-ops2",
+            formal_name:
+                "RCMP Technical Security Standard for Information Technology (TSSIT) OPS-II",
+            sources: vec![
+                Fixed(0x00),
+                Fixed(0xFF),
+                Fixed(0x00),
+                Fixed(0xFF),
+                Fixed(0x00),
+                Fixed(0xFF),
+                Random,
+            ],
+            verified: &[false, false, false, false, false, false, true],
+        },
+        Standard {
+            method: &GostWipe,
+            selected_as: "gost",
+            formal_name: "GOST R 50739-95",
+            sources: vec![Fixed(0x00), Random],
+            verified: &[false, false],
+        },
+        Standard {
+            method: &SchneierWipe,
+            selected_as: "schneier",
+            formal_name: "Bruce Schneier's Algorithm (Applied Cryptography, 2nd ed.)",
+            sources: vec![
+                Fixed(0xFF),
+                Fixed(0x00),
+                Random,
+                Random,
+                Random,
+                Random,
+                Random,
+            ],
+            verified: &[false; 7],
+        },
+        Standard {
+            method: &NavsoWipe,
+            selected_as: "navso",
+            formal_name: "NAVSO P-5239-26 (RLL)",
+            sources: vec![
+                Fixed(0x01),
+                Pattern(NavsoWipe::RLL_PATTERN.to_vec()),
+                Random,
+            ],
+            verified: &[false, false, true],
+        },
+    ]
+}
+
+/// Check `path` holds what `source` writes
+fn assert_leaves(path: &std::path::Path, source: &PassSource, what: &str) -> Result<()> {
+    match source {
+        PassSource::Fixed(byte) => assert!(verify_pattern(path, &[*byte])?, "{}", what),
+        PassSource::Pattern(pattern) => assert!(verify_pattern(path, pattern)?, "{}", what),
+        PassSource::Random => {
+            let entropy = calculate_file_entropy(path)?;
+            assert!(entropy > 7.5, "{}: entropy {:.2}", what, entropy);
+        }
+    }
+    Ok(())
+}
+
+// ==================== PASS SEQUENCE TESTS ====================
+
+#[test]
+fn test_pass_sequences_exact() {
+    for standard in standards() {
+        let passes = standard.method.passes();
+        let sources: Vec<_> = passes.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(sources, standard.sources, "{}", standard.formal_name);
+
+        let verified: Vec<_> = passes.iter().map(|p| p.verify).collect();
+        assert_eq!(verified, standard.verified, "{}", standard.formal_name);
+    }
+}
+
+// ==================== SELECTION AND RECORDING TESTS ====================
+
+#[test]
+fn test_selected_by_name_with_formal_name() {
+    for standard in standards() {
+        let algorithm: Algorithm = standard.selected_as.parse().unwrap();
+        let method = algorithm
+            .wipe_method()
+            .unwrap_or_else(|| panic!("{} is a wipe method", standard.selected_as));
+        assert_eq!(method.name(), standard.method.name());
+        assert_eq!(method.standard(), Some(standard.formal_name));
+    }
+}
+
+// ==================== PATTERN APPLICATION TESTS ====================
+
+#[test]
+fn test_each_pass_leaves_its_data() -> Result<()> {
+    for standard in standards() {
+        let passes = standard.method.passes();
+        let file = NamedTempFile::new()?;
+        std::fs::write(file.path(), vec![0xA5; 64 * 1024])?;
+
+        for (index, pass) in passes.iter().enumerate() {
+            apply_passes(file.path(), &passes[index..=index])?;
+            assert_leaves(
+                file.path(),
+                &pass.source,
+                &format!("{} pass {}", standard.formal_name, index + 1),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// ==================== EXECUTION TESTS ====================
+
+#[test]
+fn test_execute_reads_every_pass_back() -> Result<()> {
+    // Checkpoints go to an in-memory database
+    std::env::set_var("SAYONARA_TEST_MODE", "1");
+    let size = 256 * 1024u64;
+    let config = WipeConfig {
+        verify_passes: true,
+        ..WipeConfig::default()
+    };
+
+    for standard in standards() {
+        let file = NamedTempFile::new()?;
+        std::fs::write(file.path(), vec![0xA5; size as usize])?;
+        let path = file.path().to_str().unwrap();
+        evidence::clear_recorded(path);
+
+        method::execute(standard.method, path, size, DriveType::HDD, &config)?;
+
+        let passes = evidence::take_recorded(path).passes;
+        assert_eq!(
+            passes.len(),
+            standard.sources.len(),
+            "{}",
+            standard.formal_name
+        );
+        for pass in &passes {
+            let verification = pass.verification.as_ref().unwrap_or_else(|| {
+                panic!(
+                    "{} pass {} was not read back",
+                    standard.formal_name, pass.pass
+                )
+            });
+            assert_eq!(verification.bytes, size, "{}", standard.formal_name);
+            assert!(
+                verification.mismatched.is_empty(),
+                "{} pass {} {}: {:?} {:?}",
+                standard.formal_name,
+                pass.pass,
+                pass.pattern,
+                verification.mismatched,
+                verification.rewritten
+            );
+        }
+
+        assert_leaves(
+            file.path(),
+            &standard.method.expected_contents(),
+            standard.formal_name,
+        )?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_execute_with_sampled_verification_succeeds() -> Result<()> {
+    // Without `verify_passes` the passes a standard verifies are sampled,
+    // through direct I/O; that sampling finds what it should is checked
+    // in `algorithms::method`
+    std::env::set_var("SAYONARA_TEST_MODE", "1");
+    let size = 256 * 1024u64;
+
+    for standard in standards() {
+        let file = NamedTempFile::new()?;
+        std::fs::write(file.path(), vec![0xA5; size as usize])?;
+        let path = file.path().to_str().unwrap();
+
+        method::execute(
+            standard.method,
+            path,
+            size,
+            DriveType::HDD,
+            &WipeConfig::default(),
+        )
+        .unwrap_or_else(|e| panic!("{}: {}", standard.formal_name, e));
+        evidence::clear_recorded(path);
+
+        assert_leaves(
+            file.path(),
+            &standard.method.expected_contents(),
+            standard.formal_name,
+        )?;
+    }
+    Ok(())
+}
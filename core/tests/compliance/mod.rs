@@ -2,11 +2,15 @@
 ///
 /// This module contains tests validating compliance with various data sanitization standards:
 /// - DoD 5220.22-M (Department of Defense wiping standard)
+/// - International overwrite standards, from one table: DoD 5220.22-M ECE,
+///   HMG IS5 Baseline and Enhanced, BSI VSITR, RCMP TSSIT OPS-II,
+///   GOST R 50739-95, Bruce Schneier's 7-pass and NAVSO P-5239-26
 /// - NIST 800-88 Rev. 1 (NIST guidelines for media sanitization)
 /// - NIST SP 800-22 (Statistical test suite for random and pseudorandom number generators)
 /// - Certificate validation (cryptographic authenticity and tamper detection)
 
 pub mod dod_5220_22m;
+pub mod international_standards;
 pub mod nist_800_88;
 pub mod statistical_suite;
 pub mod certificate_validation;
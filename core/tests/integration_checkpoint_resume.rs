@@ -197,7 +197,10 @@ fn test_checkpoint_list_all() -> Result<()> {
     assert_eq!(checkpoints.len(), 3);
 
     // Verify all devices are present
-    let device_paths: Vec<String> = checkpoints.iter().map(|c| c.device_path.clone()).collect();
+    let device_paths: Vec<String> = checkpoints
+        .iter()
+        .map(|c| c.checkpoint.device_path.clone())
+        .collect();
     assert!(device_paths.contains(&"/dev/sdg".to_string()));
     assert!(device_paths.contains(&"/dev/sdh".to_string()));
     assert!(device_paths.contains(&"/dev/sdi".to_string()));
@@ -325,14 +328,16 @@ fn test_checkpoint_concurrent_access() -> Result<()> {
     let temp_dir = tempfile::tempdir()?;
     let db_path = temp_dir.path().join("concurrent_test.db");
 
-    let mut manager1 = CheckpointManager::new(Some(db_path.to_str().unwrap()))?;
+    let key_path = temp_dir.path().join("checkpoint.key");
+
+    let mut manager1 = CheckpointManager::with_key_file(db_path.to_str().unwrap(), &key_path)?;
 
     // Save checkpoint from first manager
     let checkpoint = Checkpoint::new("/dev/sdn", "DoD", "concurrent-op-016", 3, 100_000_000_000);
     manager1.save(&checkpoint)?;
 
     // Load from second manager (different connection)
-    let manager2 = CheckpointManager::new(Some(db_path.to_str().unwrap()))?;
+    let manager2 = CheckpointManager::with_key_file(db_path.to_str().unwrap(), &key_path)?;
     let loaded = manager2.load("/dev/sdn", "DoD")?;
 
     assert!(loaded.is_some());